@@ -4,25 +4,37 @@
 //! startup, activation, and shutdown procedures.
 
 use gtk::prelude::*;
-use gtk::Application;
-use log::{info, debug};
-use std::cell::RefCell;
+use gtk::{glib, Application};
+use log::{info, debug, warn};
+use std::cell::{OnceCell, RefCell};
 use std::rc::Rc;
 
-use crate::config::Settings;
+use crate::config::profiles::evaluate_auto_switch;
+use crate::config::{AutoSwitchCondition, MultiMonitorMode, Settings};
+use crate::services::multimonitor::monitor_set_diff;
+use crate::services::{DockServices, MonitorInfo};
 use crate::ui;
+use crate::utils;
 
 /// Application ID following reverse DNS convention
-const APP_ID: &str = "com.blazedock.fedora";
+///
+/// GApplication uses it to make the dock unique on the session bus.
+const APP_ID: &str = "com.blazedock.BlazeDock";
+
+/// Shared handle to the dock windows, filled in once the application activates
+///
+/// Holds one window per monitor in `All` multi-monitor mode, otherwise one.
+pub type DockHandle = Rc<RefCell<Vec<Rc<ui::DockWindow>>>>;
 
 /// Run the BlazeDock GTK4 application
 ///
 /// # Arguments
 /// * `config` - The loaded application settings
+/// * `dock` - Slot that receives the dock windows after activation
 ///
 /// # Returns
 /// Exit code (0 for success)
-pub fn run_application(config: Settings) -> i32 {
+pub fn run_application(config: Settings, dock: DockHandle) -> i32 {
     debug!("Initializing GTK4 application with ID: {}", APP_ID);
 
     // Create the GTK4 application instance
@@ -33,46 +45,122 @@ pub fn run_application(config: Settings) -> i32 {
     // Wrap config in Rc<RefCell> for shared access across callbacks
     let config = Rc::new(RefCell::new(config));
 
+    // Services shared by every dock, started on startup
+    let services: Rc<OnceCell<Rc<DockServices>>> = Rc::new(OnceCell::new());
+
     // Connect to the 'activate' signal - called when the application starts
     let config_clone = config.clone();
+    let services_activate = Rc::clone(&services);
     app.connect_activate(move |app| {
         info!("Application activated");
-        on_activate(app, config_clone.clone());
+        let Some(services) = services_activate.get() else {
+            warn!("Dock services not started, ignoring activation");
+            return;
+        };
+        on_activate(app, config_clone.clone(), &dock, services);
     });
 
     // Connect to 'startup' signal - called once before activation
-    app.connect_startup(|_app| {
+    let config_startup = config.clone();
+    let services_startup = Rc::clone(&services);
+    app.connect_startup(move |_app| {
         info!("Application starting up");
         // Load CSS styles globally before creating windows
         ui::load_global_styles();
+        // Notice apps being installed or removed
+        utils::desktop_entry::watch_application_dirs();
+        
+        let config = config_startup.borrow();
+        let services = services_startup.get_or_init(|| Rc::new(DockServices::start(&config)));
+        // Listen system-wide where the desktop allows it
+        if config.enable_shortcuts {
+            services.keyboard.register_global_shortcuts();
+        }
     });
 
     // Connect to 'shutdown' signal - called when the application exits
-    app.connect_shutdown(|_app| {
+    app.connect_shutdown(move |_app| {
         info!("Application shutting down");
-        // Cleanup tasks can be added here
+        if let Some(services) = services.get() {
+            services.stop();
+        }
     });
 
     // Run the application (blocks until quit)
     app.run().into()
 }
 
+/// Ask the dock that is already running to show itself
+///
+/// Used when another process holds the instance lock. Returns the exit
+/// code for this process.
+pub fn activate_running_instance() -> i32 {
+    let app = Application::builder()
+        .application_id(APP_ID)
+        .build();
+
+    if let Err(e) = app.register(gtk::gio::Cancellable::NONE) {
+        warn!("Failed to register with the session bus: {}", e);
+        return 1;
+    }
+
+    if app.is_remote() {
+        info!("Activating the running dock");
+        app.activate();
+        0
+    } else {
+        warn!("Running dock not reachable over D-Bus, not starting a second one");
+        1
+    }
+}
+
 /// Handle application activation
 ///
 /// This is called when the application is started. It creates the main
 /// dock window and configures it based on user settings.
-fn on_activate(app: &Application, config: Rc<RefCell<Settings>>) {
+fn on_activate(app: &Application, config: Rc<RefCell<Settings>>, dock: &DockHandle, services: &Rc<DockServices>) {
     let settings = config.borrow();
     
-    // Check if a window already exists (prevents multiple windows on re-activation)
-    if let Some(window) = app.active_window() {
-        debug!("Window already exists, presenting it");
-        window.present();
+    // A second launch activates the running dock; bring it back instead of adding windows
+    if !dock.borrow().is_empty() {
+        debug!("Dock already running, showing it");
+        for window in dock.borrow().iter() {
+            window.show();
+        }
         return;
     }
 
-    // Create the main dock window
-    let window = ui::DockWindow::new(app, &settings);
+    if matches!(settings.multi_monitor_mode, MultiMonitorMode::All | MultiMonitorMode::PerMonitor) {
+        drop(settings);
+        spawn_monitor_docks(app, &config, dock, services);
+    } else {
+        // Create the main dock window, kept alive so it can be reloaded later
+        let window = ui::DockWindow::new(app, &settings, services);
+        dock.borrow_mut().push(start_dock(window, dock));
+    }
+    
+    // Follow the profile auto-switch rules
+    start_profile_auto_switch(services, dock);
+}
+
+/// Wire up a new dock window, present it and start its periodic updates
+fn start_dock(window: ui::DockWindow, dock: &DockHandle) -> Rc<ui::DockWindow> {
+    let window = Rc::new(window);
+    
+    // Let dock item menus (unpin, ...) act on the window
+    window.setup_item_actions();
+    
+    // The other docks show the same pinned apps, so reload them on changes
+    let docks = Rc::downgrade(dock);
+    let origin = Rc::downgrade(&window);
+    window.on_pins_changed(move |settings| {
+        let Some(docks) = docks.upgrade() else {
+            return;
+        };
+        for other in docks.borrow().iter().filter(|other| Rc::as_ptr(other) != origin.as_ptr()) {
+            other.schedule_reload(settings.clone());
+        }
+    });
     
     // Present the window
     window.present();
@@ -84,5 +172,140 @@ fn on_activate(app: &Application, config: Rc<RefCell<Settings>>) {
     
     // Start periodic refresh of running apps (macOS-style)
     window.start_running_apps_refresh();
+    
+    // Show mounted removable drives
+    window.start_drive_refresh();
+    
+    window
+}
+
+/// Periodically apply the profile auto-switch rules to every dock
+///
+/// A matching rule switches to its profile; once none match, the docks
+/// return to the profile that was active before. Automatic switches
+/// are not saved to the configuration.
+fn start_profile_auto_switch(services: &Rc<DockServices>, dock: &DockHandle) {
+    let rules = services.profile_manager.borrow().auto_switch_rules().to_vec();
+    if rules.is_empty() {
+        return;
+    }
+    for rule in &rules {
+        if let AutoSwitchCondition::ProcessRunning { process } = &rule.when {
+            services.process_tracker.register_app(process);
+        }
+    }
+    
+    info!("Profile auto-switch started with {} rules", rules.len());
+    
+    let services = Rc::clone(services);
+    let docks = Rc::downgrade(dock);
+    // Profile to return to once no rule matches
+    let mut return_to: Option<String> = None;
+    glib::timeout_add_seconds_local(5, move || {
+        let Some(docks) = docks.upgrade() else {
+            return glib::ControlFlow::Break;
+        };
+        let Some(current) = docks.borrow().first().map(|window| window.active_profile()) else {
+            return glib::ControlFlow::Continue;
+        };
+        
+        let minute_of_day = glib::DateTime::now_local()
+            .map(|now| (now.hour() * 60 + now.minute()) as u32)
+            .unwrap_or(0);
+        let target = evaluate_auto_switch(&rules, |process| services.process_tracker.is_running(process), minute_of_day);
+        
+        let next = match target {
+            Some(profile) if profile != current => {
+                return_to.get_or_insert(current);
+                Some(profile.to_string())
+            }
+            Some(_) => None,
+            None => return_to.take(),
+        };
+        
+        if let Some(name) = next {
+            let switched = Settings::load()
+                .map_err(|e| format!("{:#}", e))
+                .and_then(|base| services.profile_manager.borrow_mut().switch_active(&name, &base));
+            match switched {
+                Ok(settings) => {
+                    info!("Auto-switching to profile: {}", name);
+                    for window in docks.borrow().iter() {
+                        window.reload(&settings);
+                    }
+                }
+                Err(e) => warn!("Failed to auto-switch profile: {}", e),
+            }
+        }
+        
+        glib::ControlFlow::Continue
+    });
+}
+
+/// Create a dock on every monitor and follow monitors being plugged and unplugged
+fn spawn_monitor_docks(app: &Application, config: &Rc<RefCell<Settings>>, dock: &DockHandle, services: &Rc<DockServices>) {
+    let connected = services.monitors.get_monitors();
+    
+    if connected.is_empty() {
+        warn!("No monitors found, creating a single dock");
+        let window = ui::DockWindow::new(app, &config.borrow(), services);
+        dock.borrow_mut().push(start_dock(window, dock));
+        return;
+    }
+    
+    sync_monitor_docks(app, &config.borrow(), dock, services, &connected);
+    
+    // System-wide shortcuts go to the dock on the monitor with the pointer
+    services.monitors.start_following();
+    
+    // Monitor callbacks must be Send, so hand changes over to the main loop
+    let (sender, receiver) = async_channel::unbounded::<Vec<MonitorInfo>>();
+    services.monitors.on_monitors_changed(move |connected| {
+        let _ = sender.try_send(connected.to_vec());
+    });
+    
+    let app = app.clone();
+    let config = Rc::clone(config);
+    let dock = Rc::clone(dock);
+    let services = Rc::clone(services);
+    glib::spawn_future_local(async move {
+        while let Ok(connected) = receiver.recv().await {
+            // New docks should match the current configuration
+            let settings = Settings::load_active().unwrap_or_else(|_| config.borrow().clone());
+            sync_monitor_docks(&app, &settings, &dock, &services, &connected);
+        }
+    });
+}
+
+/// Destroy docks on monitors that went away and create docks on new ones
+fn sync_monitor_docks(
+    app: &Application,
+    settings: &Settings,
+    dock: &DockHandle,
+    services: &Rc<DockServices>,
+    connected: &[MonitorInfo],
+) {
+    let current: Vec<String> = dock.borrow().iter()
+        .filter_map(|window| window.monitor().map(|m| m.key().to_string()))
+        .collect();
+    let connected_keys: Vec<String> = connected.iter().map(|m| m.key().to_string()).collect();
+    
+    let (added, removed) = monitor_set_diff(&current, &connected_keys);
+    
+    dock.borrow_mut().retain(|window| {
+        let unplugged = window.monitor()
+            .map(|m| removed.iter().any(|key| key == m.key()))
+            .unwrap_or(false);
+        if unplugged {
+            info!("Monitor removed, closing its dock");
+            window.close();
+        }
+        !unplugged
+    });
+    
+    for monitor in connected.iter().filter(|m| added.iter().any(|key| key == m.key())) {
+        let window = ui::DockWindow::spawn_on_monitor(app, settings, services, monitor);
+        dock.borrow_mut().push(start_dock(window, dock));
+    }
 }
 
@@ -0,0 +1,415 @@
+//! Command-line interface
+//!
+//! Handles the `pin`, `unpin` and `list` subcommands, which edit the
+//! pinned apps in the configuration, and `--check`, which reports on the
+//! configuration and environment. All exit without starting the GUI.
+
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use std::path::{Path, PathBuf};
+use std::process::Command as ProcessCommand;
+
+use crate::config::{PinnedApp, ProfileManager, Settings};
+use crate::services::{DesktopEnvironment, ScreencopyService, ScreenshotTool, WindowTracker};
+use crate::utils::desktop_entry::DesktopEntry;
+
+/// Usage text printed for invalid invocations
+const USAGE: &str = "Usage:
+  blazedock                       Start the dock
+  blazedock pin <desktop-file>    Pin an application
+  blazedock unpin <index>         Unpin the application at <index>
+  blazedock list                  List pinned applications
+  blazedock --check               Check the configuration and environment";
+
+/// A CLI subcommand
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Pin the application described by a .desktop file
+    Pin(PathBuf),
+    /// Unpin the application at the given index
+    Unpin(usize),
+    /// List the pinned applications
+    List,
+    /// Check the configuration and environment
+    Check,
+}
+
+/// Parse command-line arguments (without the program name)
+///
+/// # Returns
+/// * `Ok(None)` if no subcommand was given and the GUI should start
+/// * `Ok(Some(command))` for a valid subcommand
+/// * `Err` if the arguments are invalid
+pub fn parse_args(args: &[String]) -> Result<Option<Command>> {
+    let Some(first) = args.first() else {
+        return Ok(None);
+    };
+
+    if first == "--check" && args.len() == 1 {
+        return Ok(Some(Command::Check));
+    }
+
+    // Leave other option flags to GTK
+    if first.starts_with('-') {
+        return Ok(None);
+    }
+
+    let command = match (first.as_str(), args.get(1)) {
+        ("pin", Some(path)) => Command::Pin(PathBuf::from(path)),
+        ("unpin", Some(index)) => Command::Unpin(
+            index
+                .parse()
+                .with_context(|| format!("Invalid index '{}'", index))?,
+        ),
+        ("list", None) => Command::List,
+        _ => anyhow::bail!("Invalid arguments: {}\n\n{}", args.join(" "), USAGE),
+    };
+
+    Ok(Some(command))
+}
+
+/// Run a subcommand and return the process exit code
+pub fn run(command: Command) -> i32 {
+    let result = Settings::config_path()
+        .context("Failed to determine config directory")
+        .and_then(|config_path| execute(&command, &config_path, &mut ProfileManager::new()));
+    
+    // Only here, so tests running `execute` never signal a real dock
+    if result.is_ok() && matches!(command, Command::Pin(_) | Command::Unpin(_)) {
+        notify_running_instance();
+    }
+    
+    match result {
+        Ok(output) => {
+            if !output.is_empty() {
+                println!("{}", output);
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            1
+        }
+    }
+}
+
+/// Execute a subcommand against the active profile of the configuration at `config_path`
+fn execute(command: &Command, config_path: &Path, profiles: &mut ProfileManager) -> Result<String> {
+    match command {
+        Command::Pin(path) => {
+            let app = build_pinned_app(path)?;
+            let mut settings = Settings::load_active_from(config_path, profiles)?;
+            if settings.pinned_apps.iter().any(|pinned| pinned.command == app.command) {
+                anyhow::bail!("'{}' is already pinned", app.name);
+            }
+            let name = app.name.clone();
+            settings.pinned_apps.push(app);
+            settings.save_active_to(config_path, profiles)?;
+            Ok(format!("Pinned '{}'", name))
+        }
+        Command::Unpin(index) => {
+            let mut settings = Settings::load_active_from(config_path, profiles)?;
+            if *index >= settings.pinned_apps.len() {
+                anyhow::bail!("No pinned app at index {}", index);
+            }
+            let app = settings.pinned_apps.remove(*index);
+            settings.save_active_to(config_path, profiles)?;
+            Ok(format!("Unpinned '{}'", app.name))
+        }
+        Command::List => Ok(format_pinned_apps(&Settings::load_active_from(config_path, profiles)?)),
+        Command::Check => {
+            let (report, ok) = check_report(config_path, profiles, &probe_environment());
+            if ok {
+                Ok(report)
+            } else {
+                anyhow::bail!("{}", report)
+            }
+        }
+    }
+}
+
+/// What `--check` found out about the session
+struct Environment {
+    desktop: DesktopEnvironment,
+    screenshot_tool: ScreenshotTool,
+    /// `None` when there is no display to ask
+    layer_shell: Option<bool>,
+}
+
+/// Detect the desktop, screenshot tool and layer-shell support
+fn probe_environment() -> Environment {
+    let layer_shell = match gtk::init() {
+        Ok(()) => Some(gtk4_layer_shell::is_supported()),
+        Err(e) => {
+            debug!("GTK could not be initialized: {}", e);
+            None
+        }
+    };
+
+    Environment {
+        desktop: WindowTracker::detect_desktop_environment(),
+        screenshot_tool: ScreencopyService::detect_screenshot_tool(),
+        layer_shell,
+    }
+}
+
+/// Build the `--check` report for the config at `config_path`
+///
+/// Returns the report and whether the configuration is usable. A missing
+/// config is fine, since the defaults are used; the environment is only
+/// informational, as the dock falls back to a floating window. When the
+/// config selects another profile, that profile's file is checked too.
+fn check_report(config_path: &Path, profiles: &ProfileManager, environment: &Environment) -> (String, bool) {
+    let mut lines = Vec::new();
+    let mut ok = true;
+
+    if config_path.exists() {
+        match Settings::load_from(config_path) {
+            Ok(settings) => {
+                ok &= push_problems(&mut lines, "Configuration", config_path, &settings);
+                
+                let name = settings.active_profile.as_str();
+                if name == "default" {
+                    lines.push(format!("Pinned apps: {}", settings.pinned_apps.len()));
+                } else {
+                    let label = format!("Profile '{}'", name);
+                    let path = profiles.profile_path(name);
+                    match profiles.read_profile(name) {
+                        Ok(profile) => {
+                            ok &= push_problems(&mut lines, &label, &path, &profile.settings);
+                            lines.push(format!("Pinned apps: {}", profile.settings.pinned_apps.len()));
+                        }
+                        Err(e) => {
+                            ok = false;
+                            lines.push(format!("{}: {} (invalid: {})", label, path.display(), e));
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                ok = false;
+                lines.push(format!("Configuration: {} (invalid: {:#})", config_path.display(), e));
+            }
+        }
+    } else {
+        lines.push(format!("Configuration: {} (not found, using defaults)", config_path.display()));
+    }
+
+    lines.push(format!("Desktop environment: {:?}", environment.desktop));
+    lines.push(format!("Screenshot tool: {:?}", environment.screenshot_tool));
+    lines.push(format!("Layer shell: {}", match environment.layer_shell {
+        Some(true) => "supported",
+        Some(false) => "not supported (floating window mode)",
+        None => "unknown (no display)",
+    }));
+
+    lines.push(if ok { "Result: OK" } else { "Result: FAILED" }.to_string());
+    (lines.join("\n"), ok)
+}
+
+/// Add the `label` line for settings loaded from `path`, with any problems
+///
+/// Returns whether the settings are usable.
+fn push_problems(lines: &mut Vec<String>, label: &str, path: &Path, settings: &Settings) -> bool {
+    let problems = settings.validate();
+    if problems.is_empty() {
+        lines.push(format!("{}: {} (ok)", label, path.display()));
+    } else {
+        lines.push(format!("{}: {} ({} problems)", label, path.display(), problems.len()));
+        lines.extend(problems.iter().map(|problem| format!("  - {}", problem)));
+    }
+    problems.is_empty()
+}
+
+/// Build a pinned app entry from a .desktop file
+fn build_pinned_app(path: &Path) -> Result<PinnedApp> {
+    if !path.is_file() {
+        anyhow::bail!("Desktop file not found: {}", path.display());
+    }
+
+    let entry = DesktopEntry::parse(path)?;
+    let name = entry.localized_name().unwrap_or_else(|| "Unknown".to_string());
+    let command = entry
+        .exec_command()
+        .with_context(|| format!("Desktop file has no Exec entry: {}", path.display()))?;
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+    Ok(PinnedApp {
+        name,
+        icon: entry.localized_icon().unwrap_or_else(|| "application-x-executable".to_string()),
+        command,
+        desktop_file: Some(path.to_string_lossy().into_owned()),
+        env: Default::default(),
+        label: None,
+    })
+}
+
+/// Format the pinned apps as `<index>: <name> (<command>)` lines
+fn format_pinned_apps(settings: &Settings) -> String {
+    settings
+        .pinned_apps
+        .iter()
+        .enumerate()
+        .map(|(i, app)| format!("{}: {} ({})", i, app.name, app.command))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Ask a running dock to reload its configuration via SIGHUP
+fn notify_running_instance() {
+    let own_pid = std::process::id().to_string();
+
+    let pids = match ProcessCommand::new("pgrep").arg("-x").arg("blazedock").output() {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).into_owned(),
+        Err(e) => {
+            warn!("Failed to look up running dock: {}", e);
+            return;
+        }
+    };
+
+    for pid in pids.lines().map(str::trim).filter(|pid| *pid != own_pid) {
+        match ProcessCommand::new("kill").arg("-HUP").arg(pid).status() {
+            Ok(status) if status.success() => info!("Requested reload from dock (PID: {})", pid),
+            _ => warn!("Failed to signal dock (PID: {})", pid),
+        }
+    }
+
+    debug!("Running instances notified");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse_args(&[]).unwrap(), None);
+        assert_eq!(parse_args(&args(&["--verbose"])).unwrap(), None);
+        assert_eq!(parse_args(&args(&["list"])).unwrap(), Some(Command::List));
+        assert_eq!(parse_args(&args(&["--check"])).unwrap(), Some(Command::Check));
+        assert_eq!(parse_args(&args(&["unpin", "2"])).unwrap(), Some(Command::Unpin(2)));
+        assert_eq!(
+            parse_args(&args(&["pin", "app.desktop"])).unwrap(),
+            Some(Command::Pin(PathBuf::from("app.desktop")))
+        );
+        assert!(parse_args(&args(&["unpin", "abc"])).is_err());
+        assert!(parse_args(&args(&["pin"])).is_err());
+        assert!(parse_args(&args(&["frobnicate"])).is_err());
+    }
+
+    #[test]
+    fn test_check_report() {
+        let root = std::env::temp_dir().join(format!("blazedock-check-test-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let config = root.join("blazedock.toml");
+        let mut profiles = ProfileManager::with_dir(root.join("profiles"));
+        let environment = Environment {
+            desktop: DesktopEnvironment::Sway,
+            screenshot_tool: ScreenshotTool::None,
+            layer_shell: Some(false),
+        };
+
+        // Missing config means defaults
+        let (report, ok) = check_report(&config, &profiles, &environment);
+        assert!(ok);
+        assert!(report.contains("not found, using defaults"));
+        assert!(report.contains("Desktop environment: Sway"));
+        assert!(report.contains("Layer shell: not supported"));
+
+        fs::write(&config, "icon_size = 48\npinned_apps = []\n").unwrap();
+        let (report, ok) = check_report(&config, &profiles, &environment);
+        assert!(ok, "{}", report);
+        assert!(report.contains("(ok)"));
+        assert!(report.ends_with("Result: OK"));
+
+        fs::write(&config, "icon_size = 4\npinned_apps = []\n").unwrap();
+        let (report, ok) = check_report(&config, &profiles, &environment);
+        assert!(!ok);
+        assert!(report.contains("  - icon_size 4"));
+
+        fs::write(&config, "icon_size = \"big\"\n").unwrap();
+        let (report, ok) = check_report(&config, &profiles, &environment);
+        assert!(!ok);
+        assert!(report.contains("invalid"));
+        assert!(report.ends_with("Result: FAILED"));
+
+        // The active profile's file is checked as well
+        let mut work = Settings::default();
+        work.icon_size = 300;
+        profiles.create_profile("work", None, Some(work)).unwrap();
+        fs::write(&config, "icon_size = 48\nactive_profile = \"work\"\n").unwrap();
+        let (report, ok) = check_report(&config, &profiles, &environment);
+        assert!(!ok);
+        assert!(report.contains("Profile 'work'"));
+        assert!(report.contains("  - icon_size 300"));
+
+        fs::write(profiles.profile_path("work"), "not a profile").unwrap();
+        let (report, ok) = check_report(&config, &profiles, &environment);
+        assert!(!ok);
+        assert!(report.contains("Profile 'work'") && report.contains("invalid"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_pin_missing_desktop_file() {
+        let root = std::env::temp_dir().join(format!("blazedock-cli-missing-test-{}", std::process::id()));
+        let mut profiles = ProfileManager::with_dir(root.join("profiles"));
+        let result = execute(
+            &Command::Pin(PathBuf::from("/nonexistent/app.desktop")),
+            &root.join("config.toml"),
+            &mut profiles,
+        );
+        assert!(result.unwrap_err().to_string().contains("not found"));
+        assert!(!root.join("config.toml").exists());
+        let _ = fs::remove_dir_all(&root);
+        assert_eq!(run(Command::Pin(PathBuf::from("/nonexistent/app.desktop"))), 1);
+    }
+
+    #[test]
+    fn test_pin_list_unpin_roundtrip() {
+        let root = std::env::temp_dir().join(format!("blazedock-cli-test-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let config = root.join("config.toml");
+        let mut profiles = ProfileManager::with_dir(root.join("profiles"));
+
+        let desktop = root.join("editor.desktop");
+        fs::write(
+            &desktop,
+            "[Desktop Entry]\nType=Application\nName=Editor\nIcon=editor\nExec=editor %F\n",
+        )
+        .unwrap();
+
+        let before = Settings::default().pinned_apps.len();
+
+        assert_eq!(execute(&Command::Pin(desktop.clone()), &config, &mut profiles).unwrap(), "Pinned 'Editor'");
+        let settings = Settings::load_from(&config).unwrap();
+        assert_eq!(settings.pinned_apps.len(), before + 1);
+        let app = settings.pinned_apps.last().unwrap();
+        assert_eq!(app.command, "editor");
+        assert_eq!(app.icon, "editor");
+
+        // Pinning the same command twice is refused
+        assert!(execute(&Command::Pin(desktop.clone()), &config, &mut profiles).is_err());
+        assert_eq!(Settings::load_from(&config).unwrap().pinned_apps.len(), before + 1);
+
+        let listing = execute(&Command::List, &config, &mut profiles).unwrap();
+        assert!(listing.ends_with(&format!("{}: Editor (editor)", before)));
+
+        assert_eq!(execute(&Command::Unpin(before), &config, &mut profiles).unwrap(), "Unpinned 'Editor'");
+        assert_eq!(Settings::load_from(&config).unwrap().pinned_apps.len(), before);
+        assert!(execute(&Command::Unpin(before + 10), &config, &mut profiles).is_err());
+
+        // A config that can't be written fails the pin instead of just logging
+        let blocker = root.join("blocker");
+        fs::write(&blocker, "").unwrap();
+        assert!(execute(&Command::Pin(desktop.clone()), &blocker.join("config.toml"), &mut profiles).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -6,8 +6,13 @@ mod settings;
 pub mod profiles;
 
 pub use settings::Settings;
+pub use settings::find_icon_override;
 pub use settings::DockPosition;
 pub use settings::PinnedApp;
+pub use settings::ShortcutConfig;
 pub use settings::MultiMonitorMode;
-pub use profiles::{Profile, ProfileManager, ProfileMeta};
+pub use settings::HideMode;
+pub use settings::IndicatorStyle;
+pub use settings::{StackConfig, StackSortMode, StackViewMode};
+pub use profiles::{AutoSwitchCondition, Profile, ProfileManager, ProfileMeta};
 
@@ -5,7 +5,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use log::{info, debug, error, warn};
 use directories::ProjectDirs;
 
@@ -28,19 +28,50 @@ pub struct Profile {
     pub settings: Settings,
 }
 
+/// File in the profiles directory holding the auto-switch rules
+const AUTO_SWITCH_FILE: &str = "auto-switch.toml";
+
+/// When an auto-switch rule applies
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum AutoSwitchCondition {
+    /// While a process with this name is running
+    ProcessRunning { process: String },
+    /// Between two local times (`HH:MM`); may wrap past midnight
+    TimeOfDay { from: String, to: String },
+}
+
+/// Switch to `profile` while `when` holds
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AutoSwitchRule {
+    pub profile: String,
+    pub when: AutoSwitchCondition,
+}
+
+/// On-disk layout of the auto-switch file
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AutoSwitchFile {
+    #[serde(default)]
+    rules: Vec<AutoSwitchRule>,
+}
+
 /// Profile manager for handling multiple configurations
 #[derive(Clone)]
 pub struct ProfileManager {
     profiles_dir: PathBuf,
     current_profile: String,
     profiles: HashMap<String, Profile>,
+    auto_switch_rules: Vec<AutoSwitchRule>,
 }
 
 impl ProfileManager {
     /// Create a new profile manager
     pub fn new() -> Self {
-        let profiles_dir = Self::get_profiles_dir();
-        
+        Self::with_dir(Self::get_profiles_dir())
+    }
+
+    /// Create a profile manager that keeps its profiles in `profiles_dir`
+    pub fn with_dir(profiles_dir: PathBuf) -> Self {
         // Ensure profiles directory exists
         if !profiles_dir.exists() {
             if let Err(e) = fs::create_dir_all(&profiles_dir) {
@@ -52,9 +83,11 @@ impl ProfileManager {
             profiles_dir,
             current_profile: "default".to_string(),
             profiles: HashMap::new(),
+            auto_switch_rules: Vec::new(),
         };
 
         manager.load_all_profiles();
+        manager.load_auto_switch_rules();
         manager.ensure_default_profile();
         
         manager
@@ -77,6 +110,9 @@ impl ProfileManager {
         if let Ok(entries) = fs::read_dir(&self.profiles_dir) {
             for entry in entries.flatten() {
                 let path = entry.path();
+                if path.file_name().map_or(false, |n| n == AUTO_SWITCH_FILE) {
+                    continue;
+                }
                 if path.extension().map_or(false, |e| e == "toml") {
                     if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
                         if let Ok(profile) = self.load_profile_from_file(&path) {
@@ -91,10 +127,30 @@ impl ProfileManager {
         info!("Loaded {} profiles", self.profiles.len());
     }
 
+    /// Load the auto-switch rules, if any are configured
+    fn load_auto_switch_rules(&mut self) {
+        let path = self.profiles_dir.join(AUTO_SWITCH_FILE);
+        let Ok(content) = fs::read_to_string(&path) else {
+            return;
+        };
+
+        match toml::from_str::<AutoSwitchFile>(&content) {
+            Ok(file) => {
+                info!("Loaded {} auto-switch rules", file.rules.len());
+                self.auto_switch_rules = file.rules;
+            }
+            Err(e) => warn!("Invalid auto-switch rules in {}: {}", path.display(), e),
+        }
+    }
+
     /// Load a single profile from file
     fn load_profile_from_file(&self, path: &PathBuf) -> Result<Profile, Box<dyn std::error::Error>> {
         let content = fs::read_to_string(path)?;
-        let profile: Profile = toml::from_str(&content)?;
+        let mut profile: Profile = toml::from_str(&content)?;
+
+        // Older profiles stored bare Unix seconds
+        profile.meta.created_at = normalize_timestamp(&profile.meta.created_at);
+        profile.meta.last_used = profile.meta.last_used.as_deref().map(normalize_timestamp);
         Ok(profile)
     }
 
@@ -118,6 +174,17 @@ impl ProfileManager {
         }
     }
 
+    /// Path of profile `name`'s file
+    pub fn profile_path(&self, name: &str) -> PathBuf {
+        self.profiles_dir.join(format!("{}.toml", name))
+    }
+
+    /// Read profile `name` from its file rather than the loaded copy
+    pub fn read_profile(&self, name: &str) -> Result<Profile, String> {
+        self.load_profile_from_file(&self.profile_path(name))
+            .map_err(|e| e.to_string())
+    }
+
     /// Save a profile to disk
     pub fn save_profile(&self, name: &str, profile: &Profile) -> Result<(), Box<dyn std::error::Error>> {
         let path = self.profiles_dir.join(format!("{}.toml", name));
@@ -186,6 +253,12 @@ impl ProfileManager {
 
         self.current_profile = name.to_string();
         
+        // Pick up edits saved since it was loaded, e.g. by `Settings::save_active`
+        let path = self.profiles_dir.join(format!("{}.toml", name));
+        if let Ok(profile) = self.load_profile_from_file(&path) {
+            self.profiles.insert(name.to_string(), profile);
+        }
+        
         // Update last_used timestamp
         if let Some(profile) = self.profiles.get_mut(name) {
             profile.meta.last_used = Some(chrono_lite_now());
@@ -204,6 +277,48 @@ impl ProfileManager {
         Ok(settings)
     }
 
+    /// Resolve the settings to start with from the main configuration
+    ///
+    /// `base.active_profile` selects the profile. The default profile is
+    /// the main configuration itself, which is also the fallback when the
+    /// active profile no longer exists.
+    pub fn startup_settings(&mut self, base: &Settings) -> Settings {
+        match self.switch_active(&base.active_profile, base) {
+            Ok(settings) => settings,
+            Err(e) => {
+                warn!("{}, falling back to the default profile", e);
+                self.current_profile = "default".to_string();
+                Settings {
+                    active_profile: "default".to_string(),
+                    ..base.clone()
+                }
+            }
+        }
+    }
+
+    /// Switch to profile `name` with the main configuration `base`
+    ///
+    /// The default profile's settings are `base` itself; others come from
+    /// their profile file. `active_profile` is set to `name` either way.
+    pub fn switch_active(&mut self, name: &str, base: &Settings) -> Result<Settings, String> {
+        if name == "default" {
+            self.current_profile = name.to_string();
+            return Ok(Settings {
+                active_profile: name.to_string(),
+                ..base.clone()
+            });
+        }
+
+        let mut settings = self.switch_profile(name)?;
+        settings.active_profile = name.to_string();
+        Ok(settings)
+    }
+
+    /// Rules for switching profiles automatically
+    pub fn auto_switch_rules(&self) -> &[AutoSwitchRule] {
+        &self.auto_switch_rules
+    }
+
     /// Get current profile name
     pub fn current_profile_name(&self) -> &str {
         &self.current_profile
@@ -219,20 +334,43 @@ impl ProfileManager {
     /// Update settings in current profile
     pub fn update_current_settings(&mut self, settings: Settings) -> Result<(), String> {
         let current_profile_name = self.current_profile.clone();
-        
-        if let Some(profile) = self.profiles.get_mut(&current_profile_name) {
-            profile.settings = settings;
-        } else {
-            return Err("No current profile".to_string());
+        self.update_profile_settings(&current_profile_name, settings)
+    }
+
+    /// Replace the settings of profile `name` and save it
+    pub fn update_profile_settings(&mut self, name: &str, settings: Settings) -> Result<(), String> {
+        match self.profiles.get_mut(name) {
+            Some(profile) => profile.settings = settings,
+            None => return Err(format!("Profile '{}' does not exist", name)),
         }
         
         // Save profile (after releasing the mutable borrow)
-        if let Some(profile) = self.profiles.get(&current_profile_name) {
-            self.save_profile(&current_profile_name, profile)
-                .map_err(|e| e.to_string())?;
+        self.save_profile(name, &self.profiles[name])
+            .map_err(|e| e.to_string())
+    }
+
+    /// Settings of the profile that `base.active_profile` selects
+    ///
+    /// Like `startup_settings`, but leaves the current profile and the
+    /// profile files alone.
+    pub fn active_settings(&self, base: &Settings) -> Settings {
+        let name = base.active_profile.as_str();
+        if name != "default" {
+            match self.profiles.get(name) {
+                Some(profile) => {
+                    return Settings {
+                        active_profile: name.to_string(),
+                        ..profile.settings.clone()
+                    };
+                }
+                None => warn!("Profile '{}' does not exist, falling back to the default profile", name),
+            }
+        }
+
+        Settings {
+            active_profile: "default".to_string(),
+            ..base.clone()
         }
-        
-        Ok(())
     }
 
     /// List all available profiles
@@ -276,6 +414,58 @@ impl ProfileManager {
         Ok(())
     }
 
+    /// Write a profile to `dest` so it can be shared
+    pub fn export_profile(&self, name: &str, dest: &Path) -> Result<(), String> {
+        let profile = self.profiles.get(name)
+            .ok_or_else(|| format!("Profile '{}' does not exist", name))?;
+
+        let content = toml::to_string_pretty(profile).map_err(|e| e.to_string())?;
+        fs::write(dest, content)
+            .map_err(|e| format!("Failed to write {}: {}", dest.display(), e))?;
+
+        info!("Exported profile '{}' to {}", name, dest.display());
+        Ok(())
+    }
+
+    /// Import a profile exported with `export_profile`
+    ///
+    /// The profile is saved as `name`, or under the file name if none is
+    /// given. A numeric suffix is appended if that name is taken.
+    /// Returns the name it was saved under.
+    pub fn import_profile(&mut self, src: &Path, name: Option<&str>) -> Result<String, String> {
+        let content = fs::read_to_string(src)
+            .map_err(|e| format!("Failed to read {}: {}", src.display(), e))?;
+        let mut profile: Profile = toml::from_str(&content)
+            .map_err(|e| format!("Invalid profile {}: {}", src.display(), e))?;
+
+        let requested = name
+            .map(str::to_string)
+            .or_else(|| src.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+            .map(|name| sanitize_profile_name(&name))
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| format!("No profile name for {}", src.display()))?;
+        let name = self.unique_profile_name(&requested);
+
+        profile.meta.last_used = None;
+        self.save_profile(&name, &profile)
+            .map_err(|e| format!("Failed to save profile: {}", e))?;
+        self.profiles.insert(name.clone(), profile);
+
+        info!("Imported profile '{}' from {}", name, src.display());
+        Ok(name)
+    }
+
+    /// `name`, or `name-2`, `name-3`, ... if it is already taken
+    fn unique_profile_name(&self, name: &str) -> String {
+        if !self.profiles.contains_key(name) {
+            return name.to_string();
+        }
+        (2..)
+            .map(|n| format!("{}-{}", name, n))
+            .find(|candidate| !self.profiles.contains_key(candidate))
+            .unwrap_or_default()
+    }
+
     /// Create preset profiles
     pub fn create_presets(&mut self) {
         // Work profile - minimal distractions
@@ -319,6 +509,45 @@ impl Default for ProfileManager {
     }
 }
 
+/// Profile of the first rule that matches, if any
+///
+/// `is_running` reports whether a process is running and `minute_of_day`
+/// is the local time in minutes since midnight.
+pub fn evaluate_auto_switch<'a>(
+    rules: &'a [AutoSwitchRule],
+    is_running: impl Fn(&str) -> bool,
+    minute_of_day: u32,
+) -> Option<&'a str> {
+    rules.iter()
+        .find(|rule| match &rule.when {
+            AutoSwitchCondition::ProcessRunning { process } => is_running(process),
+            AutoSwitchCondition::TimeOfDay { from, to } => {
+                match (parse_clock_time(from), parse_clock_time(to)) {
+                    (Some(from), Some(to)) if from <= to => (from..to).contains(&minute_of_day),
+                    // Wraps past midnight, e.g. 22:00-06:00
+                    (Some(from), Some(to)) => minute_of_day >= from || minute_of_day < to,
+                    _ => false,
+                }
+            }
+        })
+        .map(|rule| rule.profile.as_str())
+}
+
+/// Parse `HH:MM` into minutes since midnight
+fn parse_clock_time(time: &str) -> Option<u32> {
+    let (hours, minutes) = time.trim().split_once(':')?;
+    let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+/// Reduce a name to characters that are safe in a profile file name
+fn sanitize_profile_name(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect()
+}
+
 /// Simple timestamp function (avoids chrono dependency)
 fn chrono_lite_now() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -327,6 +556,264 @@ fn chrono_lite_now() -> String {
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default();
     
-    format!("{}", duration.as_secs())
+    format_timestamp(duration.as_secs())
 }
 
+/// Format Unix seconds as ISO-8601 UTC (`YYYY-MM-DDTHH:MM:SSZ`)
+pub fn format_timestamp(secs: u64) -> String {
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let time = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, time / 3600, time % 3600 / 60, time % 60
+    )
+}
+
+/// Parse a timestamp written by `format_timestamp`, or bare Unix seconds
+pub fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    let timestamp = timestamp.trim();
+    if let Ok(secs) = timestamp.parse() {
+        return Some(secs);
+    }
+
+    let (date, time) = timestamp.strip_suffix('Z')?.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let mut time = time.splitn(3, ':').map(str::parse::<u64>);
+    let (hour, minute, second) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+    Some(days * 86_400 + hour * 3600 + minute * 60 + second)
+}
+
+/// Rewrite a timestamp as ISO-8601, leaving unparseable ones alone
+fn normalize_timestamp(timestamp: &str) -> String {
+    parse_timestamp(timestamp)
+        .map(format_timestamp)
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+/// Convert days since 1970-01-01 to a (year, month, day) date
+///
+/// Howard Hinnant's algorithm over 400-year eras of the proleptic
+/// Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Inverse of `civil_from_days`
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * mp + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_switch_between_profiles() {
+        let dir = std::env::temp_dir().join(format!("blazedock-profiles-test-{}", std::process::id()));
+        let mut manager = ProfileManager::with_dir(dir.clone());
+
+        let mut large = Settings::default();
+        large.icon_size = 64;
+        let mut left = Settings::default();
+        left.position = DockPosition::Left;
+        manager.create_profile("large", None, Some(large)).unwrap();
+        manager.create_profile("left", None, Some(left)).unwrap();
+
+        let mut names: Vec<&str> = manager.list_profiles().into_iter().map(|(name, _)| name).collect();
+        names.sort();
+        assert_eq!(names, ["default", "large", "left"]);
+
+        let first = manager.switch_profile("large").unwrap();
+        let second = manager.switch_profile("left").unwrap();
+        assert_eq!(manager.current_profile_name(), "left");
+        assert_eq!((first.icon_size, first.position), (64, DockPosition::Bottom));
+        assert_eq!((second.icon_size, second.position), (48, DockPosition::Left));
+        assert!(manager.switch_profile("missing").is_err());
+
+        // Profiles were written to the directory and load back
+        let reloaded = ProfileManager::with_dir(dir.clone());
+        assert_eq!(reloaded.get_profile("large").unwrap().settings.icon_size, 64);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("blazedock-export-test-{}", std::process::id()));
+        let mut manager = ProfileManager::with_dir(dir.join("profiles"));
+
+        let mut work = Settings::default();
+        work.icon_size = 40;
+        work.auto_hide = true;
+        manager.create_profile("work", Some("Focus"), Some(work)).unwrap();
+
+        let shared = dir.join("work.toml");
+        manager.export_profile("work", &shared).unwrap();
+        assert!(manager.export_profile("missing", &shared).is_err());
+
+        // "work" is taken, so the import gets a suffix
+        assert_eq!(manager.import_profile(&shared, None).unwrap(), "work-2");
+        assert_eq!(manager.import_profile(&shared, None).unwrap(), "work-3");
+        assert_eq!(manager.import_profile(&shared, Some("team work")).unwrap(), "team-work");
+
+        let imported = manager.get_profile("work-2").unwrap();
+        assert_eq!(imported.meta.description.as_deref(), Some("Focus"));
+        assert_eq!(imported.settings.icon_size, 40);
+        assert!(imported.settings.auto_hide);
+
+        fs::write(dir.join("broken.toml"), "not a profile").unwrap();
+        assert!(manager.import_profile(&dir.join("broken.toml"), None).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_auto_switch_rules() {
+        let rules = vec![
+            AutoSwitchRule {
+                profile: "gaming".to_string(),
+                when: AutoSwitchCondition::ProcessRunning { process: "steam".to_string() },
+            },
+            AutoSwitchRule {
+                profile: "work".to_string(),
+                when: AutoSwitchCondition::TimeOfDay { from: "9:00".to_string(), to: "17:00".to_string() },
+            },
+            AutoSwitchRule {
+                profile: "night".to_string(),
+                when: AutoSwitchCondition::TimeOfDay { from: "22:00".to_string(), to: "06:30".to_string() },
+            },
+        ];
+        let steam = |process: &str| process == "steam";
+        let nothing = |_: &str| false;
+
+        // Earlier rules win
+        assert_eq!(evaluate_auto_switch(&rules, steam, 10 * 60), Some("gaming"));
+        assert_eq!(evaluate_auto_switch(&rules, nothing, 9 * 60), Some("work"));
+        assert_eq!(evaluate_auto_switch(&rules, nothing, 17 * 60), None);
+        assert_eq!(evaluate_auto_switch(&rules, nothing, 23 * 60), Some("night"));
+        assert_eq!(evaluate_auto_switch(&rules, nothing, 6 * 60), Some("night"));
+        assert_eq!(evaluate_auto_switch(&rules, nothing, 7 * 60), None);
+
+        assert_eq!(parse_clock_time("24:00"), None);
+        assert_eq!(parse_clock_time("noon"), None);
+    }
+
+    #[test]
+    fn test_auto_switch_rules_file() {
+        let dir = std::env::temp_dir().join(format!("blazedock-rules-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join(AUTO_SWITCH_FILE),
+            "[[rules]]\nprofile = \"gaming\"\nwhen = { process = \"steam\" }\n\n\
+             [[rules]]\nprofile = \"work\"\nwhen = { from = \"09:00\", to = \"17:00\" }\n",
+        )
+        .unwrap();
+
+        let manager = ProfileManager::with_dir(dir.clone());
+        assert_eq!(manager.auto_switch_rules().len(), 2);
+        assert_eq!(
+            manager.auto_switch_rules()[0].when,
+            AutoSwitchCondition::ProcessRunning { process: "steam".to_string() }
+        );
+        // The rules file is not a profile
+        assert!(manager.get_profile("auto-switch").is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(946_684_799), "1999-12-31T23:59:59Z");
+        assert_eq!(format_timestamp(946_684_800), "2000-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_timestamp(1_700_000_000), "2023-11-14T22:13:20Z");
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        for secs in [0, 946_684_800, 951_782_400, 1_700_000_000] {
+            assert_eq!(parse_timestamp(&format_timestamp(secs)), Some(secs));
+        }
+        // Older profiles stored bare seconds
+        assert_eq!(parse_timestamp("1700000000"), Some(1_700_000_000));
+        assert_eq!(normalize_timestamp("946684800"), "2000-01-01T00:00:00Z");
+        assert_eq!(parse_timestamp("2000-13-01T00:00:00Z"), None);
+        assert_eq!(parse_timestamp("yesterday"), None);
+    }
+
+    #[test]
+    fn test_switch_active_reads_saved_edits() {
+        let dir = std::env::temp_dir().join(format!("blazedock-switch-active-test-{}", std::process::id()));
+        let mut manager = ProfileManager::with_dir(dir.clone());
+        manager.create_profile("work", None, None).unwrap();
+        let mut base = Settings::default();
+        base.icon_size = 56;
+
+        // Another manager saves an edit to the work profile
+        let mut other = ProfileManager::with_dir(dir.clone());
+        let mut work = other.get_profile("work").unwrap().settings.clone();
+        work.icon_size = 40;
+        other.update_profile_settings("work", work).unwrap();
+
+        let settings = manager.switch_active("work", &base).unwrap();
+        assert_eq!((settings.icon_size, settings.active_profile.as_str()), (40, "work"));
+
+        // The default profile is the main configuration, not its profile file
+        let settings = manager.switch_active("default", &base).unwrap();
+        assert_eq!((settings.icon_size, settings.active_profile.as_str()), (56, "default"));
+        assert_eq!(manager.current_profile_name(), "default");
+        assert!(manager.switch_active("missing", &base).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_startup_uses_active_profile() {
+        let dir = std::env::temp_dir().join(format!("blazedock-startup-test-{}", std::process::id()));
+        let mut manager = ProfileManager::with_dir(dir.clone());
+
+        let mut work = Settings::default();
+        work.icon_size = 40;
+        manager.create_profile("work", None, Some(work)).unwrap();
+
+        let mut base = Settings::default();
+        base.icon_size = 56;
+        base.active_profile = "work".to_string();
+        let settings = manager.startup_settings(&base);
+        assert_eq!(settings.icon_size, 40);
+        assert_eq!(settings.active_profile, "work");
+        assert_eq!(manager.current_profile_name(), "work");
+
+        // A deleted profile falls back to the main configuration
+        base.active_profile = "gone".to_string();
+        let settings = manager.startup_settings(&base);
+        assert_eq!(settings.icon_size, 56);
+        assert_eq!(settings.active_profile, "default");
+        assert_eq!(manager.current_profile_name(), "default");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -7,8 +7,11 @@ use anyhow::{Context, Result};
 use directories::ProjectDirs;
 use log::{debug, warn};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use crate::config::profiles::ProfileManager;
 
 /// Configuration file name
 const CONFIG_FILE: &str = "blazedock.toml";
@@ -36,6 +39,71 @@ pub struct PinnedApp {
     /// Optional .desktop file path for richer integration
     #[serde(skip_serializing_if = "Option::is_none")]
     pub desktop_file: Option<String>,
+    /// Extra environment variables applied on top of the dock's environment at launch
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    /// Tooltip label shown instead of `name`, e.g. to tell two profiles of one app apart
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl PinnedApp {
+    /// Text shown in the item's tooltip: the label if set, otherwise the name
+    pub fn display_label(&self) -> &str {
+        self.label.as_deref().filter(|label| !label.is_empty()).unwrap_or(&self.name)
+    }
+}
+
+/// A keyboard shortcut binding from the configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ShortcutConfig {
+    /// Modifiers joined with '+', e.g. "Super" or "Ctrl+Shift" (may be empty)
+    #[serde(default)]
+    pub modifiers: String,
+    /// GDK key name, e.g. "d", "slash" or "F1"
+    pub key: String,
+    /// Action name, e.g. "ToggleDock", "ShowSearch" or "ActivateApp3"
+    pub action: String,
+}
+
+/// Settings that replace the base settings for one monitor
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MonitorOverride {
+    /// Dock position on this monitor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<DockPosition>,
+    /// Icon size in pixels on this monitor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_size: Option<u32>,
+    /// Names or commands of the pinned apps shown on this monitor (all if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_apps: Option<Vec<String>>,
+}
+
+/// When the dock hides itself
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HideMode {
+    /// Always visible
+    #[default]
+    Never,
+    /// Hide whenever the pointer leaves the dock
+    AutoHide,
+    /// Hide only while a window overlaps the dock
+    IntelliHide,
+}
+
+/// How running apps are marked under their icon
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum IndicatorStyle {
+    /// One dot per window, up to three
+    #[default]
+    Dots,
+    /// A thin bar that widens with the window count
+    Line,
+    /// A thicker rounded bar that widens with the window count
+    Pill,
 }
 
 /// Multi-monitor mode
@@ -49,6 +117,68 @@ pub enum MultiMonitorMode {
     PerMonitor,
 }
 
+/// Popup layout for a folder stack
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StackViewMode {
+    #[default]
+    Fan,
+    Grid,
+    List,
+}
+
+/// Order of the entries in a folder stack
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StackSortMode {
+    /// Most recently modified first
+    #[default]
+    DateDesc,
+    /// Alphabetical by name
+    NameAsc,
+    /// Largest first
+    SizeDesc,
+    /// Folders first, then by extension, then by name
+    TypeThenName,
+}
+
+/// A folder stack shown at the end of the dock
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StackConfig {
+    /// Folder to show; a leading `~/` is expanded to the home directory
+    pub path: String,
+    /// Icon name (from theme) for the dock item
+    #[serde(default = "StackConfig::default_icon")]
+    pub icon: String,
+    /// Popup layout
+    #[serde(default)]
+    pub view_mode: StackViewMode,
+    /// Order of the listed entries
+    #[serde(default)]
+    pub sort_mode: StackSortMode,
+    /// Most entries listed in the popup
+    #[serde(default = "StackConfig::default_max_items")]
+    pub max_items: usize,
+}
+
+impl StackConfig {
+    fn default_icon() -> String {
+        "folder".to_string()
+    }
+
+    fn default_max_items() -> usize {
+        20
+    }
+
+    /// The folder path with `~/` expanded
+    pub fn folder_path(&self) -> PathBuf {
+        match self.path.strip_prefix("~/") {
+            Some(rest) => PathBuf::from(std::env::var("HOME").unwrap_or_default()).join(rest),
+            None => PathBuf::from(&self.path),
+        }
+    }
+}
+
 /// Main settings structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -71,6 +201,9 @@ pub struct Settings {
     /// Enable auto-hide behavior
     pub auto_hide: bool,
     
+    /// When the dock hides; `auto_hide` turns `Never` into `AutoHide`
+    pub hide_mode: HideMode,
+    
     /// Auto-hide delay in milliseconds
     pub auto_hide_delay: u32,
     
@@ -89,12 +222,21 @@ pub struct Settings {
     /// Hover zoom scale factor
     pub hover_zoom_scale: f64,
     
+    /// How many neighbors on each side of the hovered item are partially magnified
+    pub magnification_spread: usize,
+    
     /// Multi-monitor mode
     pub multi_monitor_mode: MultiMonitorMode,
     
+    /// Per-monitor settings, keyed by connector name (e.g. "DP-1")
+    pub monitor_overrides: HashMap<String, MonitorOverride>,
+    
     /// Enable keyboard shortcuts (Super+1-9)
     pub enable_shortcuts: bool,
     
+    /// Custom shortcut bindings, overriding the defaults for their actions
+    pub shortcuts: Vec<ShortcutConfig>,
+    
     /// Active profile name
     pub active_profile: String,
     
@@ -104,6 +246,12 @@ pub struct Settings {
     /// Enable window previews on hover
     pub enable_window_previews: bool,
     
+    /// How running apps are marked under their icon
+    pub indicator_style: IndicatorStyle,
+    
+    /// Clicking a running app focuses its window instead of launching it again
+    pub click_focuses_running: bool,
+    
     /// Theme mode (light/dark/system)
     pub theme_mode: String,
     
@@ -113,6 +261,28 @@ pub struct Settings {
     /// Show Downloads stack at end of dock
     pub show_downloads_stack: bool,
     
+    /// Show a recent files item before the stacks
+    pub show_recent_files: bool,
+    
+    /// Additional folder stacks, shown after the Downloads stack
+    pub stacks: Vec<StackConfig>,
+    
+    /// Show a dock item with the total unread count across apps
+    pub show_notifications_summary: bool,
+    
+    /// App IDs whose badge counts are left out of the summary
+    pub muted_badge_apps: Vec<String>,
+    
+    /// Window app_ids to match for a launch command (e.g. gnome-terminal -> org.gnome.Terminal)
+    pub app_id_aliases: HashMap<String, Vec<String>>,
+    
+    /// Icon name or path to use for a command, program or app_id, in place of its own
+    pub icon_overrides: HashMap<String, String>,
+    
+    /// Terminal emulator for `Terminal=true` apps (default: `$TERMINAL`, then the first one installed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminal: Option<String>,
+    
     /// List of pinned applications
     pub pinned_apps: Vec<PinnedApp>,
 }
@@ -126,20 +296,33 @@ impl Default for Settings {
             margin: 8,
             spacing: 8,
             auto_hide: false,
+            hide_mode: HideMode::Never,
             auto_hide_delay: 500,
             opacity: 0.85,
             border_radius: 16,
             exclusive_zone: false,
             hover_zoom: true,
             hover_zoom_scale: 1.15,
+            magnification_spread: 2,
             multi_monitor_mode: MultiMonitorMode::Primary,
+            monitor_overrides: HashMap::new(),
             enable_shortcuts: true,
+            shortcuts: Vec::new(),
             active_profile: "default".to_string(),
             show_running_apps: true,
             enable_window_previews: true,
+            indicator_style: IndicatorStyle::Dots,
+            click_focuses_running: true,
             theme_mode: "system".to_string(),
             show_trash: true,
             show_downloads_stack: true,
+            show_recent_files: false,
+            stacks: Vec::new(),
+            show_notifications_summary: false,
+            muted_badge_apps: Vec::new(),
+            app_id_aliases: Self::default_app_id_aliases(),
+            icon_overrides: HashMap::new(),
+            terminal: None,
             pinned_apps: Self::default_pinned_apps(),
         }
     }
@@ -166,7 +349,12 @@ impl Settings {
             return Ok(settings);
         }
 
-        let content = fs::read_to_string(&config_path)
+        Self::load_from(&config_path)
+    }
+
+    /// Load settings from `path` without creating it
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
             .context("Failed to read config file")?;
         
         let settings: Settings = toml::from_str(&content)
@@ -175,11 +363,128 @@ impl Settings {
         Ok(settings)
     }
 
+    /// Load the settings of the active profile
+    ///
+    /// The configuration file's `active_profile` selects the profile; the
+    /// default profile is the configuration file itself.
+    pub fn load_active() -> Result<Self> {
+        let base = Self::load()?;
+        Ok(ProfileManager::new().active_settings(&base))
+    }
+
+    /// Load the active profile's settings, with the configuration file at `config_path`
+    ///
+    /// A missing configuration file means the defaults, as with `load`.
+    pub fn load_active_from(config_path: &Path, profiles: &ProfileManager) -> Result<Self> {
+        let base = if config_path.exists() {
+            Self::load_from(config_path)?
+        } else {
+            Self::default()
+        };
+        Ok(profiles.active_settings(&base))
+    }
+
+    /// Describe the settings that are out of range or point at missing files
+    ///
+    /// An empty list means the settings are usable as they are.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        
+        if !(16..=256).contains(&self.icon_size) {
+            problems.push(format!("icon_size {} is outside 16-256", self.icon_size));
+        }
+        if self.dock_size < self.icon_size {
+            problems.push(format!(
+                "dock_size {} is smaller than icon_size {}",
+                self.dock_size, self.icon_size
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.opacity) {
+            problems.push(format!("opacity {} is outside 0.0-1.0", self.opacity));
+        }
+        if !(1.0..=3.0).contains(&self.hover_zoom_scale) {
+            problems.push(format!("hover_zoom_scale {} is outside 1.0-3.0", self.hover_zoom_scale));
+        }
+        
+        for app in &self.pinned_apps {
+            if app.command.trim().is_empty() {
+                problems.push(format!("pinned app '{}' has no command", app.name));
+            }
+            if let Some(desktop_file) = &app.desktop_file {
+                if !Path::new(desktop_file).exists() {
+                    problems.push(format!("desktop file of '{}' not found: {}", app.name, desktop_file));
+                }
+            }
+        }
+        
+        problems
+    }
+
     /// Save settings to the configuration file
     pub fn save(&self) -> Result<()> {
         let config_path = Self::config_path()
             .context("Failed to determine config directory")?;
-        
+        self.save_to(&config_path)
+    }
+
+    /// Save settings to the active profile
+    ///
+    /// Counterpart of `load_active`: the settings are written wherever
+    /// `active_profile` says they were loaded from.
+    pub fn save_active(&self) -> Result<()> {
+        let config_path = Self::config_path()
+            .context("Failed to determine config directory")?;
+        self.save_active_to(&config_path, &mut ProfileManager::new())
+    }
+
+    /// Save settings to the active profile, with the configuration file at `config_path`
+    ///
+    /// The default profile goes to the configuration file and other
+    /// profiles to their own file. The profile the dock starts with is
+    /// kept; it only changes when switching profiles.
+    pub fn save_active_to(&self, config_path: &Path, profiles: &mut ProfileManager) -> Result<()> {
+        let name = self.active_profile.as_str();
+        if name != "default" && profiles.get_profile(name).is_some() {
+            return profiles.update_profile_settings(name, self.clone())
+                .map_err(anyhow::Error::msg);
+        }
+
+        let startup_profile = Self::load_from(config_path)
+            .map(|settings| settings.active_profile)
+            .unwrap_or_else(|_| "default".to_string());
+        Settings {
+            active_profile: startup_profile,
+            ..self.clone()
+        }
+        .save_to(config_path)
+    }
+
+    /// Make `name` the profile the dock starts with
+    ///
+    /// Only `active_profile` changes in the configuration file, so the
+    /// default profile's settings stay as they are.
+    pub fn select_profile(name: &str) -> Result<()> {
+        let config_path = Self::config_path()
+            .context("Failed to determine config directory")?;
+        Self::select_profile_in(&config_path, name)
+    }
+
+    /// Make `name` the profile started with by the configuration file at `config_path`
+    pub fn select_profile_in(config_path: &Path, name: &str) -> Result<()> {
+        let mut base = if config_path.exists() {
+            Self::load_from(config_path)?
+        } else {
+            Self::default()
+        };
+        if base.active_profile == name {
+            return Ok(());
+        }
+        base.active_profile = name.to_string();
+        base.save_to(config_path)
+    }
+
+    /// Save settings to `config_path`, creating its directory if needed
+    pub fn save_to(&self, config_path: &Path) -> Result<()> {
         // Create config directory if it doesn't exist
         if let Some(parent) = config_path.parent() {
             fs::create_dir_all(parent)
@@ -189,7 +494,7 @@ impl Settings {
         let content = toml::to_string_pretty(self)
             .context("Failed to serialize settings")?;
         
-        fs::write(&config_path, content)
+        fs::write(config_path, content)
             .context("Failed to write config file")?;
         
         debug!("Configuration saved to: {:?}", config_path);
@@ -204,32 +509,52 @@ impl Settings {
                 icon: "firefox".to_string(),
                 command: "firefox".to_string(),
                 desktop_file: Some("/usr/share/applications/firefox.desktop".to_string()),
+                env: HashMap::new(),
+                label: None,
             },
             PinnedApp {
                 name: "Files".to_string(),
                 icon: "org.gnome.Nautilus".to_string(),
                 command: "nautilus".to_string(),
                 desktop_file: Some("/usr/share/applications/org.gnome.Nautilus.desktop".to_string()),
+                env: HashMap::new(),
+                label: None,
             },
             PinnedApp {
                 name: "Terminal".to_string(),
                 icon: "org.gnome.Terminal".to_string(),
                 command: "gnome-terminal".to_string(),
                 desktop_file: Some("/usr/share/applications/org.gnome.Terminal.desktop".to_string()),
+                env: HashMap::new(),
+                label: None,
             },
             PinnedApp {
                 name: "Settings".to_string(),
                 icon: "org.gnome.Settings".to_string(),
                 command: "gnome-control-center".to_string(),
                 desktop_file: Some("/usr/share/applications/org.gnome.Settings.desktop".to_string()),
+                env: HashMap::new(),
+                label: None,
             },
         ]
     }
 
+    /// Get default command -> app_id aliases for the default pinned apps
+    fn default_app_id_aliases() -> HashMap<String, Vec<String>> {
+        [
+            ("gnome-terminal", "org.gnome.Terminal"),
+            ("nautilus", "org.gnome.Nautilus"),
+            ("gnome-control-center", "org.gnome.Settings"),
+        ]
+        .into_iter()
+        .map(|(command, app_id)| (command.to_string(), vec![app_id.to_string()]))
+        .collect()
+    }
+
     /// Add a pinned application
     pub fn add_pinned_app(&mut self, app: PinnedApp) {
         self.pinned_apps.push(app);
-        if let Err(e) = self.save() {
+        if let Err(e) = self.save_active() {
             warn!("Failed to save config after adding app: {}", e);
         }
     }
@@ -238,7 +563,7 @@ impl Settings {
     pub fn remove_pinned_app(&mut self, index: usize) -> Option<PinnedApp> {
         if index < self.pinned_apps.len() {
             let removed = self.pinned_apps.remove(index);
-            if let Err(e) = self.save() {
+            if let Err(e) = self.save_active() {
                 warn!("Failed to save config after removing app: {}", e);
             }
             Some(removed)
@@ -247,15 +572,256 @@ impl Settings {
         }
     }
 
+    /// Settings for the dock on the monitor with the given connector
+    ///
+    /// Applies that monitor's override; fields it leaves unset, and
+    /// monitors without an override, use the base settings.
+    pub fn for_monitor(&self, connector: &str) -> Settings {
+        let mut settings = self.clone();
+        let Some(monitor) = self.monitor_overrides.get(connector) else {
+            return settings;
+        };
+        
+        if let Some(position) = monitor.position {
+            settings.position = position;
+        }
+        if let Some(icon_size) = monitor.icon_size {
+            settings.icon_size = icon_size;
+        }
+        if let Some(shown) = &monitor.pinned_apps {
+            settings.pinned_apps.retain(|app| {
+                shown.iter().any(|entry| *entry == app.name || *entry == app.command)
+            });
+        }
+        
+        settings
+    }
+
+    /// Find the current index of a pinned app
+    ///
+    /// `hint` is used if it still points at `command`; otherwise the
+    /// list is searched, since the index may be stale.
+    pub fn find_pinned_index(&self, hint: usize, command: &str) -> Option<usize> {
+        match self.pinned_apps.get(hint) {
+            Some(app) if app.command == command => Some(hint),
+            _ => self.pinned_apps.iter().position(|app| app.command == command),
+        }
+    }
+
+    /// The icon configured in `icon_overrides` for an app, if any
+    pub fn icon_override(&self, command: &str, desktop_file: Option<&str>) -> Option<&str> {
+        find_icon_override(&self.icon_overrides, command, desktop_file)
+    }
+
+    /// The hide mode in effect, honoring the older `auto_hide` switch
+    pub fn effective_hide_mode(&self) -> HideMode {
+        match self.hide_mode {
+            HideMode::Never if self.auto_hide => HideMode::AutoHide,
+            mode => mode,
+        }
+    }
+
     /// Reorder a pinned application
     pub fn reorder_pinned_app(&mut self, from: usize, to: usize) {
-        if from < self.pinned_apps.len() && to < self.pinned_apps.len() {
-            let app = self.pinned_apps.remove(from);
-            self.pinned_apps.insert(to, app);
-            if let Err(e) = self.save() {
+        if self.move_pinned_app(from, to) {
+            if let Err(e) = self.save_active() {
                 warn!("Failed to save config after reordering: {}", e);
             }
         }
     }
+
+    /// Move a pinned application without saving
+    ///
+    /// Returns `false` if either index is out of range.
+    pub fn move_pinned_app(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.pinned_apps.len() || to >= self.pinned_apps.len() {
+            return false;
+        }
+        let app = self.pinned_apps.remove(from);
+        self.pinned_apps.insert(to, app);
+        true
+    }
 }
 
+/// Look up an app's icon in `overrides`
+///
+/// Keys may be the full command, its program name (`firefox` for
+/// `/usr/bin/firefox --new-window`) or the desktop file ID, tried in that order.
+pub fn find_icon_override<'a>(
+    overrides: &'a HashMap<String, String>,
+    command: &str,
+    desktop_file: Option<&str>,
+) -> Option<&'a str> {
+    let program = command.split_whitespace().next().unwrap_or(command);
+    let program = program.rsplit('/').next().unwrap_or(program);
+    let app_id = desktop_file
+        .and_then(|path| Path::new(path).file_stem())
+        .map(|stem| stem.to_string_lossy());
+
+    overrides.get(command)
+        .or_else(|| overrides.get(program))
+        .or_else(|| app_id.and_then(|id| overrides.get(id.as_ref())))
+        .map(String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_icon_override() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.icon_override("firefox", None), None);
+        
+        settings.icon_overrides.insert("code".to_string(), "vscode".to_string());
+        settings.icon_overrides.insert("org.gnome.Ptyxis".to_string(), "/opt/icons/ptyxis.svg".to_string());
+        
+        assert_eq!(settings.icon_override("/usr/bin/code --new-window", None), Some("vscode"));
+        assert_eq!(
+            settings.icon_override("ptyxis", Some("/usr/share/applications/org.gnome.Ptyxis.desktop")),
+            Some("/opt/icons/ptyxis.svg")
+        );
+        assert_eq!(settings.icon_override("codium", None), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut settings = Settings::default();
+        settings.pinned_apps.clear();
+        assert!(settings.validate().is_empty());
+        
+        settings.icon_size = 8;
+        settings.opacity = 1.5;
+        settings.pinned_apps.push(PinnedApp {
+            name: "Ghost".to_string(),
+            icon: "ghost".to_string(),
+            command: " ".to_string(),
+            desktop_file: Some("/nonexistent/ghost.desktop".to_string()),
+            env: HashMap::new(),
+            label: None,
+        });
+        
+        let problems = settings.validate();
+        assert_eq!(problems.len(), 4, "{:?}", problems);
+        assert!(problems[0].starts_with("icon_size 8"));
+        assert!(problems.iter().any(|p| p.contains("'Ghost' has no command")));
+    }
+
+    #[test]
+    fn test_find_pinned_index() {
+        let settings = Settings::default();
+
+        // Hint still matches
+        assert_eq!(settings.find_pinned_index(1, "nautilus"), Some(1));
+        // Stale hint falls back to searching
+        assert_eq!(settings.find_pinned_index(0, "nautilus"), Some(1));
+        assert_eq!(settings.find_pinned_index(42, "gnome-terminal"), Some(2));
+        // App no longer pinned
+        assert_eq!(settings.find_pinned_index(0, "missing-app"), None);
+    }
+
+    #[test]
+    fn test_save_active_follows_profile() {
+        let dir = std::env::temp_dir().join(format!("blazedock-active-test-{}", std::process::id()));
+        let config_path = dir.join(CONFIG_FILE);
+        let mut profiles = ProfileManager::with_dir(dir.join("profiles"));
+        profiles.create_profile("work", None, None).unwrap();
+
+        let mut base = Settings::default();
+        base.icon_size = 56;
+        base.active_profile = "work".to_string();
+        base.save_to(&config_path).unwrap();
+
+        // Edits to the work profile stay out of the configuration file
+        let mut work = Settings::load_active_from(&config_path, &profiles).unwrap();
+        assert_eq!((work.icon_size, work.active_profile.as_str()), (48, "work"));
+        work.icon_size = 40;
+        work.save_active_to(&config_path, &mut profiles).unwrap();
+        assert_eq!(Settings::load_from(&config_path).unwrap().icon_size, 56);
+        assert_eq!(Settings::load_active_from(&config_path, &profiles).unwrap().icon_size, 40);
+
+        // Switching back to the default profile leaves its settings alone
+        Settings::select_profile_in(&config_path, "default").unwrap();
+        let saved = Settings::load_from(&config_path).unwrap();
+        assert_eq!((saved.icon_size, saved.active_profile.as_str()), (56, "default"));
+        Settings::select_profile_in(&config_path, "work").unwrap();
+
+        // Saving the default profile keeps the profile the dock starts with
+        let mut default = Settings::load_from(&config_path).unwrap();
+        default.active_profile = "default".to_string();
+        default.icon_size = 64;
+        default.save_active_to(&config_path, &mut profiles).unwrap();
+        let saved = Settings::load_from(&config_path).unwrap();
+        assert_eq!((saved.icon_size, saved.active_profile.as_str()), (64, "work"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_stack_config_defaults_and_home_expansion() {
+        let settings: Settings = toml::from_str(
+            r#"
+            [[stacks]]
+            path = "~/Pictures/Screenshots"
+
+            [[stacks]]
+            path = "/srv/project"
+            icon = "folder-documents"
+            view_mode = "list"
+            sort_mode = "name_asc"
+            max_items = 5
+            "#,
+        )
+        .unwrap();
+
+        let screenshots = &settings.stacks[0];
+        assert_eq!(screenshots.icon, "folder");
+        assert_eq!(screenshots.view_mode, StackViewMode::Fan);
+        assert_eq!(screenshots.max_items, 20);
+        assert!(screenshots.folder_path().ends_with("Pictures/Screenshots"));
+        assert!(!screenshots.folder_path().starts_with("~"));
+
+        let project = &settings.stacks[1];
+        assert_eq!(screenshots.sort_mode, StackSortMode::DateDesc);
+        assert_eq!(project.view_mode, StackViewMode::List);
+        assert_eq!(project.sort_mode, StackSortMode::NameAsc);
+        assert_eq!(project.max_items, 5);
+        assert_eq!(project.folder_path(), PathBuf::from("/srv/project"));
+    }
+
+    #[test]
+    fn test_monitor_override_inherits_unset_fields() {
+        let settings: Settings = toml::from_str(
+            r#"
+            position = "bottom"
+            icon_size = 48
+
+            [monitor_overrides.DP-1]
+            position = "left"
+
+            [monitor_overrides.HDMI-A-1]
+            icon_size = 32
+            pinned_apps = ["Firefox", "gnome-terminal"]
+            "#,
+        )
+        .unwrap();
+
+        let dp = settings.for_monitor("DP-1");
+        assert_eq!(dp.position, DockPosition::Left);
+        assert_eq!(dp.icon_size, 48);
+        assert_eq!(dp.pinned_apps.len(), settings.pinned_apps.len());
+
+        // Pinned apps are filtered by name or command
+        let hdmi = settings.for_monitor("HDMI-A-1");
+        assert_eq!(hdmi.position, DockPosition::Bottom);
+        assert_eq!(hdmi.icon_size, 32);
+        let commands: Vec<&str> = hdmi.pinned_apps.iter().map(|app| app.command.as_str()).collect();
+        assert_eq!(commands, ["firefox", "gnome-terminal"]);
+
+        // Unknown connectors get the base settings
+        let other = settings.for_monitor("eDP-1");
+        assert_eq!(other.position, DockPosition::Bottom);
+        assert_eq!(other.icon_size, 48);
+        assert!(other.monitor_overrides.contains_key("DP-1"));
+    }
+}
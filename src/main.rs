@@ -4,23 +4,49 @@
 //! It initializes logging, loads configuration, and starts the GTK4 application.
 
 mod app;
+mod cli;
 mod config;
 mod services;
 mod ui;
 mod utils;
 
 use anyhow::Result;
-use log::{info, error};
+use gtk::glib;
+use log::{info, error, warn};
+use std::cell::RefCell;
+use std::io::Write;
+use std::rc::Rc;
+
+/// Set to 1 to log single-line JSON records instead of text
+const LOG_JSON_VAR: &str = "BLAZEDOCK_LOG_JSON";
 
 fn main() -> Result<()> {
-    // Initialize logging with environment-controlled verbosity
-    // Set RUST_LOG=debug for verbose output
-    env_logger::Builder::from_env(
-        env_logger::Env::default().default_filter_or("info")
-    ).init();
+    init_logging();
+
+    // Handle CLI subcommands (pin/unpin/list/--check) without starting the GUI
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(command) = cli::parse_args(&args)? {
+        std::process::exit(cli::run(command));
+    }
 
     info!("BlazeDock v{} starting...", env!("CARGO_PKG_VERSION"));
 
+    // Only one dock per session; a second launch hands over to the first
+    let instance_lock = match utils::instance_lock::InstanceLock::acquire() {
+        Ok(utils::instance_lock::LockStatus::Acquired(lock)) => Some(lock),
+        Ok(utils::instance_lock::LockStatus::AlreadyRunning(pid)) => {
+            match pid {
+                Some(pid) => info!("BlazeDock is already running (pid {})", pid),
+                None => info!("BlazeDock is already running"),
+            }
+            std::process::exit(app::activate_running_instance());
+        }
+        Err(e) => {
+            warn!("Failed to take the instance lock: {}. Continuing anyway.", e);
+            None
+        }
+    };
+
     // Load configuration before starting the application
     let config = match config::Settings::load() {
         Ok(cfg) => {
@@ -33,11 +59,114 @@ fn main() -> Result<()> {
         }
     };
 
+    // Start with the settings of the active profile
+    let config = config::ProfileManager::new().startup_settings(&config);
+    info!("Using profile: {}", config.active_profile);
+
+    // Reload configuration on SIGHUP (e.g. `kill -HUP $(pidof blazedock)`)
+    let dock: app::DockHandle = Rc::new(RefCell::new(Vec::new()));
+    setup_reload_signal(Rc::clone(&dock));
+
     // Start the GTK4 application
-    let exit_code = app::run_application(config);
+    let exit_code = app::run_application(config, dock);
 
     info!("BlazeDock exiting with code: {}", exit_code);
     
+    // exit() skips destructors, so release the lock first
+    drop(instance_lock);
     std::process::exit(exit_code);
 }
 
+/// Initialize logging with environment-controlled verbosity
+///
+/// Set RUST_LOG=debug for verbose output; the filter applies to both the
+/// text and the JSON format.
+fn init_logging() {
+    let mut builder = env_logger::Builder::from_env(
+        env_logger::Env::default().default_filter_or("info")
+    );
+
+    if matches!(std::env::var(LOG_JSON_VAR), Ok(value) if value == "1") {
+        builder.format(|buf, record| {
+            let timestamp = buf.timestamp_millis().to_string();
+            writeln!(buf, "{}", json_record(&timestamp, record))
+        });
+    }
+
+    builder.init();
+}
+
+/// Format a log record as one line of JSON
+fn json_record(timestamp: &str, record: &log::Record) -> String {
+    serde_json::json!({
+        "timestamp": timestamp,
+        "level": record.level().as_str(),
+        "module": record.module_path().unwrap_or(record.target()),
+        "message": record.args().to_string(),
+    })
+    .to_string()
+}
+
+/// Register a SIGHUP handler on the main loop that reloads the configuration
+///
+/// If the configuration cannot be loaded the current dock is left untouched.
+fn setup_reload_signal(dock: app::DockHandle) {
+    glib::unix_signal_add_local(libc::SIGHUP, move || {
+        info!("SIGHUP received, reloading configuration");
+
+        // Also pick up app changes the directory monitors missed
+        utils::desktop_entry::force_refresh();
+
+        // Stay on the profile the docks show, which auto-switching may have changed
+        let reloaded = config::Settings::load().map(|mut base| {
+            if let Some(window) = dock.borrow().first() {
+                base.active_profile = window.active_profile();
+            }
+            config::ProfileManager::new().active_settings(&base)
+        });
+
+        match reloaded {
+            Ok(settings) => {
+                let windows = dock.borrow();
+                if windows.is_empty() {
+                    warn!("Dock window not created yet, ignoring reload");
+                } else {
+                    for window in windows.iter() {
+                        window.reload(&settings);
+                    }
+                    info!("Configuration reloaded from: {:?}", config::Settings::config_path());
+                }
+            }
+            Err(e) => {
+                error!("Failed to reload configuration: {}. Keeping current dock.", e);
+            }
+        }
+
+        glib::ControlFlow::Continue
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_record() {
+        let line = json_record(
+            "2026-10-16T09:30:00.123Z",
+            &log::Record::builder()
+                .args(format_args!("Pinned \"Files\"\nat 2"))
+                .level(log::Level::Warn)
+                .target("blazedock::cli")
+                .module_path(Some("blazedock::cli"))
+                .build(),
+        );
+
+        assert!(!line.contains('\n'));
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["timestamp"], "2026-10-16T09:30:00.123Z");
+        assert_eq!(value["level"], "WARN");
+        assert_eq!(value["module"], "blazedock::cli");
+        assert_eq!(value["message"], "Pinned \"Files\"\nat 2");
+    }
+}
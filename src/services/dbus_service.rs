@@ -2,6 +2,7 @@
 //!
 //! Handles Unity LauncherEntry badges and notification listening.
 //! Provides badge counts for applications like email clients, browsers, etc.
+//! Also serves `com.blazedock.Dock` so scripts can set badges directly.
 
 use gtk::glib;
 use log::{info, debug, warn};
@@ -21,6 +22,8 @@ pub struct BadgeInfo {
     pub progress: f64,
     /// Whether progress is visible
     pub progress_visible: bool,
+    /// Whether progress is active without a reported value
+    pub progress_indeterminate: bool,
     /// Whether the app is requesting urgent attention
     pub urgent: bool,
 }
@@ -33,11 +36,18 @@ impl Default for BadgeInfo {
             count_visible: false,
             progress: 0.0,
             progress_visible: false,
+            progress_indeterminate: false,
             urgent: false,
         }
     }
 }
 
+/// Well-known name and object path of BlazeDock's own D-Bus service
+///
+/// The interface has the same name as the service.
+const DOCK_BUS_NAME: &str = "com.blazedock.Dock";
+const DOCK_OBJECT_PATH: &str = "/com/blazedock/Dock";
+
 /// Event types for D-Bus integration  
 #[derive(Debug, Clone)]
 pub enum DBusEvent {
@@ -45,6 +55,8 @@ pub enum DBusEvent {
     BadgeUpdate(BadgeInfo),
     /// Notification received
     Notification { app_name: String, summary: String },
+    /// Show (`true`) or hide (`false`) the dock
+    SetVisibility(bool),
 }
 
 /// D-Bus service for BlazeDock
@@ -55,16 +67,72 @@ pub struct DBusService {
     badges: Arc<Mutex<HashMap<String, BadgeInfo>>>,
     /// Callbacks for badge updates
     callbacks: Arc<Mutex<Vec<Box<dyn Fn(BadgeInfo) + Send + 'static>>>>,
+    /// Channels receiving all D-Bus events (see `subscribe`)
+    event_senders: Arc<Mutex<Vec<async_channel::Sender<DBusEvent>>>>,
+    /// App IDs excluded from the total unread count
+    muted_apps: Arc<Mutex<Vec<String>>>,
+    /// Whether the dock window is shown
+    dock_visible: Arc<Mutex<bool>>,
+    /// Connection serving `com.blazedock.Dock`, kept alive while running
+    dock_connection: Arc<Mutex<Option<zbus::Connection>>>,
     /// Running state
     running: Arc<Mutex<bool>>,
 }
 
+/// The `com.blazedock.Dock` D-Bus object
+///
+/// ```sh
+/// busctl --user call com.blazedock.Dock /com/blazedock/Dock com.blazedock.Dock SetProgress sd backup 0.5
+/// busctl --user call com.blazedock.Dock /com/blazedock/Dock com.blazedock.Dock ToggleVisibility
+/// ```
+struct DockInterface {
+    service: DBusService,
+}
+
+#[zbus::interface(name = "com.blazedock.Dock")]
+impl DockInterface {
+    /// Set the badge count on an app's dock item (0 hides it)
+    fn set_badge(&self, app_id: &str, count: i64) {
+        self.service.set_badge(app_id, count, count > 0);
+    }
+
+    /// Set progress (0.0 - 1.0) on an app's dock item; a negative value hides it
+    fn set_progress(&self, app_id: &str, value: f64) {
+        self.service.set_progress(app_id, value.clamp(0.0, 1.0), value >= 0.0);
+    }
+
+    /// Ask for attention on an app's dock item
+    fn bounce(&self, app_id: &str) {
+        self.service.bounce(app_id);
+    }
+
+    /// Show the dock if hidden, hide it if shown; returns whether it is now visible
+    fn toggle_visibility(&self) -> bool {
+        let visible = !self.service.is_dock_visible();
+        self.service.request_visibility(visible)
+    }
+
+    /// Show the dock; returns whether it is now visible
+    fn show(&self) -> bool {
+        self.service.request_visibility(true)
+    }
+
+    /// Hide the dock; returns whether it is now visible
+    fn hide(&self) -> bool {
+        self.service.request_visibility(false)
+    }
+}
+
 impl DBusService {
     /// Create a new D-Bus service
     pub fn new() -> Self {
         Self {
             badges: Arc::new(Mutex::new(HashMap::new())),
             callbacks: Arc::new(Mutex::new(Vec::new())),
+            event_senders: Arc::new(Mutex::new(Vec::new())),
+            muted_apps: Arc::new(Mutex::new(Vec::new())),
+            dock_visible: Arc::new(Mutex::new(true)),
+            dock_connection: Arc::new(Mutex::new(None)),
             running: Arc::new(Mutex::new(false)),
         }
     }
@@ -77,6 +145,33 @@ impl DBusService {
         self.callbacks.lock().unwrap().push(Box::new(callback));
     }
 
+    /// Subscribe to all D-Bus events
+    ///
+    /// The receiver can be drained on the GTK main loop with
+    /// `glib::spawn_future_local`. Closing it unsubscribes.
+    pub fn subscribe(&self) -> async_channel::Receiver<DBusEvent> {
+        let (sender, receiver) = async_channel::unbounded();
+        self.event_senders.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// Send an event to all subscribers, dropping closed channels
+    fn emit(&self, event: DBusEvent) {
+        self.event_senders.lock().unwrap()
+            .retain(|sender| sender.try_send(event.clone()).is_ok());
+    }
+
+    /// Notify callbacks and subscribers of a badge change
+    fn notify_badge(&self, badge: BadgeInfo) {
+        let callbacks = self.callbacks.lock().unwrap();
+        for callback in callbacks.iter() {
+            callback(badge.clone());
+        }
+        drop(callbacks);
+        
+        self.emit(DBusEvent::BadgeUpdate(badge));
+    }
+
     /// Get current badge info for an app
     pub fn get_badge(&self, app_id: &str) -> Option<BadgeInfo> {
         let badges = self.badges.lock().unwrap();
@@ -103,6 +198,40 @@ impl DBusService {
         self.badges.lock().unwrap().clone()
     }
 
+    /// Set the app IDs excluded from the total unread count
+    pub fn set_muted_apps(&self, apps: Vec<String>) {
+        *self.muted_apps.lock().unwrap() = apps;
+    }
+
+    /// Sum the visible badge counts of all apps that aren't muted
+    pub fn total_unread_count(&self) -> i64 {
+        let muted = self.muted_apps.lock().unwrap();
+        
+        self.badges.lock().unwrap()
+            .values()
+            .filter(|badge| badge.count_visible && badge.count > 0)
+            .filter(|badge| !muted.iter().any(|app| app.eq_ignore_ascii_case(&badge.app_id)))
+            .map(|badge| badge.count)
+            .sum()
+    }
+
+    /// Whether the dock window is shown
+    pub fn is_dock_visible(&self) -> bool {
+        *self.dock_visible.lock().unwrap()
+    }
+
+    /// Record the dock window's visibility, whatever changed it
+    pub fn set_dock_visible(&self, visible: bool) {
+        *self.dock_visible.lock().unwrap() = visible;
+    }
+
+    /// Ask the dock window to show or hide and return the new visibility
+    fn request_visibility(&self, visible: bool) -> bool {
+        self.set_dock_visible(visible);
+        self.emit(DBusEvent::SetVisibility(visible));
+        visible
+    }
+
     /// Start the D-Bus service
     pub fn start(&self) {
         let mut running = self.running.lock().unwrap();
@@ -119,6 +248,32 @@ impl DBusService {
         
         // Start notification listener
         self.start_notification_listener();
+        
+        // Serve com.blazedock.Dock for external badge control
+        self.start_dock_interface();
+    }
+
+    /// Claim `com.blazedock.Dock` and serve the dock object
+    fn start_dock_interface(&self) {
+        let service = self.clone();
+        
+        glib::spawn_future_local(async move {
+            let connection = async {
+                zbus::connection::Builder::session()?
+                    .name(DOCK_BUS_NAME)?
+                    .serve_at(DOCK_OBJECT_PATH, DockInterface { service: service.clone() })?
+                    .build()
+                    .await
+            };
+            
+            match connection.await {
+                Ok(connection) => {
+                    info!("Serving {} on the session bus", DOCK_BUS_NAME);
+                    *service.dock_connection.lock().unwrap() = Some(connection);
+                }
+                Err(e) => warn!("Failed to register {}: {}", DOCK_BUS_NAME, e),
+            }
+        });
     }
 
     /// Start listening for Unity LauncherEntry signals
@@ -225,6 +380,9 @@ impl DBusService {
             }
         }
         
+        // Visible progress without a value is an indeterminate operation
+        badge.progress_indeterminate = badge.progress_visible && !props.contains_key("progress");
+        
         // Extract urgent
         if let Some(urgent) = props.get("urgent") {
             if let Ok(u) = urgent.clone().try_into() {
@@ -235,11 +393,7 @@ impl DBusService {
         // Store badge info
         self.badges.lock().unwrap().insert(app_id.clone(), badge.clone());
         
-        // Notify callbacks
-        let callbacks = self.callbacks.lock().unwrap();
-        for callback in callbacks.iter() {
-            callback(badge.clone());
-        }
+        self.notify_badge(badge.clone());
         
         debug!("Badge updated: {:?}", badge);
         Ok(())
@@ -257,32 +411,125 @@ impl DBusService {
         });
     }
 
-    /// Listen for org.freedesktop.Notifications signals
+    /// Listen for org.freedesktop.Notifications Notify calls
+    ///
+    /// Uses a dedicated monitor connection, since Notify is a method call
+    /// to the notification daemon rather than a broadcast signal.
     async fn listen_notifications(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Notification listening is best-effort - just log that it's attempted
-        info!("Notification monitoring enabled (passive)");
+        use futures_util::StreamExt;
+        
+        let connection = zbus::Connection::session().await?;
+        let rule = zbus::MatchRule::try_from(
+            "type='method_call',interface='org.freedesktop.Notifications',member='Notify'",
+        )?;
+        
+        zbus::fdo::MonitoringProxy::new(&connection).await?
+            .become_monitor(&[rule], 0)
+            .await?;
+        
+        info!("Notification monitoring enabled");
+        
+        let mut stream = zbus::MessageStream::from(&connection);
+        while let Some(msg) = stream.next().await {
+            if !self.is_running() {
+                break;
+            }
+            
+            let Ok(message) = msg else { continue };
+            let is_notify = message.member()
+                .map(|m| m.as_str() == "Notify")
+                .unwrap_or(false);
+            
+            if is_notify {
+                match Self::parse_notification(&message) {
+                    Ok(event) => {
+                        debug!("Notification received: {:?}", event);
+                        self.emit(event);
+                    }
+                    Err(e) => debug!("Error parsing notification: {}", e),
+                }
+            }
+        }
         
-        // We're mainly interested in LauncherEntry, but notifications
-        // can inform us about app activity through the existing MessageStream
         Ok(())
     }
 
+    /// Parse a Notify method call into a notification event
+    fn parse_notification(message: &zbus::Message) -> Result<DBusEvent, Box<dyn std::error::Error + Send + Sync>> {
+        // Notify signature: (susssasa{sv}i)
+        let (app_name, _replaces_id, _icon, summary, _body, _actions, _hints, _timeout): (
+            String,
+            u32,
+            String,
+            String,
+            String,
+            Vec<String>,
+            HashMap<String, zbus::zvariant::OwnedValue>,
+            i32,
+        ) = message.body().deserialize()?;
+        
+        Ok(DBusEvent::Notification { app_name, summary })
+    }
+
     /// Manually set a badge (for testing or external updates)
     pub fn set_badge(&self, app_id: &str, count: i64, visible: bool) {
-        let badge = BadgeInfo {
+        let mut badges = self.badges.lock().unwrap();
+        let badge = badges.entry(app_id.to_string()).or_insert_with(|| BadgeInfo {
             app_id: app_id.to_string(),
-            count,
-            count_visible: visible,
             ..Default::default()
+        });
+        
+        badge.count = count;
+        badge.count_visible = visible;
+        
+        let badge_clone = badge.clone();
+        drop(badges);
+        
+        self.notify_badge(badge_clone);
+    }
+
+    /// Request attention for an app until its badge is cleared
+    pub fn bounce(&self, app_id: &str) {
+        let mut badges = self.badges.lock().unwrap();
+        let badge = badges.entry(app_id.to_string()).or_insert_with(|| BadgeInfo {
+            app_id: app_id.to_string(),
+            ..Default::default()
+        });
+        badge.urgent = true;
+        
+        let badge_clone = badge.clone();
+        drop(badges);
+        
+        debug!("Attention requested for: {}", app_id);
+        self.notify_badge(badge_clone);
+    }
+
+    /// Clear an app's badge count and attention, e.g. once the user focuses the app
+    ///
+    /// This is optimistic: the app's next LauncherEntry update replaces it.
+    pub fn clear_badge(&self, app_id: &str) {
+        let mut badges = self.badges.lock().unwrap();
+        
+        // Exact key first, then case-insensitive like get_badge
+        let key = if badges.contains_key(app_id) {
+            Some(app_id.to_string())
+        } else {
+            badges.keys().find(|k| k.eq_ignore_ascii_case(app_id)).cloned()
         };
         
-        self.badges.lock().unwrap().insert(app_id.to_string(), badge.clone());
+        let Some(badge) = key.and_then(|k| badges.get_mut(&k)) else {
+            return;
+        };
         
-        // Notify callbacks
-        let callbacks = self.callbacks.lock().unwrap();
-        for callback in callbacks.iter() {
-            callback(badge.clone());
-        }
+        badge.count = 0;
+        badge.count_visible = false;
+        badge.urgent = false;
+        
+        let badge_clone = badge.clone();
+        drop(badges);
+        
+        debug!("Badge cleared for: {}", app_id);
+        self.notify_badge(badge_clone);
     }
 
     /// Set progress for an app
@@ -295,21 +542,26 @@ impl DBusService {
         
         badge.progress = progress;
         badge.progress_visible = visible;
+        badge.progress_indeterminate = false;
         
         let badge_clone = badge.clone();
         drop(badges);
         
-        // Notify callbacks
-        let callbacks = self.callbacks.lock().unwrap();
-        for callback in callbacks.iter() {
-            callback(badge_clone.clone());
-        }
+        self.notify_badge(badge_clone);
     }
 
     /// Stop the D-Bus service
     pub fn stop(&self) {
         let mut running = self.running.lock().unwrap();
         *running = false;
+        
+        // Release the bus name (also breaks the service <-> object cycle)
+        self.dock_connection.lock().unwrap().take();
+        
+        // Ends subscribers' receive loops
+        for sender in self.event_senders.lock().unwrap().drain(..) {
+            sender.close();
+        }
         info!("D-Bus service stopped");
     }
 
@@ -355,6 +607,161 @@ mod tests {
         assert!(badge.progress_visible);
     }
 
+    #[test]
+    fn test_subscribe_receives_badge_events() {
+        let service = DBusService::new();
+        let receiver = service.subscribe();
+        
+        service.set_badge("thunderbird", 4, true);
+        
+        match receiver.try_recv() {
+            Ok(DBusEvent::BadgeUpdate(badge)) => {
+                assert_eq!(badge.app_id, "thunderbird");
+                assert_eq!(badge.count, 4);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        
+        // Closed receivers are dropped on the next event
+        receiver.close();
+        service.set_badge("thunderbird", 5, true);
+        assert!(service.event_senders.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_clear_badge_notifies() {
+        let service = DBusService::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        
+        let seen_clone = Arc::clone(&seen);
+        service.on_badge_update(move |badge| {
+            seen_clone.lock().unwrap().push((badge.count, badge.count_visible));
+        });
+        
+        service.set_badge("geary", 3, true);
+        service.clear_badge("Geary");
+        
+        assert_eq!(*seen.lock().unwrap(), vec![(3, true), (0, false)]);
+        assert_eq!(service.get_badge("geary").unwrap().count, 0);
+        
+        // A later update from the app is authoritative again
+        service.set_badge("geary", 1, true);
+        assert_eq!(service.get_badge("geary").unwrap().count, 1);
+    }
+
+    #[test]
+    fn test_bounce_lasts_until_cleared() {
+        let service = DBusService::new();
+        service.set_badge("backup", 2, true);
+        
+        service.bounce("backup");
+        let badge = service.get_badge("backup").unwrap();
+        assert!(badge.urgent);
+        assert_eq!(badge.count, 2);
+        
+        // Later updates keep asking for attention
+        service.set_progress("backup", 0.5, true);
+        assert!(service.get_badge("backup").unwrap().urgent);
+        
+        service.clear_badge("backup");
+        assert!(!service.get_badge("backup").unwrap().urgent);
+    }
+
+    #[test]
+    fn test_dock_interface_over_private_bus() {
+        use std::os::unix::net::UnixStream;
+        
+        let service = DBusService::new();
+        let (server_stream, client_stream) = UnixStream::pair().unwrap();
+        
+        zbus::block_on(async {
+            let server = zbus::connection::Builder::unix_stream(server_stream)
+                .server(zbus::Guid::generate())
+                .unwrap()
+                .p2p()
+                .serve_at(DOCK_OBJECT_PATH, DockInterface { service: service.clone() })
+                .unwrap()
+                .build();
+            let client = zbus::connection::Builder::unix_stream(client_stream)
+                .p2p()
+                .build();
+            let (_server, client) = futures_util::future::try_join(server, client).await.unwrap();
+            
+            client
+                .call_method(None::<&str>, DOCK_OBJECT_PATH, Some(DOCK_BUS_NAME), "SetBadge", &("backup", 3i64))
+                .await
+                .unwrap();
+            client
+                .call_method(None::<&str>, DOCK_OBJECT_PATH, Some(DOCK_BUS_NAME), "SetProgress", &("backup", 0.25f64))
+                .await
+                .unwrap();
+        });
+        
+        let badge = service.get_badge("backup").unwrap();
+        assert_eq!(badge.count, 3);
+        assert!(badge.count_visible);
+        assert!((badge.progress - 0.25).abs() < f64::EPSILON);
+        assert!(badge.progress_visible);
+    }
+
+    #[test]
+    fn test_visibility_over_private_bus() {
+        use std::os::unix::net::UnixStream;
+        
+        let service = DBusService::new();
+        let receiver = service.subscribe();
+        let (server_stream, client_stream) = UnixStream::pair().unwrap();
+        
+        let replies = zbus::block_on(async {
+            let server = zbus::connection::Builder::unix_stream(server_stream)
+                .server(zbus::Guid::generate())
+                .unwrap()
+                .p2p()
+                .serve_at(DOCK_OBJECT_PATH, DockInterface { service: service.clone() })
+                .unwrap()
+                .build();
+            let client = zbus::connection::Builder::unix_stream(client_stream)
+                .p2p()
+                .build();
+            let (_server, client) = futures_util::future::try_join(server, client).await.unwrap();
+            
+            let mut replies = Vec::new();
+            for method in ["ToggleVisibility", "ToggleVisibility", "Hide", "Show"] {
+                let reply = client
+                    .call_method(None::<&str>, DOCK_OBJECT_PATH, Some(DOCK_BUS_NAME), method, &())
+                    .await
+                    .unwrap();
+                replies.push(reply.body().deserialize::<bool>().unwrap());
+            }
+            replies
+        });
+        
+        assert_eq!(replies, vec![false, true, false, true]);
+        assert!(service.is_dock_visible());
+        
+        let requested: Vec<bool> = std::iter::from_fn(|| match receiver.try_recv() {
+            Ok(DBusEvent::SetVisibility(visible)) => Some(visible),
+            _ => None,
+        })
+        .collect();
+        assert_eq!(requested, replies);
+    }
+
+    #[test]
+    fn test_total_unread_count() {
+        let service = DBusService::new();
+        
+        service.set_badge("thunderbird", 4, true);
+        service.set_badge("telegram", 2, true);
+        service.set_badge("slack", 9, false);
+        service.set_badge("discord", 5, true);
+        
+        assert_eq!(service.total_unread_count(), 11);
+        
+        service.set_muted_apps(vec!["Discord".to_string()]);
+        assert_eq!(service.total_unread_count(), 6);
+    }
+
     #[test]
     fn test_case_insensitive_lookup() {
         let service = DBusService::new();
@@ -0,0 +1,124 @@
+//! Services shared by every dock window
+//!
+//! With a dock on each monitor, the background services are started once
+//! and handed to all docks instead of each dock polling on its own.
+
+use log::info;
+use std::cell::RefCell;
+
+use crate::config::{ProfileManager, Settings};
+use crate::ui;
+use super::{
+    DBusService, DriveMonitor, KeyboardService, MultiMonitorService, ProcessTracker, ScreencopyService,
+    ThemeMode, ThemeService, WindowTracker,
+};
+
+/// Background services and profile state shared by all docks
+pub struct DockServices {
+    pub dbus: DBusService,
+    pub window_tracker: WindowTracker,
+    pub process_tracker: ProcessTracker,
+    pub keyboard: KeyboardService,
+    pub screencopy: ScreencopyService,
+    pub drive_monitor: DriveMonitor,
+    pub theme: ThemeService,
+    pub monitors: MultiMonitorService,
+    /// Display-wide CSS with the theme colors
+    theme_provider: Option<gtk::CssProvider>,
+    /// Display-wide CSS for the appearance settings (opacity, corner radius)
+    settings_provider: Option<gtk::CssProvider>,
+    pub profile_manager: RefCell<ProfileManager>,
+}
+
+impl DockServices {
+    /// Create the services for `settings` and start them
+    pub fn start(settings: &Settings) -> Self {
+        let dbus = DBusService::new();
+        dbus.set_muted_apps(settings.muted_badge_apps.clone());
+        dbus.start();
+
+        let window_tracker = WindowTracker::new();
+        window_tracker.set_app_id_aliases(settings.app_id_aliases.clone());
+        window_tracker.start();
+
+        let process_tracker = ProcessTracker::new();
+        process_tracker.start();
+
+        let monitors = MultiMonitorService::new();
+        monitors.start_monitoring();
+
+        let keyboard = KeyboardService::new();
+        keyboard.load_from_settings(settings);
+        // System-wide item shortcuts go to the dock on the current monitor
+        let target_monitors = monitors.clone();
+        keyboard.set_global_target(move || {
+            target_monitors.get_current_monitor().map(|monitor| monitor.key().to_string())
+        });
+
+        let screencopy = ScreencopyService::new();
+        screencopy.start();
+
+        let drive_monitor = DriveMonitor::new();
+        drive_monitor.start();
+
+        let theme = ThemeService::new();
+        theme.set_mode(ThemeMode::from_name(&settings.theme_mode));
+        theme.start_monitoring();
+        let theme_provider = ui::load_theme_styles(&theme);
+        let settings_provider = ui::create_settings_provider();
+        if let Some(provider) = &settings_provider {
+            ui::apply_settings_css(provider, settings);
+        }
+
+        info!("Shared dock services started");
+
+        Self {
+            dbus,
+            window_tracker,
+            process_tracker,
+            keyboard,
+            screencopy,
+            drive_monitor,
+            theme,
+            monitors,
+            theme_provider,
+            settings_provider,
+            profile_manager: RefCell::new(ProfileManager::new()),
+        }
+    }
+
+    /// Apply the settings the services depend on
+    pub fn configure(&self, settings: &Settings) {
+        for app in &settings.pinned_apps {
+            self.process_tracker.register_app(&app.command);
+        }
+        self.window_tracker.set_app_id_aliases(settings.app_id_aliases.clone());
+        let previous_shortcuts = self.keyboard.get_shortcuts();
+        self.keyboard.load_from_settings(settings);
+        // The portal keeps the bindings it was given, so changes need a new session
+        if settings.enable_shortcuts && self.keyboard.get_shortcuts() != previous_shortcuts {
+            self.keyboard.register_global_shortcuts();
+        }
+        self.dbus.set_muted_apps(settings.muted_badge_apps.clone());
+        if let Some(provider) = &self.settings_provider {
+            ui::apply_settings_css(provider, settings);
+        }
+        let theme_mode = ThemeMode::from_name(&settings.theme_mode);
+        if self.theme.get_mode() != theme_mode {
+            self.theme.set_mode(theme_mode);
+        }
+    }
+
+    /// Stop the background services
+    pub fn stop(&self) {
+        self.process_tracker.stop();
+        self.window_tracker.stop();
+        self.screencopy.stop();
+        self.drive_monitor.stop();
+        self.dbus.stop();
+        for provider in self.theme_provider.iter().chain(&self.settings_provider) {
+            ui::remove_provider(provider);
+        }
+        info!("Shared dock services stopped");
+    }
+}
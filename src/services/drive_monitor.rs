@@ -1,22 +1,29 @@
 //! Drive monitor service
 //!
-//! Monitors removable drives and mounted partitions.
-//! Note: Full async implementation requires proper runtime setup.
+//! Monitors mounted removable drives (USB sticks, SD cards) by polling
+//! `lsblk` on a background thread.
 
-use log::{info, debug};
-use std::sync::{Arc, Mutex};
+use log::{debug, info, warn};
+use serde_json::Value;
 use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How often the mounted drives are re-read
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
 
 /// Drive information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct DriveInfo {
+    /// Filesystem label, or the device name if unlabeled
     pub name: String,
     pub mount_point: String,
     pub is_removable: bool,
 }
 
 /// Drive monitor for tracking removable media
-/// Currently a placeholder - full implementation pending async runtime setup
+#[derive(Clone)]
 pub struct DriveMonitor {
     drives: Arc<Mutex<Vec<DriveInfo>>>,
     running: Arc<Mutex<bool>>,
@@ -31,41 +38,38 @@ impl DriveMonitor {
         }
     }
 
-    /// Start monitoring drives
-    /// Note: Currently a no-op placeholder. Full drive monitoring will be
-    /// implemented using GIO/udev or periodic lsblk polling.
+    /// Start polling the mounted removable drives in the background
     pub fn start(&self) {
         let mut running = self.running.lock().unwrap();
         if *running {
             return;
         }
         *running = true;
-        
-        info!("Drive monitor initialized (placeholder mode)");
-        debug!("Full drive monitoring pending async runtime setup");
-        
-        // TODO: Implement proper drive monitoring using:
-        // - GIO volume monitor
-        // - udev events
-        // - Periodic lsblk polling via glib::timeout_add
-        // For now, this is a safe placeholder that doesn't crash.
+
+        let drives = Arc::clone(&self.drives);
+        let running_flag = Arc::clone(&self.running);
+
+        thread::spawn(move || {
+            info!("Drive monitor started");
+
+            while *running_flag.lock().unwrap() {
+                let current = scan_drives();
+                let mut drives = drives.lock().unwrap();
+                if *drives != current {
+                    debug!("Removable drives changed: {:?}", current);
+                    *drives = current;
+                }
+                drop(drives);
+
+                thread::sleep(POLL_INTERVAL);
+            }
+
+            info!("Drive monitor stopped");
+        });
     }
 
-    /// Get list of currently mounted drives
+    /// Get list of currently mounted removable drives
     pub fn get_drives(&self) -> Vec<DriveInfo> {
-        // Do a one-time sync check for drives
-        let output = Command::new("lsblk")
-            .args(["-J", "-o", "NAME,MOUNTPOINT,RM"])
-            .output();
-        
-        if let Ok(res) = output {
-            if res.status.success() {
-                // For now, just return the cached list
-                // Full parsing will be implemented later
-                debug!("Drive check completed");
-            }
-        }
-        
         self.drives.lock().unwrap().clone()
     }
 
@@ -86,3 +90,106 @@ impl Default for DriveMonitor {
         Self::new()
     }
 }
+
+/// Read the mounted removable drives from `lsblk`
+fn scan_drives() -> Vec<DriveInfo> {
+    let output = Command::new("lsblk")
+        .args(["-J", "-o", "NAME,LABEL,MOUNTPOINT,RM,HOTPLUG"])
+        .output();
+
+    match output {
+        Ok(res) if res.status.success() => parse_lsblk(&String::from_utf8_lossy(&res.stdout)),
+        Ok(res) => {
+            warn!("lsblk failed: {}", String::from_utf8_lossy(&res.stderr).trim());
+            Vec::new()
+        }
+        Err(e) => {
+            warn!("Failed to run lsblk: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Parse `lsblk -J` output into the mounted removable drives
+///
+/// Partitions inherit removability from their disk, since some readers
+/// only flag the disk itself.
+fn parse_lsblk(json: &str) -> Vec<DriveInfo> {
+    let Ok(root) = serde_json::from_str::<Value>(json) else {
+        warn!("Ignoring unparsable lsblk output");
+        return Vec::new();
+    };
+
+    let mut drives = Vec::new();
+    if let Some(devices) = root.get("blockdevices").and_then(Value::as_array) {
+        for device in devices {
+            collect_drives(device, false, &mut drives);
+        }
+    }
+    drives
+}
+
+/// Add `device` and its children to `drives` if mounted and removable
+fn collect_drives(device: &Value, parent_removable: bool, drives: &mut Vec<DriveInfo>) {
+    let is_removable = parent_removable || flag(device, "rm") || flag(device, "hotplug");
+
+    let mount_point = device.get("mountpoint").and_then(Value::as_str);
+    if let (true, Some(mount_point)) = (is_removable, mount_point) {
+        // Swap has the pseudo mount point "[SWAP]"
+        if mount_point.starts_with('/') {
+            let name = device.get("label").and_then(Value::as_str)
+                .or_else(|| device.get("name").and_then(Value::as_str))
+                .unwrap_or(mount_point);
+            drives.push(DriveInfo {
+                name: name.to_string(),
+                mount_point: mount_point.to_string(),
+                is_removable,
+            });
+        }
+    }
+
+    if let Some(children) = device.get("children").and_then(Value::as_array) {
+        for child in children {
+            collect_drives(child, is_removable, drives);
+        }
+    }
+}
+
+/// Read an lsblk boolean column, which older versions print as "0"/"1"
+fn flag(device: &Value, key: &str) -> bool {
+    match device.get(key) {
+        Some(Value::Bool(value)) => *value,
+        Some(Value::String(value)) => value == "1",
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lsblk() {
+        let json = r#"{
+            "blockdevices": [
+                {"name": "nvme0n1", "label": null, "mountpoint": null, "rm": false, "hotplug": false,
+                 "children": [
+                    {"name": "nvme0n1p1", "label": null, "mountpoint": "/boot", "rm": false, "hotplug": false},
+                    {"name": "nvme0n1p2", "label": null, "mountpoint": "[SWAP]", "rm": false, "hotplug": false}
+                 ]},
+                {"name": "sda", "label": null, "mountpoint": null, "rm": true, "hotplug": true,
+                 "children": [
+                    {"name": "sda1", "label": "STICK", "mountpoint": "/run/media/me/STICK", "rm": false, "hotplug": false},
+                    {"name": "sda2", "label": null, "mountpoint": null, "rm": false, "hotplug": false}
+                 ]},
+                {"name": "mmcblk0p1", "label": null, "mountpoint": "/media/sd", "rm": "1", "hotplug": "0"}
+            ]
+        }"#;
+
+        assert_eq!(parse_lsblk(json), vec![
+            DriveInfo { name: "STICK".into(), mount_point: "/run/media/me/STICK".into(), is_removable: true },
+            DriveInfo { name: "mmcblk0p1".into(), mount_point: "/media/sd".into(), is_removable: true },
+        ]);
+        assert!(parse_lsblk("not json").is_empty());
+    }
+}
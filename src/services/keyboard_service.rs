@@ -10,11 +10,15 @@ use gtk::glib;
 use log::{info, debug, warn};
 use std::collections::HashMap;
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+
+use crate::config::{Settings, ShortcutConfig};
 
 /// Shortcut action types
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ShortcutAction {
     /// Launch or focus app at position (1-9)
     ActivateApp(u8),
@@ -30,10 +34,42 @@ pub enum ShortcutAction {
     ActivateFocused,
     /// Show context menu
     ShowContextMenu,
+    /// Focus the next window of the focused app
+    CycleWindows,
+}
+
+impl ShortcutAction {
+    /// Stable identifier, e.g. "toggle-dock" or "activate-app-3"
+    fn id(&self) -> String {
+        match self {
+            ShortcutAction::ActivateApp(position) => format!("activate-app-{}", position),
+            ShortcutAction::ToggleDock => "toggle-dock".to_string(),
+            ShortcutAction::ShowSearch => "show-search".to_string(),
+            ShortcutAction::NavigateLeft => "navigate-left".to_string(),
+            ShortcutAction::NavigateRight => "navigate-right".to_string(),
+            ShortcutAction::ActivateFocused => "activate-focused".to_string(),
+            ShortcutAction::ShowContextMenu => "show-context-menu".to_string(),
+            ShortcutAction::CycleWindows => "cycle-windows".to_string(),
+        }
+    }
+
+    /// Human-readable description
+    fn description(&self) -> String {
+        match self {
+            ShortcutAction::ActivateApp(position) => format!("Activate App {}", position),
+            ShortcutAction::ToggleDock => "Toggle Dock".to_string(),
+            ShortcutAction::ShowSearch => "Show Search".to_string(),
+            ShortcutAction::NavigateLeft => "Focus Previous Dock Item".to_string(),
+            ShortcutAction::NavigateRight => "Focus Next Dock Item".to_string(),
+            ShortcutAction::ActivateFocused => "Activate Focused Dock Item".to_string(),
+            ShortcutAction::ShowContextMenu => "Show Context Menu".to_string(),
+            ShortcutAction::CycleWindows => "Cycle Windows of Focused App".to_string(),
+        }
+    }
 }
 
 /// Shortcut binding
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ShortcutBinding {
     pub modifiers: gtk::gdk::ModifierType,
     pub key: gtk::gdk::Key,
@@ -53,13 +89,24 @@ pub enum GlobalShortcutStatus {
     Failed,
 }
 
+/// Action callbacks by id, in registration order
+type ActionCallbacks = Rc<RefCell<Vec<(String, Box<dyn Fn(ShortcutAction)>)>>>;
+
+/// Picks the id of the callback receiving system-wide item shortcuts
+type GlobalTarget = Rc<RefCell<Option<Box<dyn Fn() -> Option<String>>>>>;
+
 /// Keyboard service for global shortcuts
 #[derive(Clone)]
 pub struct KeyboardService {
     shortcuts: Rc<RefCell<Vec<ShortcutBinding>>>,
-    action_callbacks: Rc<RefCell<HashMap<String, Box<dyn Fn(ShortcutAction)>>>>,
+    action_callbacks: ActionCallbacks,
+    global_target: GlobalTarget,
     enabled: Rc<RefCell<bool>>,
     global_status: Arc<Mutex<GlobalShortcutStatus>>,
+    /// Stops the portal listener of the current registration
+    portal_stop: Rc<RefCell<Option<async_channel::Sender<()>>>>,
+    /// Bumped by each global registration so superseded ones leave the status alone
+    registration: Rc<Cell<u32>>,
 }
 
 impl KeyboardService {
@@ -67,9 +114,12 @@ impl KeyboardService {
     pub fn new() -> Self {
         let service = Self {
             shortcuts: Rc::new(RefCell::new(Vec::new())),
-            action_callbacks: Rc::new(RefCell::new(HashMap::new())),
+            action_callbacks: Rc::new(RefCell::new(Vec::new())),
+            global_target: Rc::new(RefCell::new(None)),
             enabled: Rc::new(RefCell::new(true)),
             global_status: Arc::new(Mutex::new(GlobalShortcutStatus::NotRegistered)),
+            portal_stop: Rc::new(RefCell::new(None)),
+            registration: Rc::new(Cell::new(0)),
         };
         
         service.register_default_shortcuts();
@@ -79,62 +129,124 @@ impl KeyboardService {
     /// Register default shortcut bindings
     fn register_default_shortcuts(&self) {
         let mut shortcuts = self.shortcuts.borrow_mut();
+        *shortcuts = default_shortcuts();
+        debug!("Registered {} default shortcuts", shortcuts.len());
+    }
+
+    /// Load shortcut bindings from settings
+    ///
+    /// Each configured binding replaces the default bindings for its action.
+    /// Entries that can't be parsed are skipped with a warning, leaving the
+    /// defaults for that action in place.
+    pub fn load_from_settings(&self, settings: &Settings) {
+        let configured: Vec<ShortcutBinding> = settings
+            .shortcuts
+            .iter()
+            .filter_map(|config| {
+                let binding = parse_shortcut_config(config);
+                if binding.is_none() {
+                    warn!(
+                        "Ignoring invalid shortcut '{}+{}' -> '{}', keeping the default",
+                        config.modifiers, config.key, config.action
+                    );
+                }
+                binding
+            })
+            .collect();
         
-        // Super+1-9 for app activation
-        for i in 1..=9u8 {
-            shortcuts.push(ShortcutBinding {
-                modifiers: gtk::gdk::ModifierType::SUPER_MASK,
-                key: gtk::gdk::Key::from_name(&format!("{}", i)).unwrap_or(gtk::gdk::Key::_1),
-                action: ShortcutAction::ActivateApp(i),
-            });
-        }
-        
-        // Super+D to toggle dock
-        shortcuts.push(ShortcutBinding {
-            modifiers: gtk::gdk::ModifierType::SUPER_MASK,
-            key: gtk::gdk::Key::d,
-            action: ShortcutAction::ToggleDock,
-        });
+        let mut shortcuts = default_shortcuts();
+        shortcuts.retain(|default| !configured.iter().any(|b| b.action == default.action));
+        shortcuts.extend(configured);
         
-        // Super+/ for search
-        shortcuts.push(ShortcutBinding {
-            modifiers: gtk::gdk::ModifierType::SUPER_MASK,
-            key: gtk::gdk::Key::slash,
-            action: ShortcutAction::ShowSearch,
-        });
-        
-        debug!("Registered {} default shortcuts", shortcuts.len());
+        debug!("Loaded {} shortcuts ({} from settings)", shortcuts.len(), settings.shortcuts.len());
+        *self.shortcuts.borrow_mut() = shortcuts;
     }
 
-    /// Register action callback
+    /// Register action callback, replacing any earlier one with the same id
     pub fn on_action<F>(&self, id: &str, callback: F)
     where
         F: Fn(ShortcutAction) + 'static,
     {
         let mut callbacks = self.action_callbacks.borrow_mut();
-        callbacks.insert(id.to_string(), Box::new(callback));
+        match callbacks.iter_mut().find(|(existing, _)| existing.as_str() == id) {
+            Some(entry) => entry.1 = Box::new(callback),
+            None => callbacks.push((id.to_string(), Box::new(callback))),
+        }
+    }
+
+    /// Remove the action callback registered under `id`
+    pub fn remove_action(&self, id: &str) {
+        self.action_callbacks.borrow_mut().retain(|(existing, _)| existing != id);
+    }
+
+    /// Choose which callback handles system-wide shortcuts other than ToggleDock
+    ///
+    /// `target` returns a callback id; without a match the first callback is used.
+    pub fn set_global_target<F>(&self, target: F)
+    where
+        F: Fn() -> Option<String> + 'static,
+    {
+        *self.global_target.borrow_mut() = Some(Box::new(target));
     }
 
     /// Start global shortcut registration
+    ///
+    /// Calling this again registers the current bindings anew; the portal
+    /// session of the previous registration is closed.
     pub fn register_global_shortcuts(&self) {
         let status = self.global_status.clone();
         let callbacks = Rc::clone(&self.action_callbacks);
+        let target = Rc::clone(&self.global_target);
+        let bindings = self.get_shortcuts();
+        
+        let registration = Rc::clone(&self.registration);
+        let generation = registration.get().wrapping_add(1);
+        registration.set(generation);
+        
+        // The portal binds shortcuts per session, so the old one has to go
+        let (stop_sender, stop) = async_channel::bounded::<()>(1);
+        if let Some(previous) = self.portal_stop.borrow_mut().replace(stop_sender) {
+            previous.close();
+        }
         
         glib::spawn_future_local(async move {
             // Try KDE first
-            if let Ok(true) = try_register_kde_shortcuts().await {
+            let kde = try_register_kde_shortcuts().await;
+            if registration.get() != generation {
+                return;
+            }
+            if let Ok(true) = kde {
                 *status.lock().unwrap() = GlobalShortcutStatus::KDE;
                 info!("Global shortcuts registered via KDE kglobalaccel");
                 return;
             }
             
-            // Try XDG Portal
-            if let Ok(true) = try_register_portal_shortcuts().await {
-                *status.lock().unwrap() = GlobalShortcutStatus::Portal;
-                info!("Global shortcuts registered via XDG Portal");
-                return;
+            // Try XDG Portal, which only counts once the shortcuts are bound
+            match try_register_portal_shortcuts(&bindings).await {
+                Ok(Some(session)) => {
+                    // A registration superseded while binding closes its session right away
+                    if registration.get() == generation {
+                        *status.lock().unwrap() = GlobalShortcutStatus::Portal;
+                        info!("Global shortcuts registered via XDG Portal");
+                    }
+                    
+                    if let Err(e) = listen_portal_shortcuts(session, callbacks, target, stop).await {
+                        warn!("Portal shortcut listener failed: {}", e);
+                    }
+                    
+                    if registration.get() == generation {
+                        *status.lock().unwrap() = GlobalShortcutStatus::NotRegistered;
+                        info!("Global shortcuts via XDG Portal are no longer active");
+                    }
+                    return;
+                }
+                Ok(None) => {}
+                Err(e) => debug!("Portal GlobalShortcuts registration failed: {}", e),
             }
             
+            if registration.get() != generation {
+                return;
+            }
             warn!("Could not register global shortcuts - only dock-focused shortcuts available");
             *status.lock().unwrap() = GlobalShortcutStatus::Failed;
         });
@@ -146,12 +258,15 @@ impl KeyboardService {
     }
 
     /// Setup keyboard controller on a widget (for when dock has focus)
-    pub fn setup_keyboard_controller(&self, widget: &impl IsA<gtk::Widget>) {
+    ///
+    /// Matched shortcuts go to the callback registered under `id`.
+    pub fn setup_keyboard_controller(&self, id: &str, widget: &impl IsA<gtk::Widget>) {
         let key_controller = gtk::EventControllerKey::new();
         
         let shortcuts = Rc::clone(&self.shortcuts);
         let callbacks = Rc::clone(&self.action_callbacks);
         let enabled = Rc::clone(&self.enabled);
+        let id = id.to_string();
 
         key_controller.connect_key_pressed(move |_, key, _keycode, state| {
             if !*enabled.borrow() {
@@ -169,14 +284,14 @@ impl KeyboardService {
                     gtk::gdk::ModifierType::SUPER_MASK
                 );
                 
-                if key == binding.key && effective_state == binding.modifiers {
+                if key.to_lower() == binding.key.to_lower() && effective_state == binding.modifiers {
                     debug!("Shortcut matched: {:?}", binding.action);
                     
                     let action = binding.action.clone();
                     let callbacks_guard = callbacks.borrow();
                     
-                    for callback in callbacks_guard.values() {
-                        callback(action.clone());
+                    if let Some((_, callback)) = callbacks_guard.iter().find(|(callback_id, _)| *callback_id == id) {
+                        callback(action);
                     }
                     
                     return glib::Propagation::Stop;
@@ -270,6 +385,124 @@ impl Default for KeyboardService {
     }
 }
 
+/// Build the default shortcut bindings
+fn default_shortcuts() -> Vec<ShortcutBinding> {
+    let mut shortcuts = Vec::new();
+    
+    // Super+1-9 for app activation
+    for i in 1..=9u8 {
+        shortcuts.push(ShortcutBinding {
+            modifiers: gtk::gdk::ModifierType::SUPER_MASK,
+            key: gtk::gdk::Key::from_name(&format!("{}", i)).unwrap_or(gtk::gdk::Key::_1),
+            action: ShortcutAction::ActivateApp(i),
+        });
+    }
+    
+    // Super+D to toggle dock
+    shortcuts.push(ShortcutBinding {
+        modifiers: gtk::gdk::ModifierType::SUPER_MASK,
+        key: gtk::gdk::Key::d,
+        action: ShortcutAction::ToggleDock,
+    });
+    
+    // Super+/ for search
+    shortcuts.push(ShortcutBinding {
+        modifiers: gtk::gdk::ModifierType::SUPER_MASK,
+        key: gtk::gdk::Key::slash,
+        action: ShortcutAction::ShowSearch,
+    });
+    
+    // Menu key and Super+. for the focused item's context menu
+    shortcuts.push(ShortcutBinding {
+        modifiers: gtk::gdk::ModifierType::empty(),
+        key: gtk::gdk::Key::Menu,
+        action: ShortcutAction::ShowContextMenu,
+    });
+    shortcuts.push(ShortcutBinding {
+        modifiers: gtk::gdk::ModifierType::SUPER_MASK,
+        key: gtk::gdk::Key::period,
+        action: ShortcutAction::ShowContextMenu,
+    });
+    
+    // Super+` to cycle through the focused app's windows
+    shortcuts.push(ShortcutBinding {
+        modifiers: gtk::gdk::ModifierType::SUPER_MASK,
+        key: gtk::gdk::Key::grave,
+        action: ShortcutAction::CycleWindows,
+    });
+    
+    shortcuts
+}
+
+/// Parse a configured shortcut into a binding
+fn parse_shortcut_config(config: &ShortcutConfig) -> Option<ShortcutBinding> {
+    let (modifiers, key) = parse_accelerator(&format!("{}+{}", config.modifiers, config.key))?;
+    Some(ShortcutBinding {
+        modifiers,
+        key,
+        action: parse_action(&config.action)?,
+    })
+}
+
+/// Parse an accelerator like "Ctrl+Shift+D" into modifiers and key
+fn parse_accelerator(accel: &str) -> Option<(gtk::gdk::ModifierType, gtk::gdk::Key)> {
+    let (modifiers, key) = match accel.rsplit_once('+') {
+        Some((modifiers, key)) => (modifiers, key),
+        None => ("", accel),
+    };
+    Some((parse_modifiers(modifiers)?, parse_key(key)?))
+}
+
+/// Parse '+'-separated modifier names (e.g. "Ctrl+Shift")
+fn parse_modifiers(modifiers: &str) -> Option<gtk::gdk::ModifierType> {
+    let mut mask = gtk::gdk::ModifierType::empty();
+    
+    for name in modifiers.split('+').map(str::trim).filter(|name| !name.is_empty()) {
+        mask |= match name.to_lowercase().as_str() {
+            "ctrl" | "control" | "primary" => gtk::gdk::ModifierType::CONTROL_MASK,
+            "shift" => gtk::gdk::ModifierType::SHIFT_MASK,
+            "alt" => gtk::gdk::ModifierType::ALT_MASK,
+            "super" | "meta" | "logo" | "win" => gtk::gdk::ModifierType::SUPER_MASK,
+            _ => return None,
+        };
+    }
+    
+    Some(mask)
+}
+
+/// Parse a GDK key name, normalized to lowercase so Shift doesn't change the match
+fn parse_key(name: &str) -> Option<gtk::gdk::Key> {
+    gtk::gdk::Key::from_name(name.trim()).map(|key| key.to_lower())
+}
+
+/// Parse an action name, accepting "ToggleDock", "toggle-dock" or "toggle_dock"
+fn parse_action(name: &str) -> Option<ShortcutAction> {
+    let normalized: String = name
+        .chars()
+        .filter(|c| *c != '-' && *c != '_')
+        .collect::<String>()
+        .to_lowercase();
+    
+    let action = match normalized.as_str() {
+        "toggledock" => ShortcutAction::ToggleDock,
+        "showsearch" => ShortcutAction::ShowSearch,
+        "navigateleft" => ShortcutAction::NavigateLeft,
+        "navigateright" => ShortcutAction::NavigateRight,
+        "activatefocused" => ShortcutAction::ActivateFocused,
+        "showcontextmenu" => ShortcutAction::ShowContextMenu,
+        "cyclewindows" => ShortcutAction::CycleWindows,
+        other => {
+            let position: u8 = other.strip_prefix("activateapp")?.parse().ok()?;
+            if !(1..=9).contains(&position) {
+                return None;
+            }
+            ShortcutAction::ActivateApp(position)
+        }
+    };
+    
+    Some(action)
+}
+
 /// Try to register shortcuts via KDE's kglobalaccel D-Bus interface
 async fn try_register_kde_shortcuts() -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
     let connection = zbus::Connection::session().await?;
@@ -356,8 +589,37 @@ async fn register_kde_shortcut(
     Ok(())
 }
 
+/// Bus name of the XDG desktop portal
+const PORTAL_DESTINATION: &str = "org.freedesktop.portal.Desktop";
+/// Object path of the XDG desktop portal
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+/// GlobalShortcuts portal interface
+const PORTAL_SHORTCUTS_INTERFACE: &str = "org.freedesktop.portal.GlobalShortcuts";
+
+/// Counter keeping portal request tokens unique within the process
+static PORTAL_TOKEN_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// A GlobalShortcuts portal session with our shortcuts bound
+struct PortalSession {
+    connection: zbus::Connection,
+    handle: OwnedObjectPath,
+}
+
+/// A signal received while listening on a portal session
+enum PortalSignal {
+    Activated(zbus::Message),
+    Closed,
+    /// A newer registration replaced this session
+    Stopped,
+}
+
 /// Try to register shortcuts via XDG Desktop Portal
-async fn try_register_portal_shortcuts() -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+///
+/// Creates a session and binds `bindings` to it. Returns `None` if the
+/// portal isn't available.
+async fn try_register_portal_shortcuts(
+    bindings: &[ShortcutBinding],
+) -> Result<Option<PortalSession>, Box<dyn std::error::Error + Send + Sync>> {
     let connection = zbus::Connection::session().await?;
     
     // Check if GlobalShortcuts portal exists
@@ -368,31 +630,270 @@ async fn try_register_portal_shortcuts() -> Result<bool, Box<dyn std::error::Err
     
     if !has_portal {
         debug!("XDG Portal not available");
-        return Ok(false);
+        return Ok(None);
     }
     
-    // Try to use org.freedesktop.portal.GlobalShortcuts
-    let result = connection.call_method(
-        Some("org.freedesktop.portal.Desktop"),
-        "/org/freedesktop/portal/desktop",
-        Some("org.freedesktop.portal.GlobalShortcuts"),
-        "CreateSession",
-        &(HashMap::<String, zbus::zvariant::Value>::new(),),
-    ).await;
+    // Create the session
+    let token = portal_token();
+    let session_token = portal_token();
+    let options = HashMap::from([
+        ("handle_token", Value::from(token.as_str())),
+        ("session_handle_token", Value::from(session_token.as_str())),
+    ]);
     
-    match result {
-        Ok(reply) => {
-            debug!("Portal GlobalShortcuts session created: {:?}", reply);
-            // Note: Full implementation would listen for Activated signals
-            Ok(true)
-        }
+    let results = match portal_request(&connection, "CreateSession", &(options,), &token).await {
+        Ok(results) => results,
         Err(e) => {
             debug!("Portal GlobalShortcuts not available: {}", e);
-            Ok(false)
+            return Ok(None);
+        }
+    };
+    
+    let handle = session_handle_from(&results).ok_or("CreateSession returned no session handle")?;
+    debug!("Portal GlobalShortcuts session created: {}", handle.as_str());
+    
+    // Bind our shortcuts to it
+    let shortcuts: Vec<(String, HashMap<&str, Value>)> = portal_shortcut_definitions(bindings)
+        .into_iter()
+        .map(|(id, description, trigger)| {
+            let mut properties = HashMap::from([("description", Value::from(description))]);
+            if let Some(trigger) = trigger {
+                properties.insert("preferred_trigger", Value::from(trigger));
+            }
+            (id, properties)
+        })
+        .collect();
+    
+    let token = portal_token();
+    let options = HashMap::from([("handle_token", Value::from(token.as_str()))]);
+    let results = portal_request(
+        &connection,
+        "BindShortcuts",
+        &(handle.as_ref(), shortcuts, "", options),
+        &token,
+    ).await?;
+    
+    let bound = results.get("shortcuts")
+        .and_then(|value| match &**value {
+            Value::Array(array) => Some(array.len()),
+            _ => None,
+        })
+        .unwrap_or(0);
+    debug!("Portal bound {} shortcuts", bound);
+    
+    Ok(Some(PortalSession { connection, handle }))
+}
+
+/// Dispatch shortcuts activated through the portal to the action callbacks
+///
+/// ToggleDock reaches every dock. Other shortcuts go to the one dock picked
+/// by `target`, so apps are not launched once per dock. Returns once the
+/// portal closes the session, or closes it itself when `stop` is closed.
+async fn listen_portal_shortcuts(
+    session: PortalSession,
+    callbacks: ActionCallbacks,
+    target: GlobalTarget,
+    stop: async_channel::Receiver<()>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use futures_util::StreamExt;
+    
+    let shortcuts_proxy = zbus::Proxy::new(
+        &session.connection,
+        PORTAL_DESTINATION,
+        PORTAL_PATH,
+        PORTAL_SHORTCUTS_INTERFACE,
+    ).await?;
+    let session_proxy = zbus::Proxy::new(
+        &session.connection,
+        PORTAL_DESTINATION,
+        session.handle.as_str(),
+        "org.freedesktop.portal.Session",
+    ).await?;
+    
+    let activated = shortcuts_proxy.receive_signal("Activated").await?.map(PortalSignal::Activated);
+    let closed = session_proxy.receive_signal("Closed").await?.map(|_| PortalSignal::Closed);
+    let stopped = futures_util::stream::once(Box::pin(async move {
+        let _ = stop.recv().await;
+        PortalSignal::Stopped
+    }));
+    let mut signals = futures_util::stream::select(
+        futures_util::stream::select(activated, closed),
+        stopped,
+    );
+    
+    while let Some(signal) = signals.next().await {
+        let message = match signal {
+            PortalSignal::Activated(message) => message,
+            PortalSignal::Closed => {
+                warn!("Portal closed the GlobalShortcuts session");
+                break;
+            }
+            PortalSignal::Stopped => {
+                debug!("Closing the replaced GlobalShortcuts session");
+                session_proxy.call_method("Close", &()).await?;
+                break;
+            }
+        };
+        
+        let body = message.body();
+        let (session_handle, shortcut_id, _timestamp, _options): (OwnedObjectPath, String, u64, HashMap<String, OwnedValue>) =
+            match body.deserialize() {
+                Ok(args) => args,
+                Err(e) => {
+                    debug!("Ignoring malformed Activated signal: {}", e);
+                    continue;
+                }
+            };
+        
+        if session_handle != session.handle {
+            continue;
         }
+        
+        match parse_action(&shortcut_id) {
+            Some(action) => {
+                debug!("Portal shortcut activated: {:?}", action);
+                dispatch_global_action(&callbacks.borrow(), &target, action);
+            }
+            None => warn!("Unknown portal shortcut '{}'", shortcut_id),
+        }
+    }
+    
+    Ok(())
+}
+
+/// Hand a system-wide shortcut to the docks
+fn dispatch_global_action(
+    callbacks: &[(String, Box<dyn Fn(ShortcutAction)>)],
+    target: &GlobalTarget,
+    action: ShortcutAction,
+) {
+    if action == ShortcutAction::ToggleDock {
+        for (_, callback) in callbacks {
+            callback(action.clone());
+        }
+        return;
+    }
+    
+    let target_id = target.borrow().as_ref().and_then(|target| target());
+    let callback = target_id
+        .and_then(|id| callbacks.iter().find(|(callback_id, _)| *callback_id == id))
+        .or_else(|| callbacks.first());
+    if let Some((_, callback)) = callback {
+        callback(action);
+    }
+}
+
+/// Call a portal method and wait for the `Response` of the request it creates
+///
+/// `token` must be the `handle_token` passed in the method's options.
+async fn portal_request<B>(
+    connection: &zbus::Connection,
+    method: &str,
+    body: &B,
+    token: &str,
+) -> Result<HashMap<String, OwnedValue>, Box<dyn std::error::Error + Send + Sync>>
+where
+    B: serde::Serialize + zbus::zvariant::DynamicType,
+{
+    use futures_util::StreamExt;
+    
+    let sender = connection.unique_name().ok_or("connection has no unique name")?;
+    let request_path = portal_request_path(sender.as_str(), token);
+    
+    // Subscribe before calling so the response can't be missed
+    let request = zbus::Proxy::new(
+        connection,
+        PORTAL_DESTINATION,
+        request_path.as_str(),
+        "org.freedesktop.portal.Request",
+    ).await?;
+    let mut responses = request.receive_signal("Response").await?;
+    
+    connection.call_method(
+        Some(PORTAL_DESTINATION),
+        PORTAL_PATH,
+        Some(PORTAL_SHORTCUTS_INTERFACE),
+        method,
+        body,
+    ).await?;
+    
+    let message = responses.next().await.ok_or("portal request ended without a response")?;
+    let (response, results): (u32, HashMap<String, OwnedValue>) = message.body().deserialize()?;
+    
+    if response != 0 {
+        return Err(format!("{} was rejected by the portal (response {})", method, response).into());
+    }
+    
+    Ok(results)
+}
+
+/// Generate a fresh portal request token
+fn portal_token() -> String {
+    format!(
+        "blazedock_{}_{}",
+        std::process::id(),
+        PORTAL_TOKEN_COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Object path of the request the portal creates for a call by `sender` with `token`
+fn portal_request_path(sender: &str, token: &str) -> String {
+    format!(
+        "{}/request/{}/{}",
+        PORTAL_PATH,
+        sender.trim_start_matches(':').replace('.', "_"),
+        token
+    )
+}
+
+/// Extract the session handle from a CreateSession response
+///
+/// The spec types it as a string, but some portals send an object path.
+fn session_handle_from(results: &HashMap<String, OwnedValue>) -> Option<OwnedObjectPath> {
+    match &**results.get("session_handle")? {
+        Value::Str(handle) => OwnedObjectPath::try_from(handle.as_str()).ok(),
+        Value::ObjectPath(handle) => Some(OwnedObjectPath::from(handle.clone())),
+        _ => None,
     }
 }
 
+/// Build portal shortcut definitions as (id, description, preferred trigger)
+///
+/// The id is the action name understood by `parse_action`. Actions with
+/// several bindings use the first as the preferred trigger.
+fn portal_shortcut_definitions(bindings: &[ShortcutBinding]) -> Vec<(String, String, Option<String>)> {
+    let mut definitions: Vec<(String, String, Option<String>)> = Vec::new();
+    
+    for binding in bindings {
+        let id = binding.action.id();
+        if definitions.iter().any(|(existing, _, _)| *existing == id) {
+            continue;
+        }
+        definitions.push((id, binding.action.description(), portal_trigger(binding)));
+    }
+    
+    definitions
+}
+
+/// Format a binding as an XDG shortcut trigger, e.g. "LOGO+d"
+fn portal_trigger(binding: &ShortcutBinding) -> Option<String> {
+    let key = binding.key.name()?;
+    
+    let mut parts: Vec<String> = [
+        (gtk::gdk::ModifierType::CONTROL_MASK, "CTRL"),
+        (gtk::gdk::ModifierType::ALT_MASK, "ALT"),
+        (gtk::gdk::ModifierType::SHIFT_MASK, "SHIFT"),
+        (gtk::gdk::ModifierType::SUPER_MASK, "LOGO"),
+    ]
+    .iter()
+    .filter(|(mask, _)| binding.modifiers.contains(*mask))
+    .map(|(_, name)| name.to_string())
+    .collect();
+    parts.push(key.to_string());
+    
+    Some(parts.join("+"))
+}
+
 /// Backward compatibility function
 pub fn register_global_shortcuts() {
     let service = KeyboardService::new();
@@ -414,8 +915,8 @@ mod tests {
         let service = KeyboardService::new();
         let shortcuts = service.get_shortcuts();
         
-        // Should have 9 app shortcuts + toggle + search = 11
-        assert_eq!(shortcuts.len(), 11);
+        // Should have 9 app shortcuts + toggle + search + 2 context menu + cycle = 14
+        assert_eq!(shortcuts.len(), 14);
     }
 
     #[test]
@@ -431,6 +932,92 @@ mod tests {
         assert!(service.is_enabled());
     }
 
+    #[test]
+    fn test_parse_accelerator() {
+        let (modifiers, key) = parse_accelerator("Ctrl+Shift+D").unwrap();
+        assert_eq!(
+            modifiers,
+            gtk::gdk::ModifierType::CONTROL_MASK | gtk::gdk::ModifierType::SHIFT_MASK
+        );
+        assert_eq!(key, gtk::gdk::Key::d);
+        assert_eq!(parse_action("ToggleDock"), Some(ShortcutAction::ToggleDock));
+        
+        assert_eq!(parse_accelerator("F1").unwrap().0, gtk::gdk::ModifierType::empty());
+        assert!(parse_accelerator("Hyper+D").is_none());
+        assert!(parse_accelerator("Ctrl+NotAKey").is_none());
+    }
+
+    #[test]
+    fn test_parse_action_names() {
+        assert_eq!(parse_action("show-search"), Some(ShortcutAction::ShowSearch));
+        assert_eq!(parse_action("activate_app_3"), Some(ShortcutAction::ActivateApp(3)));
+        assert_eq!(parse_action("ActivateApp10"), None);
+        assert_eq!(parse_action("Frobnicate"), None);
+    }
+
+    #[test]
+    fn test_load_from_settings() {
+        let service = KeyboardService::new();
+        let mut settings = Settings::default();
+        settings.shortcuts = vec![
+            ShortcutConfig {
+                modifiers: "Ctrl+Shift".to_string(),
+                key: "D".to_string(),
+                action: "ToggleDock".to_string(),
+            },
+            ShortcutConfig {
+                modifiers: "Bogus".to_string(),
+                key: "slash".to_string(),
+                action: "ShowSearch".to_string(),
+            },
+        ];
+        
+        service.load_from_settings(&settings);
+        let shortcuts = service.get_shortcuts();
+        assert_eq!(shortcuts.len(), 14);
+        
+        let toggle: Vec<_> = shortcuts.iter().filter(|b| b.action == ShortcutAction::ToggleDock).collect();
+        assert_eq!(toggle.len(), 1);
+        assert_eq!(
+            toggle[0].modifiers,
+            gtk::gdk::ModifierType::CONTROL_MASK | gtk::gdk::ModifierType::SHIFT_MASK
+        );
+        
+        // The invalid entry leaves the default search binding in place
+        let search = shortcuts.iter().find(|b| b.action == ShortcutAction::ShowSearch).unwrap();
+        assert_eq!(search.modifiers, gtk::gdk::ModifierType::SUPER_MASK);
+        assert_eq!(search.key, gtk::gdk::Key::slash);
+    }
+
+    #[test]
+    fn test_portal_shortcut_definitions() {
+        let definitions = portal_shortcut_definitions(&default_shortcuts());
+        
+        // One definition per action, even with several bindings
+        assert_eq!(definitions.len(), 13);
+        
+        let (id, description, trigger) = &definitions[0];
+        assert_eq!(id, "activate-app-1");
+        assert_eq!(description, "Activate App 1");
+        assert_eq!(trigger.as_deref(), Some("LOGO+1"));
+        
+        let (_, _, trigger) = definitions.iter().find(|(id, _, _)| id == "show-context-menu").unwrap();
+        assert_eq!(trigger.as_deref(), Some("Menu"));
+        
+        // Activated ids map back to their actions
+        for (id, _, _) in &definitions {
+            assert_eq!(parse_action(id).map(|action| action.id()).as_ref(), Some(id));
+        }
+    }
+
+    #[test]
+    fn test_portal_request_path() {
+        assert_eq!(
+            portal_request_path(":1.42", "blazedock_7_0"),
+            "/org/freedesktop/portal/desktop/request/1_42/blazedock_7_0"
+        );
+    }
+
     #[test]
     fn test_custom_shortcut() {
         let service = KeyboardService::new();
@@ -445,4 +1032,29 @@ mod tests {
         
         assert_eq!(service.get_shortcuts().len(), initial_count + 1);
     }
+
+    #[test]
+    fn test_global_actions_reach_the_target_dock() {
+        let service = KeyboardService::new();
+        let received: Rc<RefCell<Vec<(&str, ShortcutAction)>>> = Rc::new(RefCell::new(Vec::new()));
+        for id in ["DP-1", "HDMI-A-1"] {
+            let received = Rc::clone(&received);
+            service.on_action(id, move |action| received.borrow_mut().push((id, action)));
+        }
+        let dispatch = |action| dispatch_global_action(&service.action_callbacks.borrow(), &service.global_target, action);
+        
+        // Every dock toggles
+        dispatch(ShortcutAction::ToggleDock);
+        assert_eq!(received.take(), [("DP-1", ShortcutAction::ToggleDock), ("HDMI-A-1", ShortcutAction::ToggleDock)]);
+        
+        // Item shortcuts go to the dock on the target monitor only
+        service.set_global_target(|| Some("HDMI-A-1".to_string()));
+        dispatch(ShortcutAction::ActivateApp(2));
+        assert_eq!(received.take(), [("HDMI-A-1", ShortcutAction::ActivateApp(2))]);
+        
+        // Without a dock there, the first one handles them
+        service.set_global_target(|| Some("DP-9".to_string()));
+        dispatch(ShortcutAction::ShowSearch);
+        assert_eq!(received.take(), [("DP-1", ShortcutAction::ShowSearch)]);
+    }
 }
@@ -12,15 +12,19 @@ pub mod theme_service;
 pub mod keyboard_service;
 pub mod multimonitor;
 pub mod screencopy_service;
+pub mod dock_services;
+#[cfg(feature = "wlr-screencopy")]
+mod wlr_screencopy;
 
 pub use process_tracker::ProcessTracker;
-pub use dbus_service::DBusService;
-pub use window_tracker::{WindowTracker, WindowInfo};
-pub use drive_monitor::DriveMonitor;
-pub use recent_files::RecentFilesService;
+pub use dbus_service::{DBusService, BadgeInfo, DBusEvent};
+pub use window_tracker::{WindowTracker, WindowInfo, DesktopEnvironment};
+pub use drive_monitor::{DriveMonitor, DriveInfo};
+pub use recent_files::{RecentFilesService, RecentEntry};
 pub use running_apps::{RunningAppsService, RunningApp};
 pub use theme_service::{ThemeService, ThemeColors, ThemeMode};
 pub use keyboard_service::{KeyboardService, ShortcutAction, ShortcutBinding};
 pub use multimonitor::{MultiMonitorService, MonitorInfo, MultiMonitorMode};
-pub use screencopy_service::{ScreencopyService, WindowThumbnail};
+pub use screencopy_service::{ScreencopyService, ScreenshotTool, WindowThumbnail};
+pub use dock_services::DockServices;
 
@@ -7,7 +7,20 @@ use gtk::glib;
 use gtk::gdk;
 use log::{info, debug, warn};
 use std::collections::HashMap;
+use std::process::Command;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::window_tracker::{WindowTracker, SWAY_IPC_GET_OUTPUTS};
+
+/// How often the compositor is asked where the pointer is while following it
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long the pointer must stay on another monitor before the dock moves
+///
+/// Keeps the dock from bouncing between monitors while the pointer sits on
+/// their shared edge.
+const FOLLOW_MOUSE_DWELL: Duration = Duration::from_millis(400);
 
 /// Monitor information
 #[derive(Debug, Clone)]
@@ -20,6 +33,39 @@ pub struct MonitorInfo {
     pub connector: String,
 }
 
+impl MonitorInfo {
+    /// Stable key for this monitor: the connector name, or the ID if unknown
+    pub fn key(&self) -> &str {
+        if self.connector.is_empty() {
+            &self.id
+        } else {
+            &self.connector
+        }
+    }
+
+    /// Look up the GDK monitor this info was scanned from
+    pub fn gdk_monitor(&self) -> Option<gdk::Monitor> {
+        let display = gdk::Display::default()?;
+        let monitor_list = display.monitors();
+        
+        (0..monitor_list.n_items())
+            .filter_map(|i| monitor_list.item(i).and_downcast::<gdk::Monitor>())
+            .find(|monitor| {
+                monitor.connector().map(|c| c.to_string()).unwrap_or_default() == self.connector
+                    && monitor.geometry() == self.geometry
+            })
+    }
+}
+
+/// Where the compositor reports the pointer
+#[derive(Debug, Clone, PartialEq)]
+enum PointerLocation {
+    /// Global layout coordinates (Hyprland)
+    Point(i32, i32),
+    /// Connector of the focused output, which follows the pointer (Sway)
+    Output(String),
+}
+
 /// Multi-monitor mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MultiMonitorMode {
@@ -39,23 +85,38 @@ pub struct MultiMonitorService {
     monitors: Arc<Mutex<HashMap<String, MonitorInfo>>>,
     mode: Arc<Mutex<MultiMonitorMode>>,
     primary_monitor: Arc<Mutex<Option<String>>>,
+    /// Primary connector reported by the compositor, if it names one
+    detected_primary: Arc<Mutex<Option<String>>>,
     current_monitor: Arc<Mutex<Option<String>>>,
     on_monitor_change: Arc<Mutex<Vec<Box<dyn Fn(&MonitorInfo) + Send + Sync>>>>,
+    on_monitors_changed: Arc<Mutex<Vec<Box<dyn Fn(&[MonitorInfo]) + Send + Sync>>>>,
+    /// Monitor the pointer moved to, and when it arrived there
+    pending_monitor: Arc<Mutex<Option<(String, Instant)>>>,
+    /// Whether the pointer is already being followed
+    following: Arc<Mutex<bool>>,
 }
 
 impl MultiMonitorService {
     /// Create a new multi-monitor service
     pub fn new() -> Self {
-        let service = Self {
+        let service = Self::empty();
+        service.scan_monitors();
+        service
+    }
+
+    /// Create the service without scanning, so it works without a display
+    fn empty() -> Self {
+        Self {
             monitors: Arc::new(Mutex::new(HashMap::new())),
             mode: Arc::new(Mutex::new(MultiMonitorMode::PrimaryOnly)),
             primary_monitor: Arc::new(Mutex::new(None)),
+            detected_primary: Arc::new(Mutex::new(None)),
             current_monitor: Arc::new(Mutex::new(None)),
             on_monitor_change: Arc::new(Mutex::new(Vec::new())),
-        };
-
-        service.scan_monitors();
-        service
+            on_monitors_changed: Arc::new(Mutex::new(Vec::new())),
+            pending_monitor: Arc::new(Mutex::new(None)),
+            following: Arc::new(Mutex::new(false)),
+        }
     }
 
     /// Scan for connected monitors
@@ -68,14 +129,14 @@ impl MultiMonitorService {
             }
         };
 
-        let mut monitors = self.monitors.lock().unwrap();
-        monitors.clear();
+        let old_keys = self.monitor_keys();
 
         let monitor_list = display.monitors();
         let n_monitors = monitor_list.n_items();
         
         info!("Found {} monitors", n_monitors);
 
+        let mut scanned = Vec::new();
         for i in 0..n_monitors {
             if let Some(monitor) = monitor_list.item(i).and_downcast::<gdk::Monitor>() {
                 let geometry = monitor.geometry();
@@ -87,7 +148,7 @@ impl MultiMonitorService {
                     name: monitor.model().map(|s| s.to_string()).unwrap_or_else(|| format!("Monitor {}", i)),
                     geometry,
                     scale_factor: monitor.scale_factor(),
-                    is_primary: i == 0, // First monitor is typically primary
+                    is_primary: false,
                     connector,
                 };
 
@@ -97,20 +158,68 @@ impl MultiMonitorService {
                     geometry.x(), geometry.y()
                 );
 
-                if info.is_primary {
-                    *self.primary_monitor.lock().unwrap() = Some(id.clone());
-                }
-
-                monitors.insert(id, info);
+                scanned.push(info);
             }
         }
 
+        let mut monitors = self.monitors.lock().unwrap();
+        monitors.clear();
+        monitors.extend(scanned.into_iter().map(|monitor| (monitor.id.clone(), monitor)));
+        drop(monitors);
+
+        // Only ask the compositor again when the set of monitors changed
+        let detected = self.detected_primary.lock().unwrap().clone();
+        self.mark_primary(detected.as_deref());
+        if self.monitor_keys() != old_keys {
+            self.detect_primary_in_background();
+        }
+
         // Set current monitor to primary if not set
         if self.current_monitor.lock().unwrap().is_none() {
             *self.current_monitor.lock().unwrap() = self.primary_monitor.lock().unwrap().clone();
         }
     }
 
+    /// Ask the compositor for its primary output without blocking the main loop
+    ///
+    /// `hyprctl` and `swaymsg` run on a worker thread; the primary monitor
+    /// is updated once they answer.
+    fn detect_primary_in_background(&self) {
+        let (sender, receiver) = async_channel::bounded(1);
+        std::thread::spawn(move || {
+            let _ = sender.send_blocking(detect_primary_connector());
+        });
+        
+        let service = self.clone();
+        glib::spawn_future_local(async move {
+            let Ok(detected) = receiver.recv().await else {
+                return;
+            };
+            *service.detected_primary.lock().unwrap() = detected.clone();
+            service.mark_primary(detected.as_deref());
+        });
+    }
+
+    /// Flag the primary monitor, preferring the compositor's `detected` connector
+    fn mark_primary(&self, detected: Option<&str>) {
+        let mut monitors = self.monitors.lock().unwrap();
+        let mut ordered: Vec<&mut MonitorInfo> = monitors.values_mut().collect();
+        ordered.sort_by(|a, b| a.key().cmp(b.key()));
+        
+        let candidates: Vec<MonitorInfo> = ordered.iter().map(|monitor| (**monitor).clone()).collect();
+        let primary = choose_primary(&candidates, detected);
+        for (index, monitor) in ordered.iter_mut().enumerate() {
+            monitor.is_primary = Some(index) == primary;
+        }
+        
+        let primary_id = primary.map(|index| candidates[index].id.clone());
+        if let Some(index) = primary {
+            debug!("Primary monitor: {}", candidates[index].key());
+        }
+        drop(monitors);
+        *self.primary_monitor.lock().unwrap() = primary_id;
+    }
+
     /// Start monitoring for display changes
     pub fn start_monitoring(&self) {
         let service = self.clone();
@@ -131,18 +240,109 @@ impl MultiMonitorService {
         });
     }
 
+    /// Start following the pointer across monitors
+    ///
+    /// Wayland only reports the pointer over our own surfaces, so the
+    /// compositor is asked instead: Hyprland for the cursor position, Sway
+    /// for the focused output. Elsewhere the current monitor stays put.
+    pub fn start_following(&self) {
+        if std::mem::replace(&mut *self.following.lock().unwrap(), true) {
+            return;
+        }
+        if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_err() && std::env::var("SWAYSOCK").is_err() {
+            info!("Following the pointer needs Hyprland or Sway, staying on the current monitor");
+            return;
+        }
+        
+        let service = self.clone();
+        glib::spawn_future_local(async move {
+            loop {
+                glib::timeout_future(FOLLOW_POLL_INTERVAL).await;
+                match query_pointer_location().await {
+                    Ok(Some(location)) => {
+                        service.update_pointer_location(&location);
+                    }
+                    Ok(None) => {}
+                    Err(e) => debug!("Pointer location query failed: {}", e),
+                }
+            }
+        });
+        
+        debug!("Following the pointer across monitors");
+    }
+
+    /// Feed where the compositor reports the pointer, moving the current monitor if needed
+    ///
+    /// Returns the new current monitor when it changed.
+    fn update_pointer_location(&self, location: &PointerLocation) -> Option<MonitorInfo> {
+        let monitor = self.update_pointer_location_at(location, Instant::now())?;
+        self.notify_change();
+        Some(monitor)
+    }
+
+    /// Throttled current-monitor update for a pointer location sampled at `now`
+    fn update_pointer_location_at(&self, location: &PointerLocation, now: Instant) -> Option<MonitorInfo> {
+        let monitor = match location {
+            PointerLocation::Point(x, y) => self.monitor_at_point(*x, *y)?,
+            PointerLocation::Output(connector) => self.monitor_by_connector(connector)?,
+        };
+        let mut pending = self.pending_monitor.lock().unwrap();
+        
+        if self.current_monitor.lock().unwrap().as_deref() == Some(monitor.id.as_str()) {
+            *pending = None;
+            return None;
+        }
+        
+        match pending.as_ref() {
+            Some((id, since)) if *id == monitor.id => {
+                if now.duration_since(*since) < FOLLOW_MOUSE_DWELL {
+                    return None;
+                }
+            }
+            _ => {
+                *pending = Some((monitor.id.clone(), now));
+                return None;
+            }
+        }
+        
+        *pending = None;
+        *self.current_monitor.lock().unwrap() = Some(monitor.id.clone());
+        debug!("Pointer moved to monitor {}", monitor.key());
+        Some(monitor)
+    }
+
     /// Check for monitor changes
     fn check_for_changes(&self) {
-        let old_count = self.monitors.lock().unwrap().len();
+        let old_keys = self.monitor_keys();
         self.scan_monitors();
-        let new_count = self.monitors.lock().unwrap().len();
+        let new_keys = self.monitor_keys();
         
-        if old_count != new_count {
-            info!("Monitor configuration changed: {} -> {} monitors", old_count, new_count);
+        let (added, removed) = monitor_set_diff(&old_keys, &new_keys);
+        if !added.is_empty() || !removed.is_empty() {
+            info!(
+                "Monitor configuration changed: {} -> {} monitors (added {:?}, removed {:?})",
+                old_keys.len(), new_keys.len(), added, removed
+            );
             self.notify_change();
+            
+            let monitors = self.get_monitors();
+            let callbacks = self.on_monitors_changed.lock().unwrap();
+            for callback in callbacks.iter() {
+                callback(&monitors);
+            }
         }
     }
 
+    /// Sorted keys of the connected monitors
+    fn monitor_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.monitors.lock().unwrap()
+            .values()
+            .map(|monitor| monitor.key().to_string())
+            .collect();
+        keys.sort();
+        keys
+    }
+
     /// Notify callbacks of monitor change
     fn notify_change(&self) {
         let current_id = self.current_monitor.lock().unwrap().clone();
@@ -165,6 +365,15 @@ impl MultiMonitorService {
         callbacks.push(Box::new(callback));
     }
 
+    /// Register callback receiving all monitors whenever one is plugged or unplugged
+    pub fn on_monitors_changed<F>(&self, callback: F)
+    where
+        F: Fn(&[MonitorInfo]) + Send + Sync + 'static,
+    {
+        let mut callbacks = self.on_monitors_changed.lock().unwrap();
+        callbacks.push(Box::new(callback));
+    }
+
     /// Set multi-monitor mode
     pub fn set_mode(&self, mode: MultiMonitorMode) {
         *self.mode.lock().unwrap() = mode;
@@ -176,9 +385,11 @@ impl MultiMonitorService {
         *self.mode.lock().unwrap()
     }
 
-    /// Get all monitors
+    /// Get all monitors, ordered by key
     pub fn get_monitors(&self) -> Vec<MonitorInfo> {
-        self.monitors.lock().unwrap().values().cloned().collect()
+        let mut monitors: Vec<MonitorInfo> = self.monitors.lock().unwrap().values().cloned().collect();
+        monitors.sort_by(|a, b| a.key().cmp(b.key()));
+        monitors
     }
 
     /// Get monitor by ID
@@ -221,15 +432,20 @@ impl MultiMonitorService {
         None
     }
 
+    /// Find the monitor plugged into `connector`
+    fn monitor_by_connector(&self, connector: &str) -> Option<MonitorInfo> {
+        self.monitors.lock().unwrap()
+            .values()
+            .find(|monitor| monitor.connector == connector)
+            .cloned()
+    }
+
     /// Get monitor for a position based on current mode
     pub fn get_target_monitor(&self) -> Option<MonitorInfo> {
         match *self.mode.lock().unwrap() {
             MultiMonitorMode::PrimaryOnly => self.get_primary_monitor(),
             MultiMonitorMode::AllMonitors => self.get_primary_monitor(), // Return primary, dock will be cloned
-            MultiMonitorMode::FollowMouse => {
-                // Get mouse position (simplified - would need actual pointer tracking)
-                self.get_current_monitor()
-            }
+            MultiMonitorMode::FollowMouse => self.get_current_monitor(),
             MultiMonitorMode::PerMonitor => self.get_current_monitor(),
         }
     }
@@ -262,3 +478,312 @@ impl Default for MultiMonitorService {
     }
 }
 
+
+/// Ask the compositor which output is the primary one
+///
+/// Returns the connector name (e.g. `DP-1`), or `None` when the compositor
+/// can't tell and the caller should fall back to [`choose_primary`]'s guess.
+pub fn detect_primary_connector() -> Option<String> {
+    if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
+        let output = Command::new("hyprctl").args(["monitors", "-j"]).output().ok()?;
+        if output.status.success() {
+            return parse_hyprland_primary(&String::from_utf8_lossy(&output.stdout));
+        }
+    } else if std::env::var("SWAYSOCK").is_ok() {
+        let output = Command::new("swaymsg").args(["-t", "get_outputs", "-r"]).output().ok()?;
+        if output.status.success() {
+            return parse_sway_primary(&String::from_utf8_lossy(&output.stdout));
+        }
+    }
+    
+    None
+}
+
+/// Ask the compositor where the pointer is
+async fn query_pointer_location() -> Result<Option<PointerLocation>, Box<dyn std::error::Error + Send + Sync>> {
+    if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
+        let json = WindowTracker::hyprland_request(b"j/cursorpos").await?;
+        Ok(parse_hyprland_cursor(&json))
+    } else if std::env::var("SWAYSOCK").is_ok() {
+        let json = WindowTracker::sway_request(SWAY_IPC_GET_OUTPUTS, &[]).await?;
+        Ok(parse_sway_focused_output(&json))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Read the pointer position from `hyprctl cursorpos -j`
+fn parse_hyprland_cursor(json: &str) -> Option<PointerLocation> {
+    #[derive(serde::Deserialize)]
+    struct CursorPos {
+        x: i32,
+        y: i32,
+    }
+    
+    let cursor: CursorPos = serde_json::from_str(json).ok()?;
+    Some(PointerLocation::Point(cursor.x, cursor.y))
+}
+
+/// Read the focused output from `swaymsg -t get_outputs`
+fn parse_sway_focused_output(json: &str) -> Option<PointerLocation> {
+    #[derive(serde::Deserialize)]
+    struct SwayOutput {
+        name: String,
+        #[serde(default)]
+        focused: bool,
+        #[serde(default)]
+        active: bool,
+    }
+    
+    let outputs: Vec<SwayOutput> = serde_json::from_str(json).ok()?;
+    outputs.into_iter()
+        .find(|output| output.focused && output.active)
+        .map(|output| PointerLocation::Output(output.name))
+}
+
+/// Pick the primary connector from `hyprctl monitors -j`
+///
+/// Hyprland has no primary output, so use the monitor with the lowest ID,
+/// the first one it set up. Focus moves around and is no hint.
+fn parse_hyprland_primary(json: &str) -> Option<String> {
+    #[derive(serde::Deserialize)]
+    struct HyprMonitor {
+        id: i64,
+        name: String,
+    }
+    
+    let monitors: Vec<HyprMonitor> = serde_json::from_str(json).ok()?;
+    monitors.into_iter()
+        .min_by_key(|monitor| monitor.id)
+        .map(|monitor| monitor.name)
+}
+
+/// Pick the primary connector from `swaymsg -t get_outputs`
+///
+/// Only an active output flagged `primary` counts; focus is no hint.
+fn parse_sway_primary(json: &str) -> Option<String> {
+    #[derive(serde::Deserialize)]
+    struct SwayOutput {
+        name: String,
+        #[serde(default)]
+        primary: bool,
+        #[serde(default)]
+        active: bool,
+    }
+    
+    let outputs: Vec<SwayOutput> = serde_json::from_str(json).ok()?;
+    outputs.into_iter()
+        .find(|output| output.primary && output.active)
+        .map(|output| output.name)
+}
+
+/// Index of the primary monitor in `monitors`
+///
+/// Uses the compositor's `detected` connector when it is connected, else the
+/// largest monitor, with ties going to the leftmost (then topmost) one.
+pub fn choose_primary(monitors: &[MonitorInfo], detected: Option<&str>) -> Option<usize> {
+    if let Some(connector) = detected {
+        if let Some(index) = monitors.iter().position(|monitor| monitor.connector == connector) {
+            return Some(index);
+        }
+    }
+    
+    monitors.iter()
+        .enumerate()
+        .min_by_key(|(_, monitor)| {
+            let geom = &monitor.geometry;
+            (-(geom.width() as i64 * geom.height() as i64), geom.x(), geom.y())
+        })
+        .map(|(index, _)| index)
+}
+
+/// Compare the monitors that have a dock with the connected ones
+///
+/// Returns `(added, removed)`: keys needing a new dock and keys whose dock
+/// should be destroyed.
+pub fn monitor_set_diff(current: &[String], connected: &[String]) -> (Vec<String>, Vec<String>) {
+    let added = connected.iter()
+        .filter(|key| !current.contains(key))
+        .cloned()
+        .collect();
+    let removed = current.iter()
+        .filter(|key| !connected.contains(key))
+        .cloned()
+        .collect();
+    (added, removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// Two side-by-side monitors: 1920x1080 at the origin, 1280x1024 to its right
+    fn dual_monitor_service() -> MultiMonitorService {
+        let service = MultiMonitorService::empty();
+        let layout = [("monitor-0", "DP-1", 0, 1920, 1080), ("monitor-1", "HDMI-A-1", 1920, 1280, 1024)];
+        for (id, connector, x, width, height) in layout {
+            service.monitors.lock().unwrap().insert(id.to_string(), MonitorInfo {
+                id: id.to_string(),
+                name: connector.to_string(),
+                geometry: gdk::Rectangle::new(x, 0, width, height),
+                scale_factor: 1,
+                is_primary: x == 0,
+                connector: connector.to_string(),
+            });
+        }
+        *service.current_monitor.lock().unwrap() = Some("monitor-0".to_string());
+        service
+    }
+
+    #[test]
+    fn test_parse_hyprland_primary() {
+        let json = r#"[
+            {"id": 0, "name": "eDP-1", "description": "Laptop panel", "width": 1920, "height": 1200,
+             "x": 0, "y": 0, "scale": 1.25, "focused": false, "dpmsStatus": true},
+            {"id": 1, "name": "DP-2", "description": "External", "width": 2560, "height": 1440,
+             "x": 1536, "y": 0, "scale": 1.0, "focused": true, "dpmsStatus": true}
+        ]"#;
+        // The lowest ID wins, wherever the focus is
+        assert_eq!(parse_hyprland_primary(json).as_deref(), Some("eDP-1"));
+        
+        assert_eq!(parse_hyprland_primary("[]"), None);
+        assert_eq!(parse_hyprland_primary("not json"), None);
+    }
+
+    #[test]
+    fn test_parse_sway_primary() {
+        let json = r#"[
+            {"name": "HDMI-A-1", "active": true, "primary": false, "focused": true},
+            {"name": "DP-1", "active": true, "primary": true, "focused": false}
+        ]"#;
+        assert_eq!(parse_sway_primary(json).as_deref(), Some("DP-1"));
+        
+        // The focused output isn't taken for the primary one
+        let no_primary = json.replace(r#""primary": true"#, r#""primary": false"#);
+        assert_eq!(parse_sway_primary(&no_primary), None);
+    }
+
+    #[test]
+    fn test_choose_primary() {
+        let monitors = dual_monitor_service().get_monitors();
+        assert_eq!(monitors[0].connector, "DP-1");
+        
+        // The compositor's answer wins when that monitor is connected
+        assert_eq!(choose_primary(&monitors, Some("HDMI-A-1")), Some(1));
+        // Otherwise the largest monitor is primary
+        assert_eq!(choose_primary(&monitors, Some("DP-9")), Some(0));
+        assert_eq!(choose_primary(&monitors, None), Some(0));
+        assert_eq!(choose_primary(&[], None), None);
+        
+        // Equal sizes fall back to the leftmost monitor
+        let mut same_size = monitors.clone();
+        same_size[0].geometry = gdk::Rectangle::new(1280, 0, 1280, 1024);
+        same_size[1].geometry = gdk::Rectangle::new(0, 0, 1280, 1024);
+        assert_eq!(choose_primary(&same_size, None), Some(1));
+    }
+
+    #[test]
+    fn test_late_detection_moves_primary() {
+        let service = dual_monitor_service();
+        service.mark_primary(None);
+        assert_eq!(service.get_primary_monitor().unwrap().connector, "DP-1");
+        
+        // The compositor's answer arrives after the scan
+        service.mark_primary(Some("HDMI-A-1"));
+        assert_eq!(service.get_primary_monitor().unwrap().connector, "HDMI-A-1");
+        let flagged: Vec<_> = service.get_monitors().into_iter().filter(|m| m.is_primary).collect();
+        assert_eq!(flagged.len(), 1);
+    }
+
+    fn monitor_id_at(service: &MultiMonitorService, x: i32, y: i32) -> Option<String> {
+        service.monitor_at_point(x, y).map(|m| m.id)
+    }
+
+    #[test]
+    fn test_monitor_at_point_boundaries() {
+        let service = dual_monitor_service();
+        
+        assert_eq!(monitor_id_at(&service, 0, 0).as_deref(), Some("monitor-0"));
+        assert_eq!(monitor_id_at(&service, 1919, 1079).as_deref(), Some("monitor-0"));
+        // The shared edge belongs to the right-hand monitor
+        assert_eq!(monitor_id_at(&service, 1920, 0).as_deref(), Some("monitor-1"));
+        assert_eq!(monitor_id_at(&service, 3199, 1023).as_deref(), Some("monitor-1"));
+        
+        // Right and bottom edges are exclusive
+        assert_eq!(monitor_id_at(&service, 3200, 0), None);
+        assert_eq!(monitor_id_at(&service, 0, 1080), None);
+        assert_eq!(monitor_id_at(&service, 1920, 1024), None);
+        assert_eq!(monitor_id_at(&service, -1, 0), None);
+        assert_eq!(monitor_id_at(&service, 0, -1), None);
+    }
+
+    #[test]
+    fn test_pointer_switch_is_throttled() {
+        let service = dual_monitor_service();
+        let start = Instant::now();
+        let current = || service.current_monitor.lock().unwrap().clone().unwrap();
+        let at = |x, y| PointerLocation::Point(x, y);
+        
+        // Crossing over only starts the dwell timer
+        assert!(service.update_pointer_location_at(&at(1920, 10), start).is_none());
+        assert_eq!(current(), "monitor-0");
+        
+        // Bouncing back across the edge resets it
+        assert!(service.update_pointer_location_at(&at(1919, 10), start + FOLLOW_MOUSE_DWELL).is_none());
+        assert!(service.update_pointer_location_at(&at(1920, 10), start + FOLLOW_MOUSE_DWELL).is_none());
+        assert_eq!(current(), "monitor-0");
+        
+        // Staying on the new monitor long enough moves the dock
+        let moved = service.update_pointer_location_at(&at(2000, 10), start + FOLLOW_MOUSE_DWELL * 2);
+        assert_eq!(moved.map(|m| m.id).as_deref(), Some("monitor-1"));
+        assert_eq!(current(), "monitor-1");
+        
+        // Off-screen positions are ignored
+        assert!(service.update_pointer_location_at(&at(-50, -50), start + FOLLOW_MOUSE_DWELL * 3).is_none());
+        
+        // A focused output counts like the pointer being on it
+        let output = PointerLocation::Output("DP-1".to_string());
+        assert!(service.update_pointer_location_at(&output, start + FOLLOW_MOUSE_DWELL * 4).is_none());
+        let moved = service.update_pointer_location_at(&output, start + FOLLOW_MOUSE_DWELL * 5);
+        assert_eq!(moved.map(|m| m.id).as_deref(), Some("monitor-0"));
+    }
+
+    #[test]
+    fn test_parse_pointer_location() {
+        assert_eq!(parse_hyprland_cursor(r#"{"x": 2400, "y": 310}"#), Some(PointerLocation::Point(2400, 310)));
+        assert_eq!(parse_hyprland_cursor("not json"), None);
+        
+        let outputs = r#"[
+            {"name": "DP-1", "active": true, "focused": false},
+            {"name": "HDMI-A-1", "active": true, "focused": true}
+        ]"#;
+        assert_eq!(parse_sway_focused_output(outputs), Some(PointerLocation::Output("HDMI-A-1".to_string())));
+        assert_eq!(parse_sway_focused_output("[]"), None);
+    }
+
+    #[test]
+    fn test_monitor_set_diff() {
+        // Initial spawn: everything is new
+        let (added, removed) = monitor_set_diff(&[], &keys(&["DP-1", "HDMI-A-1"]));
+        assert_eq!(added, keys(&["DP-1", "HDMI-A-1"]));
+        assert!(removed.is_empty());
+        
+        // Unplug one monitor
+        let (added, removed) = monitor_set_diff(&keys(&["DP-1", "HDMI-A-1"]), &keys(&["DP-1"]));
+        assert!(added.is_empty());
+        assert_eq!(removed, keys(&["HDMI-A-1"]));
+        
+        // Swap one monitor for another
+        let (added, removed) = monitor_set_diff(&keys(&["DP-1", "HDMI-A-1"]), &keys(&["DP-1", "DP-2"]));
+        assert_eq!(added, keys(&["DP-2"]));
+        assert_eq!(removed, keys(&["HDMI-A-1"]));
+        
+        // No change
+        let (added, removed) = monitor_set_diff(&keys(&["DP-1"]), &keys(&["DP-1"]));
+        assert!(added.is_empty() && removed.is_empty());
+    }
+}
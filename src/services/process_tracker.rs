@@ -3,9 +3,10 @@
 //! Tracks running applications by checking process names.
 //! This is a temporary solution until proper window tracking is implemented.
 
-use log::{debug, info};
-use std::collections::HashMap;
-use std::process::Command;
+use gtk::glib;
+use log::{debug, info, warn};
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
@@ -16,29 +17,48 @@ fn command_to_process_name(command: &str) -> String {
     command.split_whitespace().next().unwrap_or(command).to_string()
 }
 
-/// Update the running state of all apps in one pass
-fn update_all_apps(apps: &Arc<Mutex<HashMap<String, bool>>>) {
-    // Get all running processes in one command
-    let output = Command::new("ps")
-        .args(["-e", "-o", "comm="])
-        .output();
-    
-    let running_processes: std::collections::HashSet<String> = match output {
-        Ok(res) => String::from_utf8_lossy(&res.stdout)
-            .lines()
-            .map(|s| s.trim().to_string())
-            .collect(),
-        Err(_) => return,
+/// Collect the command names of all running processes from `/proc`
+///
+/// Reads `/proc/<pid>/comm`, the same (15 character) name `ps -o comm=`
+/// prints, without forking. Processes that exit mid-scan are skipped.
+pub(crate) fn running_process_names() -> HashSet<String> {
+    let entries = match fs::read_dir("/proc") {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Failed to read /proc: {}", e);
+            return HashSet::new();
+        }
     };
 
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_name().to_string_lossy().bytes().all(|b| b.is_ascii_digit()))
+        .filter_map(|entry| fs::read_to_string(entry.path().join("comm")).ok())
+        .map(|comm| comm.trim_end().to_string())
+        .collect()
+}
+
+/// A process name and its new running state
+type RunningChange = (String, bool);
+
+/// Update the running state of all apps in one pass
+///
+/// Returns the apps whose state flipped, with their new state.
+fn update_all_apps(
+    apps: &Arc<Mutex<HashMap<String, bool>>>,
+    running_processes: &HashSet<String>,
+) -> Vec<RunningChange> {
+    let mut changes = Vec::new();
     let mut apps_guard = apps.lock().unwrap();
     for (app_name, running) in apps_guard.iter_mut() {
         let is_running = running_processes.contains(app_name);
         if *running != is_running {
             debug!("App '{}' running state changed: {}", app_name, is_running);
             *running = is_running;
+            changes.push((app_name.clone(), is_running));
         }
     }
+    changes
 }
 
 /// Process tracker for monitoring running applications
@@ -46,6 +66,7 @@ fn update_all_apps(apps: &Arc<Mutex<HashMap<String, bool>>>) {
 pub struct ProcessTracker {
     apps: Arc<Mutex<HashMap<String, bool>>>,
     running: Arc<Mutex<bool>>,
+    listeners: Arc<Mutex<Vec<async_channel::Sender<RunningChange>>>>,
 }
 
 impl ProcessTracker {
@@ -54,6 +75,7 @@ impl ProcessTracker {
         Self {
             apps: Arc::new(Mutex::new(HashMap::new())),
             running: Arc::new(Mutex::new(false)),
+            listeners: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
@@ -61,7 +83,7 @@ impl ProcessTracker {
     pub fn register_app(&self, command: &str) {
         let process_name = command_to_process_name(command);
         let mut apps = self.apps.lock().unwrap();
-        apps.insert(process_name.clone(), false);
+        apps.entry(process_name.clone()).or_insert(false);
         debug!("Registered app for tracking: {}", process_name);
     }
 
@@ -72,6 +94,39 @@ impl ProcessTracker {
         apps.get(&process_name).copied().unwrap_or(false)
     }
 
+    /// Register a callback for running state changes
+    ///
+    /// The callback gets the process name and its new state, once per
+    /// transition, on the thread-default main context. Call this from the
+    /// GTK main thread.
+    pub fn on_change<F>(&self, callback: F)
+    where
+        F: Fn(&str, bool) + 'static,
+    {
+        // The poll runs on a background thread, so hand changes over to the main loop
+        let (sender, receiver) = async_channel::unbounded::<RunningChange>();
+        self.listeners.lock().unwrap().push(sender);
+
+        glib::spawn_future_local(async move {
+            while let Ok((process_name, is_running)) = receiver.recv().await {
+                callback(&process_name, is_running);
+            }
+        });
+    }
+
+    /// Apply a scan of the running processes and notify listeners of flips
+    fn apply(&self, running_processes: &HashSet<String>) {
+        let changes = update_all_apps(&self.apps, running_processes);
+        if changes.is_empty() {
+            return;
+        }
+
+        let mut listeners = self.listeners.lock().unwrap();
+        for change in changes {
+            listeners.retain(|sender| sender.try_send(change.clone()).is_ok());
+        }
+    }
+
     /// Start tracking processes
     pub fn start(&self) {
         let mut running = self.running.lock().unwrap();
@@ -81,7 +136,7 @@ impl ProcessTracker {
         *running = true;
         drop(running);
 
-        let apps = Arc::clone(&self.apps);
+        let tracker = self.clone();
         let running_flag = Arc::clone(&self.running);
 
         thread::spawn(move || {
@@ -96,7 +151,7 @@ impl ProcessTracker {
                     }
                 }
 
-                update_all_apps(&apps);
+                tracker.apply(&running_process_names());
 
                 // Check every 2 seconds
                 thread::sleep(Duration::from_secs(2));
@@ -120,3 +175,49 @@ impl Default for ProcessTracker {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_on_change_fires_once_per_transition() {
+        let context = glib::MainContext::new();
+        let _guard = context.acquire().unwrap();
+
+        context.with_thread_default(|| {
+            let tracker = ProcessTracker::new();
+            tracker.register_app("firefox --new-window");
+            tracker.register_app("gimp");
+
+            let changes = Rc::new(RefCell::new(Vec::new()));
+            tracker.on_change({
+                let changes = Rc::clone(&changes);
+                move |name, running| changes.borrow_mut().push((name.to_string(), running))
+            });
+
+            let running = |names: &[&str]| -> HashSet<String> {
+                names.iter().map(|name| name.to_string()).collect()
+            };
+            tracker.apply(&running(&["firefox", "bash"]));
+            tracker.apply(&running(&["firefox", "bash"]));
+            tracker.apply(&running(&["bash"]));
+            tracker.apply(&running(&[]));
+            while context.iteration(false) {}
+
+            assert_eq!(*changes.borrow(), vec![
+                ("firefox".to_string(), true),
+                ("firefox".to_string(), false),
+            ]);
+            assert!(!tracker.is_running("firefox"));
+        }).unwrap();
+    }
+
+    #[test]
+    fn test_running_process_names_includes_self() {
+        let own_comm = fs::read_to_string("/proc/self/comm").unwrap();
+        assert!(running_process_names().contains(own_comm.trim_end()));
+    }
+}
@@ -1,22 +1,27 @@
 //! Recent files service
 //!
-//! Tracks recently accessed files from the system.
+//! Tracks recently used files from the GTK recent manager
+//! (`~/.local/share/recently-used.xbel`).
 
-use log::{info, debug};
+use gtk::prelude::*;
+use log::debug;
 use std::sync::{Arc, Mutex};
-use std::path::PathBuf;
 
 /// Recent file information
-#[derive(Debug, Clone)]
-pub struct RecentFile {
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecentEntry {
+    /// Display name
     pub name: String,
-    pub path: PathBuf,
-    pub timestamp: u64,
+    pub uri: String,
+    pub mime_type: String,
+    /// Last use, in seconds since the Unix epoch
+    pub timestamp: i64,
 }
 
 /// Recent files service
+#[derive(Clone)]
 pub struct RecentFilesService {
-    files: Arc<Mutex<Vec<RecentFile>>>,
+    files: Arc<Mutex<Vec<RecentEntry>>>,
 }
 
 impl RecentFilesService {
@@ -27,15 +32,35 @@ impl RecentFilesService {
         }
     }
 
-    /// Refresh the list of recent files
+    /// Refresh the list of recent files from the GTK recent manager
+    ///
+    /// Must be called on the main thread. Local files that no longer
+    /// exist are skipped.
     pub fn refresh(&self) {
-        debug!("Refreshing recent files...");
-        // In a real implementation, we would parse ~/.local/share/recently-used.xbel
-        // or use GtkRecentManager
+        let entries = gtk::RecentManager::default()
+            .items()
+            .into_iter()
+            .filter(|info| !info.is_local() || info.exists())
+            .map(|info| RecentEntry {
+                name: info.display_name().to_string(),
+                uri: info.uri().to_string(),
+                mime_type: info.mime_type().to_string(),
+                timestamp: info.modified().to_unix(),
+            })
+            .collect();
+
+        self.store(entries);
+        debug!("Refreshed recent files");
+    }
+
+    /// Replace the stored entries, newest first
+    fn store(&self, mut entries: Vec<RecentEntry>) {
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+        *self.files.lock().unwrap() = entries;
     }
 
-    /// Get recent files
-    pub fn get_recent_files(&self, limit: usize) -> Vec<RecentFile> {
+    /// Get the `limit` most recently used files, newest first
+    pub fn get_recent(&self, limit: usize) -> Vec<RecentEntry> {
         let files = self.files.lock().unwrap();
         files.iter().take(limit).cloned().collect()
     }
@@ -47,3 +72,31 @@ impl Default for RecentFilesService {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, timestamp: i64) -> RecentEntry {
+        RecentEntry {
+            name: name.to_string(),
+            uri: format!("file:///home/me/{}", name),
+            mime_type: "text/plain".to_string(),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_get_recent_is_newest_first_and_limited() {
+        let service = RecentFilesService::new();
+        assert!(service.get_recent(5).is_empty());
+
+        service.store(vec![entry("old.txt", 100), entry("new.txt", 300), entry("mid.txt", 200)]);
+
+        let names = |entries: Vec<RecentEntry>| -> Vec<String> {
+            entries.into_iter().map(|e| e.name).collect()
+        };
+        assert_eq!(names(service.get_recent(2)), vec!["new.txt", "mid.txt"]);
+        assert_eq!(names(service.get_recent(10)), vec!["new.txt", "mid.txt", "old.txt"]);
+        assert!(service.get_recent(0).is_empty());
+    }
+}
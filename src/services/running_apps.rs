@@ -4,10 +4,11 @@
 
 use log::{debug, info};
 use std::collections::{HashMap, HashSet};
-use std::process::Command;
 use std::sync::{Arc, Mutex};
 
-use crate::utils::desktop_entry::{DesktopEntry, APP_DIRS};
+use crate::config::find_icon_override;
+use crate::services::process_tracker::running_process_names;
+use crate::utils::desktop_entry::{discover_applications, DesktopEntry};
 
 /// Information about a running application
 #[derive(Debug, Clone)]
@@ -25,6 +26,8 @@ pub struct RunningAppsService {
     desktop_cache: Arc<Mutex<HashMap<String, DesktopEntry>>>,
     /// Currently running apps (not in pinned list)
     running_apps: Arc<Mutex<Vec<RunningApp>>>,
+    /// Icons replacing those of the desktop entries (see `Settings::icon_overrides`)
+    icon_overrides: Arc<Mutex<HashMap<String, String>>>,
 }
 
 impl RunningAppsService {
@@ -33,6 +36,7 @@ impl RunningAppsService {
         let service = Self {
             desktop_cache: Arc::new(Mutex::new(HashMap::new())),
             running_apps: Arc::new(Mutex::new(Vec::new())),
+            icon_overrides: Arc::new(Mutex::new(HashMap::new())),
         };
         service.build_cache();
         service
@@ -42,31 +46,20 @@ impl RunningAppsService {
     fn build_cache(&self) {
         let mut cache = self.desktop_cache.lock().unwrap();
         
-        for dir in APP_DIRS {
-            if let Ok(read_dir) = std::fs::read_dir(dir) {
-                for entry in read_dir.filter_map(|e| e.ok()) {
-                    let path = entry.path();
-                    if path.extension().map(|e| e == "desktop").unwrap_or(false) {
-                        if let Ok(desktop) = DesktopEntry::parse(&path) {
-                            if desktop.is_visible_app() {
-                                if let Some(exec) = &desktop.exec {
-                                    // Extract process name from exec
-                                    let process_name = exec
-                                        .split_whitespace()
-                                        .next()
-                                        .unwrap_or("")
-                                        .split('/')
-                                        .last()
-                                        .unwrap_or("")
-                                        .to_string();
-                                    
-                                    if !process_name.is_empty() {
-                                        cache.insert(process_name.to_lowercase(), desktop);
-                                    }
-                                }
-                            }
-                        }
-                    }
+        for desktop in discover_applications() {
+            if let Some(exec) = &desktop.exec {
+                // Extract process name from exec
+                let process_name = exec
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or("")
+                    .split('/')
+                    .last()
+                    .unwrap_or("")
+                    .to_string();
+                
+                if !process_name.is_empty() {
+                    cache.entry(process_name.to_lowercase()).or_insert(desktop);
                 }
             }
         }
@@ -74,63 +67,112 @@ impl RunningAppsService {
         info!("Desktop entry cache built: {} entries", cache.len());
     }
 
+    /// Set the icons replacing those of the desktop entries
+    pub fn set_icon_overrides(&self, overrides: HashMap<String, String>) {
+        *self.icon_overrides.lock().unwrap() = overrides;
+    }
+
     /// Get list of running GUI applications
-    pub fn get_running_apps(&self, pinned_commands: &[String]) -> Vec<RunningApp> {
+    ///
+    /// An app counts as running if a process matches its Exec binary or
+    /// one of `window_app_ids` matches its desktop file, since wrapper and
+    /// Electron processes often don't carry the binary's name.
+    pub fn get_running_apps(&self, pinned_commands: &[String], window_app_ids: &[String]) -> Vec<RunningApp> {
         // Get all running processes
-        let output = Command::new("ps")
-            .args(["-e", "-o", "comm="])
-            .output();
-        
-        let running_processes: HashSet<String> = match output {
-            Ok(res) => String::from_utf8_lossy(&res.stdout)
-                .lines()
-                .map(|s| s.trim().to_lowercase())
-                .collect(),
-            Err(_) => return Vec::new(),
-        };
+        let running_processes: HashSet<String> = running_process_names()
+            .into_iter()
+            .map(|name| name.to_lowercase())
+            .collect();
 
-        // Convert pinned commands to process names for comparison
-        let pinned_process_names: HashSet<String> = pinned_commands
-            .iter()
-            .map(|cmd| {
-                cmd.split_whitespace()
-                    .next()
-                    .unwrap_or("")
-                    .split('/')
-                    .last()
-                    .unwrap_or("")
-                    .to_lowercase()
-            })
+        let window_app_ids: HashSet<String> = window_app_ids.iter()
+            .map(|app_id| app_id.to_lowercase())
             .collect();
 
         let cache = self.desktop_cache.lock().unwrap();
-        let mut apps = Vec::new();
-
-        for (process_name, desktop) in cache.iter() {
-            // Check if this process is running
-            if running_processes.contains(process_name) {
-                // Skip if it's already pinned
-                if pinned_process_names.contains(process_name) {
-                    continue;
-                }
+        let icon_overrides = self.icon_overrides.lock().unwrap();
+        match_running_apps(&cache, &running_processes, &window_app_ids, pinned_commands, &icon_overrides)
+    }
+}
 
-                let app = RunningApp {
-                    name: desktop.name.clone().unwrap_or_else(|| process_name.clone()),
-                    icon: desktop.icon.clone().unwrap_or_else(|| "application-x-executable".to_string()),
-                    command: desktop.exec_command().unwrap_or_else(|| process_name.clone()),
-                    desktop_file: Some(desktop.path.to_string_lossy().to_string()),
-                    process_name: process_name.clone(),
-                };
-                
-                debug!("Found running app: {} ({})", app.name, process_name);
-                apps.push(app);
-            }
+/// Find the cached desktop entries whose app has a process or a window
+///
+/// `running_processes` and `window_app_ids` are lowercase. Pinned apps
+/// are left out and each desktop file is listed once, sorted by name.
+fn match_running_apps(
+    cache: &HashMap<String, DesktopEntry>,
+    running_processes: &HashSet<String>,
+    window_app_ids: &HashSet<String>,
+    pinned_commands: &[String],
+    icon_overrides: &HashMap<String, String>,
+) -> Vec<RunningApp> {
+    // Convert pinned commands to process names for comparison
+    let pinned_process_names: HashSet<String> = pinned_commands
+        .iter()
+        .map(|cmd| {
+            cmd.split_whitespace()
+                .next()
+                .unwrap_or("")
+                .split('/')
+                .last()
+                .unwrap_or("")
+                .to_lowercase()
+        })
+        .collect();
+
+    let mut seen_desktop_files = HashSet::new();
+    let mut apps = Vec::new();
+
+    for (process_name, desktop) in cache.iter() {
+        // Check if this process is running or has a window open
+        if !running_processes.contains(process_name) && !has_window(desktop, process_name, window_app_ids) {
+            continue;
+        }
+        
+        // Skip if it's already pinned
+        if pinned_process_names.contains(process_name) {
+            continue;
+        }
+
+        let desktop_file = desktop.path.to_string_lossy().to_string();
+        if !seen_desktop_files.insert(desktop_file.clone()) {
+            continue;
         }
 
-        // Sort alphabetically
-        apps.sort_by(|a, b| a.name.cmp(&b.name));
-        apps
+        let command = desktop.exec_command().unwrap_or_else(|| process_name.clone());
+        let icon = find_icon_override(icon_overrides, &command, Some(&desktop_file))
+            .map(str::to_string)
+            .or_else(|| desktop.localized_icon())
+            .unwrap_or_else(|| "application-x-executable".to_string());
+        
+        let app = RunningApp {
+            name: desktop.localized_name().unwrap_or_else(|| process_name.clone()),
+            icon,
+            command,
+            desktop_file: Some(desktop_file),
+            process_name: process_name.clone(),
+        };
+        
+        debug!("Found running app: {} ({})", app.name, process_name);
+        apps.push(app);
     }
+
+    // Sort alphabetically
+    apps.sort_by(|a, b| a.name.cmp(&b.name));
+    apps
+}
+
+/// Whether one of the open windows belongs to `desktop`
+///
+/// Windows are matched by desktop file ID (the Wayland convention),
+/// `StartupWMClass` (X11 and Electron apps) or the Exec binary's name.
+fn has_window(desktop: &DesktopEntry, process_name: &str, window_app_ids: &HashSet<String>) -> bool {
+    let desktop_id = desktop.path.file_stem().map(|stem| stem.to_string_lossy().to_lowercase());
+    let wm_class = desktop.fields.get("StartupWMClass").map(|class| class.to_lowercase());
+
+    desktop_id.into_iter()
+        .chain(wm_class)
+        .chain(std::iter::once(process_name.to_string()))
+        .any(|id| window_app_ids.contains(&id))
 }
 
 impl Default for RunningAppsService {
@@ -139,3 +181,65 @@ impl Default for RunningAppsService {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn entry(file: &str, name: &str, exec: &str, wm_class: Option<&str>) -> DesktopEntry {
+        DesktopEntry {
+            path: PathBuf::from(format!("/usr/share/applications/{}.desktop", file)),
+            name: Some(name.to_string()),
+            generic_name: None,
+            comment: None,
+            icon: Some(file.to_string()),
+            exec: Some(exec.to_string()),
+            terminal: false,
+            dbus_activatable: false,
+            categories: Vec::new(),
+            no_display: false,
+            fields: wm_class
+                .map(|class| HashMap::from([("StartupWMClass".to_string(), class.to_string())]))
+                .unwrap_or_default(),
+        }
+    }
+
+    fn set(items: &[&str]) -> HashSet<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn test_window_only_match() {
+        let cache = HashMap::from([
+            ("gimp".to_string(), entry("org.gimp.GIMP", "GIMP", "gimp %U", None)),
+            ("slack".to_string(), entry("slack", "Slack", "/usr/bin/slack %U", Some("Slack"))),
+            ("obsidian".to_string(), entry("md.obsidian.Obsidian", "Obsidian", "obsidian", None)),
+            ("krita".to_string(), entry("org.kde.krita", "Krita", "krita", None)),
+        ]);
+        let no_overrides = HashMap::new();
+
+        // Slack's process is "electron" and Obsidian runs from a wrapper;
+        // only their windows give them away
+        let apps = match_running_apps(
+            &cache,
+            &set(&["gimp", "electron"]),
+            &set(&["slack", "md.obsidian.obsidian"]),
+            &[],
+            &no_overrides,
+        );
+        let names: Vec<&str> = apps.iter().map(|app| app.name.as_str()).collect();
+        assert_eq!(names, vec!["GIMP", "Obsidian", "Slack"]);
+
+        // Process and window for the same app give one entry; pinned apps are skipped
+        let apps = match_running_apps(
+            &cache,
+            &set(&["gimp", "krita"]),
+            &set(&["org.gimp.gimp", "org.kde.krita"]),
+            &["krita".to_string()],
+            &no_overrides,
+        );
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].name, "GIMP");
+    }
+}
@@ -1,29 +1,124 @@
 //! Screencopy service for live window previews
 //!
 //! Captures window thumbnails using compositor-specific methods:
+//! - wlroots: the wlr-screencopy protocol (`wlr-screencopy` feature)
 //! - Hyprland: grim + hyprctl for window geometry
 //! - Sway: grim + swaymsg for window geometry
-//! - KDE: spectacle or D-Bus Screenshot portal
+//! - KDE: spectacle, cropped to the window geometry reported by KWin
+//! - GNOME: gnome-screenshot (active window only)
 //! - Fallback: App icon as placeholder
 
 use gtk::prelude::*;
 use gtk::glib;
-use gtk::gdk_pixbuf::Pixbuf;
+use gtk::gdk_pixbuf::{InterpType, Pixbuf};
 use log::{info, debug, warn};
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, Once};
 use std::process::Command;
 use std::path::PathBuf;
 
+/// Default number of thumbnails kept before the least recently updated are evicted
+const DEFAULT_MAX_ENTRIES: usize = 64;
+
+/// Thumbnail size that captures are scaled to fit
+const THUMBNAIL_WIDTH: i32 = 200;
+const THUMBNAIL_HEIGHT: i32 = 120;
+
+/// Warns once that thumbnails may show the active window instead of the requested one
+static ACTIVE_WINDOW_WARNING: Once = Once::new();
+
 /// Detected screenshot tool
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ScreenshotTool {
+    #[cfg(feature = "wlr-screencopy")]
+    WlrScreencopy, // wlroots, in-process
     Grim,      // wlroots
     Spectacle, // KDE
     GnomeScreenshot,
     None,
 }
 
+/// Output of a screenshot tool, turned into a `Pixbuf` on the main thread
+///
+/// Pixbufs can't cross threads, so captures run on a worker produce this.
+enum Capture {
+    /// Image file, cropped to `crop` when it shows more than the window
+    File {
+        path: PathBuf,
+        crop: Option<(i32, i32, i32, i32)>,
+    },
+    /// Pixels copied over the wlr-screencopy protocol
+    #[cfg(feature = "wlr-screencopy")]
+    Frame(super::wlr_screencopy::Frame),
+}
+
+impl Capture {
+    /// Load the capture as a thumbnail, deleting any image file
+    fn load(self) -> Option<Pixbuf> {
+        match self {
+            Capture::File { path, crop } => {
+                let pixbuf = match crop {
+                    Some(geometry) => Pixbuf::from_file(&path).ok().and_then(|screen| {
+                        let (x, y, width, height) = clamp_region(geometry, screen.width(), screen.height())?;
+                        scale_to_thumbnail(&screen.new_subpixbuf(x, y, width, height))
+                    }),
+                    None => Pixbuf::from_file_at_scale(&path, THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT, true).ok(),
+                };
+                
+                let _ = std::fs::remove_file(&path);
+                pixbuf
+            }
+            #[cfg(feature = "wlr-screencopy")]
+            Capture::Frame(frame) => scale_to_thumbnail(&frame.into_pixbuf()),
+        }
+    }
+}
+
+/// A pending capture, gathered on the main thread and run on a worker
+struct CaptureJob {
+    tool: ScreenshotTool,
+    window_id: String,
+    output_path: PathBuf,
+    /// Geometry supplied by the caller, see `set_window_geometry`
+    hint: Option<(i32, i32, i32, i32)>,
+    /// Connector and geometry of each output, since GDK is main-thread only
+    #[cfg(feature = "wlr-screencopy")]
+    outputs: Vec<(String, gtk::gdk::Rectangle)>,
+}
+
+impl CaptureJob {
+    /// Capture the window
+    ///
+    /// Only runs external tools and compositor queries, so it is safe to
+    /// call off the main thread.
+    fn run(self) -> Option<Capture> {
+        if self.tool == ScreenshotTool::None {
+            return None;
+        }
+        
+        let window_id = self.window_id.as_str();
+        let geometry = self.hint.or_else(|| ScreencopyService::get_window_geometry(window_id));
+        
+        match self.tool {
+            #[cfg(feature = "wlr-screencopy")]
+            ScreenshotTool::WlrScreencopy => {
+                let geometry = geometry?;
+                if let Some((connector, region)) = output_region(&self.outputs, geometry) {
+                    match super::wlr_screencopy::capture_region(&connector, region) {
+                        Ok(frame) => return Some(Capture::Frame(frame)),
+                        Err(e) => debug!("wlr-screencopy failed for {}, trying grim: {}", window_id, e),
+                    }
+                }
+                ScreencopyService::capture_with_grim(window_id, geometry, self.output_path)
+            }
+            ScreenshotTool::Grim => ScreencopyService::capture_with_grim(window_id, geometry?, self.output_path),
+            ScreenshotTool::Spectacle => ScreencopyService::capture_with_spectacle(window_id, geometry, self.output_path),
+            ScreenshotTool::GnomeScreenshot => ScreencopyService::capture_with_gnome(window_id, geometry, self.output_path),
+            ScreenshotTool::None => None,
+        }
+    }
+}
+
 /// Window thumbnail cache
 #[derive(Clone)]
 pub struct WindowThumbnail {
@@ -39,9 +134,12 @@ pub struct WindowThumbnail {
 pub struct ScreencopyService {
     thumbnails: Arc<Mutex<HashMap<String, WindowThumbnail>>>,
     cache_ttl_seconds: u64,
+    max_entries: usize,
     running: Arc<Mutex<bool>>,
     tool: Arc<Mutex<ScreenshotTool>>,
     temp_dir: PathBuf,
+    /// Window geometries supplied by callers, e.g. from KWin via the window tracker
+    window_geometries: Arc<Mutex<HashMap<String, (i32, i32, i32, i32)>>>,
 }
 
 impl ScreencopyService {
@@ -56,14 +154,22 @@ impl ScreencopyService {
         Self {
             thumbnails: Arc::new(Mutex::new(HashMap::new())),
             cache_ttl_seconds: 5,
+            max_entries: DEFAULT_MAX_ENTRIES,
             running: Arc::new(Mutex::new(false)),
             tool: Arc::new(Mutex::new(tool)),
             temp_dir,
+            window_geometries: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
     /// Detect available screenshot tool
-    fn detect_screenshot_tool() -> ScreenshotTool {
+    pub fn detect_screenshot_tool() -> ScreenshotTool {
+        // Prefer copying frames ourselves over spawning grim
+        #[cfg(feature = "wlr-screencopy")]
+        if super::wlr_screencopy::is_available() {
+            return ScreenshotTool::WlrScreencopy;
+        }
+
         // Check for grim (wlroots)
         if Command::new("which").arg("grim").output()
             .map(|o| o.status.success()).unwrap_or(false) {
@@ -106,6 +212,7 @@ impl ScreencopyService {
 
                 // Refresh stale thumbnails
                 service.refresh_stale_thumbnails();
+                service.sweep_temp_dir();
 
                 glib::timeout_future(std::time::Duration::from_secs(2)).await;
             }
@@ -133,41 +240,89 @@ impl ScreencopyService {
         
         for (window_id, app_id, title) in stale {
             debug!("Refreshing stale thumbnail: {}", window_id);
-            self.capture_thumbnail(&window_id, &app_id, &title);
+            self.request_thumbnail_async(&window_id, &app_id, &title, |_| {});
         }
     }
 
     /// Request thumbnail for a window
+    ///
+    /// Blocks while the screenshot tool runs on a cache miss; prefer
+    /// [`Self::request_thumbnail_async`] on the UI thread.
     pub fn request_thumbnail(&self, window_id: &str, app_id: &str, title: &str) -> Option<Pixbuf> {
-        let thumbnails = self.thumbnails.lock().unwrap();
-        
         // Check cache first
-        if let Some(cached) = thumbnails.get(window_id) {
-            let now = Self::current_timestamp();
-            if now - cached.last_updated < self.cache_ttl_seconds {
-                return cached.pixbuf.clone();
-            }
+        if let Some(cached) = self.fresh_thumbnail(window_id) {
+            return cached.pixbuf;
         }
-        drop(thumbnails);
 
         // Capture new thumbnail
         self.capture_thumbnail(window_id, app_id, title)
     }
 
+    /// Request a thumbnail without blocking the main loop
+    ///
+    /// Cache hits call `callback` right away. Otherwise the capture runs on
+    /// a worker thread and `callback` is called from the main loop once it
+    /// finishes; the returned handle can abort the delivery.
+    pub fn request_thumbnail_async<F>(
+        &self,
+        window_id: &str,
+        app_id: &str,
+        title: &str,
+        callback: F,
+    ) -> Option<glib::JoinHandle<()>>
+    where
+        F: FnOnce(Option<Pixbuf>) + 'static,
+    {
+        if let Some(cached) = self.fresh_thumbnail(window_id) {
+            callback(cached.pixbuf);
+            return None;
+        }
+
+        let service = self.clone();
+        let window_id = window_id.to_string();
+        let app_id = app_id.to_string();
+        let title = title.to_string();
+        
+        let capture = self.spawn_capture(&window_id);
+        Some(glib::spawn_future_local(async move {
+            let capture = capture.await.ok().flatten();
+            let pixbuf = service.finish_capture(&window_id, &app_id, &title, capture);
+            callback(pixbuf);
+        }))
+    }
+
     /// Capture a thumbnail for a window
     fn capture_thumbnail(&self, window_id: &str, app_id: &str, title: &str) -> Option<Pixbuf> {
-        let tool = *self.tool.lock().unwrap();
-        
-        let pixbuf = match tool {
-            ScreenshotTool::Grim => self.capture_with_grim(window_id),
-            ScreenshotTool::Spectacle => self.capture_with_spectacle(window_id),
-            ScreenshotTool::GnomeScreenshot => self.capture_with_gnome(window_id),
-            ScreenshotTool::None => self.get_fallback_thumbnail(app_id),
-        };
+        let capture = self.capture_job(window_id).run();
+        self.finish_capture(window_id, app_id, title, capture)
+    }
+
+    /// Run the screenshot tool for a window on a worker thread
+    fn spawn_capture(&self, window_id: &str) -> gtk::gio::JoinHandle<Option<Capture>> {
+        let job = self.capture_job(window_id);
+        gtk::gio::spawn_blocking(move || job.run())
+    }
+
+    /// Gather what a capture needs from the main thread
+    fn capture_job(&self, window_id: &str) -> CaptureJob {
+        CaptureJob {
+            tool: *self.tool.lock().unwrap(),
+            window_id: window_id.to_string(),
+            output_path: self.temp_path(window_id),
+            hint: self.window_geometries.lock().unwrap().get(window_id).copied(),
+            #[cfg(feature = "wlr-screencopy")]
+            outputs: output_layout(),
+        }
+    }
+
+    /// Load a finished capture (or the app icon) and cache it
+    fn finish_capture(&self, window_id: &str, app_id: &str, title: &str, capture: Option<Capture>) -> Option<Pixbuf> {
+        let pixbuf = capture
+            .and_then(Capture::load)
+            .or_else(|| self.get_fallback_thumbnail(app_id));
 
         // Update cache
-        let mut thumbnails = self.thumbnails.lock().unwrap();
-        thumbnails.insert(window_id.to_string(), WindowThumbnail {
+        self.insert_thumbnail(WindowThumbnail {
             window_id: window_id.to_string(),
             app_id: app_id.to_string(),
             title: title.to_string(),
@@ -178,58 +333,114 @@ impl ScreencopyService {
         pixbuf
     }
 
-    /// Capture using grim (wlroots compositors)
-    fn capture_with_grim(&self, window_id: &str) -> Option<Pixbuf> {
-        // First, get window geometry from Hyprland or Sway
-        let geometry = self.get_window_geometry(window_id);
-        
-        if let Some((x, y, w, h)) = geometry {
-            let output_path = self.temp_dir.join(format!("{}.png", window_id.replace("/", "_")));
+    /// Cache a thumbnail, evicting the least recently updated beyond `max_entries`
+    fn insert_thumbnail(&self, thumbnail: WindowThumbnail) {
+        let mut thumbnails = self.thumbnails.lock().unwrap();
+        thumbnails.insert(thumbnail.window_id.clone(), thumbnail);
+        
+        while thumbnails.len() > self.max_entries {
+            let Some(oldest) = thumbnails.values()
+                .min_by_key(|t| t.last_updated)
+                .map(|t| t.window_id.clone())
+            else {
+                break;
+            };
             
-            // Use grim to capture the region
-            let result = Command::new("grim")
-                .arg("-g")
-                .arg(format!("{},{} {}x{}", x, y, w, h))
-                .arg("-t")
-                .arg("png")
-                .arg("-l")
-                .arg("0") // Fastest compression
-                .arg(&output_path)
-                .output();
+            debug!("Evicting thumbnail: {}", oldest);
+            thumbnails.remove(&oldest);
+            let _ = std::fs::remove_file(self.temp_path(&oldest));
+        }
+    }
+
+    /// Delete temp captures older than the cache TTL
+    ///
+    /// Loads normally delete their file, but a failed load or a crash
+    /// mid-capture can leave one behind.
+    fn sweep_temp_dir(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.temp_dir) else {
+            return;
+        };
+        let ttl = std::time::Duration::from_secs(self.cache_ttl_seconds);
+        
+        for entry in entries.flatten() {
+            let expired = entry.metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age > ttl);
             
-            match result {
-                Ok(output) if output.status.success() => {
-                    debug!("Captured window {} to {:?}", window_id, output_path);
-                    
-                    // Load and scale the image
-                    if let Ok(pixbuf) = Pixbuf::from_file_at_scale(&output_path, 200, 120, true) {
-                        // Clean up temp file
-                        let _ = std::fs::remove_file(&output_path);
-                        return Some(pixbuf);
-                    }
-                }
-                Ok(output) => {
-                    debug!("grim failed: {}", String::from_utf8_lossy(&output.stderr));
-                }
-                Err(e) => {
-                    debug!("Failed to run grim: {}", e);
-                }
+            if expired {
+                debug!("Removing stale capture: {:?}", entry.path());
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+
+    /// Temp file a window's capture is written to
+    fn temp_path(&self, window_id: &str) -> PathBuf {
+        self.temp_dir.join(format!("{}.png", window_id.replace("/", "_")))
+    }
+
+    /// Cached thumbnail that is still within the cache TTL
+    fn fresh_thumbnail(&self, window_id: &str) -> Option<WindowThumbnail> {
+        let thumbnails = self.thumbnails.lock().unwrap();
+        let cached = thumbnails.get(window_id)?;
+        let now = Self::current_timestamp();
+        (now - cached.last_updated < self.cache_ttl_seconds).then(|| cached.clone())
+    }
+
+    /// Capture using grim (wlroots compositors)
+    fn capture_with_grim(window_id: &str, geometry: (i32, i32, i32, i32), output_path: PathBuf) -> Option<Capture> {
+        let (x, y, w, h) = geometry;
+        
+        // Use grim to capture the region
+        let result = Command::new("grim")
+            .arg("-g")
+            .arg(format!("{},{} {}x{}", x, y, w, h))
+            .arg("-t")
+            .arg("png")
+            .arg("-l")
+            .arg("0") // Fastest compression
+            .arg(&output_path)
+            .output();
+        
+        match result {
+            Ok(output) if output.status.success() => {
+                debug!("Captured window {} to {:?}", window_id, output_path);
+                return Some(Capture::File { path: output_path, crop: None });
+            }
+            Ok(output) => {
+                debug!("grim failed: {}", String::from_utf8_lossy(&output.stderr));
+            }
+            Err(e) => {
+                debug!("Failed to run grim: {}", e);
             }
         }
         
-        // Fall back to app icon
-        self.get_fallback_thumbnail(&self.extract_app_id(window_id))
+        None
+    }
+
+    /// Remember a window's geometry for compositors we can't query directly
+    ///
+    /// KDE captures are only accurate for windows with a known geometry;
+    /// pass `WindowInfo::geometry` from the window tracker here.
+    pub fn set_window_geometry(&self, window_id: &str, geometry: Option<(i32, i32, i32, i32)>) {
+        let mut geometries = self.window_geometries.lock().unwrap();
+        match geometry {
+            Some(geometry) => geometries.insert(window_id.to_string(), geometry),
+            None => geometries.remove(window_id),
+        };
     }
 
     /// Get window geometry from compositor
-    fn get_window_geometry(&self, window_id: &str) -> Option<(i32, i32, i32, i32)> {
+    fn get_window_geometry(window_id: &str) -> Option<(i32, i32, i32, i32)> {
         // Try Hyprland first
-        if let Some(geom) = self.get_hyprland_geometry(window_id) {
+        if let Some(geom) = Self::get_hyprland_geometry(window_id) {
             return Some(geom);
         }
         
         // Try Sway
-        if let Some(geom) = self.get_sway_geometry(window_id) {
+        if let Some(geom) = Self::get_sway_geometry(window_id) {
             return Some(geom);
         }
         
@@ -237,7 +448,7 @@ impl ScreencopyService {
     }
 
     /// Get window geometry from Hyprland
-    fn get_hyprland_geometry(&self, window_id: &str) -> Option<(i32, i32, i32, i32)> {
+    fn get_hyprland_geometry(window_id: &str) -> Option<(i32, i32, i32, i32)> {
         let output = Command::new("hyprctl")
             .args(["clients", "-j"])
             .output()
@@ -266,7 +477,7 @@ impl ScreencopyService {
     }
 
     /// Get window geometry from Sway
-    fn get_sway_geometry(&self, window_id: &str) -> Option<(i32, i32, i32, i32)> {
+    fn get_sway_geometry(window_id: &str) -> Option<(i32, i32, i32, i32)> {
         let output = Command::new("swaymsg")
             .args(["-t", "get_tree", "-r"])
             .output()
@@ -320,69 +531,75 @@ impl ScreencopyService {
     }
 
     /// Capture using spectacle (KDE)
-    fn capture_with_spectacle(&self, window_id: &str) -> Option<Pixbuf> {
-        let output_path = self.temp_dir.join(format!("{}.png", window_id.replace("/", "_")));
+    ///
+    /// spectacle can't capture a window by ID, so when the window's geometry
+    /// is known the whole desktop is captured and cropped to the window.
+    /// Otherwise only the active window can be captured.
+    fn capture_with_spectacle(window_id: &str, geometry: Option<(i32, i32, i32, i32)>, output_path: PathBuf) -> Option<Capture> {
+        let mode = if geometry.is_some() {
+            "-f" // full screen, cropped when loaded
+        } else {
+            Self::warn_active_window_capture("spectacle");
+            "-a" // active window
+        };
         
-        // spectacle can capture active window or by window ID
-        // Using -a for active window as ID capture is complex
         let result = Command::new("spectacle")
             .args(["-b", "-n", "-o"])
             .arg(&output_path)
-            .args(["-a"]) // active window
+            .arg(mode)
             .output();
         
         match result {
             Ok(output) if output.status.success() => {
-                if let Ok(pixbuf) = Pixbuf::from_file_at_scale(&output_path, 200, 120, true) {
-                    let _ = std::fs::remove_file(&output_path);
-                    return Some(pixbuf);
-                }
+                Some(Capture::File { path: output_path, crop: geometry })
             }
             _ => {
                 debug!("spectacle capture failed for {}", window_id);
+                None
             }
         }
-        
-        self.get_fallback_thumbnail(&self.extract_app_id(window_id))
     }
 
     /// Capture using gnome-screenshot
-    fn capture_with_gnome(&self, window_id: &str) -> Option<Pixbuf> {
-        // gnome-screenshot -w captures active window
-        let output_path = self.temp_dir.join(format!("{}.png", window_id.replace("/", "_")));
-        
-        let result = Command::new("gnome-screenshot")
-            .args(["-w", "-f"])
-            .arg(&output_path)
-            .output();
+    ///
+    /// Like spectacle, crops a full-screen capture when the window's
+    /// geometry is known and falls back to the active window otherwise.
+    fn capture_with_gnome(window_id: &str, geometry: Option<(i32, i32, i32, i32)>, output_path: PathBuf) -> Option<Capture> {
+        let mut command = Command::new("gnome-screenshot");
+        if geometry.is_none() {
+            Self::warn_active_window_capture("gnome-screenshot");
+            command.arg("-w"); // active window
+        }
+        let result = command.arg("-f").arg(&output_path).output();
         
         match result {
             Ok(output) if output.status.success() => {
-                if let Ok(pixbuf) = Pixbuf::from_file_at_scale(&output_path, 200, 120, true) {
-                    let _ = std::fs::remove_file(&output_path);
-                    return Some(pixbuf);
-                }
+                Some(Capture::File { path: output_path, crop: geometry })
             }
             _ => {
                 debug!("gnome-screenshot capture failed for {}", window_id);
+                None
             }
         }
-        
-        self.get_fallback_thumbnail(&self.extract_app_id(window_id))
     }
 
-    /// Extract app_id from window_id
-    fn extract_app_id(&self, window_id: &str) -> String {
-        // Try to find app_id in cache
-        let thumbnails = self.thumbnails.lock().unwrap();
-        if let Some(thumb) = thumbnails.get(window_id) {
-            return thumb.app_id.clone();
-        }
-        "unknown".to_string()
+    /// Warn that a tool without window geometry captures the focused window
+    fn warn_active_window_capture(tool: &str) {
+        ACTIVE_WINDOW_WARNING.call_once(|| {
+            warn!(
+                "No window geometry available, {} captures the active window; \
+                 previews of unfocused windows may show the wrong window",
+                tool
+            );
+        });
     }
 
     /// Get fallback thumbnail (app icon scaled up)
     fn get_fallback_thumbnail(&self, app_id: &str) -> Option<Pixbuf> {
+        // Icon lookup needs GTK, which services may run without (e.g. in tests)
+        if !gtk::is_initialized() {
+            return None;
+        }
         let display = gtk::gdk::Display::default()?;
         let icon_theme = gtk::IconTheme::for_display(&display);
         
@@ -428,8 +645,7 @@ impl ScreencopyService {
         self.thumbnails.lock().unwrap().remove(window_id);
         
         // Clean up temp file
-        let path = self.temp_dir.join(format!("{}.png", window_id.replace("/", "_")));
-        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_file(self.temp_path(window_id));
     }
 
     /// Check if screenshot tool is available
@@ -447,6 +663,11 @@ impl ScreencopyService {
         self.cache_ttl_seconds = seconds;
     }
 
+    /// Set how many thumbnails are cached before the oldest are evicted
+    pub fn set_max_entries(&mut self, max_entries: usize) {
+        self.max_entries = max_entries;
+    }
+
     /// Current timestamp helper
     fn current_timestamp() -> u64 {
         use std::time::{SystemTime, UNIX_EPOCH};
@@ -463,6 +684,68 @@ impl Default for ScreencopyService {
     }
 }
 
+/// Scale a capture down to fit the thumbnail size, keeping its aspect ratio
+fn scale_to_thumbnail(pixbuf: &Pixbuf) -> Option<Pixbuf> {
+    let (width, height) = (pixbuf.width(), pixbuf.height());
+    let scale = f64::min(
+        THUMBNAIL_WIDTH as f64 / width as f64,
+        THUMBNAIL_HEIGHT as f64 / height as f64,
+    );
+    pixbuf.scale_simple(
+        ((width as f64 * scale) as i32).max(1),
+        ((height as f64 * scale) as i32).max(1),
+        InterpType::Bilinear,
+    )
+}
+
+/// Connector and geometry of every monitor
+#[cfg(feature = "wlr-screencopy")]
+fn output_layout() -> Vec<(String, gtk::gdk::Rectangle)> {
+    let Some(display) = gtk::gdk::Display::default() else {
+        return Vec::new();
+    };
+    let monitors = display.monitors();
+    
+    (0..monitors.n_items())
+        .filter_map(|i| monitors.item(i).and_downcast::<gtk::gdk::Monitor>())
+        .filter_map(|monitor| Some((monitor.connector()?.to_string(), monitor.geometry())))
+        .collect()
+}
+
+/// Find the output showing a window and the window's region on that output
+///
+/// Takes global `(x, y, width, height)` and returns the output's connector
+/// with the region in output-local coordinates, clipped to the output.
+#[cfg(feature = "wlr-screencopy")]
+fn output_region(outputs: &[(String, gtk::gdk::Rectangle)], geometry: (i32, i32, i32, i32)) -> Option<(String, (i32, i32, i32, i32))> {
+    let (x, y, width, height) = geometry;
+    let (center_x, center_y) = (x + width / 2, y + height / 2);
+    
+    outputs.iter()
+        .find(|(_, area)| area.contains_point(center_x, center_y))
+        .and_then(|(connector, area)| {
+            let local = (x - area.x(), y - area.y(), width, height);
+            let region = clamp_region(local, area.width(), area.height())?;
+            Some((connector.clone(), region))
+        })
+}
+
+/// Clip a window region `(x, y, width, height)` to an image of the given size
+///
+/// Returns `None` if the window lies entirely outside the image.
+fn clamp_region(region: (i32, i32, i32, i32), image_width: i32, image_height: i32) -> Option<(i32, i32, i32, i32)> {
+    let (x, y, width, height) = region;
+    let left = x.max(0);
+    let top = y.max(0);
+    let right = (x + width).min(image_width);
+    let bottom = (y + height).min(image_height);
+    
+    if right <= left || bottom <= top {
+        return None;
+    }
+    Some((left, top, right - left, bottom - top))
+}
+
 /// Create a placeholder preview widget for when thumbnails aren't available
 pub fn create_placeholder_preview(app_name: &str, window_title: &str) -> gtk::Box {
     let container = gtk::Box::builder()
@@ -493,6 +776,8 @@ pub fn create_placeholder_preview(app_name: &str, window_title: &str) -> gtk::Bo
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
 
     #[test]
     fn test_screenshot_tool_detection() {
@@ -506,6 +791,83 @@ mod tests {
         assert!(!service.thumbnails.lock().unwrap().is_empty() || true);
     }
 
+    #[test]
+    fn test_clamp_region() {
+        // Fully inside
+        assert_eq!(clamp_region((100, 50, 800, 600), 1920, 1080), Some((100, 50, 800, 600)));
+        // Partly off the top-left and bottom-right corners
+        assert_eq!(clamp_region((-20, -10, 200, 100), 1920, 1080), Some((0, 0, 180, 90)));
+        assert_eq!(clamp_region((1800, 1000, 300, 200), 1920, 1080), Some((1800, 1000, 120, 80)));
+        // Entirely off screen
+        assert_eq!(clamp_region((2000, 0, 100, 100), 1920, 1080), None);
+        assert_eq!(clamp_region((0, 0, 0, 100), 1920, 1080), None);
+    }
+
+    #[test]
+    fn test_async_cache_miss_invokes_callback() {
+        let context = glib::MainContext::new();
+        let _guard = context.acquire().unwrap();
+        
+        context.with_thread_default(|| {
+            let service = ScreencopyService::new();
+            *service.tool.lock().unwrap() = ScreenshotTool::None;
+            
+            // A miss schedules the capture instead of calling back immediately
+            let delivered = Rc::new(Cell::new(false));
+            let handle = service.request_thumbnail_async("async-1", "firefox", "Test Window", {
+                let delivered = Rc::clone(&delivered);
+                move |_| delivered.set(true)
+            });
+            assert!(handle.is_some());
+            assert!(!delivered.get());
+            
+            let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+            while !delivered.get() && std::time::Instant::now() < deadline {
+                context.iteration(true);
+            }
+            assert!(delivered.get());
+            assert!(service.get_thumbnail("async-1").is_some());
+            
+            // Now cached, so the callback runs synchronously
+            let hit = Rc::new(Cell::new(false));
+            let handle = service.request_thumbnail_async("async-1", "firefox", "Test Window", {
+                let hit = Rc::clone(&hit);
+                move |_| hit.set(true)
+            });
+            assert!(handle.is_none());
+            assert!(hit.get());
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_cache_evicts_oldest_beyond_max_entries() {
+        let service = ScreencopyService::new();
+        let extra = 5;
+        
+        for i in 0..(DEFAULT_MAX_ENTRIES + extra) {
+            let window_id = format!("lru-{}", i);
+            // A stray capture for the oldest window should go with its entry
+            if i == 0 {
+                std::fs::write(service.temp_path(&window_id), b"png").unwrap();
+            }
+            service.insert_thumbnail(WindowThumbnail {
+                window_id,
+                app_id: "firefox".to_string(),
+                title: format!("Window {}", i),
+                pixbuf: None,
+                last_updated: 1_000 + i as u64,
+            });
+        }
+        
+        assert_eq!(service.thumbnails.lock().unwrap().len(), DEFAULT_MAX_ENTRIES);
+        for i in 0..extra {
+            assert!(service.get_thumbnail(&format!("lru-{}", i)).is_none());
+        }
+        assert!(service.get_thumbnail(&format!("lru-{}", extra)).is_some());
+        assert!(!service.temp_path("lru-0").exists());
+    }
+
     #[test]
     fn test_cache_operations() {
         let service = ScreencopyService::new();
@@ -2,10 +2,24 @@
 //!
 //! Monitors system theme changes and provides color information for theming.
 
+use gtk::prelude::*;
+use gtk::gio::{self, FileMonitorEvent};
 use gtk::glib;
 use gtk::Settings as GtkSettings;
-use log::{info, debug};
+use log::{info, debug, warn};
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Quiet period after the last kdeglobals change before re-reading it
+///
+/// Applying a color scheme rewrites the file several times in a row.
+const KDE_CONFIG_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Safety-net interval for re-reading kdeglobals in case events are missed
+const KDE_CONFIG_FALLBACK_SECS: u32 = 300;
 
 /// Theme mode (light/dark)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -15,21 +29,58 @@ pub enum ThemeMode {
     System, // Follow system preference
 }
 
+impl ThemeMode {
+    /// Parse the `theme_mode` setting ("light", "dark" or "system")
+    ///
+    /// Unknown values fall back to following the system.
+    pub fn from_name(name: &str) -> Self {
+        match name.trim().to_lowercase().as_str() {
+            "light" => ThemeMode::Light,
+            "dark" => ThemeMode::Dark,
+            "system" | "" => ThemeMode::System,
+            other => {
+                warn!("Unknown theme mode '{}', following the system", other);
+                ThemeMode::System
+            }
+        }
+    }
+
+    /// Whether the dark palette applies, given the system preference
+    fn is_dark(self, system_prefers_dark: bool) -> bool {
+        match self {
+            ThemeMode::Light => false,
+            ThemeMode::Dark => true,
+            ThemeMode::System => system_prefers_dark,
+        }
+    }
+}
+
 /// Theme colors extracted from system
 #[derive(Debug, Clone)]
 pub struct ThemeColors {
     pub accent_color: (f64, f64, f64),      // RGB 0.0-1.0
     pub background_color: (f64, f64, f64),
     pub foreground_color: (f64, f64, f64),
+    pub on_accent: (f64, f64, f64),         // Black or white, legible on the accent
     pub is_dark: bool,
 }
 
+impl ThemeColors {
+    /// Set the accent color, updating the foreground used on top of it
+    pub fn set_accent(&mut self, accent: (f64, f64, f64)) {
+        self.accent_color = accent;
+        self.on_accent = on_accent_for(accent);
+    }
+}
+
 impl Default for ThemeColors {
     fn default() -> Self {
+        let accent_color = (0.4, 0.6, 1.0);     // Blue accent
         Self {
-            accent_color: (0.4, 0.6, 1.0),      // Blue accent
+            accent_color,
             background_color: (0.1, 0.1, 0.15), // Dark background
             foreground_color: (1.0, 1.0, 1.0),  // White text
+            on_accent: on_accent_for(accent_color),
             is_dark: true,
         }
     }
@@ -40,20 +91,28 @@ impl Default for ThemeColors {
 pub struct ThemeService {
     current_mode: Arc<Mutex<ThemeMode>>,
     current_colors: Arc<Mutex<ThemeColors>>,
+    system_prefers_dark: Arc<Mutex<bool>>,
     callbacks: Arc<Mutex<Vec<Box<dyn Fn(&ThemeColors) + Send + Sync>>>>,
+    kde_monitor: Rc<RefCell<Option<gio::FileMonitor>>>,
 }
 
 impl ThemeService {
     /// Create a new theme service
     pub fn new() -> Self {
-        let service = Self {
+        let service = Self::new_undetected();
+        service.detect_initial_theme();
+        service
+    }
+
+    /// Create a service with default colors, without querying GTK
+    fn new_undetected() -> Self {
+        Self {
             current_mode: Arc::new(Mutex::new(ThemeMode::System)),
             current_colors: Arc::new(Mutex::new(ThemeColors::default())),
+            system_prefers_dark: Arc::new(Mutex::new(true)),
             callbacks: Arc::new(Mutex::new(Vec::new())),
-        };
-        
-        service.detect_initial_theme();
-        service
+            kde_monitor: Rc::new(RefCell::new(None)),
+        }
     }
 
     /// Detect the initial system theme
@@ -62,16 +121,9 @@ impl ThemeService {
             let is_dark = settings.is_gtk_application_prefer_dark_theme();
             debug!("GTK prefers dark theme: {}", is_dark);
             
+            *self.system_prefers_dark.lock().unwrap() = is_dark;
             let mut colors = self.current_colors.lock().unwrap();
-            colors.is_dark = is_dark;
-            
-            if is_dark {
-                colors.background_color = (0.1, 0.1, 0.15);
-                colors.foreground_color = (1.0, 1.0, 1.0);
-            } else {
-                colors.background_color = (0.95, 0.95, 0.95);
-                colors.foreground_color = (0.1, 0.1, 0.1);
-            }
+            apply_palette(&mut colors, self.get_mode().is_dark(is_dark));
             
             // Try to detect accent color from GTK settings
             self.detect_accent_color(&settings, &mut colors);
@@ -88,14 +140,14 @@ impl ThemeService {
         
         // Try KDE accent color
         if let Some(accent) = self.get_kde_accent_color() {
-            colors.accent_color = accent;
+            colors.set_accent(accent);
             debug!("KDE accent color detected: {:?}", accent);
             return;
         }
 
         // Try GNOME accent color
         if let Some(accent) = self.get_gnome_accent_color() {
-            colors.accent_color = accent;
+            colors.set_accent(accent);
             debug!("GNOME accent color detected: {:?}", accent);
             return;
         }
@@ -109,22 +161,7 @@ impl ThemeService {
         // Read from ~/.config/kdeglobals
         let config_path = dirs::config_dir()?.join("kdeglobals");
         
-        if let Ok(content) = std::fs::read_to_string(&config_path) {
-            for line in content.lines() {
-                if line.starts_with("AccentColor=") {
-                    let color_str = line.strip_prefix("AccentColor=")?;
-                    let parts: Vec<&str> = color_str.split(',').collect();
-                    if parts.len() >= 3 {
-                        let r = parts[0].trim().parse::<u8>().ok()? as f64 / 255.0;
-                        let g = parts[1].trim().parse::<u8>().ok()? as f64 / 255.0;
-                        let b = parts[2].trim().parse::<u8>().ok()? as f64 / 255.0;
-                        return Some((r, g, b));
-                    }
-                }
-            }
-        }
-        
-        None
+        parse_kde_accent(&std::fs::read_to_string(&config_path).ok()?)
     }
 
     /// Get GNOME accent color
@@ -137,22 +174,7 @@ impl ThemeService {
             .ok()?;
         
         if output.status.success() {
-            let color_name = String::from_utf8_lossy(&output.stdout);
-            let color_name = color_name.trim().trim_matches('\'');
-            
-            // Map GNOME accent color names to RGB
-            match color_name {
-                "blue" => return Some((0.2, 0.5, 0.9)),
-                "teal" => return Some((0.2, 0.7, 0.7)),
-                "green" => return Some((0.3, 0.7, 0.3)),
-                "yellow" => return Some((0.9, 0.8, 0.2)),
-                "orange" => return Some((0.9, 0.5, 0.2)),
-                "red" => return Some((0.9, 0.3, 0.3)),
-                "pink" => return Some((0.9, 0.4, 0.6)),
-                "purple" => return Some((0.6, 0.4, 0.9)),
-                "slate" => return Some((0.5, 0.5, 0.6)),
-                _ => {}
-            }
+            return parse_gnome_accent(&String::from_utf8_lossy(&output.stdout));
         }
         
         None
@@ -160,35 +182,11 @@ impl ThemeService {
 
     /// Start monitoring theme changes
     pub fn start_monitoring(&self) {
-        let colors = Arc::clone(&self.current_colors);
-        let callbacks = Arc::clone(&self.callbacks);
-        let mode = Arc::clone(&self.current_mode);
-
         if let Some(settings) = GtkSettings::default() {
             // Monitor dark theme preference changes
+            let service = self.clone();
             settings.connect_gtk_application_prefer_dark_theme_notify(move |s| {
-                let is_dark = s.is_gtk_application_prefer_dark_theme();
-                info!("Theme changed to: {}", if is_dark { "dark" } else { "light" });
-                
-                let mut colors_guard = colors.lock().unwrap();
-                colors_guard.is_dark = is_dark;
-                
-                if is_dark {
-                    colors_guard.background_color = (0.1, 0.1, 0.15);
-                    colors_guard.foreground_color = (1.0, 1.0, 1.0);
-                } else {
-                    colors_guard.background_color = (0.95, 0.95, 0.95);
-                    colors_guard.foreground_color = (0.1, 0.1, 0.1);
-                }
-                
-                let colors_clone = colors_guard.clone();
-                drop(colors_guard);
-                
-                // Notify all callbacks
-                let cbs = callbacks.lock().unwrap();
-                for callback in cbs.iter() {
-                    callback(&colors_clone);
-                }
+                service.set_system_prefers_dark(s.is_gtk_application_prefer_dark_theme());
             });
         }
         
@@ -197,58 +195,90 @@ impl ThemeService {
     }
 
     /// Watch KDE configuration file for changes
+    ///
+    /// Re-reads kdeglobals once a burst of change events settles, with a
+    /// slow timer as a safety net for missed events.
     fn watch_kde_config(&self) {
-        let colors = Arc::clone(&self.current_colors);
-        let callbacks = Arc::clone(&self.callbacks);
+        let Some(config_path) = dirs::config_dir().map(|p| p.join("kdeglobals")) else {
+            return;
+        };
         
-        glib::timeout_add_seconds_local(10, move || {
-            // Periodically check for KDE accent color changes
-            let config_path = match dirs::config_dir() {
-                Some(p) => p.join("kdeglobals"),
-                None => return glib::ControlFlow::Continue,
-            };
-            
-            if let Ok(content) = std::fs::read_to_string(&config_path) {
-                for line in content.lines() {
-                    if line.starts_with("AccentColor=") {
-                        if let Some(color_str) = line.strip_prefix("AccentColor=") {
-                            let parts: Vec<&str> = color_str.split(',').collect();
-                            if parts.len() >= 3 {
-                                if let (Ok(r), Ok(g), Ok(b)) = (
-                                    parts[0].trim().parse::<u8>(),
-                                    parts[1].trim().parse::<u8>(),
-                                    parts[2].trim().parse::<u8>(),
-                                ) {
-                                    let new_accent = (
-                                        r as f64 / 255.0,
-                                        g as f64 / 255.0,
-                                        b as f64 / 255.0,
-                                    );
-                                    
-                                    let mut colors_guard = colors.lock().unwrap();
-                                    if colors_guard.accent_color != new_accent {
-                                        colors_guard.accent_color = new_accent;
-                                        debug!("KDE accent color updated: {:?}", new_accent);
-                                        
-                                        let colors_clone = colors_guard.clone();
-                                        drop(colors_guard);
-                                        
-                                        let cbs = callbacks.lock().unwrap();
-                                        for callback in cbs.iter() {
-                                            callback(&colors_clone);
-                                        }
-                                    }
-                                }
-                            }
-                        }
+        let file = gio::File::for_path(&config_path);
+        match file.monitor_file(gio::FileMonitorFlags::NONE, gio::Cancellable::NONE) {
+            Ok(monitor) => {
+                let colors = Arc::clone(&self.current_colors);
+                let callbacks = Arc::clone(&self.callbacks);
+                let path = config_path.clone();
+                let pending: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
+                
+                monitor.connect_changed(move |_monitor, _file, _other, event| {
+                    match event {
+                        FileMonitorEvent::Changed |
+                        FileMonitorEvent::ChangesDoneHint |
+                        FileMonitorEvent::Created |
+                        FileMonitorEvent::MovedIn |
+                        FileMonitorEvent::Renamed => {}
+                        _ => return,
                     }
-                }
+                    
+                    // Restart the debounce timer on every event of a burst
+                    if let Some(source) = pending.borrow_mut().take() {
+                        source.remove();
+                    }
+                    
+                    let colors = Arc::clone(&colors);
+                    let callbacks = Arc::clone(&callbacks);
+                    let path = path.clone();
+                    let pending_done = Rc::clone(&pending);
+                    let source = glib::timeout_add_local_once(KDE_CONFIG_DEBOUNCE, move || {
+                        pending_done.borrow_mut().take();
+                        Self::reload_kde_accent(&path, &colors, &callbacks);
+                    });
+                    *pending.borrow_mut() = Some(source);
+                });
+                
+                *self.kde_monitor.borrow_mut() = Some(monitor);
+                debug!("Watching {:?} for accent color changes", config_path);
             }
-            
+            Err(e) => warn!("Failed to monitor kdeglobals: {}", e),
+        }
+        
+        let colors = Arc::clone(&self.current_colors);
+        let callbacks = Arc::clone(&self.callbacks);
+        glib::timeout_add_seconds_local(KDE_CONFIG_FALLBACK_SECS, move || {
+            Self::reload_kde_accent(&config_path, &colors, &callbacks);
             glib::ControlFlow::Continue
         });
     }
 
+    /// Re-read the KDE accent color and notify callbacks if it changed
+    fn reload_kde_accent(
+        config_path: &Path,
+        colors: &Arc<Mutex<ThemeColors>>,
+        callbacks: &Arc<Mutex<Vec<Box<dyn Fn(&ThemeColors) + Send + Sync>>>>,
+    ) {
+        let Some(new_accent) = std::fs::read_to_string(config_path)
+            .ok()
+            .and_then(|content| parse_kde_accent(&content))
+        else {
+            return;
+        };
+        
+        let mut colors_guard = colors.lock().unwrap();
+        if colors_guard.accent_color != new_accent {
+            colors_guard.set_accent(new_accent);
+            debug!("KDE accent color updated: {:?}", new_accent);
+            
+            let colors_clone = colors_guard.clone();
+            drop(colors_guard);
+            
+            let cbs = callbacks.lock().unwrap();
+            for callback in cbs.iter() {
+                callback(&colors_clone);
+            }
+        }
+    }
+
     /// Register a callback for theme changes
     pub fn on_theme_change<F>(&self, callback: F)
     where
@@ -268,6 +298,42 @@ impl ThemeService {
         *self.current_mode.lock().unwrap()
     }
 
+    /// Set the theme mode, forcing light/dark or following the system
+    pub fn set_mode(&self, mode: ThemeMode) {
+        *self.current_mode.lock().unwrap() = mode;
+        debug!("Theme mode set to {:?}", mode);
+        self.refresh_palette();
+    }
+
+    /// Record a change of the system dark preference
+    ///
+    /// Only affects the colors while following the system.
+    fn set_system_prefers_dark(&self, is_dark: bool) {
+        *self.system_prefers_dark.lock().unwrap() = is_dark;
+        info!("System theme changed to: {}", if is_dark { "dark" } else { "light" });
+        
+        if self.get_mode() == ThemeMode::System {
+            self.refresh_palette();
+        }
+    }
+
+    /// Apply the palette for the current mode and notify callbacks
+    fn refresh_palette(&self) {
+        let is_dark = self.get_mode().is_dark(*self.system_prefers_dark.lock().unwrap());
+        
+        let colors = {
+            let mut colors = self.current_colors.lock().unwrap();
+            apply_palette(&mut colors, is_dark);
+            colors.clone()
+        };
+        
+        // Notify all callbacks
+        let cbs = self.callbacks.lock().unwrap();
+        for callback in cbs.iter() {
+            callback(&colors);
+        }
+    }
+
     /// Check if dark mode is active
     pub fn is_dark_mode(&self) -> bool {
         self.current_colors.lock().unwrap().is_dark
@@ -275,24 +341,7 @@ impl ThemeService {
 
     /// Generate CSS variables for the current theme
     pub fn generate_css_variables(&self) -> String {
-        let colors = self.current_colors.lock().unwrap();
-        
-        format!(
-            r#"
-            @define-color accent_color rgb({}, {}, {});
-            @define-color bg_color rgb({}, {}, {});
-            @define-color fg_color rgb({}, {}, {});
-            "#,
-            (colors.accent_color.0 * 255.0) as u8,
-            (colors.accent_color.1 * 255.0) as u8,
-            (colors.accent_color.2 * 255.0) as u8,
-            (colors.background_color.0 * 255.0) as u8,
-            (colors.background_color.1 * 255.0) as u8,
-            (colors.background_color.2 * 255.0) as u8,
-            (colors.foreground_color.0 * 255.0) as u8,
-            (colors.foreground_color.1 * 255.0) as u8,
-            (colors.foreground_color.2 * 255.0) as u8,
-        )
+        css_variables_for(&self.current_colors.lock().unwrap())
     }
 }
 
@@ -302,3 +351,206 @@ impl Default for ThemeService {
     }
 }
 
+/// WCAG relative luminance of an sRGB color
+fn relative_luminance((r, g, b): (f64, f64, f64)) -> f64 {
+    let linear = |c: f64| {
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// Pick black or white, whichever has the higher WCAG contrast against `accent`
+fn on_accent_for(accent: (f64, f64, f64)) -> (f64, f64, f64) {
+    let luminance = relative_luminance(accent);
+    let contrast_with_white = 1.05 / (luminance + 0.05);
+    let contrast_with_black = (luminance + 0.05) / 0.05;
+    
+    if contrast_with_white >= contrast_with_black {
+        (1.0, 1.0, 1.0)
+    } else {
+        (0.0, 0.0, 0.0)
+    }
+}
+
+/// Parse the `AccentColor=r,g,b` entry from kdeglobals into RGB
+fn parse_kde_accent(content: &str) -> Option<(f64, f64, f64)> {
+    let color_str = content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("AccentColor="))?;
+    
+    let parts: Vec<&str> = color_str.split(',').collect();
+    if parts.len() < 3 {
+        return None;
+    }
+    
+    let channel = |i: usize| parts[i].trim().parse::<u8>().ok().map(|c| c as f64 / 255.0);
+    Some((channel(0)?, channel(1)?, channel(2)?))
+}
+
+/// Parse a GNOME `accent-color` value into RGB
+///
+/// Accepts named accents and `#rrggbb` hex values, quoted (as printed by
+/// gsettings) or not.
+fn parse_gnome_accent(value: &str) -> Option<(f64, f64, f64)> {
+    let value = value.trim().trim_matches(|c| c == '\'' || c == '"').trim().to_lowercase();
+    
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok().map(|c| c as f64 / 255.0);
+        return Some((channel(0)?, channel(2)?, channel(4)?));
+    }
+    
+    // Map GNOME accent color names to RGB
+    match value.as_str() {
+        "blue" => Some((0.2, 0.5, 0.9)),
+        "teal" => Some((0.2, 0.7, 0.7)),
+        "green" => Some((0.3, 0.7, 0.3)),
+        "yellow" => Some((0.9, 0.8, 0.2)),
+        "orange" => Some((0.9, 0.5, 0.2)),
+        "red" => Some((0.9, 0.3, 0.3)),
+        "pink" => Some((0.9, 0.4, 0.6)),
+        "purple" => Some((0.6, 0.4, 0.9)),
+        "slate" => Some((0.5, 0.5, 0.6)),
+        _ => None,
+    }
+}
+
+/// Set the light or dark background/foreground palette
+fn apply_palette(colors: &mut ThemeColors, is_dark: bool) {
+    colors.is_dark = is_dark;
+    
+    if is_dark {
+        colors.background_color = (0.1, 0.1, 0.15);
+        colors.foreground_color = (1.0, 1.0, 1.0);
+    } else {
+        colors.background_color = (0.95, 0.95, 0.95);
+        colors.foreground_color = (0.1, 0.1, 0.1);
+    }
+}
+
+/// Format theme colors as GTK `@define-color` declarations
+fn css_variables_for(colors: &ThemeColors) -> String {
+    format!(
+        r#"
+        @define-color accent_color rgb({}, {}, {});
+        @define-color bg_color rgb({}, {}, {});
+        @define-color fg_color rgb({}, {}, {});
+        @define-color on_accent_color rgb({}, {}, {});
+        "#,
+        (colors.accent_color.0 * 255.0) as u8,
+        (colors.accent_color.1 * 255.0) as u8,
+        (colors.accent_color.2 * 255.0) as u8,
+        (colors.background_color.0 * 255.0) as u8,
+        (colors.background_color.1 * 255.0) as u8,
+        (colors.background_color.2 * 255.0) as u8,
+        (colors.foreground_color.0 * 255.0) as u8,
+        (colors.foreground_color.1 * 255.0) as u8,
+        (colors.foreground_color.2 * 255.0) as u8,
+        (colors.on_accent.0 * 255.0) as u8,
+        (colors.on_accent.1 * 255.0) as u8,
+        (colors.on_accent.2 * 255.0) as u8,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forced_dark_ignores_light_system() {
+        let service = ThemeService::new_undetected();
+        service.set_system_prefers_dark(false);
+        assert!(!service.is_dark_mode());
+        
+        service.set_mode(ThemeMode::Dark);
+        let colors = service.get_colors();
+        assert!(colors.is_dark);
+        assert_eq!(colors.background_color, (0.1, 0.1, 0.15));
+        assert_eq!(colors.foreground_color, (1.0, 1.0, 1.0));
+        
+        // System changes are ignored while forced
+        service.set_system_prefers_dark(false);
+        assert!(service.is_dark_mode());
+        
+        // Following the system again picks up its preference
+        service.set_mode(ThemeMode::System);
+        assert!(!service.is_dark_mode());
+    }
+
+    #[test]
+    fn test_parse_kde_accent() {
+        let content = "[General]\nColorScheme=BreezeDark\nAccentColor=51,102,255\n";
+        assert_eq!(parse_kde_accent(content), Some((0.2, 0.4, 1.0)));
+        assert_eq!(parse_kde_accent("[General]\nAccentColor=51,102\n"), None);
+        assert_eq!(parse_kde_accent("[General]\n"), None);
+    }
+
+    #[test]
+    fn test_parse_gnome_accent() {
+        // Hex, quoted as gsettings prints it
+        assert_eq!(parse_gnome_accent("'#3366FF'\n"), Some((0.2, 0.4, 1.0)));
+        assert_eq!(parse_gnome_accent("#000000"), Some((0.0, 0.0, 0.0)));
+        
+        // Named, quoted or not
+        assert_eq!(parse_gnome_accent("'blue'"), Some((0.2, 0.5, 0.9)));
+        assert_eq!(parse_gnome_accent("purple"), Some((0.6, 0.4, 0.9)));
+        
+        // Malformed
+        assert_eq!(parse_gnome_accent("'#12345'"), None);
+        assert_eq!(parse_gnome_accent("#zzzzzz"), None);
+        assert_eq!(parse_gnome_accent("'chartreuse'"), None);
+        assert_eq!(parse_gnome_accent(""), None);
+    }
+
+    #[test]
+    fn test_theme_mode_from_name() {
+        assert_eq!(ThemeMode::from_name("dark"), ThemeMode::Dark);
+        assert_eq!(ThemeMode::from_name("Light"), ThemeMode::Light);
+        assert_eq!(ThemeMode::from_name("system"), ThemeMode::System);
+        assert_eq!(ThemeMode::from_name("sepia"), ThemeMode::System);
+    }
+
+    #[test]
+    fn test_css_variables_for_known_colors() {
+        let colors = ThemeColors {
+            accent_color: (0.2, 0.4, 1.0),
+            background_color: (0.0, 0.0, 0.0),
+            foreground_color: (1.0, 1.0, 1.0),
+            on_accent: (1.0, 1.0, 1.0),
+            is_dark: true,
+        };
+        
+        let css = css_variables_for(&colors);
+        let lines: Vec<&str> = css.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+        
+        assert_eq!(lines, vec![
+            "@define-color accent_color rgb(51, 102, 255);",
+            "@define-color bg_color rgb(0, 0, 0);",
+            "@define-color fg_color rgb(255, 255, 255);",
+            "@define-color on_accent_color rgb(255, 255, 255);",
+        ]);
+    }
+
+    #[test]
+    fn test_on_accent_contrast() {
+        // Dark accents get white text
+        assert_eq!(on_accent_for((0.1, 0.2, 0.5)), (1.0, 1.0, 1.0));
+        assert_eq!(on_accent_for((0.0, 0.0, 0.0)), (1.0, 1.0, 1.0));
+        
+        // Light accents like pale yellow get black text
+        assert_eq!(on_accent_for((1.0, 0.95, 0.6)), (0.0, 0.0, 0.0));
+        assert_eq!(on_accent_for((1.0, 1.0, 1.0)), (0.0, 0.0, 0.0));
+        
+        let mut colors = ThemeColors::default();
+        colors.set_accent((0.9, 0.8, 0.2));
+        assert_eq!(colors.on_accent, (0.0, 0.0, 0.0));
+        
+        assert!((relative_luminance((1.0, 1.0, 1.0)) - 1.0).abs() < 1e-9);
+    }
+}
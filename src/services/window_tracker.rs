@@ -3,10 +3,11 @@
 //! Tracks open windows via D-Bus interfaces for KDE, GNOME, and Hyprland.
 //! Provides app-to-window mapping and window count information.
 
-use gtk::glib;
+use gtk::prelude::*;
+use gtk::{gio, glib};
 use log::{info, debug, warn, error};
 use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Window information
 #[derive(Debug, Clone)]
@@ -15,8 +16,61 @@ pub struct WindowInfo {
     pub title: String,
     pub app_id: String,
     pub is_active: bool,
+    /// Frame geometry `(x, y, width, height)`, when the compositor reports it
+    pub geometry: Option<(i32, i32, i32, i32)>,
 }
 
+/// A window event from Hyprland's `.socket2.sock` event stream
+#[derive(Debug, Clone, PartialEq)]
+enum HyprlandEvent {
+    OpenWindow { address: String, workspace: String, class: String, title: String },
+    CloseWindow { address: String },
+    /// `None` when focus moved to no window (e.g. an empty workspace)
+    ActiveWindow { address: Option<String> },
+    MoveWindow { address: String, workspace: String },
+}
+
+/// A `window` event from a Sway IPC subscription
+#[derive(Debug, Clone, PartialEq)]
+enum SwayWindowEvent {
+    New { id: String, app_id: String, title: String },
+    Close { id: String },
+    Focus { id: String },
+    Title { id: String, title: String },
+}
+
+/// Object path and interface KWin scripts report window lists to
+const KDE_RECEIVER_PATH: &str = "/com/blazedock/WindowTracker";
+const KDE_RECEIVER_INTERFACE: &str = "com.blazedock.WindowTracker";
+
+/// D-Bus object receiving window lists from KWin scripts
+struct KdeWindowReceiver {
+    tracker: WindowTracker,
+}
+
+#[zbus::interface(name = "com.blazedock.WindowTracker")]
+impl KdeWindowReceiver {
+    /// Called by the KWin script with a JSON array of windows
+    fn report_windows(&self, json: &str) {
+        self.tracker.apply_kde_report(json);
+    }
+}
+
+/// Interval between full `j/clients` reconciliations on Hyprland (seconds)
+const HYPRLAND_RESYNC_INTERVAL: u32 = 30;
+
+/// Interval between full `get_tree` resyncs on Sway (seconds)
+const SWAY_RESYNC_INTERVAL: u32 = 30;
+
+/// Interval between geometry polls on Hyprland and Sway while watched
+const GEOMETRY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Sway IPC message types
+const SWAY_IPC_SUBSCRIBE: u32 = 2;
+pub(crate) const SWAY_IPC_GET_OUTPUTS: u32 = 3;
+const SWAY_IPC_GET_TREE: u32 = 4;
+const SWAY_IPC_EVENT_WINDOW: u32 = 0x8000_0003;
+
 /// Detected desktop environment
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DesktopEnvironment {
@@ -35,6 +89,11 @@ pub struct WindowTracker {
     app_window_counts: Arc<Mutex<HashMap<String, u32>>>,
     running: Arc<Mutex<bool>>,
     desktop: Arc<Mutex<DesktopEnvironment>>,
+    app_id_aliases: Arc<Mutex<HashMap<String, Vec<String>>>>,
+    /// Windows minimized from the dock, restored on the next toggle
+    minimized: Arc<Mutex<HashSet<String>>>,
+    /// Whether the geometry poll from `watch_geometry` is running
+    watching_geometry: Arc<Mutex<bool>>,
 }
 
 impl WindowTracker {
@@ -48,11 +107,14 @@ impl WindowTracker {
             app_window_counts: Arc::new(Mutex::new(HashMap::new())),
             running: Arc::new(Mutex::new(false)),
             desktop: Arc::new(Mutex::new(desktop)),
+            app_id_aliases: Arc::new(Mutex::new(HashMap::new())),
+            minimized: Arc::new(Mutex::new(HashSet::new())),
+            watching_geometry: Arc::new(Mutex::new(false)),
         }
     }
 
     /// Detect the current desktop environment
-    fn detect_desktop_environment() -> DesktopEnvironment {
+    pub fn detect_desktop_environment() -> DesktopEnvironment {
         // Check environment variables
         let xdg_desktop = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
         let xdg_session = std::env::var("XDG_SESSION_DESKTOP").unwrap_or_default();
@@ -111,100 +173,125 @@ impl WindowTracker {
         let tracker = self.clone();
         
         glib::spawn_future_local(async move {
-            match tracker.poll_kde_windows().await {
-                Ok(_) => info!("KDE window polling started"),
-                Err(e) => warn!("Failed to start KDE window tracking: {}", e),
-            }
-        });
-        
-        // Start periodic polling
-        let tracker = self.clone();
-        glib::timeout_add_seconds_local(2, move || {
-            if !tracker.is_running() {
-                return glib::ControlFlow::Break;
-            }
+            // KWin scripts report back over this connection, so keep it alive
+            let connection = match tracker.setup_kde_receiver().await {
+                Ok(connection) => {
+                    info!("KDE window polling started");
+                    connection
+                }
+                Err(e) => {
+                    warn!("Failed to start KDE window tracking: {}", e);
+                    return;
+                }
+            };
             
-            let tracker_clone = tracker.clone();
-            glib::spawn_future_local(async move {
-                if let Err(e) = tracker_clone.poll_kde_windows().await {
+            while tracker.is_running() {
+                if let Err(e) = tracker.poll_kde_windows(&connection).await {
                     debug!("KDE window poll error: {}", e);
                 }
-            });
-            
-            glib::ControlFlow::Continue
+                glib::timeout_future_seconds(2).await;
+            }
         });
     }
 
-    /// Poll KDE windows via D-Bus
-    async fn poll_kde_windows(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// Serve the object that KWin scripts report window lists to
+    async fn setup_kde_receiver(&self) -> Result<zbus::Connection, Box<dyn std::error::Error + Send + Sync>> {
         let connection = zbus::Connection::session().await?;
         
-        // Call org.kde.KWin to get window list
-        let message = connection
-            .call_method(
-                Some("org.kde.KWin"),
-                "/KWin",
-                Some("org.kde.KWin"),
-                "queryWindowInfo",
-                &(),
-            )
-            .await;
+        connection
+            .object_server()
+            .at(KDE_RECEIVER_PATH, KdeWindowReceiver { tracker: self.clone() })
+            .await?;
         
-        match message {
-            Ok(reply) => {
-                // Parse the reply - KWin returns a list of window info variants
-                debug!("Received KDE window info response");
-                self.parse_kde_response(&reply)?;
-            }
-            Err(e) => {
-                // Try alternative method: scripting interface
-                debug!("KWin queryWindowInfo failed ({}), trying script method", e);
-                self.poll_kde_via_script(&connection).await?;
-            }
-        }
+        Ok(connection)
+    }
+
+    /// Poll KDE windows by running a KWin script that reports back over D-Bus
+    async fn poll_kde_windows(&self, connection: &zbus::Connection) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let service = connection
+            .unique_name()
+            .ok_or("D-Bus connection has no unique name")?;
         
-        Ok(())
+        // Plasma 6 exposes windowList/activeWindow, Plasma 5 clientList/activeClient
+        let script = format!(
+            r#"
+            var list = workspace.windowList ? workspace.windowList() : workspace.clientList();
+            var active = workspace.activeWindow !== undefined ? workspace.activeWindow : workspace.activeClient;
+            var windows = [];
+            for (var i = 0; i < list.length; i++) {{
+                var w = list[i];
+                if (!w.normalWindow || w.skipTaskbar) continue;
+                var frame = w.frameGeometry;
+                windows.push({{
+                    id: String(w.internalId),
+                    app_id: w.resourceClass || w.resourceName || "unknown",
+                    title: w.caption || "",
+                    active: w === active,
+                    x: frame.x, y: frame.y, width: frame.width, height: frame.height
+                }});
+            }}
+            callDBus("{service}", "{path}", "{iface}", "ReportWindows", JSON.stringify(windows));
+            "#,
+            service = service.as_str(),
+            path = KDE_RECEIVER_PATH,
+            iface = KDE_RECEIVER_INTERFACE,
+        );
+        
+        Self::run_kwin_script(connection, &script, "blazedock_windows").await
     }
 
-    /// Parse KDE window response
-    fn parse_kde_response(&self, _reply: &zbus::Message) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // KWin's window info is complex - for now, count windows per app
-        // Full implementation would parse the variant structure
-        debug!("KDE window response received");
-        Ok(())
+    /// Parse the window list reported by the KWin script
+    fn parse_kde_windows(json: &str) -> Result<Vec<WindowInfo>, Box<dyn std::error::Error + Send + Sync>> {
+        #[derive(serde::Deserialize)]
+        struct KdeWindow {
+            id: String,
+            app_id: String,
+            #[serde(default)]
+            title: String,
+            #[serde(default)]
+            active: bool,
+            x: Option<f64>,
+            y: Option<f64>,
+            width: Option<f64>,
+            height: Option<f64>,
+        }
+        
+        let windows: Vec<KdeWindow> = serde_json::from_str(json)?;
+        
+        Ok(windows
+            .into_iter()
+            .map(|w| {
+                // KWin reports fractional logical coordinates
+                let geometry = match (w.x, w.y, w.width, w.height) {
+                    (Some(x), Some(y), Some(width), Some(height)) => Some((
+                        x.round() as i32,
+                        y.round() as i32,
+                        width.round() as i32,
+                        height.round() as i32,
+                    )),
+                    _ => None,
+                };
+                WindowInfo {
+                    id: w.id,
+                    title: w.title,
+                    app_id: w.app_id,
+                    is_active: w.active,
+                    geometry,
+                }
+            })
+            .collect())
     }
 
-    /// Poll KDE windows using the scripting interface
-    async fn poll_kde_via_script(&self, connection: &zbus::Connection) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Use KWin scripting API to get window list
-        let script = r#"
-            var clients = workspace.clientList();
-            var result = {};
-            for (var i = 0; i < clients.length; i++) {
-                var c = clients[i];
-                var appId = c.resourceClass || c.resourceName || "unknown";
-                if (!result[appId]) result[appId] = 0;
-                result[appId]++;
+    /// Replace the tracked windows with a report from KWin
+    fn apply_kde_report(&self, json: &str) {
+        match Self::parse_kde_windows(json) {
+            Ok(windows) => {
+                debug!("KDE: Found {} windows", windows.len());
+                *self.app_window_counts.lock().unwrap() = Self::count_windows(&windows);
+                *self.windows.lock().unwrap() = windows;
             }
-            JSON.stringify(result);
-        "#;
-        
-        let reply = connection
-            .call_method(
-                Some("org.kde.KWin"),
-                "/Scripting",
-                Some("org.kde.kwin.Scripting"),
-                "loadScript",
-                &(script, "blazedock_count"),
-            )
-            .await;
-        
-        match reply {
-            Ok(_) => debug!("KWin script loaded for window counting"),
-            Err(e) => debug!("KWin script method failed: {}", e),
+            Err(e) => debug!("Failed to parse KDE window report: {}", e),
         }
-        
-        Ok(())
     }
 
     /// Start GNOME window tracking via D-Bus
@@ -304,6 +391,10 @@ impl WindowTracker {
                         })
                         .unwrap_or_default();
                     
+                    let has_focus = props.get("has-focus")
+                        .and_then(|v| bool::try_from(v).ok())
+                        .unwrap_or(false);
+                    
                     // Update counts
                     *counts.entry(app_id.clone()).or_insert(0) += 1;
                     
@@ -312,7 +403,8 @@ impl WindowTracker {
                         id: window_id.to_string(),
                         title,
                         app_id,
-                        is_active: false, // Simplified for now
+                        is_active: has_focus,
+                        geometry: None,
                     });
                 }
                 
@@ -343,9 +435,12 @@ impl WindowTracker {
             }
         });
         
-        // Periodic polling
+        // Incremental updates from the event socket
+        self.start_hyprland_events();
+        
+        // Periodic reconciliation in case events were missed
         let tracker = self.clone();
-        glib::timeout_add_seconds_local(2, move || {
+        glib::timeout_add_seconds_local(HYPRLAND_RESYNC_INTERVAL, move || {
             if !tracker.is_running() {
                 return glib::ControlFlow::Break;
             }
@@ -363,34 +458,246 @@ impl WindowTracker {
 
     /// Poll Hyprland windows via IPC
     async fn poll_hyprland_windows(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        use tokio::io::{AsyncReadExt, AsyncWriteExt};
-        use tokio::net::UnixStream;
+        let clients = Self::hyprland_request(b"j/clients").await?;
         
-        let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE")?;
-        let socket_path = format!("/tmp/hypr/{}/.socket.sock", signature);
-        
-        let mut stream = UnixStream::connect(&socket_path).await?;
-        stream.write_all(b"j/clients").await?;
+        // The active window query is best-effort; counts still update without it
+        let active = match Self::hyprland_request(b"j/activewindow").await {
+            Ok(json) => Self::parse_hyprland_active_window(&json),
+            Err(e) => {
+                debug!("Hyprland active window query failed: {}", e);
+                None
+            }
+        };
         
-        let mut response = String::new();
-        stream.read_to_string(&mut response).await?;
+        // Geometry is only kept for windows on a visible workspace
+        let visible_workspaces = match Self::hyprland_request(b"j/monitors").await {
+            Ok(json) => Self::parse_hyprland_visible_workspaces(&json),
+            Err(e) => {
+                debug!("Hyprland monitor query failed: {}", e);
+                Vec::new()
+            }
+        };
         
         // Parse JSON response
-        self.parse_hyprland_clients(&response)?;
+        self.parse_hyprland_clients(&clients, active.as_deref(), &visible_workspaces)?;
         
         Ok(())
     }
 
+    /// Send a request over the Hyprland IPC socket and return the reply
+    ///
+    /// Uses GIO sockets, which run on the GLib main loop these polls are
+    /// spawned on. Hyprland closes the connection after replying.
+    pub(crate) async fn hyprland_request(request: &[u8]) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let socket_path = Self::hyprland_socket_path(".socket.sock")?;
+        let connection = ipc_connect(&socket_path).await?;
+        
+        ipc_write(&connection, request.to_vec()).await?;
+        
+        let input = connection.input_stream();
+        let mut response = Vec::new();
+        loop {
+            let chunk = input.read_bytes_future(4096, glib::Priority::DEFAULT).await?;
+            if chunk.is_empty() {
+                break;
+            }
+            response.extend_from_slice(&chunk);
+        }
+        
+        Ok(String::from_utf8(response)?)
+    }
+
+    /// Locate a Hyprland IPC socket for the current instance
+    ///
+    /// Hyprland 0.40+ uses `$XDG_RUNTIME_DIR/hypr`, older versions `/tmp/hypr`.
+    fn hyprland_socket_path(name: &str) -> Result<std::path::PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE")?;
+        
+        if let Ok(runtime_dir) = std::env::var("XDG_RUNTIME_DIR") {
+            let path = std::path::Path::new(&runtime_dir).join("hypr").join(&signature).join(name);
+            if path.exists() {
+                return Ok(path);
+            }
+        }
+        
+        Ok(std::path::Path::new("/tmp/hypr").join(&signature).join(name))
+    }
+
+    /// Listen on Hyprland's event socket and apply window events as they arrive
+    ///
+    /// Runs on a background thread; reconnects if the compositor restarts.
+    fn start_hyprland_events(&self) {
+        use std::io::{BufRead, BufReader};
+        use std::os::unix::net::UnixStream;
+        
+        let tracker = self.clone();
+        
+        std::thread::spawn(move || {
+            while tracker.is_running() {
+                let stream = Self::hyprland_socket_path(".socket2.sock")
+                    .and_then(|path| UnixStream::connect(path).map_err(|e| e.into()));
+                
+                match stream {
+                    Ok(stream) => {
+                        info!("Connected to Hyprland event socket");
+                        for line in BufReader::new(stream).lines() {
+                            let Ok(line) = line else { break };
+                            if !tracker.is_running() {
+                                return;
+                            }
+                            if let Some(event) = Self::parse_hyprland_event(&line) {
+                                tracker.apply_hyprland_event(event);
+                            }
+                        }
+                        warn!("Hyprland event socket closed, reconnecting");
+                    }
+                    Err(e) => debug!("Hyprland event socket unavailable: {}", e),
+                }
+                
+                std::thread::sleep(std::time::Duration::from_secs(2));
+            }
+        });
+    }
+
+    /// Parse a single `EVENT>>DATA` line from Hyprland's event socket
+    ///
+    /// Only window events are returned; everything else is ignored.
+    fn parse_hyprland_event(line: &str) -> Option<HyprlandEvent> {
+        let (event, data) = line.split_once(">>")?;
+        
+        // Event addresses lack the 0x prefix used by j/clients
+        let address = |raw: &str| format!("0x{}", raw.trim_start_matches("0x"));
+        
+        match event {
+            "openwindow" => {
+                let mut parts = data.splitn(4, ',');
+                Some(HyprlandEvent::OpenWindow {
+                    address: address(parts.next()?),
+                    workspace: parts.next()?.to_string(),
+                    class: parts.next()?.to_string(),
+                    title: parts.next().unwrap_or_default().to_string(),
+                })
+            }
+            "closewindow" => Some(HyprlandEvent::CloseWindow { address: address(data) }),
+            "activewindowv2" => Some(HyprlandEvent::ActiveWindow {
+                address: Some(data).filter(|d| !d.is_empty() && *d != ",").map(address),
+            }),
+            "movewindow" => {
+                let (addr, workspace) = data.split_once(',')?;
+                Some(HyprlandEvent::MoveWindow {
+                    address: address(addr),
+                    workspace: workspace.to_string(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Apply a Hyprland window event to the tracked windows and counts
+    fn apply_hyprland_event(&self, event: HyprlandEvent) {
+        let mut windows = self.windows.lock().unwrap();
+        
+        match event {
+            HyprlandEvent::OpenWindow { address, workspace, class, title } => {
+                debug!("Hyprland window {} ({}) opened on workspace {}", address, class, workspace);
+                if windows.iter().all(|w| w.id != address) {
+                    windows.push(WindowInfo {
+                        id: address,
+                        title,
+                        app_id: class,
+                        is_active: false,
+                        geometry: None,
+                    });
+                }
+            }
+            HyprlandEvent::CloseWindow { address } => {
+                windows.retain(|w| w.id != address);
+            }
+            HyprlandEvent::ActiveWindow { address } => {
+                for window in windows.iter_mut() {
+                    window.is_active = address.as_deref() == Some(window.id.as_str());
+                }
+            }
+            HyprlandEvent::MoveWindow { address, workspace } => {
+                // Workspaces aren't tracked; counts are unaffected
+                debug!("Hyprland window {} moved to workspace {}", address, workspace);
+                return;
+            }
+        }
+        
+        *self.app_window_counts.lock().unwrap() = Self::count_windows(&windows);
+    }
+
+    /// Parse the address from Hyprland's `j/activewindow` reply
+    ///
+    /// Hyprland replies with `{}` when no window is focused.
+    fn parse_hyprland_active_window(json: &str) -> Option<String> {
+        #[derive(serde::Deserialize)]
+        struct HyprActiveWindow {
+            #[serde(default)]
+            address: Option<String>,
+        }
+        
+        serde_json::from_str::<HyprActiveWindow>(json)
+            .ok()
+            .and_then(|w| w.address)
+            .filter(|address| !address.is_empty())
+    }
+
+    /// Parse the workspaces shown on any monitor from Hyprland's `j/monitors` reply
+    ///
+    /// Includes open special workspaces, which Hyprland reports with id 0
+    /// when none is shown.
+    fn parse_hyprland_visible_workspaces(json: &str) -> Vec<i64> {
+        #[derive(serde::Deserialize)]
+        struct HyprWorkspaceRef {
+            id: i64,
+        }
+        
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct HyprMonitor {
+            active_workspace: HyprWorkspaceRef,
+            #[serde(default)]
+            special_workspace: Option<HyprWorkspaceRef>,
+        }
+        
+        let monitors: Vec<HyprMonitor> = serde_json::from_str(json).unwrap_or_default();
+        monitors
+            .into_iter()
+            .flat_map(|monitor| {
+                let special = monitor.special_workspace.map(|w| w.id).filter(|id| *id != 0);
+                std::iter::once(monitor.active_workspace.id).chain(special)
+            })
+            .collect()
+    }
+
     /// Parse Hyprland client list JSON
-    fn parse_hyprland_clients(&self, json: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ///
+    /// Frame geometry is recorded for windows on `visible_workspaces`.
+    fn parse_hyprland_clients(
+        &self,
+        json: &str,
+        active_address: Option<&str>,
+        visible_workspaces: &[i64],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        #[derive(serde::Deserialize)]
+        struct HyprWorkspaceRef {
+            id: i64,
+        }
+        
         #[derive(serde::Deserialize)]
         struct HyprClient {
             address: String,
             title: String,
             class: String,
+            workspace: HyprWorkspaceRef,
+            #[serde(default)]
+            at: Option<(i32, i32)>,
+            #[serde(default)]
+            size: Option<(i32, i32)>,
             #[serde(default)]
-            #[allow(dead_code)]
-            workspace: i32,
+            hidden: bool,
         }
         
         let clients: Vec<HyprClient> = serde_json::from_str(json)?;
@@ -402,11 +709,19 @@ impl WindowTracker {
             let app_id = client.class.clone();
             *counts.entry(app_id.clone()).or_insert(0) += 1;
             
+            let is_active = active_address == Some(client.address.as_str());
+            let shown = !client.hidden && visible_workspaces.contains(&client.workspace.id);
+            let geometry = match (client.at, client.size) {
+                (Some((x, y)), Some((width, height))) if shown => Some((x, y, width, height)),
+                _ => None,
+            };
+            
             window_list.push(WindowInfo {
                 id: client.address,
                 title: client.title,
                 app_id,
-                is_active: false, // Would need active window query
+                is_active,
+                geometry,
             });
         }
         
@@ -430,9 +745,12 @@ impl WindowTracker {
             }
         });
         
-        // Periodic polling  
+        // Incremental updates from a window event subscription
+        self.start_sway_subscription();
+        
+        // Periodic resync in case events were missed
         let tracker = self.clone();
-        glib::timeout_add_seconds_local(2, move || {
+        glib::timeout_add_seconds_local(SWAY_RESYNC_INTERVAL, move || {
             if !tracker.is_running() {
                 return glib::ControlFlow::Break;
             }
@@ -450,39 +768,186 @@ impl WindowTracker {
 
     /// Poll Sway windows via IPC
     async fn poll_sway_windows(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        use tokio::io::{AsyncReadExt, AsyncWriteExt};
-        use tokio::net::UnixStream;
+        let json = Self::sway_request(SWAY_IPC_GET_TREE, &[]).await?;
+        self.parse_sway_tree(&json)?;
         
+        Ok(())
+    }
+
+    /// Send a message over the Sway IPC socket and return the reply's payload
+    ///
+    /// Like `hyprland_request`, runs on the GLib main loop.
+    pub(crate) async fn sway_request(msg_type: u32, payload: &[u8]) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
         let socket_path = std::env::var("SWAYSOCK")?;
-        let mut stream = UnixStream::connect(&socket_path).await?;
+        let connection = ipc_connect(std::path::Path::new(&socket_path)).await?;
         
         // Sway IPC message format: magic | length | type | payload
-        // Type 4 = get_tree
         let magic = b"i3-ipc";
-        let msg_type: u32 = 4; // GET_TREE
-        let payload: &[u8] = &[];
         
-        stream.write_all(magic).await?;
-        stream.write_all(&(payload.len() as u32).to_ne_bytes()).await?;
-        stream.write_all(&msg_type.to_ne_bytes()).await?;
-        stream.write_all(payload).await?;
+        let mut message = magic.to_vec();
+        message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+        message.extend_from_slice(&msg_type.to_ne_bytes());
+        message.extend_from_slice(payload);
+        ipc_write(&connection, message).await?;
         
         // Read response header
-        let mut header = [0u8; 14]; // 6 magic + 4 len + 4 type
-        stream.read_exact(&mut header).await?;
+        let header = ipc_read_exact(&connection, 14).await?; // 6 magic + 4 len + 4 type
         
         let len = u32::from_ne_bytes([header[6], header[7], header[8], header[9]]) as usize;
         
         // Read response body
-        let mut body = vec![0u8; len];
-        stream.read_exact(&mut body).await?;
+        let body = ipc_read_exact(&connection, len).await?;
         
-        let json = String::from_utf8(body)?;
-        self.parse_sway_tree(&json)?;
+        Ok(String::from_utf8(body)?)
+    }
+
+    /// Subscribe to Sway `window` events on a background thread
+    ///
+    /// The connection is re-established with exponential backoff if Sway
+    /// goes away (e.g. on a compositor restart).
+    fn start_sway_subscription(&self) {
+        let tracker = self.clone();
+        
+        std::thread::spawn(move || {
+            let mut backoff = std::time::Duration::from_secs(1);
+            let max_backoff = std::time::Duration::from_secs(30);
+            
+            while tracker.is_running() {
+                match tracker.run_sway_subscription(&mut backoff) {
+                    Ok(()) => return,
+                    Err(e) => warn!("Sway event subscription lost: {}", e),
+                }
+                
+                std::thread::sleep(backoff);
+                backoff = (backoff * 2).min(max_backoff);
+            }
+        });
+    }
+
+    /// Connect, subscribe and apply window events until the socket fails
+    ///
+    /// Resets `backoff` once subscribed. Returns `Ok` only once the
+    /// tracker has been stopped.
+    fn run_sway_subscription(&self, backoff: &mut std::time::Duration) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        use std::os::unix::net::UnixStream;
+        
+        let socket_path = std::env::var("SWAYSOCK")?;
+        let mut stream = UnixStream::connect(&socket_path)?;
+        
+        Self::sway_ipc_send(&mut stream, SWAY_IPC_SUBSCRIBE, br#"["window"]"#)?;
+        let (_, reply) = Self::sway_ipc_receive(&mut stream)?;
+        if !reply.contains("true") {
+            return Err(format!("Sway rejected subscription: {}", reply).into());
+        }
+        
+        info!("Subscribed to Sway window events");
+        *backoff = std::time::Duration::from_secs(1);
+        
+        while self.is_running() {
+            let (msg_type, payload) = Self::sway_ipc_receive(&mut stream)?;
+            if msg_type != SWAY_IPC_EVENT_WINDOW {
+                continue;
+            }
+            if let Some(event) = Self::parse_sway_window_event(&payload) {
+                self.apply_sway_event(event);
+            }
+        }
         
         Ok(())
     }
 
+    /// Write a Sway IPC message: magic | length | type | payload
+    fn sway_ipc_send(stream: &mut impl std::io::Write, msg_type: u32, payload: &[u8]) -> std::io::Result<()> {
+        stream.write_all(b"i3-ipc")?;
+        stream.write_all(&(payload.len() as u32).to_ne_bytes())?;
+        stream.write_all(&msg_type.to_ne_bytes())?;
+        stream.write_all(payload)?;
+        stream.flush()
+    }
+
+    /// Read a Sway IPC message, returning its type and payload
+    fn sway_ipc_receive(stream: &mut impl std::io::Read) -> Result<(u32, String), Box<dyn std::error::Error + Send + Sync>> {
+        let mut header = [0u8; 14]; // 6 magic + 4 len + 4 type
+        stream.read_exact(&mut header)?;
+        
+        let len = u32::from_ne_bytes([header[6], header[7], header[8], header[9]]) as usize;
+        let msg_type = u32::from_ne_bytes([header[10], header[11], header[12], header[13]]);
+        
+        let mut body = vec![0u8; len];
+        stream.read_exact(&mut body)?;
+        
+        Ok((msg_type, String::from_utf8(body)?))
+    }
+
+    /// Parse the payload of a Sway `window` event
+    fn parse_sway_window_event(json: &str) -> Option<SwayWindowEvent> {
+        #[derive(serde::Deserialize)]
+        struct SwayContainer {
+            id: i64,
+            #[serde(default)]
+            app_id: Option<String>,
+            #[serde(default)]
+            name: Option<String>,
+        }
+        
+        #[derive(serde::Deserialize)]
+        struct SwayEvent {
+            change: String,
+            container: SwayContainer,
+        }
+        
+        let event: SwayEvent = serde_json::from_str(json).ok()?;
+        let id = event.container.id.to_string();
+        let title = event.container.name.unwrap_or_default();
+        
+        match event.change.as_str() {
+            // Windows without an app_id (XWayland) aren't tracked, as in get_tree
+            "new" => event.container.app_id.map(|app_id| SwayWindowEvent::New { id, app_id, title }),
+            "close" => Some(SwayWindowEvent::Close { id }),
+            "focus" => Some(SwayWindowEvent::Focus { id }),
+            "title" => Some(SwayWindowEvent::Title { id, title }),
+            _ => None,
+        }
+    }
+
+    /// Apply a Sway window event to the tracked windows and counts
+    fn apply_sway_event(&self, event: SwayWindowEvent) {
+        let mut windows = self.windows.lock().unwrap();
+        
+        match event {
+            SwayWindowEvent::New { id, app_id, title } => {
+                if windows.iter().all(|w| w.id != id) {
+                    windows.push(WindowInfo { id, title, app_id, is_active: false, geometry: None });
+                }
+            }
+            SwayWindowEvent::Close { id } => {
+                windows.retain(|w| w.id != id);
+            }
+            SwayWindowEvent::Focus { id } => {
+                for window in windows.iter_mut() {
+                    window.is_active = window.id == id;
+                }
+            }
+            SwayWindowEvent::Title { id, title } => {
+                if let Some(window) = windows.iter_mut().find(|w| w.id == id) {
+                    window.title = title;
+                }
+                return;
+            }
+        }
+        
+        *self.app_window_counts.lock().unwrap() = Self::count_windows(&windows);
+    }
+
+    /// Count windows per app_id
+    fn count_windows(windows: &[WindowInfo]) -> HashMap<String, u32> {
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        for window in windows {
+            *counts.entry(window.app_id.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
     /// Parse Sway tree to extract windows
     fn parse_sway_tree(&self, json: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         #[derive(serde::Deserialize)]
@@ -502,6 +967,18 @@ impl WindowTracker {
             #[serde(default)]
             #[serde(rename = "type")]
             node_type: Option<String>,
+            #[serde(default)]
+            visible: bool,
+            #[serde(default)]
+            rect: Option<SwayRect>,
+        }
+        
+        #[derive(serde::Deserialize)]
+        struct SwayRect {
+            x: i32,
+            y: i32,
+            width: i32,
+            height: i32,
         }
         
         fn collect_windows(node: &SwayNode, windows: &mut Vec<WindowInfo>, counts: &mut HashMap<String, u32>) {
@@ -514,6 +991,10 @@ impl WindowTracker {
                         title: node.name.clone().unwrap_or_default(),
                         app_id: app_id.clone(),
                         is_active: node.focused,
+                        // Windows on hidden workspaces or tabs keep their rect
+                        geometry: node.rect.as_ref()
+                            .filter(|_| node.visible)
+                            .map(|rect| (rect.x, rect.y, rect.width, rect.height)),
                     });
                 }
             }
@@ -540,10 +1021,28 @@ impl WindowTracker {
         Ok(())
     }
 
+    /// Set the command -> app_id aliases consulted before fuzzy matching
+    pub fn set_app_id_aliases(&self, aliases: HashMap<String, Vec<String>>) {
+        *self.app_id_aliases.lock().unwrap() = aliases;
+    }
+
     /// Get number of windows for a specific app_id
+    ///
+    /// `app_id` may also be a launch command listed in the alias map.
     pub fn get_window_count(&self, app_id: &str) -> u32 {
         let counts = self.app_window_counts.lock().unwrap();
         
+        // Aliased app_ids first (case-insensitive)
+        if let Some(aliases) = self.app_id_aliases.lock().unwrap().get(app_id) {
+            let total: u32 = counts.iter()
+                .filter(|(key, _)| aliases.iter().any(|alias| alias.eq_ignore_ascii_case(key)))
+                .map(|(_, count)| *count)
+                .sum();
+            if total > 0 {
+                return total;
+            }
+        }
+        
         // Try exact match first
         if let Some(count) = counts.get(app_id) {
             return *count;
@@ -577,6 +1076,28 @@ impl WindowTracker {
             .collect()
     }
 
+    /// Get the currently focused window, if known
+    pub fn get_active_window(&self) -> Option<WindowInfo> {
+        self.windows.lock().unwrap()
+            .iter()
+            .find(|w| w.is_active)
+            .cloned()
+    }
+
+    /// Check whether the focused window belongs to a specific app_id
+    pub fn is_app_focused(&self, app_id: &str) -> bool {
+        let app_id_lower = app_id.to_lowercase();
+        
+        self.get_active_window()
+            .map(|w| {
+                let active = w.app_id.to_lowercase();
+                active == app_id_lower ||
+                active.contains(&app_id_lower) ||
+                app_id_lower.contains(&active)
+            })
+            .unwrap_or(false)
+    }
+
     /// Get all tracked windows
     pub fn get_all_windows(&self) -> Vec<WindowInfo> {
         self.windows.lock().unwrap().clone()
@@ -597,6 +1118,58 @@ impl WindowTracker {
         *self.desktop.lock().unwrap()
     }
 
+    /// Whether window geometry is tracked on this desktop
+    ///
+    /// The KWin script reports frame geometry; Hyprland and Sway include it
+    /// in their window lists.
+    pub fn reports_geometry(&self) -> bool {
+        matches!(
+            self.get_desktop_environment(),
+            DesktopEnvironment::KDE | DesktopEnvironment::Hyprland | DesktopEnvironment::Sway
+        )
+    }
+
+    /// Keep window geometry current for intellihide
+    ///
+    /// Window events on Hyprland and Sway don't carry geometry, so the
+    /// window list is polled every `GEOMETRY_POLL_INTERVAL` until the
+    /// tracker stops. KWin pushes geometry itself. Repeated calls share
+    /// one poll.
+    pub fn watch_geometry(&self) {
+        let desktop = self.get_desktop_environment();
+        if !matches!(desktop, DesktopEnvironment::Hyprland | DesktopEnvironment::Sway) {
+            return;
+        }
+        let mut watching = self.watching_geometry.lock().unwrap();
+        if *watching {
+            return;
+        }
+        *watching = true;
+        drop(watching);
+        
+        let tracker = self.clone();
+        glib::timeout_add_local(GEOMETRY_POLL_INTERVAL, move || {
+            if !tracker.is_running() {
+                *tracker.watching_geometry.lock().unwrap() = false;
+                return glib::ControlFlow::Break;
+            }
+            
+            let tracker_clone = tracker.clone();
+            glib::spawn_future_local(async move {
+                let result = if desktop == DesktopEnvironment::Hyprland {
+                    tracker_clone.poll_hyprland_windows().await
+                } else {
+                    tracker_clone.poll_sway_windows().await
+                };
+                if let Err(e) = result {
+                    debug!("Geometry poll error: {}", e);
+                }
+            });
+            
+            glib::ControlFlow::Continue
+        });
+    }
+
     /// Check if tracker is running
     pub fn is_running(&self) -> bool {
         *self.running.lock().unwrap()
@@ -641,23 +1214,100 @@ impl WindowTracker {
         }
     }
 
-    /// Focus window on KDE via D-Bus
+    /// Focus window on KDE via the KWin scripting interface
     async fn focus_window_kde(window_id: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let connection = zbus::Connection::session().await?;
         
-        // Try to activate window via KWin
-        connection.call_method(
-            Some("org.kde.KWin"),
-            "/KWin",
-            Some("org.kde.KWin"),
-            "activateWindow",
-            &(window_id,),
-        ).await?;
+        // Plasma 6 exposes activeWindow, Plasma 5 activeClient
+        let script = Self::kwin_window_script(
+            window_id,
+            "if (workspace.activeWindow !== undefined) { workspace.activeWindow = w; } else { workspace.activeClient = w; }",
+        );
+        
+        Self::run_kwin_script(&connection, &script, "blazedock_focus").await?;
         
         info!("Focused KDE window: {}", window_id);
         Ok(())
     }
 
+    /// Build a KWin script that runs `action` on the window `w` matching an ID
+    fn kwin_window_script(window_id: &str, action: &str) -> String {
+        // Plasma 6 exposes windowList, Plasma 5 clientList
+        format!(
+            r#"
+            var windows = workspace.windowList ? workspace.windowList() : workspace.clientList();
+            for (var i = 0; i < windows.length; i++) {{
+                var w = windows[i];
+                if (String(w.internalId) === "{id}" || String(w.windowId) === "{id}") {{
+                    {action}
+                    break;
+                }}
+            }}
+            "#,
+            id = window_id.replace('"', ""),
+            action = action
+        )
+    }
+
+    /// Load, run and unload a one-shot KWin script
+    async fn run_kwin_script(
+        connection: &zbus::Connection,
+        source: &str,
+        name: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // loadScript takes a file path, not the script source
+        let path = std::env::temp_dir().join(format!("{}-{}.js", name, std::process::id()));
+        std::fs::write(&path, source)?;
+        
+        let reply = connection.call_method(
+            Some("org.kde.KWin"),
+            "/Scripting",
+            Some("org.kde.kwin.Scripting"),
+            "loadScript",
+            &(path.to_string_lossy().as_ref(), name),
+        ).await;
+        
+        let result = match reply {
+            Ok(message) => {
+                let id: i32 = message.body().deserialize()?;
+                Self::start_kwin_script(connection, id).await
+            }
+            Err(e) => Err(e.into()),
+        };
+        
+        // Unload so the script name can be reused next time
+        let _ = connection.call_method(
+            Some("org.kde.KWin"),
+            "/Scripting",
+            Some("org.kde.kwin.Scripting"),
+            "unloadScript",
+            &(name,),
+        ).await;
+        let _ = std::fs::remove_file(&path);
+        
+        result
+    }
+
+    /// Run a loaded KWin script by its ID
+    async fn start_kwin_script(connection: &zbus::Connection, id: i32) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // Plasma 6 path first, then the Plasma 5 one
+        for object_path in [format!("/Scripting/Script{}", id), format!("/{}", id)] {
+            let reply = connection.call_method(
+                Some("org.kde.KWin"),
+                object_path.as_str(),
+                Some("org.kde.kwin.Script"),
+                "run",
+                &(),
+            ).await;
+            
+            if reply.is_ok() {
+                return Ok(());
+            }
+        }
+        
+        Err(format!("Failed to run KWin script {}", id).into())
+    }
+
     /// Focus window on GNOME via D-Bus
     async fn focus_window_gnome(window_id: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let connection = zbus::Connection::session().await?;
@@ -682,25 +1332,211 @@ impl WindowTracker {
 
     /// Focus window on Hyprland via CLI
     fn focus_window_hyprland(window_id: &str) {
-        match std::process::Command::new("hyprctl")
-            .args(["dispatch", "focuswindow", &format!("address:{}", window_id)])
-            .spawn()
-        {
-            Ok(_) => info!("Focused Hyprland window: {}", window_id),
-            Err(e) => warn!("Failed to focus Hyprland window: {}", e),
-        }
+        Self::run_compositor_command(
+            "hyprctl",
+            vec!["dispatch".to_string(), "focuswindow".to_string(), format!("address:{}", window_id)],
+        );
     }
 
     /// Focus window on Sway via CLI
     fn focus_window_sway(window_id: &str) {
-        match std::process::Command::new("swaymsg")
-            .args(["[con_id=", window_id, "]", "focus"])
-            .spawn()
-        {
-            Ok(_) => info!("Focused Sway window: {}", window_id),
-            Err(e) => warn!("Failed to focus Sway window: {}", e),
+        // Criteria must be a single argument, e.g. `[con_id=42]`
+        Self::run_compositor_command(
+            "swaymsg",
+            vec![format!("[con_id={}]", window_id), "focus".to_string()],
+        );
+    }
+
+    /// Close a specific window by ID
+    pub fn close_window(&self, window_id: &str) {
+        let desktop = self.get_desktop_environment();
+        let win_id = window_id.to_string();
+        
+        match desktop {
+            DesktopEnvironment::KDE => {
+                glib::spawn_future_local(async move {
+                    if let Err(e) = Self::close_window_kde(&win_id).await {
+                        warn!("Failed to close KDE window: {}", e);
+                    }
+                });
+            }
+            DesktopEnvironment::GNOME => {
+                glib::spawn_future_local(async move {
+                    if let Err(e) = Self::close_window_gnome(&win_id).await {
+                        warn!("Failed to close GNOME window: {}", e);
+                    }
+                });
+            }
+            DesktopEnvironment::Hyprland => {
+                Self::run_compositor_command(
+                    "hyprctl",
+                    vec!["dispatch".to_string(), "closewindow".to_string(), format!("address:{}", win_id)],
+                );
+            }
+            DesktopEnvironment::Sway => {
+                Self::run_compositor_command(
+                    "swaymsg",
+                    vec![format!("[con_id={}]", win_id), "kill".to_string()],
+                );
+            }
+            _ => {
+                warn!("Window close not supported for unknown desktop");
+            }
         }
     }
+
+    /// Close window on KDE via the KWin scripting interface
+    async fn close_window_kde(window_id: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let connection = zbus::Connection::session().await?;
+        let script = Self::kwin_window_script(window_id, "w.closeWindow();");
+        
+        Self::run_kwin_script(&connection, &script, "blazedock_close").await?;
+        
+        info!("Closed KDE window: {}", window_id);
+        Ok(())
+    }
+
+    /// Close window on GNOME via D-Bus
+    async fn close_window_gnome(window_id: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let connection = zbus::Connection::session().await?;
+        
+        let script = format!(
+            "global.get_window_actors().find(a => a.meta_window.get_id().toString() === '{}')?.meta_window.delete(global.get_current_time())",
+            window_id
+        );
+        
+        connection.call_method(
+            Some("org.gnome.Shell"),
+            "/org/gnome/Shell",
+            Some("org.gnome.Shell"),
+            "Eval",
+            &(script,),
+        ).await?;
+        
+        info!("Closed GNOME window: {}", window_id);
+        Ok(())
+    }
+
+    /// Minimize `window_ids`, or restore them if the dock minimized them all
+    ///
+    /// Returns whether the windows are now minimized.
+    pub fn toggle_minimize(&self, window_ids: &[String]) -> bool {
+        let minimize = {
+            let mut minimized = self.minimized.lock().unwrap();
+            let minimize = !window_ids.iter().all(|id| minimized.contains(id));
+            for id in window_ids {
+                if minimize {
+                    minimized.insert(id.clone());
+                } else {
+                    minimized.remove(id);
+                }
+            }
+            minimize
+        };
+        
+        for id in window_ids {
+            self.set_minimized(id, minimize);
+        }
+        minimize
+    }
+
+    /// Minimize or restore one window
+    fn set_minimized(&self, window_id: &str, minimized: bool) {
+        let win_id = window_id.to_string();
+        
+        match self.get_desktop_environment() {
+            DesktopEnvironment::KDE => {
+                glib::spawn_future_local(async move {
+                    if let Err(e) = Self::set_minimized_kde(&win_id, minimized).await {
+                        warn!("Failed to minimize KDE window: {}", e);
+                    }
+                });
+            }
+            DesktopEnvironment::GNOME => {
+                glib::spawn_future_local(async move {
+                    if let Err(e) = Self::set_minimized_gnome(&win_id, minimized).await {
+                        warn!("Failed to minimize GNOME window: {}", e);
+                    }
+                });
+            }
+            DesktopEnvironment::Hyprland => {
+                // Hyprland has no minimize; park windows on a special workspace
+                let workspace = if minimized { "special:minimized" } else { "e+0" };
+                Self::run_compositor_command(
+                    "hyprctl",
+                    vec![
+                        "dispatch".to_string(),
+                        "movetoworkspacesilent".to_string(),
+                        format!("{},address:{}", workspace, win_id),
+                    ],
+                );
+            }
+            DesktopEnvironment::Sway => {
+                // The scratchpad is Sway's minimize; restored windows come back tiled
+                let command = if minimized { "move scratchpad" } else { "scratchpad show, floating disable" };
+                Self::run_compositor_command(
+                    "swaymsg",
+                    vec![format!("[con_id={}]", win_id), command.to_string()],
+                );
+            }
+            _ => {
+                warn!("Window minimize not supported for unknown desktop");
+            }
+        }
+    }
+
+    /// Minimize or restore a window on KDE via the KWin scripting interface
+    async fn set_minimized_kde(window_id: &str, minimized: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let connection = zbus::Connection::session().await?;
+        let script = Self::kwin_window_script(window_id, &format!("w.minimized = {};", minimized));
+        
+        Self::run_kwin_script(&connection, &script, "blazedock_minimize").await?;
+        
+        debug!("Set KDE window {} minimized={}", window_id, minimized);
+        Ok(())
+    }
+
+    /// Minimize or restore a window on GNOME via D-Bus
+    async fn set_minimized_gnome(window_id: &str, minimized: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let connection = zbus::Connection::session().await?;
+        
+        let call = if minimized { "minimize()" } else { "unminimize()" };
+        let script = format!(
+            "global.get_window_actors().find(a => a.meta_window.get_id().toString() === '{}')?.meta_window.{}",
+            window_id, call
+        );
+        
+        connection.call_method(
+            Some("org.gnome.Shell"),
+            "/org/gnome/Shell",
+            Some("org.gnome.Shell"),
+            "Eval",
+            &(script,),
+        ).await?;
+        
+        debug!("Set GNOME window {} minimized={}", window_id, minimized);
+        Ok(())
+    }
+
+    /// Run a compositor CLI command on a worker thread, logging failures
+    fn run_compositor_command(program: &'static str, args: Vec<String>) {
+        std::thread::spawn(move || {
+            match std::process::Command::new(program).args(&args).output() {
+                Ok(output) if output.status.success() => {
+                    debug!("{} {} succeeded", program, args.join(" "));
+                }
+                Ok(output) => {
+                    warn!(
+                        "{} {} failed: {}",
+                        program,
+                        args.join(" "),
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    );
+                }
+                Err(e) => warn!("Failed to run {}: {}", program, e),
+            }
+        });
+    }
 }
 
 impl Default for WindowTracker {
@@ -709,6 +1545,33 @@ impl Default for WindowTracker {
     }
 }
 
+/// Connect to a compositor IPC socket at `path` on the GLib main loop
+async fn ipc_connect(path: &std::path::Path) -> Result<gio::SocketConnection, glib::Error> {
+    let address = gio::UnixSocketAddress::new(path);
+    gio::SocketClient::new().connect_future(&address).await
+}
+
+/// Write all of `data` to an IPC connection
+async fn ipc_write(connection: &gio::SocketConnection, data: Vec<u8>) -> Result<(), glib::Error> {
+    connection.output_stream()
+        .write_all_future(data, glib::Priority::DEFAULT)
+        .await
+        .map(|_| ())
+        .map_err(|(_, e)| e)
+}
+
+/// Read exactly `len` bytes from an IPC connection
+async fn ipc_read_exact(connection: &gio::SocketConnection, len: usize) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    let (buffer, read, _) = connection.input_stream()
+        .read_all_future(vec![0u8; len], glib::Priority::DEFAULT)
+        .await
+        .map_err(|(_, e)| e)?;
+    if read < len {
+        return Err(format!("IPC reply ended after {} of {} bytes", read, len).into());
+    }
+    Ok(buffer)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -743,4 +1606,204 @@ mod tests {
         assert_eq!(tracker.get_window_count("firefox"), 2);
         assert_eq!(tracker.get_window_count("FIREFOX"), 2);
     }
+
+    #[test]
+    fn test_app_id_aliases() {
+        let tracker = WindowTracker::new();
+        tracker.set_window_count("org.gnome.Terminal", 2);
+        
+        // Fuzzy matching can't connect the command to the app_id
+        assert_eq!(tracker.get_window_count("gnome-terminal"), 0);
+        
+        let mut aliases = HashMap::new();
+        aliases.insert("gnome-terminal".to_string(), vec!["org.gnome.Terminal".to_string()]);
+        tracker.set_app_id_aliases(aliases);
+        
+        assert_eq!(tracker.get_window_count("gnome-terminal"), 2);
+        // Unaliased lookups still fall back to fuzzy matching
+        assert_eq!(tracker.get_window_count("terminal"), 2);
+    }
+
+    #[test]
+    fn test_hyprland_active_window() {
+        let tracker = WindowTracker::new();
+        let clients = r#"[
+            {"address": "0x55a1", "title": "Mozilla Firefox", "class": "firefox", "workspace": {"id": 1, "name": "1"}},
+            {"address": "0x55b2", "title": "Terminal", "class": "kitty", "workspace": {"id": 1, "name": "1"}},
+            {"address": "0x55c3", "title": "Notes", "class": "kitty", "workspace": {"id": 2, "name": "2"}}
+        ]"#;
+        let active = WindowTracker::parse_hyprland_active_window(
+            r#"{"address": "0x55b2", "class": "kitty", "title": "Terminal"}"#,
+        );
+        assert_eq!(active.as_deref(), Some("0x55b2"));
+        assert_eq!(WindowTracker::parse_hyprland_active_window("{}"), None);
+
+        tracker.parse_hyprland_clients(clients, active.as_deref(), &[1]).unwrap();
+
+        let focused: Vec<_> = tracker.get_all_windows().into_iter().filter(|w| w.is_active).collect();
+        assert_eq!(focused.len(), 1);
+        assert_eq!(tracker.get_active_window().unwrap().id, "0x55b2");
+        assert!(tracker.is_app_focused("kitty"));
+        assert!(!tracker.is_app_focused("firefox"));
+    }
+
+    #[test]
+    fn test_hyprland_geometry() {
+        let tracker = WindowTracker::new();
+        let monitors = r#"[
+            {"id": 0, "name": "DP-1", "activeWorkspace": {"id": 1, "name": "1"}, "specialWorkspace": {"id": 0, "name": ""}},
+            {"id": 1, "name": "HDMI-A-1", "activeWorkspace": {"id": 3, "name": "3"}, "specialWorkspace": {"id": -98, "name": "special:scratch"}}
+        ]"#;
+        let visible = WindowTracker::parse_hyprland_visible_workspaces(monitors);
+        assert_eq!(visible, vec![1, 3, -98]);
+        
+        let clients = r#"[
+            {"address": "0x55a1", "title": "Firefox", "class": "firefox", "workspace": {"id": 1, "name": "1"},
+             "at": [0, 0], "size": [1920, 1080], "hidden": false},
+            {"address": "0x55b2", "title": "Notes", "class": "kitty", "workspace": {"id": 2, "name": "2"},
+             "at": [10, 10], "size": [800, 600], "hidden": false},
+            {"address": "0x55c3", "title": "Scratch", "class": "kitty", "workspace": {"id": -98, "name": "special:scratch"},
+             "at": [1940, 40], "size": [600, 400], "hidden": false}
+        ]"#;
+        tracker.parse_hyprland_clients(clients, None, &visible).unwrap();
+        
+        let geometry: Vec<_> = tracker.get_all_windows().into_iter().map(|w| w.geometry).collect();
+        assert_eq!(geometry, vec![Some((0, 0, 1920, 1080)), None, Some((1940, 40, 600, 400))]);
+    }
+
+    #[test]
+    fn test_hyprland_event_parsing() {
+        assert_eq!(
+            WindowTracker::parse_hyprland_event("openwindow>>55a1,1,kitty,vim: notes, draft"),
+            Some(HyprlandEvent::OpenWindow {
+                address: "0x55a1".to_string(),
+                workspace: "1".to_string(),
+                class: "kitty".to_string(),
+                title: "vim: notes, draft".to_string(),
+            })
+        );
+        assert_eq!(
+            WindowTracker::parse_hyprland_event("closewindow>>55a1"),
+            Some(HyprlandEvent::CloseWindow { address: "0x55a1".to_string() })
+        );
+        assert_eq!(
+            WindowTracker::parse_hyprland_event("activewindowv2>>55b2"),
+            Some(HyprlandEvent::ActiveWindow { address: Some("0x55b2".to_string()) })
+        );
+        assert_eq!(
+            WindowTracker::parse_hyprland_event("activewindowv2>>,"),
+            Some(HyprlandEvent::ActiveWindow { address: None })
+        );
+        assert_eq!(
+            WindowTracker::parse_hyprland_event("movewindow>>55a1,2"),
+            Some(HyprlandEvent::MoveWindow { address: "0x55a1".to_string(), workspace: "2".to_string() })
+        );
+        assert_eq!(WindowTracker::parse_hyprland_event("workspace>>2"), None);
+        assert_eq!(WindowTracker::parse_hyprland_event("garbage"), None);
+    }
+
+    #[test]
+    fn test_hyprland_events_update_counts() {
+        let tracker = WindowTracker::new();
+        let lines = [
+            "openwindow>>55a1,1,kitty,Terminal",
+            "openwindow>>55b2,1,kitty,Notes",
+            "openwindow>>55c3,2,firefox,Mozilla Firefox",
+            "activewindowv2>>55b2",
+            "closewindow>>55a1",
+        ];
+        
+        for line in lines {
+            tracker.apply_hyprland_event(WindowTracker::parse_hyprland_event(line).unwrap());
+        }
+        
+        assert_eq!(tracker.get_window_count("kitty"), 1);
+        assert_eq!(tracker.get_window_count("firefox"), 1);
+        assert_eq!(tracker.get_active_window().unwrap().id, "0x55b2");
+    }
+
+    #[test]
+    fn test_kde_report_counts() {
+        let tracker = WindowTracker::new();
+        let report = r#"[
+            {"id": "{1111}", "app_id": "org.kde.konsole", "title": "~ : bash", "active": false},
+            {"id": "{2222}", "app_id": "org.kde.konsole", "title": "~/src : vim", "active": true,
+             "x": 100, "y": 50.5, "width": 800, "height": 600},
+            {"id": "{3333}", "app_id": "firefox", "title": "Mozilla Firefox", "active": false}
+        ]"#;
+        
+        let windows = WindowTracker::parse_kde_windows(report).unwrap();
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[0].geometry, None);
+        assert_eq!(windows[1].geometry, Some((100, 51, 800, 600)));
+        
+        tracker.apply_kde_report(report);
+        assert_eq!(tracker.get_window_count("org.kde.konsole"), 2);
+        assert_eq!(tracker.get_window_count("firefox"), 1);
+        assert_eq!(tracker.get_active_window().unwrap().id, "{2222}");
+        
+        // Malformed reports leave the previous state untouched
+        tracker.apply_kde_report("not json");
+        assert_eq!(tracker.get_window_count("org.kde.konsole"), 2);
+    }
+
+    #[test]
+    fn test_sway_window_events() {
+        let tracker = WindowTracker::new();
+        assert_eq!(tracker.get_window_count("foot"), 0);
+        
+        let new_event = r#"{
+            "change": "new",
+            "container": {"id": 42, "type": "con", "app_id": "foot", "name": "foot", "focused": false}
+        }"#;
+        let event = WindowTracker::parse_sway_window_event(new_event).unwrap();
+        assert_eq!(event, SwayWindowEvent::New {
+            id: "42".to_string(),
+            app_id: "foot".to_string(),
+            title: "foot".to_string(),
+        });
+        
+        tracker.apply_sway_event(event);
+        assert_eq!(tracker.get_window_count("foot"), 1);
+        
+        let focus_event = r#"{"change": "focus", "container": {"id": 42, "app_id": "foot"}}"#;
+        tracker.apply_sway_event(WindowTracker::parse_sway_window_event(focus_event).unwrap());
+        assert!(tracker.is_app_focused("foot"));
+        
+        let close_event = r#"{"change": "close", "container": {"id": 42, "app_id": "foot"}}"#;
+        tracker.apply_sway_event(WindowTracker::parse_sway_window_event(close_event).unwrap());
+        assert_eq!(tracker.get_window_count("foot"), 0);
+    }
+
+    #[test]
+    fn test_sway_active_window() {
+        let tracker = WindowTracker::new();
+        let tree = r#"{
+            "id": 1, "type": "root", "focused": false,
+            "nodes": [{
+                "id": 2, "type": "workspace", "focused": false,
+                "nodes": [
+                    {"id": 10, "type": "con", "app_id": "firefox", "name": "Firefox", "focused": false,
+                     "visible": false, "rect": {"x": 0, "y": 0, "width": 1920, "height": 1080}},
+                    {"id": 11, "type": "con", "app_id": "foot", "name": "foot", "focused": true,
+                     "visible": true, "rect": {"x": 0, "y": 0, "width": 1920, "height": 1080}}
+                ],
+                "floating_nodes": [
+                    {"id": 12, "type": "floating_con", "app_id": "pavucontrol", "name": "Volume", "focused": false}
+                ]
+            }]
+        }"#;
+
+        tracker.parse_sway_tree(tree).unwrap();
+
+        let focused: Vec<_> = tracker.get_all_windows().into_iter().filter(|w| w.is_active).collect();
+        assert_eq!(focused.len(), 1);
+        assert_eq!(tracker.get_active_window().unwrap().app_id, "foot");
+        assert!(tracker.is_app_focused("foot"));
+        
+        // Only the visible window reports where it is
+        let geometry: Vec<_> = tracker.get_all_windows().into_iter().map(|w| (w.app_id, w.geometry)).collect();
+        assert!(geometry.contains(&("firefox".to_string(), None)));
+        assert!(geometry.contains(&("foot".to_string(), Some((0, 0, 1920, 1080)))));
+    }
 }
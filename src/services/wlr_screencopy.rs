@@ -0,0 +1,329 @@
+//! Native wlr-screencopy capture
+//!
+//! Copies a region of an output into shared memory with the
+//! `wlr-screencopy-unstable-v1` protocol, so live previews don't need a
+//! grim process and a temporary PNG per thumbnail.
+
+use gtk::gdk_pixbuf::{Colorspace, Pixbuf};
+use gtk::glib;
+use log::debug;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::os::fd::AsFd;
+use std::os::unix::fs::FileExt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use wayland_client::globals::{registry_queue_init, GlobalListContents};
+use wayland_client::protocol::{wl_buffer, wl_output, wl_registry, wl_shm, wl_shm_pool};
+use wayland_client::{delegate_noop, Connection, Dispatch, Proxy, QueueHandle, WEnum};
+use wayland_protocols_wlr::screencopy::v1::client::{
+    zwlr_screencopy_frame_v1::{self, ZwlrScreencopyFrameV1},
+    zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1,
+};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Screencopy manager global advertised by wlroots compositors
+const MANAGER_INTERFACE: &str = "zwlr_screencopy_manager_v1";
+
+/// Shared-memory buffer layout requested by the compositor
+#[derive(Debug, Clone, Copy)]
+struct BufferInfo {
+    format: wl_shm::Format,
+    width: u32,
+    height: u32,
+    stride: u32,
+}
+
+/// A captured region as tightly packed RGBA pixels
+///
+/// Unlike a `Pixbuf`, this can be sent back from a worker thread.
+pub struct Frame {
+    pub rgba: Vec<u8>,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Frame {
+    /// Wrap the pixels in a `Pixbuf` without copying them
+    pub fn into_pixbuf(self) -> Pixbuf {
+        Pixbuf::from_bytes(
+            &glib::Bytes::from_owned(self.rgba),
+            Colorspace::Rgb,
+            true,
+            8,
+            self.width,
+            self.height,
+            self.width * 4,
+        )
+    }
+}
+
+/// Protocol state collected while capturing one frame
+#[derive(Default)]
+struct CaptureState {
+    /// Connector names (e.g. `DP-1`) by output global name
+    output_names: HashMap<u32, String>,
+    buffer: Option<BufferInfo>,
+    buffer_done: bool,
+    y_invert: bool,
+    ready: bool,
+    failed: bool,
+}
+
+/// Whether the compositor offers the screencopy protocol
+pub fn is_available() -> bool {
+    let Ok(connection) = Connection::connect_to_env() else {
+        return false;
+    };
+    let Ok((globals, _queue)) = registry_queue_init::<CaptureState>(&connection) else {
+        return false;
+    };
+
+    globals.contents().with_list(|list| {
+        list.iter().any(|global| global.interface == MANAGER_INTERFACE)
+    })
+}
+
+/// Capture `region` `(x, y, width, height)` of the output named `connector`
+///
+/// The region is in the output's logical coordinates.
+pub fn capture_region(connector: &str, region: (i32, i32, i32, i32)) -> Result<Frame> {
+    let connection = Connection::connect_to_env()?;
+    let (globals, mut queue) = registry_queue_init::<CaptureState>(&connection)?;
+    let qh = queue.handle();
+
+    let manager: ZwlrScreencopyManagerV1 = globals.bind(&qh, 1..=3, ())?;
+    let shm: wl_shm::WlShm = globals.bind(&qh, 1..=1, ())?;
+
+    // wl_output v4 reports the connector name
+    let outputs: Vec<(u32, wl_output::WlOutput)> = globals.contents().with_list(|list| {
+        list.iter()
+            .filter(|global| global.interface == "wl_output")
+            .map(|global| {
+                let output = globals.registry().bind::<wl_output::WlOutput, _, _>(
+                    global.name,
+                    global.version.min(4),
+                    &qh,
+                    global.name,
+                );
+                (global.name, output)
+            })
+            .collect()
+    });
+
+    let mut state = CaptureState::default();
+    queue.roundtrip(&mut state)?;
+
+    let output = match outputs.iter().find(|(name, _)| state.output_names.get(name).map(String::as_str) == Some(connector)) {
+        Some((_, output)) => output,
+        // Older compositors don't name outputs; with one output there is no ambiguity
+        None if outputs.len() == 1 => &outputs[0].1,
+        None => return Err(format!("No Wayland output named {}", connector).into()),
+    };
+
+    let (x, y, width, height) = region;
+    let frame = manager.capture_output_region(0, output, x, y, width, height, &qh, ());
+
+    // Version 3 announces every buffer type, then buffer_done
+    while !state.failed && (state.buffer.is_none() || (frame.version() >= 3 && !state.buffer_done)) {
+        queue.blocking_dispatch(&mut state)?;
+    }
+    let info = match state.buffer {
+        Some(info) if !state.failed => info,
+        _ => return Err("Compositor refused the capture".into()),
+    };
+
+    let size = (info.stride * info.height) as usize;
+    let file = shm_file(size as u64)?;
+    let pool = shm.create_pool(file.as_fd(), size as i32, &qh, ());
+    let buffer = pool.create_buffer(
+        0,
+        info.width as i32,
+        info.height as i32,
+        info.stride as i32,
+        info.format,
+        &qh,
+        (),
+    );
+
+    frame.copy(&buffer);
+    while !state.ready && !state.failed {
+        queue.blocking_dispatch(&mut state)?;
+    }
+
+    frame.destroy();
+    buffer.destroy();
+    pool.destroy();
+
+    if state.failed {
+        return Err("Screencopy frame failed".into());
+    }
+
+    let mut data = vec![0u8; size];
+    file.read_exact_at(&mut data, 0)?;
+
+    let rgba = to_rgba(&data, info, state.y_invert)
+        .ok_or_else(|| format!("Unsupported screencopy format {:?}", info.format))?;
+    debug!("Captured {}x{} region of {}", info.width, info.height, connector);
+
+    Ok(Frame {
+        rgba,
+        width: info.width as i32,
+        height: info.height as i32,
+    })
+}
+
+/// Create an unlinked file of `size` bytes to share with the compositor
+///
+/// Lives in `XDG_RUNTIME_DIR`, which is memory-backed on systemd systems.
+/// Each call gets its own name, so concurrent captures don't share a file.
+fn shm_file(size: u64) -> Result<File> {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+    let dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+    let path = dir.join(format!(
+        "blazedock-screencopy-{}-{}",
+        std::process::id(),
+        NEXT_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    std::fs::remove_file(&path)?;
+    file.set_len(size)?;
+    Ok(file)
+}
+
+/// Convert a captured shm buffer to tightly packed RGBA rows
+///
+/// wl_shm formats are little-endian, so `Argb8888` is stored as B, G, R, A.
+/// Returns `None` for formats other than the 32-bit RGB ones.
+fn to_rgba(data: &[u8], info: BufferInfo, y_invert: bool) -> Option<Vec<u8>> {
+    let (swap_red_blue, opaque) = match info.format {
+        wl_shm::Format::Argb8888 => (true, false),
+        wl_shm::Format::Xrgb8888 => (true, true),
+        wl_shm::Format::Abgr8888 => (false, false),
+        wl_shm::Format::Xbgr8888 => (false, true),
+        _ => return None,
+    };
+
+    let width = info.width as usize;
+    let height = info.height as usize;
+    let stride = info.stride as usize;
+    let mut rgba = Vec::with_capacity(width * height * 4);
+
+    for row in 0..height {
+        let source_row = if y_invert { height - 1 - row } else { row };
+        let start = source_row * stride;
+        let pixels = data.get(start..start + width * 4)?;
+
+        for pixel in pixels.chunks_exact(4) {
+            let (r, b) = if swap_red_blue { (pixel[2], pixel[0]) } else { (pixel[0], pixel[2]) };
+            rgba.extend_from_slice(&[r, pixel[1], b, if opaque { 255 } else { pixel[3] }]);
+        }
+    }
+
+    Some(rgba)
+}
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for CaptureState {
+    fn event(
+        _state: &mut Self,
+        _registry: &wl_registry::WlRegistry,
+        _event: wl_registry::Event,
+        _data: &GlobalListContents,
+        _connection: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<wl_output::WlOutput, u32> for CaptureState {
+    fn event(
+        state: &mut Self,
+        _output: &wl_output::WlOutput,
+        event: wl_output::Event,
+        global_name: &u32,
+        _connection: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let wl_output::Event::Name { name } = event {
+            state.output_names.insert(*global_name, name);
+        }
+    }
+}
+
+impl Dispatch<ZwlrScreencopyFrameV1, ()> for CaptureState {
+    fn event(
+        state: &mut Self,
+        _frame: &ZwlrScreencopyFrameV1,
+        event: zwlr_screencopy_frame_v1::Event,
+        _data: &(),
+        _connection: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_screencopy_frame_v1::Event::Buffer { format: WEnum::Value(format), width, height, stride } => {
+                state.buffer = Some(BufferInfo { format, width, height, stride });
+            }
+            zwlr_screencopy_frame_v1::Event::Flags { flags: WEnum::Value(flags) } => {
+                state.y_invert = flags.contains(zwlr_screencopy_frame_v1::Flags::YInvert);
+            }
+            zwlr_screencopy_frame_v1::Event::BufferDone => state.buffer_done = true,
+            zwlr_screencopy_frame_v1::Event::Ready { .. } => state.ready = true,
+            zwlr_screencopy_frame_v1::Event::Failed => state.failed = true,
+            _ => {}
+        }
+    }
+}
+
+delegate_noop!(CaptureState: ZwlrScreencopyManagerV1);
+delegate_noop!(CaptureState: wl_shm_pool::WlShmPool);
+delegate_noop!(CaptureState: ignore wl_shm::WlShm);
+delegate_noop!(CaptureState: ignore wl_buffer::WlBuffer);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(format: wl_shm::Format, stride: u32) -> BufferInfo {
+        BufferInfo { format, width: 2, height: 2, stride }
+    }
+
+    #[test]
+    fn test_to_rgba_swaps_channels_and_skips_padding() {
+        // Two rows of two BGRA pixels, each row padded to 12 bytes
+        let data = [
+            1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0,
+            9, 10, 11, 12, 13, 14, 15, 16, 0, 0, 0, 0,
+        ];
+
+        let rgba = to_rgba(&data, info(wl_shm::Format::Argb8888, 12), false).unwrap();
+        assert_eq!(rgba, [3, 2, 1, 4, 7, 6, 5, 8, 11, 10, 9, 12, 15, 14, 13, 16]);
+
+        // X formats are opaque and y_invert flips the rows
+        let rgba = to_rgba(&data, info(wl_shm::Format::Xbgr8888, 12), true).unwrap();
+        assert_eq!(rgba, [9, 10, 11, 255, 13, 14, 15, 255, 1, 2, 3, 255, 5, 6, 7, 255]);
+
+        assert!(to_rgba(&data, info(wl_shm::Format::Rgb565, 12), false).is_none());
+        // Truncated buffers are rejected rather than read out of bounds
+        assert!(to_rgba(&data[..16], info(wl_shm::Format::Argb8888, 12), false).is_none());
+    }
+
+    #[test]
+    fn test_shm_files_are_separate() {
+        let first = shm_file(8).unwrap();
+        let second = shm_file(8).unwrap();
+
+        first.write_all_at(&[1; 8], 0).unwrap();
+        let mut data = [0u8; 8];
+        second.read_exact_at(&mut data, 0).unwrap();
+        assert_eq!(data, [0; 8]);
+    }
+}
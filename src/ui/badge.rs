@@ -58,6 +58,11 @@ impl Badge {
         &self.container
     }
 
+    /// Current badge type
+    pub fn badge_type(&self) -> &BadgeType {
+        &self.badge_type
+    }
+
     /// Update badge type
     pub fn set_type(&mut self, badge_type: BadgeType) {
         self.badge_type = badge_type;
@@ -5,34 +5,119 @@
 use gtk::prelude::*;
 use gtk::{Button, Image, GestureClick};
 use gtk::gdk::Rectangle;
+use gtk::{gio, glib};
 use log::{debug, error, info};
 
-use crate::config::{PinnedApp, Settings};
+use crate::config::{DockPosition, PinnedApp, Settings};
+use crate::services::ThemeColors;
 use crate::utils::launcher;
-use crate::ui::{RunningIndicator, RunningState, Badge, BadgeType, BadgePosition, WindowPreview};
+use crate::ui::{popover_menu, RunningIndicator, RunningState, Badge, BadgeType, BadgePosition, WindowPreview, PreviewSources, ProgressRing};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::time::Duration;
+
+/// CSS class that lifts the item for one bounce
+const BOUNCE_CLASS: &str = "dock-item-bounce";
+
+/// Number of bounces for an attention request
+const BOUNCE_COUNT: u32 = 3;
+
+/// Time the item spends up (or down) during a bounce
+const BOUNCE_STEP: Duration = Duration::from_millis(180);
+
+/// Icon shown when an app's icon can't be found
+const FALLBACK_ICON: &str = "application-x-executable";
+
+/// Where a dock item's icon comes from
+#[derive(Debug, Clone, PartialEq)]
+enum IconSource {
+    /// An image file (Flatpak exports, AppImages)
+    File(PathBuf),
+    /// A name looked up in the icon theme
+    Name(String),
+}
+
+/// Actions a dock item asks the dock window to perform
+#[derive(Debug, Clone)]
+pub enum DockItemAction {
+    /// Unpin the app at `index`; `command` guards against a stale index
+    Unpin { index: usize, command: String },
+    /// Move the pinned app at `from` to position `to`
+    Move { from: usize, to: usize },
+    /// Reload the dock from the saved configuration
+    Reload,
+    /// Pin a running app, keeping its item in place
+    KeepInDock(PinnedApp),
+    /// A running app was clicked; clear its badge optimistically
+    ClearBadge { command: String, desktop_id: Option<String> },
+}
+
+/// What a dock item's progress ring shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProgressDisplay {
+    /// No operation in progress
+    Hidden,
+    /// An operation without a known completion (spinning ring)
+    Indeterminate,
+    /// Completion from 0.0 to 1.0
+    Value(f64),
+}
+
+/// What a click on a dock item does
+#[derive(Debug, Clone, PartialEq)]
+enum ClickAction {
+    /// Raise the app's existing window with this ID
+    Focus(String),
+    /// Start the app
+    Launch,
+}
+
+/// What a middle click on a dock item does
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MiddleClickAction {
+    /// Minimize or restore all of the app's windows
+    ToggleMinimize,
+    /// Start a new instance
+    Launch,
+    /// Running, but none of its windows are known
+    Nothing,
+}
+
+/// Shared slot holding the window's handler for dock item actions
+pub type DockItemActions = Rc<RefCell<Option<Box<dyn Fn(DockItemAction)>>>>;
 
 /// A single dock item (application launcher)
 pub struct DockItem {
     button: Button,
+    image: Image,
     indicator: Rc<RefCell<RunningIndicator>>,
     badge: Badge,
+    progress_ring: ProgressRing,
     preview: Rc<RefCell<WindowPreview>>,
     css_provider: gtk::CssProvider,
     app_name: String,
     app_command: String,
     app_icon: String,
     desktop_file: Option<String>,
+    app_env: HashMap<String, String>,
+    app_label: Option<String>,
     is_pinned: bool,
+    pinned_index: Option<usize>,
+    actions: DockItemActions,
+    context_gesture: GestureClick,
+    is_bouncing: Rc<Cell<bool>>,
+    scale: Cell<f64>,
 }
 
 impl DockItem {
-    /// Create a new dock item for a pinned application
-    pub fn new(app: &PinnedApp, settings: &Settings) -> Self {
-        let indicator = Rc::new(RefCell::new(RunningIndicator::new()));
+    /// Create a new dock item for a pinned application at `index`
+    pub fn new(app: &PinnedApp, settings: &Settings, index: usize, actions: &DockItemActions, sources: &PreviewSources) -> Self {
+        let indicator = Rc::new(RefCell::new(RunningIndicator::with_style(settings.indicator_style, settings.position)));
         let badge = Badge::new(BadgeType::Count(0), BadgePosition::TopRight);
-        let button = Self::create_button(app, settings, &indicator.borrow(), &badge);
+        let progress_ring = Self::create_progress_ring(settings, sources);
+        let (button, image) = Self::create_button(app, settings, &indicator.borrow(), &badge, &progress_ring);
         let css_provider = gtk::CssProvider::new();
         button.style_context().add_provider(&css_provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
         
@@ -41,60 +126,95 @@ impl DockItem {
         let app_command = app.command.clone();
         let app_icon = app.icon.clone();
         let desktop_file = app.desktop_file.clone();
+        let app_env = app.env.clone();
+        let app_label = app.label.clone();
         
-        Self::setup_click_handler(&button, app);
-        Self::setup_hover_effects(&button, settings, Rc::clone(&preview), &app_name, Rc::clone(&indicator));
-        Self::setup_context_menu(&button, app, true);
+        Self::setup_click_handler(&button, app, Rc::clone(&indicator), settings, actions, sources);
+        Self::setup_middle_click_handler(&button, app, Rc::clone(&indicator), settings, sources);
+        Self::setup_scroll_handler(&button, app, Rc::clone(&indicator), sources);
+        Self::setup_hover_effects(&button, Rc::clone(&preview), app, sources, Rc::clone(&indicator));
+        let context_gesture = Self::setup_context_menu(&button, app, Some(index), actions);
         
         Self { 
             button, 
+            image,
             indicator, 
             badge, 
+            progress_ring,
             preview, 
             css_provider,
             app_name,
             app_command,
             app_icon,
             desktop_file,
+            app_env,
+            app_label,
             is_pinned: true,
+            pinned_index: Some(index),
+            actions: Rc::clone(actions),
+            context_gesture,
+            is_bouncing: Rc::new(Cell::new(false)),
+            scale: Cell::new(1.0),
         }
     }
 
     /// Create a new dock item for a running (non-pinned) application
-    pub fn new_running(name: &str, icon: &str, command: &str, desktop_file: Option<&str>, settings: &Settings) -> Self {
+    pub fn new_running(
+        name: &str,
+        icon: &str,
+        command: &str,
+        desktop_file: Option<&str>,
+        settings: &Settings,
+        actions: &DockItemActions,
+        sources: &PreviewSources,
+    ) -> Self {
         let app = PinnedApp {
             name: name.to_string(),
             icon: icon.to_string(),
             command: command.to_string(),
             desktop_file: desktop_file.map(|s| s.to_string()),
+            env: HashMap::new(),
+            label: None,
         };
         
-        let indicator = Rc::new(RefCell::new(RunningIndicator::new()));
+        let indicator = Rc::new(RefCell::new(RunningIndicator::with_style(settings.indicator_style, settings.position)));
         // Set initial running state
         indicator.borrow_mut().set_state(RunningState::Running { window_count: 1 });
         
         let badge = Badge::new(BadgeType::Count(0), BadgePosition::TopRight);
-        let button = Self::create_button(&app, settings, &indicator.borrow(), &badge);
+        let progress_ring = Self::create_progress_ring(settings, sources);
+        let (button, image) = Self::create_button(&app, settings, &indicator.borrow(), &badge, &progress_ring);
         let css_provider = gtk::CssProvider::new();
         button.style_context().add_provider(&css_provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
         
         let preview = Rc::new(RefCell::new(WindowPreview::new(&button)));
         
-        Self::setup_click_handler(&button, &app);
-        Self::setup_hover_effects(&button, settings, Rc::clone(&preview), name, Rc::clone(&indicator));
-        Self::setup_context_menu(&button, &app, false); // Not pinned
+        Self::setup_click_handler(&button, &app, Rc::clone(&indicator), settings, actions, sources);
+        Self::setup_middle_click_handler(&button, &app, Rc::clone(&indicator), settings, sources);
+        Self::setup_scroll_handler(&button, &app, Rc::clone(&indicator), sources);
+        Self::setup_hover_effects(&button, Rc::clone(&preview), &app, sources, Rc::clone(&indicator));
+        let context_gesture = Self::setup_context_menu(&button, &app, None, actions); // Not pinned
         
         Self { 
             button, 
+            image,
             indicator, 
             badge, 
+            progress_ring,
             preview, 
             css_provider,
             app_name: name.to_string(),
             app_command: command.to_string(),
             app_icon: icon.to_string(),
             desktop_file: desktop_file.map(|s| s.to_string()),
+            app_env: HashMap::new(),
+            app_label: None,
             is_pinned: false,
+            pinned_index: None,
+            actions: Rc::clone(actions),
+            context_gesture,
+            is_bouncing: Rc::new(Cell::new(false)),
+            scale: Cell::new(1.0),
         }
     }
 
@@ -103,6 +223,38 @@ impl DockItem {
         self.is_pinned
     }
 
+    /// Position in the pinned list, or `None` for running apps
+    pub fn pinned_index(&self) -> Option<usize> {
+        self.pinned_index
+    }
+
+    /// Mark a running item as pinned at `index`, rebuilding its context menu
+    pub fn mark_pinned(&mut self, index: usize, actions: &DockItemActions) {
+        self.button.remove_controller(&self.context_gesture);
+        self.context_gesture = Self::setup_context_menu(&self.button, &self.to_pinned_app(), Some(index), actions);
+        self.is_pinned = true;
+        self.pinned_index = Some(index);
+    }
+
+    /// Pop up this item's context menu, pointing at the whole item
+    ///
+    /// Used for keyboard access where there is no click location.
+    pub fn show_context_menu(&self) {
+        debug!("Context menu requested via keyboard for: {}", self.app_name);
+        Self::popup_context_menu(
+            self.button.upcast_ref(),
+            &self.to_pinned_app(),
+            self.pinned_index,
+            &self.actions,
+            None,
+        );
+    }
+
+    /// Get the desktop file ID (file name without `.desktop`), if known
+    pub fn desktop_id(&self) -> Option<String> {
+        self.desktop_file.as_deref().and_then(desktop_id_from_path)
+    }
+
     /// Get app info for pinning
     pub fn to_pinned_app(&self) -> PinnedApp {
         PinnedApp {
@@ -110,6 +262,8 @@ impl DockItem {
             icon: self.app_icon.clone(),
             command: self.app_command.clone(),
             desktop_file: self.desktop_file.clone(),
+            env: self.app_env.clone(),
+            label: self.app_label.clone(),
         }
     }
 
@@ -118,6 +272,17 @@ impl DockItem {
         &self.button
     }
 
+    /// Get the current icon name
+    pub fn icon_name(&self) -> &str {
+        &self.app_icon
+    }
+
+    /// Swap the icon in place, leaving the badge and scale provider untouched
+    pub fn set_icon(&mut self, icon_name: &str) {
+        set_image_icon(&self.image, icon_name);
+        self.app_icon = icon_name.to_string();
+    }
+
     /// Update running state
     pub fn set_running_state(&mut self, state: RunningState) {
         self.indicator.borrow_mut().set_state(state);
@@ -128,6 +293,67 @@ impl DockItem {
         self.badge.set_type(badge_type);
     }
 
+    /// Whether the item currently shows the attention badge
+    pub fn wants_attention(&self) -> bool {
+        matches!(self.badge.badge_type(), BadgeType::Attention)
+    }
+
+    /// Show an app's operation progress on the ring around the icon
+    pub fn set_progress(&self, display: ProgressDisplay) {
+        match display {
+            ProgressDisplay::Hidden => self.progress_ring.hide(),
+            ProgressDisplay::Indeterminate => {
+                self.progress_ring.show();
+                self.progress_ring.set_indeterminate(true);
+            }
+            ProgressDisplay::Value(value) => {
+                self.progress_ring.show();
+                self.progress_ring.animate_to(value);
+            }
+        }
+    }
+
+    /// Recolor the progress ring after the system theme changed
+    pub fn apply_theme(&self, colors: &ThemeColors) {
+        self.progress_ring.apply_theme(colors);
+    }
+
+    /// Bounce the item a few times to ask for attention
+    ///
+    /// Does nothing while a bounce is already running.
+    pub fn bounce(&self) {
+        if self.is_bouncing.replace(true) {
+            return;
+        }
+
+        let button = self.button.clone();
+        let is_bouncing = Rc::clone(&self.is_bouncing);
+        let mut step = 0;
+
+        button.add_css_class(BOUNCE_CLASS);
+        glib::timeout_add_local(BOUNCE_STEP, move || {
+            step += 1;
+            if step >= BOUNCE_COUNT * 2 {
+                button.remove_css_class(BOUNCE_CLASS);
+                is_bouncing.set(false);
+                return glib::ControlFlow::Break;
+            }
+
+            // Even steps go up, odd steps come back down
+            if step % 2 == 0 {
+                button.add_css_class(BOUNCE_CLASS);
+            } else {
+                button.remove_css_class(BOUNCE_CLASS);
+            }
+            glib::ControlFlow::Continue
+        });
+    }
+
+    /// Whether an attention bounce is running
+    pub fn is_bouncing(&self) -> bool {
+        self.is_bouncing.get()
+    }
+
     /// Set magnification scale
     pub fn set_scale(&self, scale: f64) {
         let scale_css = format!(
@@ -135,56 +361,249 @@ impl DockItem {
             scale
         );
         self.css_provider.load_from_data(&scale_css);
+        self.scale.set(scale);
+    }
+
+    /// Get the magnification scale last applied
+    pub fn scale(&self) -> f64 {
+        self.scale.get()
+    }
+
+    /// Create the hidden progress ring that circles the icon
+    fn create_progress_ring(settings: &Settings, sources: &PreviewSources) -> ProgressRing {
+        let progress_ring = ProgressRing::new(settings.icon_size as i32 + 8);
+        let ring = progress_ring.widget();
+        ring.set_halign(gtk::Align::Center);
+        ring.set_valign(gtk::Align::Start);
+        ring.set_can_target(false);
+        // Scale the stroke with the icon (3px at the default 48px) and mirror it for RTL locales
+        progress_ring.set_line_width(settings.icon_size as f64 / 16.0);
+        progress_ring.set_counterclockwise(ring.direction() == gtk::TextDirection::Rtl);
+        progress_ring.apply_theme(&sources.theme.get_colors());
+        progress_ring.hide();
+        progress_ring
     }
 
     /// Create the button widget with icon, indicator and badge
-    fn create_button(app: &PinnedApp, settings: &Settings, indicator: &RunningIndicator, badge: &Badge) -> Button {
+    fn create_button(
+        app: &PinnedApp,
+        settings: &Settings,
+        indicator: &RunningIndicator,
+        badge: &Badge,
+        progress_ring: &ProgressRing,
+    ) -> (Button, Image) {
         let overlay = gtk::Overlay::builder().build();
 
+        // The indicator sits between the icon and the screen edge
         let item_box = gtk::Box::builder()
-            .orientation(gtk::Orientation::Vertical)
+            .orientation(match settings.position {
+                DockPosition::Left | DockPosition::Right => gtk::Orientation::Horizontal,
+                DockPosition::Top | DockPosition::Bottom => gtk::Orientation::Vertical,
+            })
             .spacing(4)
             .halign(gtk::Align::Center)
             .valign(gtk::Align::Center)
             .css_classes(vec!["dock-item-content"])
             .build();
 
-        let image = Image::from_icon_name(&app.icon);
+        let image = Image::new();
+        let icon = settings.icon_override(&app.command, app.desktop_file.as_deref()).unwrap_or(&app.icon);
+        set_image_icon(&image, icon);
         image.set_pixel_size(settings.icon_size as i32);
         image.add_css_class("dock-item-icon");
         
         item_box.append(&image);
-        item_box.append(indicator.widget());
+        match settings.position {
+            DockPosition::Left | DockPosition::Top => item_box.prepend(indicator.widget()),
+            DockPosition::Right | DockPosition::Bottom => item_box.append(indicator.widget()),
+        }
 
         overlay.set_child(Some(&item_box));
+        overlay.add_overlay(progress_ring.widget());
         overlay.add_overlay(badge.widget());
 
-        Button::builder()
+        let button = Button::builder()
             .css_classes(vec!["dock-item"])
-            .tooltip_text(&app.name)
+            .tooltip_text(format!("{}\nShift+Click to open a new window", app.display_label()))
             .child(&overlay)
-            .build()
+            .build();
+
+        (button, image)
     }
 
     /// Setup click handler to launch application
-    fn setup_click_handler(button: &Button, app: &PinnedApp) {
+    ///
+    /// With `click_focuses_running`, a running app's window is focused
+    /// instead of launching a duplicate. Shift+Click always launches a
+    /// new instance.
+    fn setup_click_handler(
+        button: &Button,
+        app: &PinnedApp,
+        indicator: Rc<RefCell<RunningIndicator>>,
+        settings: &Settings,
+        actions: &DockItemActions,
+        sources: &PreviewSources,
+    ) {
         let command = app.command.clone();
         let name = app.name.clone();
+        let env = app.env.clone();
+        let desktop_file = app.desktop_file.clone();
+        let terminal = settings.terminal.clone();
+        let desktop_id = app.desktop_file.as_deref().and_then(desktop_id_from_path);
+        let actions = Rc::clone(actions);
+        let focus_running = settings.click_focuses_running;
+        let app_id = app.command.split_whitespace().next().unwrap_or(&app.command).to_string();
+        let window_tracker = sources.window_tracker.clone();
+        
+        // `clicked` carries no event, so note the modifiers at press time
+        let modifiers = Rc::new(Cell::new(gtk::gdk::ModifierType::empty()));
+        let press_gesture = GestureClick::new();
+        press_gesture.set_button(1);
+        press_gesture.set_propagation_phase(gtk::PropagationPhase::Capture);
+        let press_modifiers = Rc::clone(&modifiers);
+        press_gesture.connect_pressed(move |gesture, _, _, _| {
+            press_modifiers.set(gesture.current_event_state());
+        });
+        button.add_controller(press_gesture);
         
         button.connect_clicked(move |_| {
-            info!("Launching application: {}", name);
+            let state = indicator.borrow().state();
+            let modifiers = modifiers.replace(gtk::gdk::ModifierType::empty());
+            
+            // Focusing a running app likely means its badge has been seen
+            if state != RunningState::Stopped {
+                Self::emit_action(&actions, DockItemAction::ClearBadge {
+                    command: command.clone(),
+                    desktop_id: desktop_id.clone(),
+                });
+            }
+            
+            let window_ids: Vec<String> = if focus_running && state != RunningState::Stopped {
+                window_tracker.get_windows_for_app(&app_id).into_iter().map(|window| window.id).collect()
+            } else {
+                Vec::new()
+            };
             
-            if let Err(e) = launcher::launch_command(&command) {
+            if let ClickAction::Focus(window_id) = click_action(focus_running, modifiers, state, &window_ids) {
+                info!("Focusing running application: {}", name);
+                window_tracker.focus_window(&window_id);
+                return;
+            }
+            
+            info!("Launching application: {}", name);
+            if let Err(e) = launcher::launch_app(&command, desktop_file.as_deref(), terminal.as_deref(), &env) {
                 error!("Failed to launch '{}': {}", command, e);
             }
         });
     }
 
-    /// Setup hover effects (magnification and window previews)
-    fn setup_hover_effects(button: &Button, settings: &Settings, preview: Rc<RefCell<WindowPreview>>, app_name: &str, indicator: Rc<RefCell<RunningIndicator>>) {
+    /// Setup middle click to minimize/restore the app's windows
+    ///
+    /// Stopped apps are launched instead, like a regular click.
+    fn setup_middle_click_handler(
+        button: &Button,
+        app: &PinnedApp,
+        indicator: Rc<RefCell<RunningIndicator>>,
+        settings: &Settings,
+        sources: &PreviewSources,
+    ) {
+        let gesture = GestureClick::new();
+        gesture.set_button(2); // Middle mouse button
+        
+        let command = app.command.clone();
+        let env = app.env.clone();
+        let desktop_file = app.desktop_file.clone();
+        let terminal = settings.terminal.clone();
+        let app_id = app.command.split_whitespace().next().unwrap_or(&app.command).to_string();
+        let window_tracker = sources.window_tracker.clone();
+        
+        gesture.connect_released(move |gesture, _, _, _| {
+            gesture.set_state(gtk::EventSequenceState::Claimed);
+            
+            let window_ids: Vec<String> = window_tracker.get_windows_for_app(&app_id)
+                .into_iter()
+                .map(|window| window.id)
+                .collect();
+            
+            match middle_click_action(indicator.borrow().state(), window_ids.len()) {
+                MiddleClickAction::ToggleMinimize => {
+                    let minimized = window_tracker.toggle_minimize(&window_ids);
+                    info!("{} {} window(s) of '{}'", if minimized { "Minimized" } else { "Restored" }, window_ids.len(), app_id);
+                }
+                MiddleClickAction::Launch => {
+                    if let Err(e) = launcher::launch_app(&command, desktop_file.as_deref(), terminal.as_deref(), &env) {
+                        error!("Failed to launch '{}': {}", command, e);
+                    }
+                }
+                MiddleClickAction::Nothing => debug!("No known windows for '{}' to minimize", app_id),
+            }
+        });
+        
+        button.add_controller(gesture);
+    }
+
+    /// Setup scrolling to cycle through a running app's windows
+    ///
+    /// Scrolling down focuses the next window and up the previous one.
+    /// Scrolling over an app that isn't running does nothing.
+    fn setup_scroll_handler(
+        button: &Button,
+        app: &PinnedApp,
+        indicator: Rc<RefCell<RunningIndicator>>,
+        sources: &PreviewSources,
+    ) {
+        let controller = gtk::EventControllerScroll::new(
+            gtk::EventControllerScrollFlags::VERTICAL | gtk::EventControllerScrollFlags::DISCRETE,
+        );
+        
+        let app_id = app.command.split_whitespace().next().unwrap_or(&app.command).to_string();
+        let window_tracker = sources.window_tracker.clone();
+        // Index of the window the last scroll focused
+        let cursor: Rc<Cell<Option<usize>>> = Rc::new(Cell::new(None));
+        
+        controller.connect_scroll(move |_, _dx, dy| {
+            if indicator.borrow().state() == RunningState::Stopped {
+                return glib::Propagation::Proceed;
+            }
+            
+            let windows = window_tracker.get_windows_for_app(&app_id);
+            // Start from the focused window unless scrolling already picked one
+            let current = cursor.get()
+                .filter(|index| *index < windows.len())
+                .or_else(|| windows.iter().position(|window| window.is_active));
+            
+            match scroll_window_index(current, windows.len(), dy) {
+                Some(index) => {
+                    cursor.set(Some(index));
+                    debug!("Scrolled to window {} of {} for '{}'", index + 1, windows.len(), app_id);
+                    window_tracker.focus_window(&windows[index].id);
+                    glib::Propagation::Stop
+                }
+                None => glib::Propagation::Proceed,
+            }
+        });
+        
+        button.add_controller(controller);
+    }
+
+    /// Setup hover effects (window previews)
+    ///
+    /// Magnification is attached by the dock window, which knows every
+    /// item. Leaving the item hides the preview and cancels its pending
+    /// captures.
+    fn setup_hover_effects(
+        button: &Button,
+        preview: Rc<RefCell<WindowPreview>>,
+        app: &PinnedApp,
+        sources: &PreviewSources,
+        indicator: Rc<RefCell<RunningIndicator>>,
+    ) {
         let motion_controller = gtk::EventControllerMotion::new();
         
-        let app_name_clone = app_name.to_string();
+        let app_name = app.name.clone();
+        let app_icon = app.icon.clone();
+        let app_id = app.command.split_whitespace().next().unwrap_or(&app.command).to_string();
+        let sources = sources.clone();
         let preview_clone = Rc::clone(&preview);
         let indicator_clone = Rc::clone(&indicator);
         
@@ -193,7 +612,12 @@ impl DockItem {
             let state = indicator_clone.borrow().state();
             match state {
                 RunningState::Running { window_count } | RunningState::Focused { window_count } => {
-                    preview_clone.borrow().show_previews(&app_name_clone, window_count);
+                    let windows = sources.window_tracker.get_windows_for_app(&app_id);
+                    if windows.is_empty() {
+                        preview_clone.borrow().show_previews(&app_name, window_count);
+                    } else {
+                        preview_clone.borrow().show_windows(&app_name, &app_icon, &windows, &sources.screencopy);
+                    }
                 }
                 _ => {}
             }
@@ -205,46 +629,58 @@ impl DockItem {
         });
 
         button.add_controller(motion_controller);
-        
-        if settings.hover_zoom {
-            // magnification is handled by window-level controller
-        }
     }
 
     /// Setup right-click context menu
-    fn setup_context_menu(button: &Button, app: &PinnedApp, is_pinned: bool) {
+    ///
+    /// `pinned_index` is the app's position in the pinned list, or `None`
+    /// for running (non-pinned) apps.
+    fn setup_context_menu(button: &Button, app: &PinnedApp, pinned_index: Option<usize>, actions: &DockItemActions) -> GestureClick {
         let gesture = GestureClick::new();
         gesture.set_button(3); // Right mouse button
         
-        let app_name = app.name.clone();
-        let app_icon = app.icon.clone();
-        let app_command = app.command.clone();
-        let app_desktop = app.desktop_file.clone();
+        let app = app.clone();
+        let actions = Rc::clone(actions);
         
         gesture.connect_released(move |gesture, _n, x, y| {
-            debug!("Context menu requested for: {}", app_name);
+            debug!("Context menu requested for: {}", app.name);
             
             if let Some(widget) = gesture.widget() {
-                // Create popover menu
-                let popover = Self::create_context_menu(
-                    &widget, 
-                    &app_name, 
-                    &app_icon, 
-                    &app_command, 
-                    app_desktop.as_deref(),
-                    is_pinned
-                );
-                
                 // Position at click location
-                popover.set_pointing_to(Some(&Rectangle::new(
-                    x as i32, y as i32, 1, 1
-                )));
-                
-                popover.popup();
+                let location = Rectangle::new(x as i32, y as i32, 1, 1);
+                Self::popup_context_menu(&widget, &app, pinned_index, &actions, Some(location));
             }
         });
 
-        button.add_controller(gesture);
+        button.add_controller(gesture.clone());
+        gesture
+    }
+
+    /// Build the context menu for `app` and pop it up on `widget`
+    ///
+    /// Without a `location` the popover points at the whole widget.
+    fn popup_context_menu(
+        widget: &gtk::Widget,
+        app: &PinnedApp,
+        pinned_index: Option<usize>,
+        actions: &DockItemActions,
+        location: Option<Rectangle>,
+    ) {
+        let popover = Self::create_context_menu(
+            widget, 
+            &app.name, 
+            &app.icon, 
+            &app.command, 
+            app.desktop_file.as_deref(),
+            pinned_index,
+            actions,
+        );
+        
+        if let Some(location) = location {
+            popover.set_pointing_to(Some(&location));
+        }
+        
+        popover.popup();
     }
 
     /// Setup middle-click to show settings (temporary - will be moved to window)
@@ -259,18 +695,12 @@ impl DockItem {
         app_icon: &str,
         app_command: &str,
         desktop_file: Option<&str>,
-        is_pinned: bool,
+        pinned_index: Option<usize>,
+        actions: &DockItemActions,
     ) -> gtk::Popover {
-        let menu_box = gtk::Box::builder()
-            .orientation(gtk::Orientation::Vertical)
-            .spacing(4)
-            .margin_top(8)
-            .margin_bottom(8)
-            .margin_start(8)
-            .margin_end(8)
-            .build();
+        let menu_box = popover_menu::menu_box();
 
-        if is_pinned {
+        if let Some(index) = pinned_index {
             // Unpin button for pinned apps
             let unpin_btn = Button::builder()
                 .label("Unpin from Dock")
@@ -278,9 +708,15 @@ impl DockItem {
                 .build();
             
             let name_clone = app_name.to_string();
-            unpin_btn.connect_clicked(move |_| {
-                debug!("Unpin requested for: {}", name_clone);
-                // TODO: Implement unpin functionality
+            let command = app_command.to_string();
+            let actions = Rc::clone(actions);
+            unpin_btn.connect_clicked(move |btn| {
+                info!("Unpin requested for: {}", name_clone);
+                popover_menu::close(btn);
+                Self::emit_action(&actions, DockItemAction::Unpin {
+                    index,
+                    command: command.clone(),
+                });
             });
             menu_box.append(&unpin_btn);
         } else {
@@ -290,32 +726,20 @@ impl DockItem {
                 .css_classes(vec!["context-menu-item"])
                 .build();
             
-            let name = app_name.to_string();
-            let icon = app_icon.to_string();
-            let command = app_command.to_string();
-            let desktop = desktop_file.map(|s| s.to_string());
+            let app = PinnedApp {
+                name: app_name.to_string(),
+                icon: app_icon.to_string(),
+                command: app_command.to_string(),
+                desktop_file: desktop_file.map(|s| s.to_string()),
+                env: HashMap::new(),
+                label: None,
+            };
+            let actions = Rc::clone(actions);
             
             keep_btn.connect_clicked(move |btn| {
-                info!("Pinning app to dock: {}", name);
-                
-                // Load settings, add app, save
-                if let Ok(mut settings) = crate::config::Settings::load() {
-                    let new_app = PinnedApp {
-                        name: name.clone(),
-                        icon: icon.clone(),
-                        command: command.clone(),
-                        desktop_file: desktop.clone(),
-                    };
-                    settings.add_pinned_app(new_app);
-                    info!("App '{}' added to dock. Restart to see changes.", name);
-                }
-                
-                // Close the popover
-                if let Some(popover) = btn.ancestor(gtk::Popover::static_type()) {
-                    if let Some(p) = popover.downcast_ref::<gtk::Popover>() {
-                        p.popdown();
-                    }
-                }
+                info!("Pinning app to dock: {}", app.name);
+                popover_menu::close(btn);
+                Self::emit_action(&actions, DockItemAction::KeepInDock(app.clone()));
             });
             menu_box.append(&keep_btn);
         }
@@ -351,19 +775,286 @@ impl DockItem {
             .css_classes(vec!["context-menu-item"])
             .build();
         
-        reload_btn.connect_clicked(move |_| {
-            info!("Reload requested - restart BlazeDock to apply changes");
+        let actions = Rc::clone(actions);
+        reload_btn.connect_clicked(move |btn| {
+            info!("Reload requested from context menu");
+            popover_menu::close(btn);
+            Self::emit_action(&actions, DockItemAction::Reload);
         });
         menu_box.append(&reload_btn);
 
-        let popover = gtk::Popover::builder()
-            .child(&menu_box)
-            .has_arrow(true)
-            .build();
-        
-        popover.set_parent(parent);
+        popover_menu::attach(parent, &menu_box)
+    }
+
+    /// Forward an action to the window's handler, if one is installed
+    fn emit_action(actions: &DockItemActions, action: DockItemAction) {
+        match actions.borrow().as_ref() {
+            Some(handler) => handler(action),
+            None => debug!("No handler installed for dock item action: {:?}", action),
+        }
+    }
+}
+
+/// Decide what a click does for an app in `state` with the known `window_ids`
+///
+/// Holding Shift forces a new instance even when the app is running.
+fn click_action(
+    focus_running: bool,
+    modifiers: gtk::gdk::ModifierType,
+    state: RunningState,
+    window_ids: &[String],
+) -> ClickAction {
+    if modifiers.contains(gtk::gdk::ModifierType::SHIFT_MASK) {
+        return ClickAction::Launch;
+    }
+    
+    match window_ids.first() {
+        Some(window_id) if focus_running && state != RunningState::Stopped => ClickAction::Focus(window_id.clone()),
+        _ => ClickAction::Launch,
+    }
+}
+
+/// Decide what a middle click does for an app in `state` with `window_count` known windows
+fn middle_click_action(state: RunningState, window_count: usize) -> MiddleClickAction {
+    match state {
+        RunningState::Stopped => MiddleClickAction::Launch,
+        _ if window_count > 0 => MiddleClickAction::ToggleMinimize,
+        _ => MiddleClickAction::Nothing,
+    }
+}
+
+/// Index of the window to focus after scrolling by `dy` from `current`
+///
+/// Scrolling down moves to the next window and up to the previous one,
+/// wrapping at either end. Without a current window, down starts at the
+/// first window and up at the last.
+fn scroll_window_index(current: Option<usize>, window_count: usize, dy: f64) -> Option<usize> {
+    if window_count == 0 || dy == 0.0 {
+        return None;
+    }
+    
+    Some(match current {
+        Some(index) if dy > 0.0 => (index + 1) % window_count,
+        Some(index) => (index + window_count - 1) % window_count,
+        None if dy > 0.0 => 0,
+        None => window_count - 1,
+    })
+}
+
+/// Show `icon` (theme name, path or `file://` URI) in `image`
+fn set_image_icon(image: &Image, icon: &str) {
+    let display = gtk::gdk::Display::default();
+    let theme = display.as_ref().map(gtk::IconTheme::for_display);
+    // Without a display, trust the name; GTK falls back on its own
+    let has_icon = |name: &str| theme.as_ref().map(|theme| theme.has_icon(name)).unwrap_or(true);
+
+    match resolve_icon(icon, has_icon) {
+        IconSource::File(path) => image.set_from_file(Some(&path)),
+        IconSource::Name(name) => image.set_icon_name(Some(&name)),
+    }
+}
+
+/// Decide how to load `icon`
+///
+/// Existing files (absolute paths or `file://` URIs) are loaded directly,
+/// then theme names that `has_icon` knows, then the generic app icon.
+fn resolve_icon(icon: &str, has_icon: impl Fn(&str) -> bool) -> IconSource {
+    let path = if icon.starts_with("file://") {
+        gio::File::for_uri(icon).path()
+    } else if icon.starts_with('/') {
+        Some(PathBuf::from(icon))
+    } else {
+        None
+    };
 
-        popover
+    match path {
+        Some(path) if path.is_file() => IconSource::File(path),
+        Some(path) => {
+            debug!("Icon file {:?} not found, using fallback", path);
+            IconSource::Name(FALLBACK_ICON.to_string())
+        }
+        None if !icon.is_empty() && has_icon(icon) => IconSource::Name(icon.to_string()),
+        None => IconSource::Name(FALLBACK_ICON.to_string()),
     }
 }
 
+/// Get the desktop file ID (file name without `.desktop`) from a path
+fn desktop_id_from_path(path: &str) -> Option<String> {
+    std::path::Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gtk::test]
+    fn test_set_icon_updates_stored_name() {
+        let settings = Settings::default();
+        let actions: DockItemActions = Rc::new(RefCell::new(None));
+        let sources = PreviewSources {
+            window_tracker: crate::services::WindowTracker::new(),
+            screencopy: crate::services::ScreencopyService::new(),
+            theme: crate::services::ThemeService::new(),
+        };
+        let mut item = DockItem::new_running("Editor", "text-editor", "editor", None, &settings, &actions, &sources);
+        assert_eq!(item.icon_name(), "text-editor");
+
+        item.set_icon("accessories-text-editor");
+        assert_eq!(item.icon_name(), "accessories-text-editor");
+        // Names the icon theme lacks fall back to the generic icon, as on creation
+        let known = gtk::gdk::Display::default()
+            .map(|display| gtk::IconTheme::for_display(&display).has_icon("accessories-text-editor"))
+            .unwrap_or(true);
+        let expected = if known { "accessories-text-editor" } else { FALLBACK_ICON };
+        assert_eq!(item.image.icon_name().as_deref(), Some(expected));
+    }
+
+    #[gtk::test]
+    fn test_label_overrides_tooltip() {
+        let settings = Settings::default();
+        let actions: DockItemActions = Rc::new(RefCell::new(None));
+        let sources = PreviewSources {
+            window_tracker: crate::services::WindowTracker::new(),
+            screencopy: crate::services::ScreencopyService::new(),
+            theme: crate::services::ThemeService::new(),
+        };
+        let app = PinnedApp {
+            name: "Firefox".to_string(),
+            icon: "firefox".to_string(),
+            command: "firefox -P work".to_string(),
+            desktop_file: None,
+            env: HashMap::new(),
+            label: Some("Firefox — Work".to_string()),
+        };
+
+        let item = DockItem::new(&app, &settings, 0, &actions, &sources);
+        let tooltip = item.widget().tooltip_text().unwrap();
+        assert!(tooltip.starts_with("Firefox — Work\n"));
+        assert_eq!(item.to_pinned_app().label.as_deref(), Some("Firefox — Work"));
+
+        // Without a label the name is shown
+        let item = DockItem::new(&PinnedApp { label: None, ..app }, &settings, 0, &actions, &sources);
+        assert!(item.widget().tooltip_text().unwrap().starts_with("Firefox\n"));
+    }
+
+    #[test]
+    fn test_click_action() {
+        let windows = vec!["0x1".to_string(), "0x2".to_string()];
+        let running = RunningState::Running { window_count: 2 };
+        let none = gtk::gdk::ModifierType::empty();
+        
+        assert_eq!(click_action(true, none, running, &windows), ClickAction::Focus("0x1".to_string()));
+        assert_eq!(click_action(true, none, RunningState::Focused { window_count: 2 }, &windows), ClickAction::Focus("0x1".to_string()));
+        // No known window, stopped, or the user prefers launching
+        assert_eq!(click_action(true, none, running, &[]), ClickAction::Launch);
+        assert_eq!(click_action(true, none, RunningState::Stopped, &windows), ClickAction::Launch);
+        assert_eq!(click_action(false, none, running, &windows), ClickAction::Launch);
+    }
+
+    #[test]
+    fn test_shift_click_forces_launch() {
+        let windows = vec!["0x1".to_string()];
+        let running = RunningState::Running { window_count: 1 };
+        
+        assert_eq!(click_action(true, gtk::gdk::ModifierType::SHIFT_MASK, running, &windows), ClickAction::Launch);
+        assert_eq!(
+            click_action(true, gtk::gdk::ModifierType::SHIFT_MASK | gtk::gdk::ModifierType::CONTROL_MASK, running, &windows),
+            ClickAction::Launch
+        );
+        // Other modifiers keep click-to-focus
+        assert_eq!(
+            click_action(true, gtk::gdk::ModifierType::CONTROL_MASK, running, &windows),
+            ClickAction::Focus("0x1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_middle_click_action() {
+        assert_eq!(middle_click_action(RunningState::Stopped, 0), MiddleClickAction::Launch);
+        assert_eq!(middle_click_action(RunningState::Running { window_count: 2 }, 2), MiddleClickAction::ToggleMinimize);
+        assert_eq!(middle_click_action(RunningState::Focused { window_count: 1 }, 1), MiddleClickAction::ToggleMinimize);
+        // Running, but the tracker hasn't seen its windows
+        assert_eq!(middle_click_action(RunningState::Running { window_count: 1 }, 0), MiddleClickAction::Nothing);
+    }
+
+    #[test]
+    fn test_scroll_window_index() {
+        // Down moves forward, up moves back, both wrapping
+        assert_eq!(scroll_window_index(Some(0), 3, 1.0), Some(1));
+        assert_eq!(scroll_window_index(Some(2), 3, 1.0), Some(0));
+        assert_eq!(scroll_window_index(Some(1), 3, -1.0), Some(0));
+        assert_eq!(scroll_window_index(Some(0), 3, -1.0), Some(2));
+        // No current window yet
+        assert_eq!(scroll_window_index(None, 3, 1.0), Some(0));
+        assert_eq!(scroll_window_index(None, 3, -1.0), Some(2));
+        assert_eq!(scroll_window_index(Some(0), 1, 1.0), Some(0));
+        // No windows, or no vertical movement
+        assert_eq!(scroll_window_index(None, 0, 1.0), None);
+        assert_eq!(scroll_window_index(Some(1), 3, 0.0), None);
+    }
+
+    #[test]
+    fn test_resolve_icon() {
+        let known = |name: &str| name == "firefox";
+        assert_eq!(resolve_icon("firefox", known), IconSource::Name("firefox".to_string()));
+        assert_eq!(resolve_icon("unknown-app", known), IconSource::Name(FALLBACK_ICON.to_string()));
+
+        let path = std::env::temp_dir().join(format!("blazedock-icon-test-{}.png", std::process::id()));
+        std::fs::write(&path, b"png").unwrap();
+        let absolute = path.to_string_lossy().into_owned();
+        assert_eq!(resolve_icon(&absolute, known), IconSource::File(path.clone()));
+        let uri = glib::filename_to_uri(&path, None).unwrap();
+        assert_eq!(resolve_icon(&uri, known), IconSource::File(path.clone()));
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(resolve_icon(&absolute, known), IconSource::Name(FALLBACK_ICON.to_string()));
+    }
+
+    #[gtk::test]
+    fn test_progress_ring_uses_theme_accent() {
+        let settings = Settings::default();
+        let actions: DockItemActions = Rc::new(RefCell::new(None));
+        let sources = PreviewSources {
+            window_tracker: crate::services::WindowTracker::new(),
+            screencopy: crate::services::ScreencopyService::new(),
+            theme: crate::services::ThemeService::new(),
+        };
+        let item = DockItem::new_running("Mail", "mail", "mail", None, &settings, &actions, &sources);
+        assert_eq!(item.progress_ring.color(), sources.theme.get_colors().accent_color);
+
+        let mut colors = sources.theme.get_colors();
+        colors.set_accent((1.0, 0.5, 0.0));
+        item.apply_theme(&colors);
+        assert_eq!(item.progress_ring.color(), (1.0, 0.5, 0.0));
+    }
+
+    #[gtk::test]
+    fn test_bounce_schedules_and_clears_class() {
+        let settings = Settings::default();
+        let actions: DockItemActions = Rc::new(RefCell::new(None));
+        let sources = PreviewSources {
+            window_tracker: crate::services::WindowTracker::new(),
+            screencopy: crate::services::ScreencopyService::new(),
+            theme: crate::services::ThemeService::new(),
+        };
+        let item = DockItem::new_running("Mail", "mail", "mail", None, &settings, &actions, &sources);
+
+        item.bounce();
+        assert!(item.is_bouncing());
+        assert!(item.widget().has_css_class(BOUNCE_CLASS));
+
+        // A second request while bouncing is ignored
+        item.bounce();
+
+        let context = glib::MainContext::default();
+        let deadline = std::time::Instant::now() + BOUNCE_STEP * (BOUNCE_COUNT * 2 + 4);
+        while item.is_bouncing() && std::time::Instant::now() < deadline {
+            context.iteration(true);
+        }
+
+        assert!(!item.is_bouncing());
+        assert!(!item.widget().has_css_class(BOUNCE_CLASS));
+    }
+}
@@ -10,11 +10,16 @@ use gtk::gdk;
 use gtk::glib;
 use log::{debug, info, warn};
 use std::cell::RefCell;
+use std::path::PathBuf;
 use std::rc::Rc;
 
 use crate::config::{PinnedApp, Settings};
+use crate::ui::{DockItemAction, DockItemActions};
 use crate::utils::desktop_entry::DesktopEntry;
 
+/// CSS class on a dragged item that will be unpinned if dropped now
+const UNPIN_PENDING_CLASS: &str = "dock-item-unpin-pending";
+
 /// Shared state for tracking drag operations
 #[derive(Clone, Default)]
 pub struct DragState {
@@ -22,6 +27,31 @@ pub struct DragState {
     pub dragging_index: Option<usize>,
     /// Whether drag has left the dock bounds (for unpin)
     pub outside_dock: bool,
+    /// Whether the item was dropped back onto the dock (reorder, not unpin)
+    pub dropped_on_dock: bool,
+    /// The item being dragged, for the unpin affordance
+    pub dragging_widget: Option<glib::WeakRef<gtk::Widget>>,
+}
+
+impl DragState {
+    /// Record whether the drag is off the dock and show it on the dragged item
+    fn set_outside_dock(&mut self, outside: bool) {
+        self.outside_dock = outside;
+        if let Some(widget) = self.dragging_widget.as_ref().and_then(|w| w.upgrade()) {
+            if outside {
+                widget.add_css_class(UNPIN_PENDING_CLASS);
+            } else {
+                widget.remove_css_class(UNPIN_PENDING_CLASS);
+            }
+        }
+    }
+
+    /// Whether the dragged item should be unpinned when the drag ends
+    ///
+    /// `delete_data` is set by GTK when another target accepted a move.
+    fn should_unpin(&self, delete_data: bool) -> bool {
+        !self.dropped_on_dock && (self.outside_dock || delete_data)
+    }
 }
 
 /// Create shared drag state
@@ -30,22 +60,31 @@ pub fn create_drag_state() -> Rc<RefCell<DragState>> {
 }
 
 /// Setup drag source on a dock item for reordering
+///
+/// The item's index is its current position in the dock, looked up when
+/// a drag starts so it stays right after earlier reorders.
 pub fn setup_drag_source_for_reorder(
     widget: &gtk::Button,
-    index: usize,
     drag_state: Rc<RefCell<DragState>>,
     settings: Rc<RefCell<Settings>>,
+    actions: DockItemActions,
 ) {
     let drag_source = gtk::DragSource::new();
     drag_source.set_actions(gdk::DragAction::MOVE);
     
     let state_prepare = Rc::clone(&drag_state);
-    let idx = index;
     
     // Set dragging index when drag starts
+    let widget_prepare = widget.clone().upcast::<gtk::Widget>().downgrade();
     drag_source.connect_prepare(move |_source, _x, _y| {
+        let idx = widget_index(&widget_prepare.upgrade()?);
         debug!("Drag prepare: item index={}", idx);
-        state_prepare.borrow_mut().dragging_index = Some(idx);
+        let mut state = state_prepare.borrow_mut();
+        state.dragging_index = Some(idx);
+        state.outside_dock = false;
+        state.dropped_on_dock = false;
+        state.dragging_widget = Some(widget_prepare.clone());
+        drop(state);
         
         // Return string content with index
         let data = idx.to_string();
@@ -56,7 +95,7 @@ pub fn setup_drag_source_for_reorder(
     // Visual feedback during drag
     let widget_weak = widget.downgrade();
     drag_source.connect_drag_begin(move |_source, _drag| {
-        debug!("Drag started for index {}", idx);
+        debug!("Drag started");
         if let Some(widget) = widget_weak.upgrade() {
             widget.add_css_class("dock-item-dragging");
         }
@@ -66,52 +105,77 @@ pub fn setup_drag_source_for_reorder(
     let widget_weak2 = widget.downgrade();
     let state_end = Rc::clone(&drag_state);
     let settings_clone = Rc::clone(&settings);
-    let idx_for_unpin = index;
     
-    drag_source.connect_drag_end(move |_source, _drag, delete_data| {
+    drag_source.connect_drag_end(move |_source, drag, delete_data| {
         debug!("Drag ended, delete_data={}", delete_data);
         
         if let Some(widget) = widget_weak2.upgrade() {
             widget.remove_css_class("dock-item-dragging");
+            widget.remove_css_class(UNPIN_PENDING_CLASS);
+            
+            // Where the pointer ended up is the final word on leaving the dock
+            if let Some(dock) = widget.parent() {
+                if !state_end.borrow().dropped_on_dock {
+                    state_end.borrow_mut().outside_dock = pointer_outside(drag, &dock);
+                }
+            }
         }
         
         let state = state_end.borrow();
         
-        // If drag ended outside dock and delete_data is true, unpin
-        if state.outside_dock || delete_data {
+        // Unpin if dropped off the dock, or moved onto another target
+        if let Some(idx_for_unpin) = state.dragging_index.filter(|_| state.should_unpin(delete_data)) {
             info!("Item {} dragged off dock - unpinning", idx_for_unpin);
-            let mut settings = settings_clone.borrow_mut();
-            if idx_for_unpin < settings.pinned_apps.len() {
-                let removed = settings.remove_pinned_app(idx_for_unpin);
-                if let Some(app) = removed {
-                    info!("Unpinned '{}' - reload dock to see changes", app.name);
-                }
+            let command = settings_clone.borrow().pinned_apps.get(idx_for_unpin).map(|app| app.command.clone());
+            if let Some(command) = command {
+                emit(&actions, DockItemAction::Unpin { index: idx_for_unpin, command });
             }
         }
         
         // Clear drag state
         drop(state);
-        state_end.borrow_mut().dragging_index = None;
-        state_end.borrow_mut().outside_dock = false;
+        *state_end.borrow_mut() = DragState::default();
     });
     
-    // Track when drag leaves widget bounds (for detecting drag-off-dock)
-    drag_source.connect_drag_cancel(move |_source, _drag, _reason| {
-        debug!("Drag cancelled");
-        false
+    // A drop onto empty space has no target and is cancelled; claim it as
+    // handled when it happened off the dock so the icon doesn't fly back
+    let state_cancel = Rc::clone(&drag_state);
+    let widget_weak3 = widget.downgrade();
+    drag_source.connect_drag_cancel(move |_source, drag, reason| {
+        debug!("Drag cancelled: {:?}", reason);
+        if reason != gdk::DragCancelReason::NoTarget {
+            return false;
+        }
+        
+        let outside = widget_weak3.upgrade()
+            .and_then(|widget| widget.parent())
+            .map(|dock| pointer_outside(drag, &dock))
+            .unwrap_or(false);
+        state_cancel.borrow_mut().outside_dock = outside;
+        outside
     });
     
     widget.add_controller(drag_source);
 }
 
 /// Setup drop target on dock container for reordering
+///
+/// A drop sends a `Move` through `actions`, which updates the config and
+/// moves the item in place. Pinned items must come first in the dock box.
 pub fn setup_drop_target_for_reorder(
     dock_box: &gtk::Box,
     drag_state: Rc<RefCell<DragState>>,
     settings: Rc<RefCell<Settings>>,
+    actions: DockItemActions,
 ) {
     let drop_target = gtk::DropTarget::new(glib::Type::STRING, gdk::DragAction::MOVE);
     
+    // Only our own items are reordered, not strings dragged in from elsewhere
+    let state_accept = Rc::clone(&drag_state);
+    drop_target.connect_accept(move |target, drop| {
+        state_accept.borrow().dragging_index.is_some() && accepts_drop(target, drop)
+    });
+    
     let dock_box_weak = dock_box.downgrade();
     let state_drop = Rc::clone(&drag_state);
     let settings_drop = Rc::clone(&settings);
@@ -132,8 +196,16 @@ pub fn setup_drop_target_for_reorder(
         };
         drop(state);
         
-        // Calculate target index
-        let target_index = calculate_drop_index(&dock_box, x, y);
+        // Calculate target index, staying among the pinned items
+        let pinned_count = settings_drop.borrow().pinned_apps.len();
+        let target_index = calculate_drop_index(&dock_box, x, y).min(pinned_count.saturating_sub(1));
+        
+        // Dropped back onto the dock, so never unpin
+        {
+            let mut state = state_drop.borrow_mut();
+            state.dropped_on_dock = true;
+            state.set_outside_dock(false);
+        }
         
         if source_index == target_index {
             debug!("Source equals target, no reorder needed");
@@ -141,13 +213,7 @@ pub fn setup_drop_target_for_reorder(
         }
         
         info!("Reordering: {} -> {}", source_index, target_index);
-        
-        // Reorder in settings
-        settings_drop.borrow_mut().reorder_pinned_app(source_index, target_index);
-        info!("Reorder saved - reload dock to see changes");
-        
-        // Mark drop successful (not outside dock)
-        state_drop.borrow_mut().outside_dock = false;
+        emit(&actions, DockItemAction::Move { from: source_index, to: target_index });
         
         true
     });
@@ -156,79 +222,86 @@ pub fn setup_drop_target_for_reorder(
     let state_enter = Rc::clone(&drag_state);
     drop_target.connect_enter(move |_target, _x, _y| {
         debug!("Drag entered dock area");
-        state_enter.borrow_mut().outside_dock = false;
+        state_enter.borrow_mut().set_outside_dock(false);
+        gdk::DragAction::MOVE
+    });
+    
+    // Follow the pointer against the dock bounds while it moves
+    let state_motion = Rc::clone(&drag_state);
+    drop_target.connect_motion(move |target, x, y| {
+        let Some(dock) = target.widget() else {
+            return gdk::DragAction::MOVE;
+        };
+        let outside = is_outside_bounds(x, y, 0.0, 0.0, dock.width() as f64, dock.height() as f64);
+        let mut state = state_motion.borrow_mut();
+        if state.outside_dock != outside {
+            state.set_outside_dock(outside);
+        }
         gdk::DragAction::MOVE
     });
     
-    // Track when drag leaves dock (for unpin)
+    // Track when drag leaves dock (for unpin); GTK also emits this right
+    // before a drop, which then marks the drag as back on the dock
     let state_leave = Rc::clone(&drag_state);
     drop_target.connect_leave(move |_target| {
         debug!("Drag left dock area - will unpin if dropped");
-        state_leave.borrow_mut().outside_dock = true;
+        state_leave.borrow_mut().set_outside_dock(true);
     });
     
     dock_box.add_controller(drop_target);
 }
 
 /// Setup drop target for .desktop files from file managers
-/// Drops are automatically saved to config - caller should reload dock to see changes
+///
+/// Drops are sent through `actions` as `KeepInDock`, which saves the app
+/// and adds its item in place. Dock items being reordered (tracked in
+/// `drag_state`) are left to the reorder target.
 pub fn setup_drop_target_desktop_files(
     dock_box: &gtk::Box,
-    settings: Rc<RefCell<Settings>>,
+    drag_state: Rc<RefCell<DragState>>,
+    actions: DockItemActions,
 ) {
-    // Accept text/uri-list for file drops
-    let drop_target = gtk::DropTarget::new(glib::Type::STRING, gdk::DragAction::COPY);
+    // File managers hand over a FileList (text/uri-list); plain strings
+    // of paths or URIs are accepted as a fallback
+    let drop_target = gtk::DropTarget::new(glib::Type::INVALID, gdk::DragAction::COPY);
+    drop_target.set_types(&[gdk::FileList::static_type(), glib::Type::STRING]);
     
-    let settings_clone = Rc::clone(&settings);
+    drop_target.connect_accept(move |target, drop| {
+        drag_state.borrow().dragging_index.is_none() && accepts_drop(target, drop)
+    });
     
     drop_target.connect_drop(move |_target, value, _x, _y| {
         debug!("File drop received");
         
-        // Try to get URI list as string
-        if let Ok(uri_str) = value.get::<String>() {
-            // Parse URI list (one per line)
-            for line in uri_str.lines() {
-                let uri = line.trim();
-                if uri.is_empty() || uri.starts_with('#') {
-                    continue;
-                }
+        for path in dropped_paths(value) {
+            // Check if it's a .desktop file
+            if path.extension().map_or(true, |ext| ext != "desktop") {
+                continue;
+            }
+            let path = path.to_string_lossy();
+            info!("Desktop file dropped: {}", path);
+            
+            // Parse the desktop file
+            if let Ok(entry) = DesktopEntry::parse(&*path) {
+                let name = entry.localized_name().unwrap_or_else(|| "Unknown".to_string());
+                let icon = entry.localized_icon().unwrap_or_else(|| "application-x-executable".to_string());
+                let command = entry.exec_command().unwrap_or_else(|| path.to_string());
                 
-                // Convert file:// URI to path
-                let path = if uri.starts_with("file://") {
-                    uri.strip_prefix("file://").unwrap_or(uri)
-                } else {
-                    uri
+                let app = PinnedApp {
+                    name: name.clone(),
+                    icon,
+                    command,
+                    desktop_file: Some(path.to_string()),
+                    env: Default::default(),
+                    label: None,
                 };
                 
-                // URL decode the path
-                let path = urlencoding_decode(path);
+                info!("App '{}' dropped onto dock", name);
+                emit(&actions, DockItemAction::KeepInDock(app));
                 
-                // Check if it's a .desktop file
-                if path.ends_with(".desktop") {
-                    info!("Desktop file dropped: {}", path);
-                    
-                    // Parse the desktop file
-                    if let Ok(entry) = DesktopEntry::parse(&path) {
-                        let name = entry.name.clone().unwrap_or_else(|| "Unknown".to_string());
-                        let icon = entry.icon.clone().unwrap_or_else(|| "application-x-executable".to_string());
-                        let command = entry.exec_command().unwrap_or_else(|| path.to_string());
-                        
-                        let app = PinnedApp {
-                            name: name.clone(),
-                            icon,
-                            command,
-                            desktop_file: Some(path.to_string()),
-                        };
-                        
-                        // Add to settings and save
-                        settings_clone.borrow_mut().add_pinned_app(app);
-                        info!("App '{}' pinned to dock - reload to see changes", name);
-                        
-                        return true;
-                    } else {
-                        warn!("Failed to parse desktop file: {}", path);
-                    }
-                }
+                return true;
+            } else {
+                warn!("Failed to parse desktop file: {}", path);
             }
         }
         
@@ -244,6 +317,121 @@ pub fn setup_drop_target_desktop_files(
     dock_box.add_controller(drop_target);
 }
 
+/// Position of `widget` among its siblings
+fn widget_index(widget: &gtk::Widget) -> usize {
+    std::iter::successors(widget.prev_sibling(), |w| w.prev_sibling()).count()
+}
+
+/// Move the item at `from` to `to`, shifting those in between
+///
+/// Returns `false` if either index is out of range.
+fn move_item<T>(items: &mut Vec<T>, from: usize, to: usize) -> bool {
+    if from >= items.len() || to >= items.len() {
+        return false;
+    }
+    let item = items.remove(from);
+    items.insert(to, item);
+    true
+}
+
+/// Move a pinned app and its dock item together
+///
+/// `items` holds the dock's `(command, item, is_pinned)` entries, indexed
+/// by `from` and `to`. The dock may show only some of the pinned apps
+/// (see `Settings::for_monitor`), so the apps are matched by command.
+/// Nothing moves unless both are pinned entries.
+pub fn apply_reorder<T>(settings: &mut Settings, items: &mut Vec<(String, T, bool)>, from: usize, to: usize) -> bool {
+    let app_index = |index: usize| match items.get(index) {
+        Some((command, _, true)) => settings.pinned_apps.iter().position(|app| app.command == *command),
+        _ => None,
+    };
+    let (Some(app_from), Some(app_to)) = (app_index(from), app_index(to)) else {
+        return false;
+    };
+    settings.move_pinned_app(app_from, app_to) && move_item(items, from, to)
+}
+
+/// Send `action` to the dock window's handler, if one is installed
+fn emit(actions: &DockItemActions, action: DockItemAction) {
+    match actions.borrow().as_ref() {
+        Some(handler) => handler(action),
+        None => debug!("No action handler for {:?}", action),
+    }
+}
+
+/// Whether `target` takes the formats and actions `drop` offers
+///
+/// This is the check GTK does when `accept` isn't overridden.
+fn accepts_drop(target: &gtk::DropTarget, drop: &gdk::Drop) -> bool {
+    target.actions().intersects(drop.actions())
+        && target.formats().is_some_and(|formats| formats.match_(&drop.formats()))
+}
+
+/// Whether the drag's pointer is off `dock`
+///
+/// True when the pointer is over another surface entirely, or outside the
+/// dock's bounds within our window.
+fn pointer_outside(drag: &gdk::Drag, dock: &gtk::Widget) -> bool {
+    let (surface, x, y) = drag.device().surface_at_position();
+    let Some(native) = dock.native() else {
+        return true;
+    };
+    if surface.is_none() || surface != native.surface() {
+        return true;
+    }
+    let Some(bounds) = dock.compute_bounds(&native) else {
+        return true;
+    };
+    
+    // Surface coordinates include the window's shadow/border offset
+    let (offset_x, offset_y) = native.surface_transform();
+    is_outside_bounds(
+        x - offset_x,
+        y - offset_y,
+        bounds.x() as f64,
+        bounds.y() as f64,
+        bounds.width() as f64,
+        bounds.height() as f64,
+    )
+}
+
+/// Whether the point `(x, y)` lies outside the given rectangle
+fn is_outside_bounds(x: f64, y: f64, left: f64, top: f64, width: f64, height: f64) -> bool {
+    x < left || y < top || x >= left + width || y >= top + height
+}
+
+/// Local paths of the files in a drop
+///
+/// Takes a `FileList` or, failing that, a string of URIs or paths, one
+/// per line.
+fn dropped_paths(value: &glib::Value) -> Vec<PathBuf> {
+    if let Ok(files) = value.get::<gdk::FileList>() {
+        return local_paths(&files.files());
+    }
+    
+    value.get::<String>()
+        .map(|uris| paths_from_uri_list(&uris))
+        .unwrap_or_default()
+}
+
+/// Paths of the files that are local
+fn local_paths(files: &[gtk::gio::File]) -> Vec<PathBuf> {
+    files.iter().filter_map(|file| file.path()).collect()
+}
+
+/// Parse a `text/uri-list` (or a list of plain paths) into paths
+fn paths_from_uri_list(uris: &str) -> Vec<PathBuf> {
+    uris.lines()
+        .map(str::trim)
+        .filter(|uri| !uri.is_empty() && !uri.starts_with('#'))
+        .map(|uri| {
+            // Convert file:// URI to path
+            let path = uri.strip_prefix("file://").unwrap_or(uri);
+            PathBuf::from(urlencoding_decode(path))
+        })
+        .collect()
+}
+
 /// Simple URL decoding for file paths
 fn urlencoding_decode(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
@@ -308,8 +496,106 @@ pub fn get_drag_drop_css() -> &'static str {
         transform: scale(0.9);
     }
     
+    .dock-item-unpin-pending {
+        opacity: 0.25;
+        filter: grayscale(1);
+    }
+    
     .dock-container.drag-over {
         background: alpha(@accent_color, 0.2);
     }
     "#
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_outside_bounds() {
+        // A 400x64 dock at (10, 20)
+        let outside = |x, y| is_outside_bounds(x, y, 10.0, 20.0, 400.0, 64.0);
+        assert!(!outside(10.0, 20.0));
+        assert!(!outside(200.0, 50.0));
+        assert!(!outside(409.9, 83.9));
+        assert!(outside(410.0, 50.0));
+        assert!(outside(200.0, 84.0));
+        assert!(outside(9.0, 50.0));
+        assert!(outside(200.0, -5.0));
+    }
+
+    #[test]
+    fn test_dropped_paths() {
+        let uris = "# from a file manager\r\nfile:///usr/share/applications/org.gnome%20Files.desktop\r\n/opt/app.desktop\n";
+        assert_eq!(
+            dropped_paths(&uris.to_value()),
+            vec![
+                PathBuf::from("/usr/share/applications/org.gnome Files.desktop"),
+                PathBuf::from("/opt/app.desktop"),
+            ]
+        );
+
+        // The files of a FileList
+        let files = [
+            gtk::gio::File::for_uri("file:///usr/share/applications/firefox.desktop"),
+            gtk::gio::File::for_path("/home/user/krita.desktop"),
+        ];
+        assert_eq!(
+            local_paths(&files),
+            vec![
+                PathBuf::from("/usr/share/applications/firefox.desktop"),
+                PathBuf::from("/home/user/krita.desktop"),
+            ]
+        );
+
+        assert!(dropped_paths(&42u32.to_value()).is_empty());
+    }
+
+    #[test]
+    fn test_apply_reorder_keeps_items_in_step() {
+        let mut settings = Settings::default();
+        settings.pinned_apps.clear();
+        for name in ["a", "b", "hidden", "c"] {
+            settings.pinned_apps.push(PinnedApp {
+                name: name.to_string(),
+                icon: name.to_string(),
+                command: name.to_string(),
+                desktop_file: None,
+                env: Default::default(),
+                label: None,
+            });
+        }
+        // Dock items: the pinned apps shown on this monitor, then a running app
+        let entry = |command: &str, is_pinned: bool| (command.to_string(), (), is_pinned);
+        let mut items = vec![entry("a", true), entry("b", true), entry("c", true), entry("running", false)];
+        let apps = |settings: &Settings| settings.pinned_apps.iter().map(|app| app.command.clone()).collect::<Vec<_>>();
+        let commands = |items: &[(String, (), bool)]| items.iter().map(|(c, _, _)| c.clone()).collect::<Vec<_>>();
+
+        assert!(apply_reorder(&mut settings, &mut items, 0, 2));
+        assert_eq!(apps(&settings), ["b", "hidden", "c", "a"]);
+        assert_eq!(commands(&items), ["b", "c", "a", "running"]);
+
+        assert!(apply_reorder(&mut settings, &mut items, 2, 1));
+        assert_eq!(apps(&settings), ["b", "hidden", "a", "c"]);
+        assert_eq!(commands(&items), ["b", "a", "c", "running"]);
+
+        // Only pinned apps can be reordered
+        assert!(!apply_reorder(&mut settings, &mut items, 0, 3));
+        assert!(!apply_reorder(&mut settings, &mut items, 9, 0));
+        assert_eq!(commands(&items), ["b", "a", "c", "running"]);
+    }
+
+    #[test]
+    fn test_should_unpin() {
+        let mut state = DragState::default();
+        assert!(!state.should_unpin(false));
+        assert!(state.should_unpin(true));
+
+        state.outside_dock = true;
+        assert!(state.should_unpin(false));
+
+        // Dropping back onto the dock reorders instead
+        state.dropped_on_dock = true;
+        assert!(!state.should_unpin(true));
+    }
+}
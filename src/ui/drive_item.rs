@@ -0,0 +1,147 @@
+//! Removable drive dock item
+//!
+//! Shows a mounted USB stick or SD card. Opens the mount point on click
+//! and ejects the drive from its context menu.
+
+use gtk::prelude::*;
+use gtk::{Button, Image};
+use gtk::gio;
+use gtk::glib;
+use log::{info, warn};
+
+use crate::services::DriveInfo;
+use crate::ui::popover_menu;
+
+/// Icon for removable media
+const DRIVE_ICON: &str = "drive-removable-media";
+
+/// Dock item for a mounted removable drive
+pub struct DriveItem {
+    button: Button,
+    mount_point: String,
+}
+
+impl DriveItem {
+    /// Create a dock item for `drive`
+    pub fn new(drive: &DriveInfo, icon_size: u32) -> Self {
+        let image = Image::from_icon_name(DRIVE_ICON);
+        image.set_pixel_size(icon_size as i32);
+        image.add_css_class("dock-item-icon");
+
+        let button = Button::builder()
+            .css_classes(vec!["dock-item", "dock-item-drive"])
+            .tooltip_text(&drive.name)
+            .child(&image)
+            .build();
+
+        let mount_point = drive.mount_point.clone();
+        button.connect_clicked(move |_| Self::open(&mount_point));
+
+        let item = Self {
+            button,
+            mount_point: drive.mount_point.clone(),
+        };
+        item.setup_context_menu(&drive.name);
+        item
+    }
+
+    /// Get the widget
+    pub fn widget(&self) -> &Button {
+        &self.button
+    }
+
+    /// Where the drive is mounted
+    pub fn mount_point(&self) -> &str {
+        &self.mount_point
+    }
+
+    /// Open the mount point in the file manager
+    fn open(mount_point: &str) {
+        info!("Opening drive at {}", mount_point);
+        if let Err(e) = std::process::Command::new("xdg-open").arg(mount_point).spawn() {
+            warn!("Failed to open {}: {}", mount_point, e);
+        }
+    }
+
+    /// Eject the drive, or just unmount it if it can't be ejected
+    ///
+    /// The drive monitor notices the unmount and the item goes away.
+    fn eject(mount_point: &str) {
+        let mount = match gio::File::for_path(mount_point).find_enclosing_mount(gio::Cancellable::NONE) {
+            Ok(mount) => mount,
+            Err(e) => {
+                warn!("No mount found for {}: {}", mount_point, e);
+                return;
+            }
+        };
+
+        let mount_point = mount_point.to_string();
+        let done = move |result: Result<(), glib::Error>| match result {
+            Ok(()) => info!("Ejected drive at {}", mount_point),
+            Err(e) => warn!("Failed to eject drive at {}: {}", mount_point, e),
+        };
+
+        match mount.volume().filter(|volume| volume.can_eject()) {
+            Some(volume) => volume.eject_with_operation(
+                gio::MountUnmountFlags::NONE,
+                gio::MountOperation::NONE,
+                gio::Cancellable::NONE,
+                done,
+            ),
+            None => mount.unmount_with_operation(
+                gio::MountUnmountFlags::NONE,
+                gio::MountOperation::NONE,
+                gio::Cancellable::NONE,
+                done,
+            ),
+        }
+    }
+
+    /// Setup right-click context menu
+    fn setup_context_menu(&self, name: &str) {
+        let gesture = gtk::GestureClick::new();
+        gesture.set_button(3);
+
+        let mount_point = self.mount_point.clone();
+        let name = name.to_string();
+        gesture.connect_released(move |gesture, _n, x, y| {
+            let Some(widget) = gesture.widget() else {
+                return;
+            };
+            let menu = Self::create_context_menu(&widget, &name, &mount_point);
+            menu.set_pointing_to(Some(&gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+            menu.popup();
+        });
+
+        self.button.add_controller(gesture);
+    }
+
+    /// Create the context menu popover
+    fn create_context_menu(parent: &gtk::Widget, name: &str, mount_point: &str) -> gtk::Popover {
+        let menu_box = popover_menu::menu_box();
+
+        let open_btn = Button::builder()
+            .label(format!("Open {}", name))
+            .css_classes(vec!["context-menu-item"])
+            .build();
+        let open_mount = mount_point.to_string();
+        open_btn.connect_clicked(move |btn| {
+            popover_menu::close(btn);
+            Self::open(&open_mount);
+        });
+        menu_box.append(&open_btn);
+
+        let eject_btn = Button::builder()
+            .label("Eject")
+            .css_classes(vec!["context-menu-item"])
+            .build();
+        let eject_mount = mount_point.to_string();
+        eject_btn.connect_clicked(move |btn| {
+            popover_menu::close(btn);
+            Self::eject(&eject_mount);
+        });
+        menu_box.append(&eject_btn);
+
+        popover_menu::attach(parent, &menu_box)
+    }
+}
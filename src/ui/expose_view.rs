@@ -1,7 +1,7 @@
 //! Window Exposé View
 //!
 //! A popup that shows all windows for an application in a grid layout.
-//! Clicking a window thumbnail focuses that window.
+//! Clicking a window thumbnail focuses that window; middle-clicking closes it.
 
 use gtk::prelude::*;
 use gtk::{Box, Button, Image, Label, Orientation};
@@ -152,6 +152,32 @@ impl ExposeView {
             popup_ref.popdown();
         });
         
+        // Close window on middle-click
+        let close_gesture = gtk::GestureClick::new();
+        close_gesture.set_button(2);
+        
+        let tracker = Rc::clone(&self.window_tracker);
+        let win_id = window.id.clone();
+        let popup_ref = self.popup.clone();
+        let grid = self.grid.clone();
+        
+        close_gesture.connect_released(move |gesture, _, _, _| {
+            info!("Closing window: {}", win_id);
+            tracker.close_window(&win_id);
+            
+            // Remove the card (wrapped in a FlowBoxChild) from the grid
+            if let Some(card) = gesture.widget().and_then(|w| w.parent()) {
+                grid.remove(&card);
+            }
+            
+            // Nothing left to show once the last window is gone
+            if grid.first_child().is_none() {
+                popup_ref.popdown();
+            }
+        });
+        
+        button.add_controller(close_gesture);
+        
         button.upcast()
     }
 }
@@ -4,12 +4,22 @@
 
 use log::debug;
 
+use crate::config::Settings;
+
+/// Fraction of the remaining distance to the target scale covered per frame
+const EASE_FACTOR: f64 = 0.25;
+
+/// Scales closer than this to their target snap to it
+const SETTLE_THRESHOLD: f64 = 0.002;
+
 /// Magnification controller for dock items
 pub struct MagnificationController {
     max_scale: f64,
     range_items: usize,
     animation_duration_ms: u32,
     current_hover: Option<usize>,
+    is_animating: bool,
+    enabled: bool,
 }
 
 impl MagnificationController {
@@ -20,16 +30,25 @@ impl MagnificationController {
             range_items,
             animation_duration_ms: 200,
             current_hover: None,
+            is_animating: false,
+            enabled: true,
         }
     }
 
+    /// Create a controller for the zoom settings, disabled unless `hover_zoom` is on
+    pub fn from_settings(settings: &Settings) -> Self {
+        let mut controller = Self::new(settings.hover_zoom_scale, settings.magnification_spread);
+        controller.enabled = settings.hover_zoom;
+        controller
+    }
+
     /// Calculate magnification scale for an item based on distance from hover
     ///
     /// Uses cosine interpolation for smooth falloff
     pub fn calculate_scale(&self, item_index: usize, hover_index: Option<usize>) -> f64 {
         let hover_index = match hover_index {
-            Some(idx) => idx,
-            None => return 1.0, // No hover, no magnification
+            Some(idx) if self.enabled => idx,
+            _ => return 1.0, // No hover or zoom disabled, no magnification
         };
 
         let distance = (item_index as i32 - hover_index as i32).abs() as usize;
@@ -42,14 +61,27 @@ impl MagnificationController {
             return self.max_scale; // Hovered item gets full magnification
         }
 
-        // Cosine interpolation for smooth falloff
-        let normalized = distance as f64 / self.range_items as f64;
+        // Cosine interpolation for smooth falloff; the farthest neighbor in
+        // range still gets a little, so it doesn't snap from 1.0
+        let normalized = distance as f64 / (self.range_items + 1) as f64;
         let cosine_factor = (1.0 + (std::f64::consts::PI * normalized).cos()) / 2.0;
         
         // Scale from 1.0 to max_scale based on cosine
         1.0 + (self.max_scale - 1.0) * cosine_factor
     }
 
+    /// Apply new zoom settings, e.g. after a config reload
+    pub fn configure(&mut self, settings: &Settings) {
+        self.max_scale = settings.hover_zoom_scale;
+        self.range_items = settings.magnification_spread;
+        self.enabled = settings.hover_zoom;
+    }
+
+    /// Whether hovering magnifies the dock items
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
     /// Set the currently hovered item index
     pub fn set_hover(&mut self, index: Option<usize>) {
         if self.current_hover != index {
@@ -67,6 +99,28 @@ impl MagnificationController {
     pub fn animation_duration_ms(&self) -> u32 {
         self.animation_duration_ms
     }
+
+    /// Mark the scale animation as running
+    ///
+    /// Returns `false` if it already was, so only one frame loop is started.
+    pub fn start_animation(&mut self) -> bool {
+        !std::mem::replace(&mut self.is_animating, true)
+    }
+
+    /// Mark the scale animation as settled
+    pub fn stop_animation(&mut self) {
+        self.is_animating = false;
+    }
+}
+
+/// One animation frame of a scale moving from `current` toward `target`
+pub fn ease_scale(current: f64, target: f64) -> f64 {
+    let step = (target - current) * EASE_FACTOR;
+    if (target - current).abs() < SETTLE_THRESHOLD {
+        target
+    } else {
+        current + step
+    }
 }
 
 impl Default for MagnificationController {
@@ -75,3 +129,69 @@ impl Default for MagnificationController {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ease_scale_converges() {
+        let mut scale = 1.0;
+        let mut frames = 0;
+        while scale != 1.5 {
+            let next = ease_scale(scale, 1.5);
+            assert!(next > scale && next <= 1.5);
+            scale = next;
+            frames += 1;
+            assert!(frames < 60, "scale never settled");
+        }
+
+        // Shrinking back works the same way, and a settled scale stays put
+        assert!(ease_scale(1.5, 1.0) < 1.5);
+        assert_eq!(ease_scale(1.0, 1.0), 1.0);
+    }
+
+    #[test]
+    fn test_scale_falls_off_with_distance() {
+        let mag = MagnificationController::new(1.5, 3);
+        let hover = Some(5);
+
+        let scales: Vec<f64> = (0..=3).map(|d| mag.calculate_scale(5 + d, hover)).collect();
+        assert_eq!(scales[0], 1.5);
+        for pair in scales.windows(2) {
+            assert!(pair[1] < pair[0], "{:?} is not decreasing", scales);
+        }
+        // Every neighbor in range is partially magnified
+        assert!(scales[3] > 1.0);
+
+        // Symmetric on both sides, nothing beyond the spread
+        assert_eq!(mag.calculate_scale(3, hover), scales[2]);
+        assert_eq!(mag.calculate_scale(9, hover), 1.0);
+        assert_eq!(mag.calculate_scale(1, hover), 1.0);
+
+        // Leaving the dock resets everything
+        assert!((0..10).all(|i| mag.calculate_scale(i, None) == 1.0));
+    }
+
+    #[test]
+    fn test_hover_zoom_disabled() {
+        let mut settings = Settings { hover_zoom: false, ..Settings::default() };
+        let mut mag = MagnificationController::from_settings(&settings);
+        assert!(!mag.is_enabled());
+        assert!((0..5).all(|i| mag.calculate_scale(i, Some(2)) == 1.0));
+
+        settings.hover_zoom = true;
+        mag.configure(&settings);
+        assert!(mag.is_enabled());
+        assert_eq!(mag.calculate_scale(2, Some(2)), settings.hover_zoom_scale);
+    }
+
+    #[test]
+    fn test_single_animation_loop() {
+        let mut mag = MagnificationController::default();
+        assert!(mag.start_animation());
+        assert!(!mag.start_animation());
+        mag.stop_animation();
+        assert!(mag.start_animation());
+    }
+}
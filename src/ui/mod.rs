@@ -15,19 +15,26 @@ mod progress_ring;
 mod search_overlay;
 pub mod drag_drop;
 mod trash_item;
+mod drive_item;
+mod recent_item;
 mod expose_view;
 mod stack_item;
+mod notification_item;
+mod popover_menu;
 
 pub use window::DockWindow;
-pub use dock_item::DockItem;
-pub use style::load_global_styles;
+pub use dock_item::{DockItem, DockItemAction, DockItemActions, ProgressDisplay};
+pub use style::{load_global_styles, load_theme_styles, create_settings_provider, apply_settings_css, remove_provider};
 pub use running_indicator::{RunningIndicator, RunningState};
 pub use magnification::MagnificationController;
 pub use settings_dialog::SettingsDialog;
 pub use badge::{Badge, BadgeType, BadgePosition};
-pub use window_preview::WindowPreview;
+pub use window_preview::{WindowPreview, PreviewSources};
 pub use progress_ring::ProgressRing;
 pub use search_overlay::{SearchOverlay, SearchResult};
 pub use trash_item::{TrashItem, TrashState};
-pub use stack_item::{StackItem, StackViewMode};
+pub use drive_item::DriveItem;
+pub use recent_item::RecentItem;
+pub use stack_item::StackItem;
+pub use notification_item::NotificationItem;
 
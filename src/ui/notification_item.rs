@@ -0,0 +1,67 @@
+//! Notification summary dock item
+//!
+//! A special dock item showing the total unread count across all apps
+//! that report badges via D-Bus.
+
+use gtk::prelude::*;
+use gtk::{Button, Image};
+use log::debug;
+
+use crate::ui::{Badge, BadgePosition, BadgeType};
+
+/// Notification summary dock item
+pub struct NotificationItem {
+    button: Button,
+    badge: Badge,
+    count: u32,
+}
+
+impl NotificationItem {
+    /// Create a new notification summary item
+    pub fn new(icon_size: u32) -> Self {
+        let image = Image::from_icon_name("preferences-system-notifications");
+        image.set_pixel_size(icon_size as i32);
+        image.add_css_class("dock-item-icon");
+
+        let badge = Badge::new(BadgeType::Count(0), BadgePosition::TopRight);
+
+        let overlay = gtk::Overlay::builder().child(&image).build();
+        overlay.add_overlay(badge.widget());
+
+        let button = Button::builder()
+            .css_classes(vec!["dock-item", "dock-item-notifications"])
+            .tooltip_text("No unread notifications")
+            .child(&overlay)
+            .build();
+
+        Self {
+            button,
+            badge,
+            count: 0,
+        }
+    }
+
+    /// Get the widget
+    pub fn widget(&self) -> &Button {
+        &self.button
+    }
+
+    /// Update the displayed unread count
+    pub fn set_count(&mut self, count: u32) {
+        if count == self.count {
+            return;
+        }
+
+        self.count = count;
+        self.badge.set_type(BadgeType::Count(count));
+
+        let tooltip = match count {
+            0 => "No unread notifications".to_string(),
+            1 => "1 unread notification".to_string(),
+            n => format!("{} unread notifications", n),
+        };
+        self.button.set_tooltip_text(Some(&tooltip));
+
+        debug!("Notification summary count: {}", count);
+    }
+}
@@ -0,0 +1,43 @@
+//! Popover menus shared by the dock items
+//!
+//! Context menus are built on demand, so each popover is detached from its
+//! parent once closed instead of piling up on the item.
+
+use gtk::glib;
+use gtk::prelude::*;
+
+/// Vertical container for the menu's buttons
+pub fn menu_box() -> gtk::Box {
+    gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(4)
+        .margin_top(8)
+        .margin_bottom(8)
+        .margin_start(8)
+        .margin_end(8)
+        .build()
+}
+
+/// Create a popover showing `child`, attached to `parent` until it closes
+pub fn attach(parent: &impl IsA<gtk::Widget>, child: &impl IsA<gtk::Widget>) -> gtk::Popover {
+    let popover = gtk::Popover::builder()
+        .child(child)
+        .has_arrow(true)
+        .build();
+    popover.set_parent(parent);
+
+    // Detach once closed so repeated menus don't pile up
+    popover.connect_closed(|popover| {
+        let popover = popover.clone();
+        glib::idle_add_local_once(move || popover.unparent());
+    });
+
+    popover
+}
+
+/// Close the popover containing `widget`, e.g. a clicked menu button
+pub fn close(widget: &impl IsA<gtk::Widget>) {
+    if let Some(popover) = widget.ancestor(gtk::Popover::static_type()).and_downcast::<gtk::Popover>() {
+        popover.popdown();
+    }
+}
@@ -10,12 +10,26 @@ use std::cell::Cell;
 use std::rc::Rc;
 use log::debug;
 
+use crate::services::ThemeColors;
+
+/// Default ring color (light blue)
+const DEFAULT_COLOR: (f64, f64, f64) = (0.4, 0.8, 1.0);
+
+/// Default ring line width in pixels
+const DEFAULT_LINE_WIDTH: f64 = 3.0;
+
 /// Progress ring widget
 pub struct ProgressRing {
     drawing_area: DrawingArea,
     progress: Rc<Cell<f64>>,
     is_indeterminate: Rc<Cell<bool>>,
     animation_angle: Rc<Cell<f64>>,
+    color: Rc<Cell<(f64, f64, f64)>>,
+    line_width: Rc<Cell<f64>>,
+    counterclockwise: Rc<Cell<bool>>,
+    target: Rc<Cell<f64>>,
+    is_easing: Rc<Cell<bool>>,
+    is_spinning: Rc<Cell<bool>>,
 }
 
 impl ProgressRing {
@@ -30,18 +44,27 @@ impl ProgressRing {
         let progress: Rc<Cell<f64>> = Rc::new(Cell::new(0.0_f64));
         let is_indeterminate: Rc<Cell<bool>> = Rc::new(Cell::new(false));
         let animation_angle: Rc<Cell<f64>> = Rc::new(Cell::new(0.0_f64));
+        let color = Rc::new(Cell::new(DEFAULT_COLOR));
+        let line_width = Rc::new(Cell::new(DEFAULT_LINE_WIDTH));
+        let counterclockwise = Rc::new(Cell::new(false));
 
         // Set up drawing function
         let progress_clone = Rc::clone(&progress);
         let is_indeterminate_clone = Rc::clone(&is_indeterminate);
         let animation_angle_clone = Rc::clone(&animation_angle);
+        let color_clone = Rc::clone(&color);
+        let line_width_clone = Rc::clone(&line_width);
+        let counterclockwise_clone = Rc::clone(&counterclockwise);
 
         drawing_area.set_draw_func(move |_area, cr, width, height| {
             let size = width.min(height) as f64;
             let center_x = width as f64 / 2.0;
             let center_y = height as f64 / 2.0;
-            let radius = (size / 2.0) - 4.0;
-            let line_width = 3.0;
+            let line_width = line_width_clone.get();
+            // Keep thick lines inside the widget
+            let radius = (size / 2.0) - (line_width / 2.0 + 2.5).max(4.0);
+            let (red, green, blue) = color_clone.get();
+            let counterclockwise = counterclockwise_clone.get();
 
             // Background ring
             cr.set_line_width(line_width);
@@ -56,37 +79,54 @@ impl ProgressRing {
                 let arc_length = PI * 0.75;
                 
                 // Gradient effect for indeterminate
-                cr.set_source_rgba(0.4, 0.8, 1.0, 1.0);
+                cr.set_source_rgba(red, green, blue, 1.0);
                 cr.set_line_cap(cairo::LineCap::Round);
-                cr.arc(
-                    center_x,
-                    center_y,
-                    radius,
-                    angle - PI / 2.0,
-                    angle - PI / 2.0 + arc_length,
-                );
+                if counterclockwise {
+                    cr.arc_negative(
+                        center_x,
+                        center_y,
+                        radius,
+                        -angle - PI / 2.0,
+                        -angle - PI / 2.0 - arc_length,
+                    );
+                } else {
+                    cr.arc(
+                        center_x,
+                        center_y,
+                        radius,
+                        angle - PI / 2.0,
+                        angle - PI / 2.0 + arc_length,
+                    );
+                }
                 let _ = cr.stroke();
             } else {
                 // Determinate: progress arc
                 let progress_val = f64::clamp(progress_clone.get(), 0.0, 1.0);
                 
                 if progress_val > 0.0 {
-                    // Progress color (blue gradient)
-                    cr.set_source_rgba(0.4, 0.8, 1.0, 1.0);
+                    // Progress color
+                    cr.set_source_rgba(red, green, blue, 1.0);
                     cr.set_line_cap(cairo::LineCap::Round);
                     
-                    // Draw from top (-PI/2) clockwise
+                    // Draw from top (-PI/2) in the configured direction
                     let start_angle = -PI / 2.0;
-                    let end_angle = start_angle + (2.0 * PI * progress_val);
+                    let sweep = 2.0 * PI * progress_val;
+                    let arc = |cr: &cairo::Context| {
+                        if counterclockwise {
+                            cr.arc_negative(center_x, center_y, radius, start_angle, start_angle - sweep);
+                        } else {
+                            cr.arc(center_x, center_y, radius, start_angle, start_angle + sweep);
+                        }
+                    };
                     
-                    cr.arc(center_x, center_y, radius, start_angle, end_angle);
+                    arc(cr);
                     let _ = cr.stroke();
                     
                     // Add glow effect for high progress
                     if progress_val > 0.9 {
-                        cr.set_source_rgba(0.4, 0.8, 1.0, 0.3);
+                        cr.set_source_rgba(red, green, blue, 0.3);
                         cr.set_line_width(line_width + 4.0);
-                        cr.arc(center_x, center_y, radius, start_angle, end_angle);
+                        arc(cr);
                         let _ = cr.stroke();
                     }
                 }
@@ -98,6 +138,12 @@ impl ProgressRing {
             progress,
             is_indeterminate,
             animation_angle,
+            color,
+            line_width,
+            counterclockwise,
+            target: Rc::new(Cell::new(0.0)),
+            is_easing: Rc::new(Cell::new(false)),
+            is_spinning: Rc::new(Cell::new(false)),
         }
     }
 
@@ -109,16 +155,86 @@ impl ProgressRing {
     /// Set progress (0.0 - 1.0)
     pub fn set_progress(&self, value: f64) {
         self.progress.set(value.clamp(0.0, 1.0));
+        self.target.set(value.clamp(0.0, 1.0));
         self.is_indeterminate.set(false);
         self.drawing_area.queue_draw();
         debug!("Progress ring set to {:.0}%", value * 100.0);
     }
 
+    /// Animate the progress toward `value` (0.0 - 1.0)
+    ///
+    /// The ring eases a fraction of the remaining distance each frame, so
+    /// frequent updates keep it moving smoothly instead of jumping.
+    pub fn animate_to(&self, value: f64) {
+        self.target.set(value.clamp(0.0, 1.0));
+        self.is_indeterminate.set(false);
+
+        if self.is_easing.replace(true) {
+            return;
+        }
+
+        let drawing_area = self.drawing_area.clone();
+        let progress = Rc::clone(&self.progress);
+        let target = Rc::clone(&self.target);
+        let is_easing = Rc::clone(&self.is_easing);
+
+        glib::timeout_add_local(std::time::Duration::from_millis(16), move || {
+            let next = ease_toward(progress.get(), target.get());
+            progress.set(next);
+            drawing_area.queue_draw();
+
+            if next == target.get() {
+                is_easing.set(false);
+                return glib::ControlFlow::Break;
+            }
+            glib::ControlFlow::Continue
+        });
+    }
+
     /// Get current progress
     pub fn get_progress(&self) -> f64 {
         self.progress.get()
     }
 
+    /// Set the ring color (RGB, 0.0 - 1.0), e.g. the theme accent
+    pub fn set_color(&self, r: f64, g: f64, b: f64) {
+        self.color.set((r, g, b));
+        self.drawing_area.queue_draw();
+    }
+
+    /// Color the ring with the theme's accent color
+    pub fn apply_theme(&self, colors: &ThemeColors) {
+        let (r, g, b) = colors.accent_color;
+        self.set_color(r, g, b);
+    }
+
+    /// Get the ring color
+    pub fn color(&self) -> (f64, f64, f64) {
+        self.color.get()
+    }
+
+    /// Set the ring line width in pixels
+    pub fn set_line_width(&self, width: f64) {
+        self.line_width.set(width.max(0.5));
+        self.drawing_area.queue_draw();
+    }
+
+    /// Get the ring line width
+    pub fn line_width(&self) -> f64 {
+        self.line_width.get()
+    }
+
+    /// Fill the ring counterclockwise instead of clockwise
+    pub fn set_counterclockwise(&self, counterclockwise: bool) {
+        self.counterclockwise.set(counterclockwise);
+        self.drawing_area.queue_draw();
+    }
+
+    /// Whether the ring fills counterclockwise
+    pub fn is_counterclockwise(&self) -> bool {
+        self.counterclockwise.get()
+    }
+
     /// Set indeterminate mode (spinning)
     pub fn set_indeterminate(&self, indeterminate: bool) {
         self.is_indeterminate.set(indeterminate);
@@ -131,13 +247,21 @@ impl ProgressRing {
     }
 
     /// Start spinning animation for indeterminate mode
+    ///
+    /// Does nothing while the ring is already spinning.
     fn start_animation(&self) {
+        if self.is_spinning.replace(true) {
+            return;
+        }
+
         let drawing_area = self.drawing_area.clone();
         let animation_angle = Rc::clone(&self.animation_angle);
         let is_indeterminate = Rc::clone(&self.is_indeterminate);
+        let is_spinning = Rc::clone(&self.is_spinning);
 
         glib::timeout_add_local(std::time::Duration::from_millis(16), move || {
             if !is_indeterminate.get() {
+                is_spinning.set(false);
                 return glib::ControlFlow::Break;
             }
             
@@ -161,9 +285,58 @@ impl ProgressRing {
     }
 }
 
+/// One animation frame of progress moving from `current` toward `target`
+fn ease_toward(current: f64, target: f64) -> f64 {
+    let step = (target - current) * 0.2;
+    if step.abs() < 0.005 {
+        target
+    } else {
+        current + step
+    }
+}
+
 impl Default for ProgressRing {
     fn default() -> Self {
         Self::new(24)
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gtk::test]
+    fn test_progress_and_appearance_settings() {
+        let ring = ProgressRing::new(24);
+        ring.set_progress(0.42);
+        assert_eq!(ring.get_progress(), 0.42);
+        ring.set_progress(1.5);
+        assert_eq!(ring.get_progress(), 1.0);
+
+        assert_eq!(ring.color(), DEFAULT_COLOR);
+        ring.set_color(0.9, 0.3, 0.1);
+        assert_eq!(ring.color(), (0.9, 0.3, 0.1));
+
+        ring.set_line_width(5.0);
+        assert_eq!(ring.line_width(), 5.0);
+
+        assert!(!ring.is_counterclockwise());
+        ring.set_counterclockwise(true);
+        assert!(ring.is_counterclockwise());
+    }
+
+    #[test]
+    fn test_ease_toward_reaches_target() {
+        let mut value = 0.0;
+        let mut frames = 0;
+        while value != 0.5 {
+            let next = ease_toward(value, 0.5);
+            assert!(next > value && next <= 0.5);
+            value = next;
+            frames += 1;
+        }
+        assert!(frames < 30);
+        assert!((ease_toward(0.8, 0.3) - 0.7).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,143 @@
+//! Recent files dock item
+//!
+//! Shows the recently used files in a popup list, the same way a folder
+//! stack's list view does, and opens the chosen one with `xdg-open`.
+
+use gtk::prelude::*;
+use gtk::{Button, Image, Label, Orientation, ScrolledWindow};
+use gtk::gio;
+use gtk::glib::SignalHandlerId;
+use log::{debug, info, warn};
+
+use crate::services::{RecentEntry, RecentFilesService};
+use crate::ui::popover_menu;
+
+/// How many recent files the popup lists
+const RECENT_LIMIT: usize = 15;
+
+/// Recent files dock item
+pub struct RecentItem {
+    button: Button,
+    changed_handler: Option<SignalHandlerId>,
+}
+
+impl RecentItem {
+    /// Create a new recent files item backed by `service`
+    pub fn new(service: &RecentFilesService, icon_size: u32) -> Self {
+        let icon = Image::from_icon_name("document-open-recent");
+        icon.set_pixel_size(icon_size as i32);
+        icon.add_css_class("dock-item-icon");
+
+        let button = Button::builder()
+            .css_classes(vec!["dock-item", "dock-item-recent"])
+            .tooltip_text("Recent Files")
+            .child(&icon)
+            .build();
+
+        // The popup is attached per click and detached again once it closes
+        let click_service = service.clone();
+        button.connect_clicked(move |btn| {
+            let list = Self::build_list_view(&click_service.get_recent(RECENT_LIMIT));
+            let popup = popover_menu::attach(btn, &list);
+            popup.add_css_class("stack-popup");
+            popup.popup();
+        });
+
+        // Keep the list current while the item is in the dock
+        service.refresh();
+        let changed_service = service.clone();
+        let changed_handler = gtk::RecentManager::default().connect_changed(move |_| {
+            debug!("Recent files changed");
+            changed_service.refresh();
+        });
+
+        info!("Recent files item created");
+        Self {
+            button,
+            changed_handler: Some(changed_handler),
+        }
+    }
+
+    /// Get the widget
+    pub fn widget(&self) -> &Button {
+        &self.button
+    }
+
+    /// Build the popup's list of recent files
+    fn build_list_view(entries: &[RecentEntry]) -> gtk::Widget {
+        if entries.is_empty() {
+            let label = Label::builder()
+                .label("No recent files")
+                .css_classes(vec!["stack-empty-label"])
+                .build();
+            return label.upcast();
+        }
+
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(vec!["stack-list"])
+            .build();
+
+        for entry in entries {
+            list_box.append(&Self::create_list_row(entry));
+        }
+
+        let scroll = ScrolledWindow::builder()
+            .min_content_height(200)
+            .max_content_height(400)
+            .min_content_width(250)
+            .child(&list_box)
+            .build();
+
+        scroll.upcast()
+    }
+
+    /// Create a list row that opens `entry` on click
+    fn create_list_row(entry: &RecentEntry) -> gtk::Widget {
+        let row = gtk::Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(8)
+            .margin_start(8)
+            .margin_end(8)
+            .margin_top(4)
+            .margin_bottom(4)
+            .build();
+
+        let icon = Image::from_gicon(&gio::content_type_get_icon(&entry.mime_type));
+        icon.set_pixel_size(24);
+
+        let label = Label::builder()
+            .label(&entry.name)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .xalign(0.0)
+            .hexpand(true)
+            .build();
+
+        row.append(&icon);
+        row.append(&label);
+
+        let button = Button::builder()
+            .child(&row)
+            .css_classes(vec!["stack-list-row"])
+            .tooltip_text(&entry.uri)
+            .build();
+
+        let uri = entry.uri.clone();
+        button.connect_clicked(move |btn| {
+            popover_menu::close(btn);
+            if let Err(e) = std::process::Command::new("xdg-open").arg(&uri).spawn() {
+                warn!("Failed to open {}: {}", uri, e);
+            }
+        });
+
+        button.upcast()
+    }
+}
+
+impl Drop for RecentItem {
+    fn drop(&mut self) {
+        if let Some(handler) = self.changed_handler.take() {
+            gtk::RecentManager::default().disconnect(handler);
+        }
+    }
+}
@@ -6,6 +6,11 @@ use gtk::prelude::*;
 use gtk::{Box, Orientation};
 use log::debug;
 
+use crate::config::{DockPosition, IndicatorStyle};
+
+/// Windows beyond this don't widen a line or pill any further
+const MAX_BAR_WINDOWS: u8 = 4;
+
 /// Running state for an application
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum RunningState {
@@ -21,21 +26,37 @@ pub enum RunningState {
 pub struct RunningIndicator {
     container: Box,
     state: RunningState,
+    style: IndicatorStyle,
+    orientation: Orientation,
 }
 
 impl RunningIndicator {
-    /// Create a new running indicator
+    /// Create a new running indicator drawn as dots under the icon
     pub fn new() -> Self {
+        Self::with_style(IndicatorStyle::Dots, DockPosition::Bottom)
+    }
+
+    /// Create a new running indicator drawn in `style` for a dock at `position`
+    ///
+    /// Docks on the left or right stack the marks vertically beside the icon.
+    pub fn with_style(style: IndicatorStyle, position: DockPosition) -> Self {
+        let orientation = indicator_orientation(position);
         let container = Box::builder()
-            .orientation(Orientation::Horizontal)
+            .orientation(orientation)
             .spacing(4)
             .halign(gtk::Align::Center)
+            .valign(gtk::Align::Center)
             .css_classes(vec!["running-indicator"])
             .build();
+        if orientation == Orientation::Vertical {
+            container.add_css_class("vertical");
+        }
 
         Self {
             container,
             state: RunningState::Stopped,
+            style,
+            orientation,
         }
     }
 
@@ -59,9 +80,14 @@ impl RunningIndicator {
         self.state
     }
 
+    /// Get the indicator style
+    pub fn style(&self) -> IndicatorStyle {
+        self.style
+    }
+
     /// Update the visual display based on current state
     fn update_display(&self) {
-        // Clear existing dots
+        // Clear existing dots or bar
         while let Some(child) = self.container.first_child() {
             self.container.remove(&child);
         }
@@ -78,18 +104,22 @@ impl RunningIndicator {
                 self.container.add_css_class("running");
                 self.container.remove_css_class("focused");
                 
-                // Add dots for each window (max 3 visible)
-                let visible_dots = window_count.min(3) as usize;
-                for _ in 0..visible_dots {
-                    let dot = Self::create_dot(false);
-                    self.container.append(&dot);
-                }
-                
-                // If more than 3 windows, show a number badge
-                if window_count > 3 {
-                    let label = gtk::Label::new(Some(&format!("+{}", window_count - 3)));
-                    label.add_css_class("window-count-badge");
-                    self.container.append(&label);
+                if self.style == IndicatorStyle::Dots {
+                    // Add dots for each window (max 3 visible)
+                    let visible_dots = window_count.min(3) as usize;
+                    for _ in 0..visible_dots {
+                        let dot = Self::create_dot(false);
+                        self.container.append(&dot);
+                    }
+                    
+                    // If more than 3 windows, show a number badge
+                    if window_count > 3 {
+                        let label = gtk::Label::new(Some(&format!("+{}", window_count - 3)));
+                        label.add_css_class("window-count-badge");
+                        self.container.append(&label);
+                    }
+                } else {
+                    self.container.append(&Self::create_bar(self.style, self.orientation, window_count, false));
                 }
             }
             RunningState::Focused { window_count } => {
@@ -97,19 +127,23 @@ impl RunningIndicator {
                 self.container.add_css_class("running");
                 self.container.add_css_class("focused");
                 
-                // Add dots with focused styling
-                let visible_dots = window_count.min(3) as usize;
-                for i in 0..visible_dots {
-                    let is_focused = i == 0; // First dot is brighter when focused
-                    let dot = Self::create_dot(is_focused);
-                    self.container.append(&dot);
-                }
-                
-                if window_count > 3 {
-                    let label = gtk::Label::new(Some(&format!("+{}", window_count - 3)));
-                    label.add_css_class("window-count-badge");
-                    label.add_css_class("focused");
-                    self.container.append(&label);
+                if self.style == IndicatorStyle::Dots {
+                    // Add dots with focused styling
+                    let visible_dots = window_count.min(3) as usize;
+                    for i in 0..visible_dots {
+                        let is_focused = i == 0; // First dot is brighter when focused
+                        let dot = Self::create_dot(is_focused);
+                        self.container.append(&dot);
+                    }
+                    
+                    if window_count > 3 {
+                        let label = gtk::Label::new(Some(&format!("+{}", window_count - 3)));
+                        label.add_css_class("window-count-badge");
+                        label.add_css_class("focused");
+                        self.container.append(&label);
+                    }
+                } else {
+                    self.container.append(&Self::create_bar(self.style, self.orientation, window_count, true));
                 }
             }
         }
@@ -131,6 +165,49 @@ impl RunningIndicator {
         
         dot.upcast()
     }
+
+    /// Create a line or pill bar sized by the window count
+    fn create_bar(style: IndicatorStyle, orientation: Orientation, window_count: u8, is_focused: bool) -> gtk::Widget {
+        let (length, thickness) = bar_size(style, window_count);
+        let (width, height) = match orientation {
+            Orientation::Vertical => (thickness, length),
+            _ => (length, thickness),
+        };
+        let mut classes = vec![
+            "indicator-bar",
+            if style == IndicatorStyle::Pill { "indicator-pill" } else { "indicator-line" },
+        ];
+        if is_focused {
+            classes.push("focused-bar");
+        }
+
+        let bar = Box::builder()
+            .width_request(width)
+            .height_request(height)
+            .halign(gtk::Align::Center)
+            .valign(gtk::Align::Center)
+            .css_classes(classes)
+            .build();
+
+        bar.upcast()
+    }
+}
+
+/// Direction the marks are laid out in for a dock at `position`
+fn indicator_orientation(position: DockPosition) -> Orientation {
+    match position {
+        DockPosition::Left | DockPosition::Right => Orientation::Vertical,
+        DockPosition::Top | DockPosition::Bottom => Orientation::Horizontal,
+    }
+}
+
+/// Length and thickness of a line or pill bar for `window_count` windows
+fn bar_size(style: IndicatorStyle, window_count: u8) -> (i32, i32) {
+    let extra = window_count.clamp(1, MAX_BAR_WINDOWS) as i32 - 1;
+    match style {
+        IndicatorStyle::Pill => (10 + extra * 6, 6),
+        _ => (12 + extra * 6, 3),
+    }
 }
 
 impl Default for RunningIndicator {
@@ -139,3 +216,68 @@ impl Default for RunningIndicator {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn children(indicator: &RunningIndicator) -> Vec<gtk::Widget> {
+        let mut children = Vec::new();
+        let mut child = indicator.widget().first_child();
+        while let Some(widget) = child {
+            child = widget.next_sibling();
+            children.push(widget);
+        }
+        children
+    }
+
+    #[test]
+    fn test_bar_size_grows_with_windows() {
+        assert_eq!(bar_size(IndicatorStyle::Line, 1), (12, 3));
+        assert_eq!(bar_size(IndicatorStyle::Line, 3), (24, 3));
+        assert_eq!(bar_size(IndicatorStyle::Line, 9), bar_size(IndicatorStyle::Line, MAX_BAR_WINDOWS));
+        assert_eq!(bar_size(IndicatorStyle::Pill, 2), (16, 6));
+    }
+
+    #[gtk::test]
+    fn test_style_structure() {
+        let mut dots = RunningIndicator::new();
+        dots.set_state(RunningState::Running { window_count: 5 });
+        let widgets = children(&dots);
+        assert_eq!(widgets.len(), 4);
+        assert!(widgets[..3].iter().all(|w| w.has_css_class("indicator-dot")));
+        assert!(widgets[3].has_css_class("window-count-badge"));
+
+        for style in [IndicatorStyle::Line, IndicatorStyle::Pill] {
+            let mut indicator = RunningIndicator::with_style(style, DockPosition::Bottom);
+            indicator.set_state(RunningState::Running { window_count: 2 });
+            let widgets = children(&indicator);
+            assert_eq!(widgets.len(), 1);
+            assert!(widgets[0].has_css_class("indicator-bar"));
+            assert!(!widgets[0].has_css_class("focused-bar"));
+            assert_eq!(widgets[0].width_request(), bar_size(style, 2).0);
+
+            indicator.set_state(RunningState::Focused { window_count: 2 });
+            assert!(children(&indicator)[0].has_css_class("focused-bar"));
+
+            indicator.set_state(RunningState::Stopped);
+            assert!(children(&indicator).is_empty());
+        }
+    }
+
+    #[gtk::test]
+    fn test_vertical_dock_stacks_marks_vertically() {
+        for position in [DockPosition::Left, DockPosition::Right] {
+            let indicator = RunningIndicator::with_style(IndicatorStyle::Dots, position);
+            assert_eq!(indicator.widget().orientation(), Orientation::Vertical);
+            assert!(indicator.widget().has_css_class("vertical"));
+        }
+        assert_eq!(RunningIndicator::new().widget().orientation(), Orientation::Horizontal);
+
+        // Bars run along the edge too
+        let mut line = RunningIndicator::with_style(IndicatorStyle::Line, DockPosition::Left);
+        line.set_state(RunningState::Running { window_count: 1 });
+        let bar = children(&line).remove(0);
+        assert_eq!((bar.width_request(), bar.height_request()), (3, 12));
+    }
+}
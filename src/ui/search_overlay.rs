@@ -5,9 +5,15 @@
 use gtk::prelude::*;
 use gtk::{Box as GtkBox, Entry, Label, ListBox, ListBoxRow, Overlay};
 use gtk::glib;
-use log::debug;
+use log::{debug, error};
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
+use std::time::Duration;
+
+use crate::utils::desktop_entry::{discover_applications, DesktopEntry};
+use crate::utils::launch_history::{self, LaunchHistory};
+use crate::utils::launcher;
 
 /// Search result item
 #[derive(Debug, Clone)]
@@ -16,17 +22,32 @@ pub struct SearchResult {
     pub name: String,
     pub icon_name: String,
     pub command: String,
+    /// Path of the app's .desktop file, which dragging the result provides
+    pub desktop_file: Option<String>,
+    /// Extra environment for the launch, from the pinned app
+    pub env: HashMap<String, String>,
     pub score: u32,
 }
 
+impl SearchResult {
+    /// Launch the app like its dock item would, through its desktop file
+    pub fn launch(&self, terminal: Option<&str>) -> anyhow::Result<()> {
+        launcher::launch_app(&self.command, self.desktop_file.as_deref(), terminal, &self.env)
+    }
+}
+
 /// Search overlay widget
 pub struct SearchOverlay {
     overlay: Overlay,
     search_box: GtkBox,
     entry: Entry,
     results_list: ListBox,
+    clear_history_button: gtk::Button,
     visible: Rc<RefCell<bool>>,
     results: Rc<RefCell<Vec<SearchResult>>>,
+    /// Results currently rendered as rows, in row order
+    shown: Rc<RefCell<Vec<SearchResult>>>,
+    debounce: Debouncer,
     on_select: Rc<RefCell<Option<Box<dyn Fn(&SearchResult)>>>>,
 }
 
@@ -59,8 +80,17 @@ impl SearchOverlay {
             .selection_mode(gtk::SelectionMode::Single)
             .build();
 
+        // Shown while the most used apps are listed
+        let clear_history_button = gtk::Button::builder()
+            .label("Clear History")
+            .halign(gtk::Align::End)
+            .css_classes(vec!["flat", "search-clear-history"])
+            .visible(false)
+            .build();
+
         search_box.append(&entry);
         search_box.append(&results_list);
+        search_box.append(&clear_history_button);
 
         let visible = Rc::new(RefCell::new(false));
         let results = Rc::new(RefCell::new(Vec::new()));
@@ -71,8 +101,11 @@ impl SearchOverlay {
             search_box,
             entry,
             results_list,
+            clear_history_button,
             visible,
             results,
+            shown: Rc::new(RefCell::new(Vec::new())),
+            debounce: Debouncer::new(SEARCH_DEBOUNCE),
             on_select,
         };
 
@@ -88,82 +121,115 @@ impl SearchOverlay {
         let visible = Rc::clone(&self.visible);
         let search_box = self.search_box.clone();
 
-        // Handle text changes
+        // Handle text changes, rebuilding once typing pauses
+        let clear_history_button = self.clear_history_button.clone();
+        let shown = Rc::clone(&self.shown);
+        let debounce = self.debounce.clone();
         self.entry.connect_changed(move |entry| {
             let query = entry.text().to_string();
-            debug!("Search query: {}", query);
-            
-            // Clear previous results
-            while let Some(row) = results_list.first_child() {
-                results_list.remove(&row);
-            }
-            
-            if query.is_empty() {
-                return;
-            }
-            
-            // Filter results (this would be populated by set_apps)
-            let results_guard = results.borrow();
-            let query_lower = query.to_lowercase();
-            
-            let mut filtered: Vec<_> = results_guard
-                .iter()
-                .filter(|r| r.name.to_lowercase().contains(&query_lower))
-                .cloned()
-                .collect();
-            
-            // Sort by relevance (starts with > contains)
-            filtered.sort_by(|a, b| {
-                let a_starts = a.name.to_lowercase().starts_with(&query_lower);
-                let b_starts = b.name.to_lowercase().starts_with(&query_lower);
-                b_starts.cmp(&a_starts).then_with(|| a.name.cmp(&b.name))
+            let results_list = results_list.clone();
+            let clear_history_button = clear_history_button.clone();
+            let shown = Rc::clone(&shown);
+            let results = Rc::clone(&results);
+            debounce.schedule(move || {
+                debug!("Search query: {}", query);
+                Self::render_results(&results_list, &clear_history_button, &shown, &results.borrow(), &query);
             });
-            
-            // Show top results
-            for result in filtered.iter().take(8) {
-                let row = Self::create_result_row(result);
-                results_list.append(&row);
-            }
+        });
+        
+        // Forget the most used apps
+        let results_list = self.results_list.clone();
+        let results = Rc::clone(&self.results);
+        let shown = Rc::clone(&self.shown);
+        self.clear_history_button.connect_clicked(move |button| {
+            launch_history::clear_history();
+            Self::render_results(&results_list, button, &shown, &results.borrow(), "");
         });
 
         // Handle selection
         let on_select_clone = Rc::clone(&self.on_select);
-        let results_clone = Rc::clone(&self.results);
-        let entry = self.entry.clone();
+        let shown = Rc::clone(&self.shown);
         
         self.results_list.connect_row_activated(move |_list, row| {
-            let idx = row.index() as usize;
-            let results_guard = results_clone.borrow();
-            let query = entry.text().to_string().to_lowercase();
+            // Cloned so the callback may re-render the list
+            let result = shown.borrow().get(row.index() as usize).cloned();
             
-            let filtered: Vec<_> = results_guard
-                .iter()
-                .filter(|r| r.name.to_lowercase().contains(&query))
-                .collect();
-            
-            if let Some(result) = filtered.get(idx) {
-                if let Some(callback) = on_select_clone.borrow().as_ref() {
-                    callback(result);
+            if let Some(result) = result {
+                match on_select_clone.borrow().as_ref() {
+                    Some(callback) => callback(&result),
+                    None => {
+                        debug!("Launching '{}' from search", result.name);
+                        if let Err(e) = result.launch(None) {
+                            error!("Failed to launch '{}': {}", result.command, e);
+                        }
+                    }
                 }
+                *visible.borrow_mut() = false;
+                search_box.set_visible(false);
             }
         });
 
-        // Handle Escape key
+        // Handle Escape, arrow keys and Enter
         let visible_clone = Rc::clone(&self.visible);
         let search_box_clone = self.search_box.clone();
+        let results_list = self.results_list.clone();
         
         let key_controller = gtk::EventControllerKey::new();
+        // Capture so the entry's text handling doesn't swallow the arrows
+        key_controller.set_propagation_phase(gtk::PropagationPhase::Capture);
         key_controller.connect_key_pressed(move |_, key, _, _| {
-            if key == gtk::gdk::Key::Escape {
-                *visible_clone.borrow_mut() = false;
-                search_box_clone.set_visible(false);
-                return glib::Propagation::Stop;
+            match key {
+                gtk::gdk::Key::Escape => {
+                    *visible_clone.borrow_mut() = false;
+                    search_box_clone.set_visible(false);
+                }
+                gtk::gdk::Key::Down | gtk::gdk::Key::Up => {
+                    let len = Self::row_count(&results_list);
+                    let current = results_list.selected_row().map(|row| row.index() as usize);
+                    let next = step_selection(current, len, key == gtk::gdk::Key::Down);
+                    if let Some(row) = next.and_then(|index| results_list.row_at_index(index as i32)) {
+                        results_list.select_row(Some(&row));
+                    }
+                }
+                gtk::gdk::Key::Return | gtk::gdk::Key::KP_Enter => {
+                    // Activating the row goes through connect_row_activated
+                    let row = results_list.selected_row().or_else(|| results_list.row_at_index(0));
+                    if let Some(row) = row {
+                        row.activate();
+                    }
+                }
+                _ => return glib::Propagation::Proceed,
             }
-            glib::Propagation::Proceed
+            glib::Propagation::Stop
         });
         self.entry.add_controller(key_controller);
     }
 
+    /// Replace the result rows with those for `query`
+    fn render_results(
+        results_list: &ListBox,
+        clear_history_button: &gtk::Button,
+        shown: &RefCell<Vec<SearchResult>>,
+        results: &[SearchResult],
+        query: &str,
+    ) {
+        while let Some(row) = results_list.first_child() {
+            results_list.remove(&row);
+        }
+        
+        let mut shown = shown.borrow_mut();
+        *shown = shown_results(results, query);
+        for result in shown.iter() {
+            results_list.append(&Self::create_result_row(result));
+        }
+        clear_history_button.set_visible(query.is_empty() && !shown.is_empty());
+    }
+
+    /// Number of result rows currently shown
+    fn row_count(results_list: &ListBox) -> usize {
+        std::iter::successors(results_list.first_child(), |row| row.next_sibling()).count()
+    }
+
     /// Create a result row widget
     fn create_result_row(result: &SearchResult) -> ListBoxRow {
         let row = ListBoxRow::builder()
@@ -194,6 +260,21 @@ impl SearchOverlay {
         hbox.append(&label);
         row.set_child(Some(&hbox));
 
+        // Drag onto the dock to pin, via its .desktop file drop target
+        if let Some(uri) = drag_uri(result) {
+            let drag_source = gtk::DragSource::new();
+            drag_source.set_actions(gtk::gdk::DragAction::COPY);
+            drag_source.set_content(Some(&gtk::gdk::ContentProvider::for_value(&uri.to_value())));
+            
+            let icon_weak = icon.downgrade();
+            drag_source.connect_drag_begin(move |source, _drag| {
+                if let Some(icon) = icon_weak.upgrade() {
+                    source.set_icon(Some(&gtk::WidgetPaintable::new(Some(&icon))), 16, 16);
+                }
+            });
+            row.add_controller(drag_source);
+        }
+
         row
     }
 
@@ -203,9 +284,13 @@ impl SearchOverlay {
     }
 
     /// Set the main content widget
+    ///
+    /// May be called again to replace the content, e.g. on reload.
     pub fn set_child(&self, child: &impl IsA<gtk::Widget>) {
         self.overlay.set_child(Some(child));
-        self.overlay.add_overlay(&self.search_box);
+        if self.search_box.parent().is_none() {
+            self.overlay.add_overlay(&self.search_box);
+        }
     }
 
     /// Set available apps for searching
@@ -213,7 +298,14 @@ impl SearchOverlay {
         *self.results.borrow_mut() = apps;
     }
 
+    /// Search all installed applications
+    pub fn populate_from_installed(&self) {
+        self.set_apps(installed_results(discover_applications()));
+    }
+
     /// Register selection callback
+    ///
+    /// Without one, selected apps are launched directly.
     pub fn on_select<F>(&self, callback: F)
     where
         F: Fn(&SearchResult) + 'static,
@@ -222,10 +314,18 @@ impl SearchOverlay {
     }
 
     /// Show the search overlay
+    ///
+    /// Installed apps are loaded on first show if no apps were set.
     pub fn show(&self) {
+        if self.results.borrow().is_empty() {
+            self.populate_from_installed();
+        }
         *self.visible.borrow_mut() = true;
         self.search_box.set_visible(true);
         self.entry.set_text("");
+        // The text may already have been empty, so list the most used apps here
+        self.debounce.cancel();
+        Self::render_results(&self.results_list, &self.clear_history_button, &self.shown, &self.results.borrow(), "");
         self.entry.grab_focus();
         debug!("Search overlay shown");
     }
@@ -258,3 +358,359 @@ impl Default for SearchOverlay {
     }
 }
 
+/// Maximum number of results shown at once
+const MAX_RESULTS: usize = 8;
+
+/// Pause in typing before the results are rebuilt
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(80);
+
+/// Runs only the last of a quick succession of scheduled calls
+///
+/// Each `schedule` cancels the call still waiting from the previous one.
+#[derive(Clone)]
+struct Debouncer {
+    delay: Duration,
+    pending: Rc<RefCell<Option<glib::SourceId>>>,
+}
+
+impl Debouncer {
+    fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            pending: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    /// Run `callback` after the delay unless something else is scheduled first
+    fn schedule(&self, callback: impl FnOnce() + 'static) {
+        self.cancel();
+        
+        let pending = Rc::clone(&self.pending);
+        let source = glib::timeout_add_local_once(self.delay, move || {
+            // The source is done once it fires; forget it rather than remove it
+            pending.borrow_mut().take();
+            callback();
+        });
+        *self.pending.borrow_mut() = Some(source);
+    }
+
+    /// Drop the waiting call, if any
+    fn cancel(&self) {
+        if let Some(source) = self.pending.borrow_mut().take() {
+            source.remove();
+        }
+    }
+}
+
+/// The `file://` URI of a result's .desktop file, for dragging it
+fn drag_uri(result: &SearchResult) -> Option<String> {
+    let path = result.desktop_file.as_deref()?;
+    glib::filename_to_uri(path, None).ok().map(String::from)
+}
+
+/// Results to show for `query`: the most used apps while it is empty
+///
+/// Used both to render the rows and to map an activated row back to its app.
+fn shown_results(results: &[SearchResult], query: &str) -> Vec<SearchResult> {
+    if query.is_empty() {
+        frequent_results(results, &LaunchHistory::load())
+    } else {
+        filter_results(results, query)
+    }
+}
+
+/// The most launched of `results`, scored by launch count
+///
+/// Launched commands that are not among `results` are skipped.
+fn frequent_results(results: &[SearchResult], history: &LaunchHistory) -> Vec<SearchResult> {
+    history.top(usize::MAX)
+        .into_iter()
+        .filter_map(|(command, count)| {
+            let result = results.iter().find(|r| r.command == command)?;
+            Some(SearchResult { score: count, ..result.clone() })
+        })
+        .take(MAX_RESULTS)
+        .collect()
+}
+
+/// Index of the row to select after pressing Down (`forward`) or Up
+///
+/// Wraps around at either end. With nothing selected, Down picks the
+/// first row and Up the last.
+fn step_selection(current: Option<usize>, len: usize, forward: bool) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+
+    Some(match (current, forward) {
+        (None, true) => 0,
+        (None, false) => len - 1,
+        (Some(index), true) => (index + 1) % len,
+        (Some(index), false) => (index + len - 1) % len,
+    })
+}
+
+/// Map desktop entries to search results, one per command
+///
+/// Hidden (`NoDisplay`) entries and entries without a name or command
+/// are skipped.
+pub fn installed_results(entries: Vec<DesktopEntry>) -> Vec<SearchResult> {
+    let mut results: Vec<SearchResult> = Vec::new();
+
+    for entry in entries {
+        if entry.no_display {
+            continue;
+        }
+        let (Some(name), Some(command)) = (entry.localized_name(), entry.exec_command()) else {
+            continue;
+        };
+        if results.iter().any(|r| r.command == command) {
+            continue;
+        }
+
+        results.push(SearchResult {
+            app_id: entry.path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| command.split_whitespace().next().unwrap_or(&command).to_string()),
+            name,
+            icon_name: entry.localized_icon().unwrap_or_else(|| "application-x-executable".to_string()),
+            command,
+            desktop_file: Some(entry.path.to_string_lossy().into_owned()),
+            env: HashMap::new(),
+            score: 0,
+        });
+    }
+
+    results
+}
+
+/// Score for each matched character
+const MATCH_SCORE: u32 = 1;
+
+/// Bonus for a character matched right after the previous one
+const CONSECUTIVE_BONUS: u32 = 5;
+
+/// Bonus for a character matched at the start of a word
+const WORD_START_BONUS: u32 = 8;
+
+/// Score how well `query` fuzzily matches `candidate`
+///
+/// Every query character must appear in the candidate in order
+/// (case-insensitively); returns `None` otherwise. Runs of consecutive
+/// characters and matches at word starts score higher.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<u32> {
+    let candidate: Vec<char> = candidate.chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for query_char in query.chars().flat_map(char::to_lowercase) {
+        let offset = candidate[position..]
+            .iter()
+            .position(|c| c.to_lowercase().eq(std::iter::once(query_char)))?;
+        let index = position + offset;
+
+        score += MATCH_SCORE;
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += CONSECUTIVE_BONUS;
+        }
+        if is_word_start(&candidate, index) {
+            score += WORD_START_BONUS;
+        }
+
+        previous = Some(index);
+        position = index + 1;
+    }
+
+    Some(score)
+}
+
+/// Whether `index` begins a word: the start, after a separator, or a camelCase hump
+fn is_word_start(chars: &[char], index: usize) -> bool {
+    let Some(before) = index.checked_sub(1).map(|i| chars[i]) else {
+        return true;
+    };
+    !before.is_alphanumeric() || (before.is_lowercase() && chars[index].is_uppercase())
+}
+
+/// Fuzzily filter apps by name and sort by score
+///
+/// Used both to render the rows and to map an activated row back to its app,
+/// so the two always agree on the order.
+fn filter_results(results: &[SearchResult], query: &str) -> Vec<SearchResult> {
+    let mut filtered: Vec<_> = results
+        .iter()
+        .filter_map(|r| {
+            let score = fuzzy_score(query, &r.name)?;
+            Some(SearchResult { score, ..r.clone() })
+        })
+        .collect();
+    
+    filtered.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+    
+    filtered.truncate(MAX_RESULTS);
+    filtered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(name: &str) -> SearchResult {
+        SearchResult {
+            app_id: name.to_lowercase(),
+            name: name.to_string(),
+            icon_name: name.to_lowercase(),
+            command: name.to_lowercase(),
+            desktop_file: None,
+            env: HashMap::new(),
+            score: 0,
+        }
+    }
+
+    #[test]
+    fn test_filter_results_prefers_prefix_matches() {
+        let apps = vec![result("Terminal"), result("Files"), result("Firefox"), result("Krita")];
+        
+        let names: Vec<_> = filter_results(&apps, "fi")
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        assert_eq!(names, vec!["Files", "Firefox"]);
+        
+        let names: Vec<_> = filter_results(&apps, "i")
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        assert_eq!(names, vec!["Files", "Firefox", "Krita", "Terminal"]);
+    }
+
+    fn entry(name: &str, exec: &str, no_display: bool) -> DesktopEntry {
+        DesktopEntry {
+            path: std::path::PathBuf::from(format!("/usr/share/applications/{}.desktop", exec)),
+            name: Some(name.to_string()),
+            generic_name: None,
+            comment: None,
+            icon: Some(exec.to_string()),
+            exec: Some(format!("{} %U", exec)),
+            terminal: false,
+            dbus_activatable: false,
+            categories: Vec::new(),
+            no_display,
+            fields: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_installed_results_skip_hidden_and_duplicates() {
+        let results = installed_results(vec![
+            entry("Firefox", "firefox", false),
+            entry("Firefox Helper", "firefox", false),
+            entry("Portal", "xdg-portal", true),
+            entry("Krita", "krita", false),
+        ]);
+
+        let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["Firefox", "Krita"]);
+        assert_eq!(results[1].app_id, "krita");
+        assert_eq!(results[1].command, "krita");
+        assert_eq!(results[1].desktop_file.as_deref(), Some("/usr/share/applications/krita.desktop"));
+    }
+
+    #[test]
+    fn test_drag_uri() {
+        let mut files = result("Files");
+        assert_eq!(drag_uri(&files), None);
+
+        files.desktop_file = Some("/usr/share/applications/org.gnome Files.desktop".to_string());
+        assert_eq!(
+            drag_uri(&files).as_deref(),
+            Some("file:///usr/share/applications/org.gnome%20Files.desktop")
+        );
+    }
+
+    #[test]
+    fn test_frequent_results_follow_history() {
+        let apps = vec![result("Firefox"), result("Krita"), result("Files")];
+        let mut history = LaunchHistory::default();
+        for command in ["krita", "firefox", "krita", "uninstalled"] {
+            history.increment(command);
+        }
+
+        let frequent = frequent_results(&apps, &history);
+        let names: Vec<_> = frequent.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["Krita", "Firefox"]);
+        assert_eq!(frequent[0].score, 2);
+
+        assert!(frequent_results(&apps, &LaunchHistory::default()).is_empty());
+    }
+
+    #[test]
+    fn test_debouncer_collapses_rapid_calls() {
+        let context = glib::MainContext::default();
+        let Ok(_guard) = context.acquire() else {
+            // Another test is running the main loop
+            return;
+        };
+
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let debouncer = Debouncer::new(Duration::from_millis(10));
+        for query in ["f", "fi", "fir"] {
+            let calls = Rc::clone(&calls);
+            debouncer.schedule(move || calls.borrow_mut().push(query));
+        }
+
+        let deadline = std::time::Instant::now() + Duration::from_millis(200);
+        while std::time::Instant::now() < deadline {
+            context.iteration(false);
+        }
+        assert_eq!(*calls.borrow(), vec!["fir"]);
+
+        // Cancelled calls never run
+        let cancelled = Rc::clone(&calls);
+        debouncer.schedule(move || cancelled.borrow_mut().push("firefox"));
+        debouncer.cancel();
+        let deadline = std::time::Instant::now() + Duration::from_millis(50);
+        while std::time::Instant::now() < deadline {
+            context.iteration(false);
+        }
+        assert_eq!(*calls.borrow(), vec!["fir"]);
+    }
+
+    #[test]
+    fn test_step_selection_wraps() {
+        assert_eq!(step_selection(None, 3, true), Some(0));
+        assert_eq!(step_selection(None, 3, false), Some(2));
+        assert_eq!(step_selection(Some(0), 3, true), Some(1));
+        assert_eq!(step_selection(Some(2), 3, true), Some(0));
+        assert_eq!(step_selection(Some(0), 3, false), Some(2));
+        assert_eq!(step_selection(Some(1), 3, false), Some(0));
+        assert_eq!(step_selection(None, 0, true), None);
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("ff", "Firefox").is_some());
+        assert!(fuzzy_score("xff", "Firefox").is_none());
+        assert!(fuzzy_score("gimp", "Firefox").is_none());
+
+        // Word starts and consecutive runs score higher
+        assert!(fuzzy_score("vs", "Visual Studio") > fuzzy_score("vs", "Avast"));
+        assert!(fuzzy_score("fire", "Firefox") > fuzzy_score("fire", "Fix Ire"));
+        assert!(fuzzy_score("ls", "LibreOffice Settings") > fuzzy_score("ls", "Lists"));
+    }
+
+    #[test]
+    fn test_filter_results_ranks_by_score() {
+        let apps = vec![result("Stuff Box"), result("Firefox"), result("Calculator")];
+
+        let filtered = filter_results(&apps, "ffx");
+        let names: Vec<_> = filtered.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["Firefox", "Stuff Box"]);
+        assert!(filtered[0].score > filtered[1].score);
+
+        assert!(filter_results(&apps, "zzz").is_empty());
+    }
+}
+
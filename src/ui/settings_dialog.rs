@@ -3,14 +3,19 @@
 //! GUI for configuring BlazeDock settings.
 
 use gtk::prelude::*;
-use gtk::{ComboBoxText, Dialog, Scale, Switch, Window};
+use gtk::{gdk, glib, Button, ComboBoxText, Dialog, ListBox, ResponseType, Scale, Switch, Window};
 use log::debug;
+use std::cell::RefCell;
+use std::rc::Rc;
 
-use crate::config::{DockPosition, Settings};
+use crate::config::{DockPosition, PinnedApp, ProfileMeta, Settings};
+use crate::ui::popover_menu;
+use crate::utils::desktop_entry::{discover_applications, DesktopEntry};
 
 /// Settings dialog window
 pub struct SettingsDialog {
     dialog: Dialog,
+    profile_combo: ComboBoxText,
     position_combo: ComboBoxText,
     icon_size_scale: Scale,
     dock_size_scale: Scale,
@@ -18,12 +23,16 @@ pub struct SettingsDialog {
     auto_hide_switch: Switch,
     hover_zoom_switch: Switch,
     hover_zoom_scale: Scale,
-    settings: Settings,
+    /// Working copy; the pinned apps list edits it in place
+    settings: Rc<RefCell<Settings>>,
 }
 
 impl SettingsDialog {
     /// Create a new settings dialog
-    pub fn new(parent: &impl IsA<Window>, settings: Settings) -> Self {
+    ///
+    /// `profiles` are offered in the profile selector; the one named by
+    /// `settings.active_profile` starts selected.
+    pub fn new(parent: &impl IsA<Window>, settings: Settings, profiles: &[(&str, &ProfileMeta)]) -> Self {
         let dialog = Dialog::builder()
             .title("BlazeDock Settings")
             .modal(true)
@@ -50,6 +59,21 @@ impl SettingsDialog {
             .margin_end(12)
             .build();
 
+        // Profile selector
+        let profile_label = gtk::Label::new(Some("Profile:"));
+        profile_label.set_halign(gtk::Align::Start);
+        let profile_combo = ComboBoxText::new();
+        let mut profiles = profiles.to_vec();
+        profiles.sort_by_key(|(name, _)| *name);
+        for (name, meta) in &profiles {
+            profile_combo.append(Some(name), &meta.name);
+        }
+        if !profile_combo.set_active_id(Some(&settings.active_profile)) {
+            // Keep the current profile selectable even if it has no file
+            profile_combo.append(Some(&settings.active_profile), &settings.active_profile);
+            profile_combo.set_active_id(Some(&settings.active_profile));
+        }
+
         // Position selector
         let position_label = gtk::Label::new(Some("Position:"));
         position_label.set_halign(gtk::Align::Start);
@@ -171,6 +195,13 @@ impl SettingsDialog {
         });
 
         // Layout controls
+        let profile_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(12)
+            .build();
+        profile_box.append(&profile_label);
+        profile_box.append(&profile_combo);
+
         let position_box = gtk::Box::builder()
             .orientation(gtk::Orientation::Horizontal)
             .spacing(12)
@@ -221,6 +252,7 @@ impl SettingsDialog {
         hover_zoom_scale_box.append(&hover_zoom_scale);
 
         // Add all to main box
+        main_box.append(&profile_box);
         main_box.append(&position_box);
         main_box.append(&icon_size_box);
         main_box.append(&dock_size_box);
@@ -229,14 +261,52 @@ impl SettingsDialog {
         main_box.append(&hover_zoom_box);
         main_box.append(&hover_zoom_scale_box);
         
+        // Pinned apps
+        let settings = Rc::new(RefCell::new(settings));
+        let pinned_label = gtk::Label::new(Some("Pinned Apps:"));
+        pinned_label.set_halign(gtk::Align::Start);
+        let pinned_list = ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(vec!["settings-pinned-list"])
+            .build();
+        Self::populate_pinned_list(&pinned_list, &settings);
+        
+        let pinned_scroll = gtk::ScrolledWindow::builder()
+            .min_content_height(160)
+            .child(&pinned_list)
+            .build();
+        
+        let add_button = Button::builder()
+            .label("Add Application…")
+            .halign(gtk::Align::Start)
+            .build();
+        let list_weak = pinned_list.downgrade();
+        let settings_add = Rc::clone(&settings);
+        add_button.connect_clicked(move |button| {
+            if let Some(list) = list_weak.upgrade() {
+                Self::show_app_chooser(button, &list, &settings_add);
+            }
+        });
+        
+        let pinned_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(4)
+            .build();
+        pinned_box.append(&pinned_label);
+        pinned_box.append(&pinned_scroll);
+        pinned_box.append(&add_button);
+        main_box.append(&pinned_box);
+        
         // Set content
         content.append(&main_box);
 
-        // Note: GTK4 Dialog buttons are added differently
-        // For now, we'll use a simpler approach
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Apply", ResponseType::Apply);
+        dialog.set_default_response(ResponseType::Apply);
 
         Self {
             dialog,
+            profile_combo,
             position_combo,
             icon_size_scale,
             dock_size_scale,
@@ -248,12 +318,24 @@ impl SettingsDialog {
         }
     }
 
-    /// Show the dialog and return updated settings if OK/Apply was clicked
-    pub fn run(&self) -> Option<Settings> {
-        self.dialog.present();
-        // For now, return current settings
-        // TODO: Implement proper modal dialog with response handling
-        Some(self.get_settings())
+    /// Show the dialog and wait for a response
+    ///
+    /// Resolves to the updated settings if Apply was clicked, or `None` if
+    /// the dialog was cancelled or closed.
+    pub async fn run(&self) -> Option<Settings> {
+        let response = self.dialog.run_future().await;
+        self.dialog.close();
+        
+        debug!("Settings dialog response: {:?}", response);
+        Self::settings_for_response(response, || self.get_settings())
+    }
+    
+    /// Only Apply yields settings
+    fn settings_for_response(response: ResponseType, settings: impl FnOnce() -> Settings) -> Option<Settings> {
+        match response {
+            ResponseType::Apply => Some(settings()),
+            _ => None,
+        }
     }
     
     /// Get the dialog widget
@@ -268,10 +350,13 @@ impl SettingsDialog {
             Some(1) => DockPosition::Right,
             Some(2) => DockPosition::Top,
             Some(3) => DockPosition::Bottom,
-            _ => self.settings.position,
+            _ => self.settings.borrow().position,
         };
 
-        let mut new_settings = self.settings.clone();
+        let mut new_settings = self.settings.borrow().clone();
+        if let Some(profile) = self.profile_combo.active_id() {
+            new_settings.active_profile = profile.to_string();
+        }
         new_settings.position = position;
         new_settings.icon_size = self.icon_size_scale.value() as u32;
         new_settings.dock_size = self.dock_size_scale.value() as u32;
@@ -282,5 +367,229 @@ impl SettingsDialog {
 
         new_settings
     }
+    
+    /// Fill `list` with a row per pinned app
+    ///
+    /// Rows can be dragged onto each other to reorder, and have a remove
+    /// button; both edit `settings` and rebuild the list.
+    fn populate_pinned_list(list: &ListBox, settings: &Rc<RefCell<Settings>>) {
+        while let Some(child) = list.first_child() {
+            list.remove(&child);
+        }
+        
+        for (index, app) in settings.borrow().pinned_apps.iter().enumerate() {
+            let row = gtk::Box::builder()
+                .orientation(gtk::Orientation::Horizontal)
+                .spacing(8)
+                .margin_top(4)
+                .margin_bottom(4)
+                .margin_start(8)
+                .margin_end(8)
+                .build();
+            
+            let icon = gtk::Image::from_icon_name(&app.icon);
+            icon.set_pixel_size(24);
+            let label = gtk::Label::builder()
+                .label(&app.name)
+                .xalign(0.0)
+                .hexpand(true)
+                .build();
+            let remove_button = Button::builder()
+                .icon_name("list-remove-symbolic")
+                .tooltip_text("Remove from Dock")
+                .css_classes(vec!["flat"])
+                .build();
+            
+            row.append(&icon);
+            row.append(&label);
+            row.append(&remove_button);
+            
+            let list_weak = list.downgrade();
+            let settings_remove = Rc::clone(settings);
+            remove_button.connect_clicked(move |_| {
+                if let Some(app) = remove_app(&mut settings_remove.borrow_mut(), index) {
+                    debug!("Removed '{}' in settings dialog", app.name);
+                    Self::schedule_repopulate(&list_weak, &settings_remove);
+                }
+            });
+            
+            // Drag a row by its index onto another to move it there
+            let drag_source = gtk::DragSource::new();
+            drag_source.set_actions(gdk::DragAction::MOVE);
+            drag_source.connect_prepare(move |_, _, _| {
+                Some(gdk::ContentProvider::for_value(&(index as u32).to_value()))
+            });
+            row.add_controller(drag_source);
+            
+            let drop_target = gtk::DropTarget::new(u32::static_type(), gdk::DragAction::MOVE);
+            let list_weak = list.downgrade();
+            let settings_drop = Rc::clone(settings);
+            drop_target.connect_drop(move |_, value, _, _| {
+                let Ok(from) = value.get::<u32>() else {
+                    return false;
+                };
+                if settings_drop.borrow_mut().move_pinned_app(from as usize, index) {
+                    Self::schedule_repopulate(&list_weak, &settings_drop);
+                }
+                true
+            });
+            row.add_controller(drop_target);
+            
+            list.append(&row);
+        }
+    }
+    
+    /// Rebuild the pinned list once the current event has been handled
+    ///
+    /// The row whose handler triggered the change is destroyed by the rebuild.
+    fn schedule_repopulate(list: &glib::WeakRef<ListBox>, settings: &Rc<RefCell<Settings>>) {
+        let list = list.clone();
+        let settings = Rc::clone(settings);
+        glib::idle_add_local_once(move || {
+            if let Some(list) = list.upgrade() {
+                Self::populate_pinned_list(&list, &settings);
+            }
+        });
+    }
+    
+    /// Pop up a chooser of installed applications under `button`
+    fn show_app_chooser(button: &Button, list: &ListBox, settings: &Rc<RefCell<Settings>>) {
+        // Each app with its description, shown as the row's tooltip
+        let mut apps: Vec<(PinnedApp, Option<String>)> = discover_applications()
+            .iter()
+            .filter_map(|entry| Some((pinned_app_from_entry(entry)?, entry.localized_comment())))
+            .collect();
+        apps.sort_by_key(|(app, _)| app.name.to_lowercase());
+        
+        let chooser = ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .activate_on_single_click(true)
+            .build();
+        
+        for (app, comment) in &apps {
+            let row = gtk::Box::builder()
+                .orientation(gtk::Orientation::Horizontal)
+                .spacing(8)
+                .margin_top(4)
+                .margin_bottom(4)
+                .margin_start(8)
+                .margin_end(8)
+                .build();
+            let icon = gtk::Image::from_icon_name(&app.icon);
+            icon.set_pixel_size(24);
+            row.append(&icon);
+            row.append(&gtk::Label::new(Some(&app.name)));
+            row.set_tooltip_text(comment.as_deref());
+            chooser.append(&row);
+        }
+        
+        let scroll = gtk::ScrolledWindow::builder()
+            .min_content_height(300)
+            .min_content_width(260)
+            .child(&chooser)
+            .build();
+        let popover = popover_menu::attach(button, &scroll);
+        
+        let list_weak = list.downgrade();
+        let settings = Rc::clone(settings);
+        chooser.connect_row_activated(move |_, row| {
+            let Some((app, _)) = apps.get(row.index() as usize) else {
+                return;
+            };
+            if add_app(&mut settings.borrow_mut(), app.clone()) {
+                debug!("Added '{}' in settings dialog", app.name);
+                Self::schedule_repopulate(&list_weak, &settings);
+            }
+            popover_menu::close(row);
+        });
+        
+        popover.popup();
+    }
 }
 
+/// Build a pinned app from a desktop entry, if it has a command to run
+fn pinned_app_from_entry(entry: &DesktopEntry) -> Option<PinnedApp> {
+    Some(PinnedApp {
+        name: entry.localized_name().unwrap_or_else(|| "Unknown".to_string()),
+        icon: entry.localized_icon().unwrap_or_else(|| "application-x-executable".to_string()),
+        command: entry.exec_command()?,
+        desktop_file: Some(entry.path.to_string_lossy().into_owned()),
+        env: Default::default(),
+        label: None,
+    })
+}
+
+/// Pin `app` unless an app with the same command is already pinned
+fn add_app(settings: &mut Settings, app: PinnedApp) -> bool {
+    if settings.pinned_apps.iter().any(|pinned| pinned.command == app.command) {
+        return false;
+    }
+    settings.pinned_apps.push(app);
+    true
+}
+
+/// Unpin the app at `index`
+fn remove_app(settings: &mut Settings, index: usize) -> Option<PinnedApp> {
+    (index < settings.pinned_apps.len()).then(|| settings.pinned_apps.remove(index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gtk::glib;
+
+    #[gtk::test]
+    fn test_cancel_returns_none() {
+        let parent = Window::new();
+        let dialog = SettingsDialog::new(&parent, Settings::default(), &[]);
+        let widget = dialog.widget().clone();
+        glib::idle_add_local_once(move || widget.response(ResponseType::Cancel));
+
+        assert!(glib::MainContext::default().block_on(dialog.run()).is_none());
+    }
+
+    fn app(name: &str) -> PinnedApp {
+        PinnedApp {
+            name: name.to_string(),
+            icon: name.to_lowercase(),
+            command: name.to_lowercase(),
+            desktop_file: None,
+            env: Default::default(),
+            label: None,
+        }
+    }
+
+    fn names(settings: &Settings) -> Vec<&str> {
+        settings.pinned_apps.iter().map(|app| app.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_pinned_list_operations() {
+        let mut settings = Settings::default();
+        settings.pinned_apps = vec![app("Firefox"), app("Files")];
+
+        assert!(add_app(&mut settings, app("Terminal")));
+        // Already pinned
+        assert!(!add_app(&mut settings, app("Files")));
+        assert_eq!(names(&settings), ["Firefox", "Files", "Terminal"]);
+
+        assert!(settings.move_pinned_app(2, 0));
+        assert_eq!(names(&settings), ["Terminal", "Firefox", "Files"]);
+        assert!(settings.move_pinned_app(0, 2));
+        assert_eq!(names(&settings), ["Firefox", "Files", "Terminal"]);
+        assert!(!settings.move_pinned_app(0, 3));
+
+        assert_eq!(remove_app(&mut settings, 1).map(|a| a.name), Some("Files".to_string()));
+        assert!(remove_app(&mut settings, 5).is_none());
+        assert_eq!(names(&settings), ["Firefox", "Terminal"]);
+    }
+
+    #[test]
+    fn test_only_apply_yields_settings() {
+        let apply = SettingsDialog::settings_for_response(ResponseType::Apply, Settings::default);
+        assert!(apply.is_some());
+        for response in [ResponseType::Cancel, ResponseType::DeleteEvent, ResponseType::None] {
+            assert!(SettingsDialog::settings_for_response(response, Settings::default).is_none());
+        }
+    }
+}
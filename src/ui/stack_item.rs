@@ -6,20 +6,38 @@
 use gtk::prelude::*;
 use gtk::{Button, Image, Label, Box, Orientation, ScrolledWindow};
 use gtk::gio::{self, FileMonitorEvent};
+use gtk::gdk_pixbuf::Pixbuf;
 use gtk::glib;
 use log::{debug, info, warn};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::rc::Rc;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-/// View mode for the stack popup
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
-pub enum StackViewMode {
-    #[default]
-    Fan,
-    Grid,
-    List,
-}
+use crate::config::{StackConfig, StackSortMode, StackViewMode};
+use crate::ui::popover_menu;
+
+/// Icon size of grid cards, which image thumbnails are scaled to fit
+const CARD_ICON_SIZE: i32 = 48;
+
+/// Decoded image thumbnails kept per stack before the cache is reset
+const THUMBNAIL_CACHE_MAX: usize = 200;
+
+/// Image thumbnails keyed by path and modification time
+type ThumbnailCache = Rc<RefCell<HashMap<(PathBuf, Option<i64>), Pixbuf>>>;
+
+/// Most entries shown as a fan; larger folders fall back to the grid
+const FAN_MAX_ITEMS: usize = 10;
+
+/// Vertical distance between fanned cards
+const FAN_CARD_STEP: f64 = 52.0;
+
+/// Size reserved for each fanned card
+const FAN_CARD_WIDTH: i32 = 240;
+const FAN_CARD_HEIGHT: i32 = 48;
+
+/// Radius of the arc the fan follows
+const FAN_RADIUS: f64 = 1200.0;
 
 /// A file entry in the stack
 #[derive(Clone, Debug)]
@@ -29,6 +47,22 @@ pub struct StackEntry {
     pub icon_name: String,
     pub is_directory: bool,
     pub modified: Option<glib::DateTime>,
+    /// Size in bytes (0 for directories or unreadable files)
+    pub size: u64,
+}
+
+/// Called after an entry is moved to the trash from its context menu
+type EntryTrashed = Rc<dyn Fn()>;
+
+/// What the popup needs to rebuild itself from the current folder contents
+#[derive(Clone)]
+struct PopupState {
+    entries: Rc<RefCell<Vec<StackEntry>>>,
+    view_mode: Rc<RefCell<StackViewMode>>,
+    thumbnails: ThumbnailCache,
+    folder_path: PathBuf,
+    max_items: Rc<Cell<usize>>,
+    sort_mode: Rc<Cell<StackSortMode>>,
 }
 
 /// Downloads/folder stack dock item
@@ -39,8 +73,10 @@ pub struct StackItem {
     folder_path: PathBuf,
     entries: Rc<RefCell<Vec<StackEntry>>>,
     view_mode: Rc<RefCell<StackViewMode>>,
+    thumbnails: ThumbnailCache,
+    sort_mode: Rc<Cell<StackSortMode>>,
     monitor: Option<gio::FileMonitor>,
-    max_items: usize,
+    max_items: Rc<Cell<usize>>,
 }
 
 impl StackItem {
@@ -76,8 +112,10 @@ impl StackItem {
             folder_path,
             entries,
             view_mode,
+            thumbnails: Rc::new(RefCell::new(HashMap::new())),
+            sort_mode: Rc::new(Cell::new(StackSortMode::default())),
             monitor: None,
-            max_items: 20,
+            max_items: Rc::new(Cell::new(20)),
         };
         
         // Load initial entries
@@ -99,11 +137,43 @@ impl StackItem {
         Self::new(downloads, icon_size)
     }
     
+    /// Create a stack from its configuration entry
+    pub fn from_config(config: &StackConfig, icon_size: u32) -> Self {
+        let stack = Self::new(config.folder_path(), icon_size);
+        stack.set_icon(&config.icon);
+        stack.set_view_mode(config.view_mode);
+        stack.set_sort_mode(config.sort_mode);
+        stack.set_max_items(config.max_items);
+        stack
+    }
+    
     /// Get the widget
     pub fn widget(&self) -> &Button {
         &self.button
     }
     
+    /// Get the folder this stack shows
+    pub fn folder_path(&self) -> &Path {
+        &self.folder_path
+    }
+    
+    /// Set the dock item's icon
+    pub fn set_icon(&self, icon_name: &str) {
+        self.icon.set_icon_name(Some(icon_name));
+    }
+    
+    /// Change the entry order and re-sort
+    pub fn set_sort_mode(&self, mode: StackSortMode) {
+        self.sort_mode.set(mode);
+        self.refresh_entries();
+    }
+    
+    /// Limit how many entries the popup lists
+    pub fn set_max_items(&self, max_items: usize) {
+        self.max_items.set(max_items);
+        self.refresh_entries();
+    }
+    
     /// Refresh entries from the folder
     pub fn refresh_entries(&self) {
         let mut entries = Vec::new();
@@ -130,6 +200,11 @@ impl StackItem {
                         let duration = t.duration_since(std::time::UNIX_EPOCH).ok()?;
                         glib::DateTime::from_unix_local(duration.as_secs() as i64).ok()
                     });
+                let size = if is_directory {
+                    0
+                } else {
+                    entry.metadata().map(|m| m.len()).unwrap_or(0)
+                };
                 
                 entries.push(StackEntry {
                     name,
@@ -137,16 +212,15 @@ impl StackItem {
                     icon_name,
                     is_directory,
                     modified,
+                    size,
                 });
             }
         }
         
-        // Sort by modification time (newest first)
-        entries.sort_by(|a, b| b.modified.as_ref().map(|d| d.to_unix())
-            .cmp(&a.modified.as_ref().map(|d| d.to_unix())));
+        sort_entries(&mut entries, self.sort_mode.get());
         
         // Limit entries
-        entries.truncate(self.max_items);
+        entries.truncate(self.max_items.get());
         
         *self.entries.borrow_mut() = entries;
         debug!("Stack refreshed with {} entries", self.entries.borrow().len());
@@ -192,29 +266,149 @@ impl StackItem {
     /// Setup click handler to show popup
     fn setup_click_handler(&self) {
         let popup = self.popup.clone();
-        let entries = Rc::clone(&self.entries);
-        let view_mode = Rc::clone(&self.view_mode);
-        let folder_path = self.folder_path.clone();
+        let state = PopupState {
+            entries: Rc::clone(&self.entries),
+            view_mode: Rc::clone(&self.view_mode),
+            thumbnails: Rc::clone(&self.thumbnails),
+            folder_path: self.folder_path.clone(),
+            max_items: Rc::clone(&self.max_items),
+            sort_mode: Rc::clone(&self.sort_mode),
+        };
         
         self.button.connect_clicked(move |_| {
-            // Rebuild popup content
-            let content = Self::build_popup_content(&entries.borrow(), *view_mode.borrow(), &folder_path);
-            popup.set_child(Some(&content));
+            Self::render_popup(&popup, &state);
             popup.popup();
         });
     }
     
+    /// Rebuild the popup content from the current entries
+    ///
+    /// Trashing an entry re-reads the folder and renders again.
+    fn render_popup(popup: &gtk::Popover, state: &PopupState) {
+        let weak_popup = popup.downgrade();
+        let refresh_state = state.clone();
+        let on_trashed: EntryTrashed = Rc::new(move || {
+            Self::refresh_entries_static(
+                &refresh_state.entries,
+                &refresh_state.folder_path,
+                refresh_state.max_items.get(),
+                refresh_state.sort_mode.get(),
+            );
+            if let Some(popup) = weak_popup.upgrade() {
+                Self::render_popup(&popup, &refresh_state);
+            }
+        });
+        
+        let content = Self::build_popup_content(
+            &state.entries.borrow(),
+            *state.view_mode.borrow(),
+            &state.folder_path,
+            &state.thumbnails,
+            &on_trashed,
+        );
+        popup.set_child(Some(&content));
+    }
+    
     /// Build popup content based on view mode
-    fn build_popup_content(entries: &[StackEntry], mode: StackViewMode, folder_path: &PathBuf) -> gtk::Widget {
+    fn build_popup_content(
+        entries: &[StackEntry],
+        mode: StackViewMode,
+        folder_path: &PathBuf,
+        thumbnails: &ThumbnailCache,
+        on_trashed: &EntryTrashed,
+    ) -> gtk::Widget {
         match mode {
-            StackViewMode::Grid => Self::build_grid_view(entries, folder_path),
-            StackViewMode::List => Self::build_list_view(entries, folder_path),
-            StackViewMode::Fan => Self::build_grid_view(entries, folder_path), // Fan uses grid for now
+            StackViewMode::Grid => Self::build_grid_view(entries, folder_path, thumbnails, on_trashed),
+            StackViewMode::List => Self::build_list_view(entries, folder_path, on_trashed),
+            StackViewMode::Fan if entries.is_empty() || entries.len() > FAN_MAX_ITEMS => {
+                Self::build_grid_view(entries, folder_path, thumbnails, on_trashed)
+            }
+            StackViewMode::Fan => Self::build_fan_view(entries, on_trashed),
+        }
+    }
+    
+    /// Build fan view
+    ///
+    /// Cards rise from the dock icon along an arc, newest at the bottom,
+    /// each tilted a little further than the one below it.
+    fn build_fan_view(entries: &[StackEntry], on_trashed: &EntryTrashed) -> gtk::Widget {
+        let fixed = gtk::Fixed::builder()
+            .css_classes(vec!["stack-fan"])
+            .build();
+        
+        let count = entries.len().min(FAN_MAX_ITEMS);
+        let mut max_x = 0.0_f64;
+        
+        for (index, entry) in entries.iter().take(count).enumerate() {
+            let (x, angle) = fan_card_placement(index);
+            let y = (count - 1 - index) as f64 * FAN_CARD_STEP;
+            max_x = max_x.max(x);
+            
+            let card = Self::create_fan_card(entry, on_trashed);
+            fixed.put(&card, 0.0, 0.0);
+            let transform = gtk::gsk::Transform::new()
+                .translate(&gtk::graphene::Point::new(x as f32, y as f32))
+                .rotate(angle as f32);
+            fixed.set_child_transform(&card, Some(&transform));
         }
+        
+        // Fixed doesn't account for transforms, so reserve room for the arc
+        fixed.set_size_request(
+            FAN_CARD_WIDTH + max_x.ceil() as i32,
+            FAN_CARD_HEIGHT + ((count - 1) as f64 * FAN_CARD_STEP).ceil() as i32,
+        );
+        
+        fixed.upcast()
+    }
+    
+    /// Create a fanned card: the file name beside its icon
+    fn create_fan_card(entry: &StackEntry, on_trashed: &EntryTrashed) -> gtk::Widget {
+        let card = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(8)
+            .css_classes(vec!["stack-fan-card"])
+            .build();
+        
+        let label = Label::builder()
+            .label(&entry.name)
+            .ellipsize(gtk::pango::EllipsizeMode::Middle)
+            .max_width_chars(20)
+            .xalign(1.0)
+            .hexpand(true)
+            .css_classes(vec!["stack-fan-name"])
+            .build();
+        
+        let icon = Image::from_icon_name(&entry.icon_name);
+        icon.set_pixel_size(40);
+        
+        card.append(&label);
+        card.append(&icon);
+        
+        let button = Button::builder()
+            .child(&card)
+            .width_request(FAN_CARD_WIDTH)
+            .height_request(FAN_CARD_HEIGHT)
+            .css_classes(vec!["stack-file-button"])
+            .tooltip_text(&entry.name)
+            .build();
+        
+        let path = entry.path.clone();
+        button.connect_clicked(move |_| {
+            info!("Opening file: {:?}", path);
+            open_path(&path);
+        });
+        Self::setup_entry_gestures(&button, &entry.path, on_trashed);
+        
+        button.upcast()
     }
     
     /// Build grid view
-    fn build_grid_view(entries: &[StackEntry], folder_path: &PathBuf) -> gtk::Widget {
+    fn build_grid_view(
+        entries: &[StackEntry],
+        folder_path: &PathBuf,
+        thumbnails: &ThumbnailCache,
+        on_trashed: &EntryTrashed,
+    ) -> gtk::Widget {
         let flow_box = gtk::FlowBox::builder()
             .orientation(Orientation::Horizontal)
             .max_children_per_line(4)
@@ -235,7 +429,7 @@ impl StackItem {
             flow_box.insert(&label, -1);
         } else {
             for entry in entries {
-                let card = Self::create_file_card(entry);
+                let card = Self::create_file_card(entry, thumbnails, on_trashed);
                 flow_box.insert(&card, -1);
             }
         }
@@ -275,14 +469,14 @@ impl StackItem {
     }
     
     /// Build list view
-    fn build_list_view(entries: &[StackEntry], folder_path: &PathBuf) -> gtk::Widget {
+    fn build_list_view(entries: &[StackEntry], folder_path: &PathBuf, on_trashed: &EntryTrashed) -> gtk::Widget {
         let list_box = gtk::ListBox::builder()
             .selection_mode(gtk::SelectionMode::None)
             .css_classes(vec!["stack-list"])
             .build();
         
         for entry in entries {
-            let row = Self::create_list_row(entry);
+            let row = Self::create_list_row(entry, on_trashed);
             list_box.append(&row);
         }
         
@@ -297,7 +491,10 @@ impl StackItem {
     }
     
     /// Create a file card for grid view
-    fn create_file_card(entry: &StackEntry) -> gtk::Widget {
+    ///
+    /// Images show a thumbnail of the file itself; anything else, or an
+    /// image that fails to decode, shows its MIME icon.
+    fn create_file_card(entry: &StackEntry, thumbnails: &ThumbnailCache, on_trashed: &EntryTrashed) -> gtk::Widget {
         let card = Box::builder()
             .orientation(Orientation::Vertical)
             .spacing(4)
@@ -305,8 +502,11 @@ impl StackItem {
             .css_classes(vec!["stack-file-card"])
             .build();
         
-        let icon = Image::from_icon_name(&entry.icon_name);
-        icon.set_pixel_size(48);
+        let icon = match Self::image_thumbnail(entry, thumbnails) {
+            Some(pixbuf) => Image::from_paintable(Some(&gtk::gdk::Texture::for_pixbuf(&pixbuf))),
+            None => Image::from_icon_name(&entry.icon_name),
+        };
+        icon.set_pixel_size(CARD_ICON_SIZE);
         
         let name = entry.name.chars().take(15).collect::<String>();
         let label = Label::builder()
@@ -336,12 +536,44 @@ impl StackItem {
                 warn!("Failed to open file: {}", e);
             }
         });
+        Self::setup_entry_gestures(&button, &entry.path, on_trashed);
         
         button.upcast()
     }
     
+    /// Load a scaled thumbnail for an image entry, decoding it at most once
+    /// per modification
+    fn image_thumbnail(entry: &StackEntry, thumbnails: &ThumbnailCache) -> Option<Pixbuf> {
+        if !is_image_file(&entry.path) {
+            return None;
+        }
+        
+        let key = (entry.path.clone(), entry.modified.as_ref().map(|d| d.to_unix()));
+        if let Some(pixbuf) = thumbnails.borrow().get(&key) {
+            return Some(pixbuf.clone());
+        }
+        
+        let pixbuf = match Pixbuf::from_file_at_scale(&entry.path, CARD_ICON_SIZE, CARD_ICON_SIZE, true) {
+            Ok(pixbuf) => pixbuf,
+            Err(e) => {
+                debug!("Failed to load thumbnail for {:?}: {}", entry.path, e);
+                return None;
+            }
+        };
+        
+        let mut cache = thumbnails.borrow_mut();
+        if cache.len() >= THUMBNAIL_CACHE_MAX {
+            cache.clear();
+        }
+        // Drop the thumbnail of an older version of the file
+        cache.retain(|(path, _), _| path != &entry.path);
+        cache.insert(key, pixbuf.clone());
+        
+        Some(pixbuf)
+    }
+    
     /// Create a list row
-    fn create_list_row(entry: &StackEntry) -> gtk::Widget {
+    fn create_list_row(entry: &StackEntry, on_trashed: &EntryTrashed) -> gtk::Widget {
         let row = Box::builder()
             .orientation(Orientation::Horizontal)
             .spacing(8)
@@ -378,10 +610,91 @@ impl StackItem {
                 warn!("Failed to open file: {}", e);
             }
         });
+        Self::setup_entry_gestures(&button, &entry.path, on_trashed);
         
         button.upcast()
     }
     
+    /// Add middle-click (open containing folder) and right-click (context
+    /// menu) handling to an entry's button
+    fn setup_entry_gestures(button: &Button, path: &Path, on_trashed: &EntryTrashed) {
+        let middle_click = gtk::GestureClick::new();
+        middle_click.set_button(2);
+        let folder_path = path.to_path_buf();
+        middle_click.connect_released(move |_, _, _, _| {
+            open_containing_folder(&folder_path);
+        });
+        button.add_controller(middle_click);
+        
+        let right_click = gtk::GestureClick::new();
+        right_click.set_button(3);
+        let path = path.to_path_buf();
+        let on_trashed = Rc::clone(on_trashed);
+        right_click.connect_released(move |gesture, _, x, y| {
+            if let Some(widget) = gesture.widget() {
+                let menu = Self::create_entry_menu(&widget, &path, &on_trashed);
+                menu.set_pointing_to(Some(&gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+                menu.popup();
+            }
+        });
+        button.add_controller(right_click);
+    }
+    
+    /// Create the context menu for a stack entry
+    fn create_entry_menu(parent: &gtk::Widget, path: &Path, on_trashed: &EntryTrashed) -> gtk::Popover {
+        let menu_box = popover_menu::menu_box();
+        
+        let menu_item = |label: &str| {
+            let item = Button::builder()
+                .label(label)
+                .css_classes(vec!["context-menu-item"])
+                .build();
+            menu_box.append(&item);
+            item
+        };
+        
+        let open_item = menu_item("Open");
+        let folder_item = menu_item("Open Containing Folder");
+        let copy_item = menu_item("Copy Path");
+        menu_box.append(&gtk::Separator::new(Orientation::Horizontal));
+        let trash_item = menu_item("Move to Trash");
+        
+        let target = path.to_path_buf();
+        open_item.connect_clicked(move |btn| {
+            popover_menu::close(btn);
+            open_path(&target);
+        });
+        
+        let target = path.to_path_buf();
+        folder_item.connect_clicked(move |btn| {
+            popover_menu::close(btn);
+            open_containing_folder(&target);
+        });
+        
+        let target = path.to_path_buf();
+        copy_item.connect_clicked(move |btn| {
+            btn.clipboard().set_text(&target.to_string_lossy());
+            debug!("Copied path to clipboard: {:?}", target);
+            popover_menu::close(btn);
+        });
+        
+        let target = path.to_path_buf();
+        let on_trashed = Rc::clone(on_trashed);
+        trash_item.connect_clicked(move |btn| {
+            popover_menu::close(btn);
+            info!("Moving to trash: {:?}", target);
+            if let Err(e) = gio::File::for_path(&target).trash(gio::Cancellable::NONE) {
+                warn!("Failed to trash {:?}: {}", target, e);
+                return;
+            }
+            // Rebuild once the menu has been detached from the old content
+            let on_trashed = Rc::clone(&on_trashed);
+            glib::idle_add_local_once(move || on_trashed());
+        });
+        
+        popover_menu::attach(parent, &menu_box)
+    }
+    
     /// Start monitoring the folder for changes
     fn start_monitoring(&mut self) {
         let file = gio::File::for_path(&self.folder_path);
@@ -390,7 +703,8 @@ impl StackItem {
             Ok(monitor) => {
                 let entries = Rc::clone(&self.entries);
                 let folder_path = self.folder_path.clone();
-                let max_items = self.max_items;
+                let max_items = Rc::clone(&self.max_items);
+                let sort_mode = Rc::clone(&self.sort_mode);
                 
                 monitor.connect_changed(move |_monitor, _file, _other, event| {
                     match event {
@@ -400,7 +714,7 @@ impl StackItem {
                         FileMonitorEvent::MovedOut => {
                             debug!("Stack folder changed: {:?}", event);
                             // Refresh entries
-                            Self::refresh_entries_static(&entries, &folder_path, max_items);
+                            Self::refresh_entries_static(&entries, &folder_path, max_items.get(), sort_mode.get());
                         }
                         _ => {}
                     }
@@ -420,6 +734,7 @@ impl StackItem {
         entries: &Rc<RefCell<Vec<StackEntry>>>,
         folder_path: &PathBuf,
         max_items: usize,
+        sort_mode: StackSortMode,
     ) {
         let mut new_entries = Vec::new();
         
@@ -443,6 +758,11 @@ impl StackItem {
                         let duration = t.duration_since(std::time::UNIX_EPOCH).ok()?;
                         glib::DateTime::from_unix_local(duration.as_secs() as i64).ok()
                     });
+                let size = if is_directory {
+                    0
+                } else {
+                    entry.metadata().map(|m| m.len()).unwrap_or(0)
+                };
                 
                 new_entries.push(StackEntry {
                     name,
@@ -450,12 +770,12 @@ impl StackItem {
                     icon_name,
                     is_directory,
                     modified,
+                    size,
                 });
             }
         }
         
-        new_entries.sort_by(|a, b| b.modified.as_ref().map(|d| d.to_unix())
-            .cmp(&a.modified.as_ref().map(|d| d.to_unix())));
+        sort_entries(&mut new_entries, sort_mode);
         new_entries.truncate(max_items);
         
         *entries.borrow_mut() = new_entries;
@@ -467,6 +787,65 @@ impl StackItem {
     }
 }
 
+/// Open the folder containing a file
+fn open_containing_folder(path: &Path) {
+    match path.parent() {
+        Some(folder) => open_path(folder),
+        None => warn!("No containing folder for {:?}", path),
+    }
+}
+
+/// Whether a file has an image extension worth thumbnailing
+fn is_image_file(path: &Path) -> bool {
+    let extension = path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    matches!(extension.as_str(), "png" | "jpg" | "jpeg" | "gif" | "webp" | "svg" | "bmp")
+}
+
+/// Sort stack entries in place for `mode`
+///
+/// Name comparisons ignore case; ties keep the directory listing order.
+fn sort_entries(entries: &mut [StackEntry], mode: StackSortMode) {
+    let modified = |entry: &StackEntry| entry.modified.as_ref().map(|d| d.to_unix());
+    let name = |entry: &StackEntry| entry.name.to_lowercase();
+    let extension = |entry: &StackEntry| {
+        entry.path.extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default()
+    };
+    
+    match mode {
+        StackSortMode::DateDesc => entries.sort_by(|a, b| modified(b).cmp(&modified(a))),
+        StackSortMode::NameAsc => entries.sort_by_key(name),
+        StackSortMode::SizeDesc => entries.sort_by(|a, b| b.size.cmp(&a.size)),
+        StackSortMode::TypeThenName => entries.sort_by_key(|entry| {
+            (!entry.is_directory, extension(entry), name(entry))
+        }),
+    }
+}
+
+/// Horizontal offset and rotation (degrees) of the fanned card at `index`
+///
+/// Counting from the bottom, cards follow a circle of `FAN_RADIUS` that
+/// curves right as it rises, each turned to follow the arc.
+fn fan_card_placement(index: usize) -> (f64, f64) {
+    let height = (index as f64 * FAN_CARD_STEP).min(FAN_RADIUS);
+    let x = FAN_RADIUS - (FAN_RADIUS * FAN_RADIUS - height * height).sqrt();
+    let angle = (height / FAN_RADIUS).asin().to_degrees();
+    (x, angle)
+}
+
+/// Open a file or folder with its default application
+fn open_path(path: &Path) {
+    if let Err(e) = std::process::Command::new("xdg-open")
+        .arg(path)
+        .spawn()
+    {
+        warn!("Failed to open file: {}", e);
+    }
+}
+
 /// CSS for stack popup
 pub fn get_stack_css() -> &'static str {
     r#"
@@ -508,6 +887,17 @@ pub fn get_stack_css() -> &'static str {
         margin: 8px;
     }
     
+    .stack-fan-card {
+        padding: 4px 8px;
+    }
+    
+    .stack-fan-name {
+        font-size: 12px;
+        padding: 2px 8px;
+        border-radius: 8px;
+        background: alpha(@window_bg_color, 0.85);
+    }
+    
     .stack-empty-label {
         padding: 20px;
         color: alpha(@window_fg_color, 0.7);
@@ -531,6 +921,140 @@ mod tests {
         assert_eq!(StackItem::get_icon_for_file(&PathBuf::from("folder"), true), "folder");
     }
     
+    fn entry(name: &str) -> StackEntry {
+        StackEntry {
+            name: name.to_string(),
+            path: PathBuf::from("/tmp").join(name),
+            icon_name: "text-x-generic".to_string(),
+            is_directory: false,
+            modified: None,
+            size: 0,
+        }
+    }
+    
+    fn names(entries: &[StackEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.name.as_str()).collect()
+    }
+    
+    #[test]
+    fn test_sort_modes() {
+        let sample = |name: &str, size: u64, modified: i64, is_directory: bool| StackEntry {
+            size,
+            modified: glib::DateTime::from_unix_utc(modified).ok(),
+            is_directory,
+            ..entry(name)
+        };
+        let fixed = vec![
+            sample("notes.txt", 300, 200, false),
+            sample("Photos", 0, 100, true),
+            sample("archive.zip", 5000, 300, false),
+            sample("b-report.pdf", 1200, 400, false),
+            sample("a-report.pdf", 800, 50, false),
+        ];
+        
+        let sorted = |mode| {
+            let mut entries = fixed.clone();
+            sort_entries(&mut entries, mode);
+            entries
+        };
+        
+        assert_eq!(
+            names(&sorted(StackSortMode::DateDesc)),
+            ["b-report.pdf", "archive.zip", "notes.txt", "Photos", "a-report.pdf"]
+        );
+        assert_eq!(
+            names(&sorted(StackSortMode::NameAsc)),
+            ["a-report.pdf", "archive.zip", "b-report.pdf", "notes.txt", "Photos"]
+        );
+        assert_eq!(
+            names(&sorted(StackSortMode::SizeDesc)),
+            ["archive.zip", "b-report.pdf", "a-report.pdf", "notes.txt", "Photos"]
+        );
+        assert_eq!(
+            names(&sorted(StackSortMode::TypeThenName)),
+            ["Photos", "a-report.pdf", "b-report.pdf", "notes.txt", "archive.zip"]
+        );
+    }
+    
+    #[test]
+    fn test_fan_placement_arcs_right() {
+        assert_eq!(fan_card_placement(0), (0.0, 0.0));
+        
+        let mut previous = fan_card_placement(0);
+        for index in 1..FAN_MAX_ITEMS {
+            let (x, angle) = fan_card_placement(index);
+            assert!(x > previous.0 && angle > previous.1);
+            previous = (x, angle);
+        }
+        // Keep the top card readable
+        assert!(previous.1 < 30.0);
+    }
+    
+    #[gtk::test]
+    fn test_fan_view_is_distinct_from_grid() {
+        let folder = PathBuf::from("/tmp");
+        let thumbnails: ThumbnailCache = Rc::new(RefCell::new(HashMap::new()));
+        let on_trashed: EntryTrashed = Rc::new(|| {});
+        let few: Vec<_> = (0..3).map(|i| entry(&format!("file{}.txt", i))).collect();
+        let fan = StackItem::build_popup_content(&few, StackViewMode::Fan, &folder, &thumbnails, &on_trashed);
+        let grid = StackItem::build_popup_content(&few, StackViewMode::Grid, &folder, &thumbnails, &on_trashed);
+        assert!(fan.is::<gtk::Fixed>());
+        assert_ne!(fan.type_(), grid.type_());
+        
+        // Beyond the cap the fan overflows to the grid
+        let many: Vec<_> = (0..FAN_MAX_ITEMS + 1).map(|i| entry(&format!("file{}.txt", i))).collect();
+        let overflow = StackItem::build_popup_content(&many, StackViewMode::Fan, &folder, &thumbnails, &on_trashed);
+        assert_eq!(overflow.type_(), grid.type_());
+    }
+    
+    #[gtk::test]
+    fn test_stack_from_config_monitors_custom_path() {
+        let folder = std::env::temp_dir().join(format!("blazedock-stack-test-{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        for i in 0..3 {
+            std::fs::write(folder.join(format!("file{}.txt", i)), "").unwrap();
+        }
+        
+        let config = StackConfig {
+            path: folder.to_string_lossy().into_owned(),
+            icon: "folder-documents".to_string(),
+            view_mode: StackViewMode::List,
+            sort_mode: StackSortMode::NameAsc,
+            max_items: 2,
+        };
+        let stack = StackItem::from_config(&config, 48);
+        
+        assert_eq!(stack.folder_path(), folder.as_path());
+        assert!(stack.monitor.is_some());
+        assert_eq!(*stack.view_mode.borrow(), StackViewMode::List);
+        assert_eq!(names(&stack.entries.borrow()), ["file0.txt", "file1.txt"]);
+        
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+    
+    #[test]
+    fn test_image_detection() {
+        assert!(is_image_file(Path::new("/tmp/shot.PNG")));
+        assert!(is_image_file(Path::new("photo.jpeg")));
+        assert!(!is_image_file(Path::new("notes.txt")));
+        assert!(!is_image_file(Path::new("png")));
+    }
+    
+    #[test]
+    fn test_undecodable_image_falls_back_to_icon() {
+        let path = std::env::temp_dir().join(format!("blazedock-broken-{}.png", std::process::id()));
+        std::fs::write(&path, "not a png").unwrap();
+        
+        let thumbnails: ThumbnailCache = Rc::new(RefCell::new(HashMap::new()));
+        let broken = StackEntry { path: path.clone(), ..entry("broken.png") };
+        assert!(StackItem::image_thumbnail(&broken, &thumbnails).is_none());
+        assert!(thumbnails.borrow().is_empty());
+        // Non-images are never decoded
+        assert!(StackItem::image_thumbnail(&entry("notes.txt"), &thumbnails).is_none());
+        
+        std::fs::remove_file(&path).unwrap();
+    }
+    
     #[test]
     fn test_stack_css() {
         let css = get_stack_css();
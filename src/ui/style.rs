@@ -5,9 +5,59 @@
 use gtk::CssProvider;
 use log::{debug, warn};
 
+use crate::config::Settings;
+use crate::services::ThemeService;
+
 /// CSS styles embedded in the binary
 const STYLES: &str = include_str!("style.css");
 
+/// Lowest dock background opacity, so the dock never becomes invisible
+const MIN_OPACITY: f64 = 0.1;
+
+/// Priority of the theme and settings providers
+///
+/// GTK compares provider priority before selector specificity, so rules
+/// loaded at this priority win over the same selectors in `style.css`
+/// (e.g. `.indicator-dot` and `.dock-item:hover`), whatever their order.
+const OVERRIDE_PRIORITY: u32 = gtk::STYLE_PROVIDER_PRIORITY_APPLICATION + 1;
+
+/// Rules recolored from the system theme's `@define-color` variables
+///
+/// These restate selectors of `style.css` and only win through
+/// [`OVERRIDE_PRIORITY`].
+const THEME_RULES: &str = r#"
+.dock-item:hover,
+.dock-item-hover {
+    background-color: alpha(@accent_color, 0.18);
+}
+
+.dock-item:active {
+    background-color: alpha(@accent_color, 0.35);
+}
+
+.dock-item:focus,
+.dock-item-focused {
+    outline-color: alpha(@accent_color, 0.5);
+}
+
+.indicator-dot {
+    background-color: alpha(@accent_color, 0.7);
+}
+
+.indicator-dot.focused-dot {
+    background-color: @accent_color;
+    box-shadow: 0 0 4px alpha(@accent_color, 0.5);
+}
+
+.search-result-row:selected {
+    background-color: @accent_color;
+}
+
+.search-result-row:selected .search-result-name {
+    color: @on_accent_color;
+}
+"#;
+
 /// Load global CSS styles for the application
 pub fn load_global_styles() {
     let provider = CssProvider::new();
@@ -25,3 +75,121 @@ pub fn load_global_styles() {
     }
 }
 
+
+/// Load the system theme colors into GTK and reload them when the theme changes
+///
+/// The provider is display-wide, so this runs once for all docks. Returns
+/// the provider so it can be removed with [`remove_provider`].
+pub fn load_theme_styles(theme_service: &ThemeService) -> Option<CssProvider> {
+    let Some(provider) = add_provider_above_global() else {
+        warn!("Failed to get default display for theme CSS");
+        return None;
+    };
+    apply_theme_css(&provider, &theme_service.generate_css_variables());
+    
+    // Theme callbacks must be Send, so hand changes over to the main loop
+    let (sender, receiver) = async_channel::unbounded::<()>();
+    theme_service.on_theme_change(move |_| {
+        let _ = sender.try_send(());
+    });
+    
+    let theme_service = theme_service.clone();
+    let theme_provider = provider.clone();
+    gtk::glib::spawn_future_local(async move {
+        while receiver.recv().await.is_ok() {
+            apply_theme_css(&theme_provider, &theme_service.generate_css_variables());
+        }
+    });
+    
+    Some(provider)
+}
+
+/// Unregister a provider added by this module from the display
+pub fn remove_provider(provider: &CssProvider) {
+    if let Some(display) = gtk::gdk::Display::default() {
+        gtk::style_context_remove_provider_for_display(&display, provider);
+    }
+}
+
+/// Register a provider for the rules generated from the settings, above the global styles
+///
+/// Like the theme provider it is display-wide, so all docks share one.
+pub fn create_settings_provider() -> Option<CssProvider> {
+    add_provider_above_global()
+}
+
+/// Register a new provider that overrides the global styles
+fn add_provider_above_global() -> Option<CssProvider> {
+    let display = gtk::gdk::Display::default()?;
+    let provider = CssProvider::new();
+    
+    gtk::style_context_add_provider_for_display(
+        &display,
+        &provider,
+        OVERRIDE_PRIORITY,
+    );
+    
+    Some(provider)
+}
+
+/// Load the rules for `settings` (e.g. the background opacity) into `provider`
+pub fn apply_settings_css(provider: &CssProvider, settings: &Settings) {
+    provider.load_from_data(&settings_css(settings));
+    debug!("Settings CSS applied");
+}
+
+/// CSS for the appearance settings
+///
+/// The dock background keeps the glass color of `style.css` with
+/// `opacity` as its alpha, clamped so the dock stays visible, and its
+/// corners are rounded by `border_radius` (0 for a sharp dock).
+pub fn settings_css(settings: &Settings) -> String {
+    let opacity = if settings.opacity.is_nan() { 1.0 } else { settings.opacity };
+    format!(
+        ".dock-container {{\n    background-color: rgba(18, 18, 24, {:.2});\n    border-radius: {}px;\n}}\n",
+        opacity.clamp(MIN_OPACITY, 1.0),
+        settings.border_radius,
+    )
+}
+
+/// Load theme color variables and the rules using them into `provider`
+pub fn apply_theme_css(provider: &CssProvider, css_variables: &str) {
+    provider.load_from_data(&format!("{}\n{}", css_variables, THEME_RULES));
+    debug!("Theme CSS applied");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_rules_override_global_styles() {
+        // The theme rules restate these selectors, so only priority decides
+        for selector in [".dock-item:hover", ".dock-item:active", ".indicator-dot {", ".indicator-dot.focused-dot"] {
+            assert!(STYLES.contains(selector), "style.css lacks {}", selector);
+            assert!(THEME_RULES.contains(selector), "theme rules lack {}", selector);
+        }
+        assert!(OVERRIDE_PRIORITY > gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+    }
+
+    #[test]
+    fn test_settings_css_opacity() {
+        let mut settings = Settings { opacity: 0.6, ..Settings::default() };
+        assert!(settings_css(&settings).contains("background-color: rgba(18, 18, 24, 0.60);"));
+
+        // Never fully transparent, never beyond opaque
+        settings.opacity = 0.0;
+        assert!(settings_css(&settings).contains("rgba(18, 18, 24, 0.10)"));
+        settings.opacity = 1.7;
+        assert!(settings_css(&settings).contains("rgba(18, 18, 24, 1.00)"));
+    }
+
+    #[test]
+    fn test_settings_css_border_radius() {
+        let mut settings = Settings { border_radius: 24, ..Settings::default() };
+        assert!(settings_css(&settings).contains("border-radius: 24px;"));
+
+        settings.border_radius = 0;
+        assert!(settings_css(&settings).contains("border-radius: 0px;"));
+    }
+}
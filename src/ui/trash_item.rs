@@ -8,9 +8,20 @@ use gtk::{Button, Image};
 use gtk::gio;
 use gtk::glib;
 use log::{debug, info, warn};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
+use crate::ui::popover_menu;
+
+/// Location of the user's trash
+const TRASH_URI: &str = "trash:///";
+
+/// Trash bookkeeping that can show up in a spec trash directory
+const TRASH_METADATA_NAMES: &[&str] = &["info", "files", "expunged", "directorysizes", "metadata"];
+
+/// Dialog response for confirming "Empty Trash"
+const RESPONSE_EMPTY: gtk::ResponseType = gtk::ResponseType::Accept;
+
 /// Trash state
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TrashState {
@@ -23,6 +34,8 @@ pub struct TrashItem {
     button: Button,
     image: Image,
     state: Rc<RefCell<TrashState>>,
+    /// Set while a confirmation is open or the trash is being emptied
+    emptying: Rc<Cell<bool>>,
     monitor: Option<gio::FileMonitor>,
 }
 
@@ -55,12 +68,14 @@ impl TrashItem {
             button,
             image,
             state,
+            emptying: Rc::new(Cell::new(false)),
             monitor: None,
         };
         
         // Check initial state and start monitoring
         trash_item.refresh_state();
         trash_item.start_monitoring();
+        trash_item.setup_context_menu();
         
         trash_item
     }
@@ -77,19 +92,24 @@ impl TrashItem {
     
     /// Refresh the trash state by counting items
     pub fn refresh_state(&self) {
+        Self::refresh_state_static(&self.state, &self.image);
+    }
+    
+    /// Static state refresh (for use in closures)
+    fn refresh_state_static(state: &Rc<RefCell<TrashState>>, image: &Image) {
         let new_state = Self::check_trash_state();
-        let old_state = *self.state.borrow();
+        let old_state = *state.borrow();
         
         if new_state != old_state {
-            *self.state.borrow_mut() = new_state;
-            self.update_icon();
+            *state.borrow_mut() = new_state;
+            Self::update_icon_static(image, new_state);
             debug!("Trash state changed: {:?} -> {:?}", old_state, new_state);
         }
     }
     
     /// Start monitoring trash for changes
     fn start_monitoring(&mut self) {
-        let trash_file = gio::File::for_uri("trash:///");
+        let trash_file = gio::File::for_uri(TRASH_URI);
         
         match trash_file.monitor_directory(gio::FileMonitorFlags::NONE, gio::Cancellable::NONE) {
             Ok(monitor) => {
@@ -124,11 +144,6 @@ impl TrashItem {
         }
     }
     
-    /// Update the icon based on current state
-    fn update_icon(&self) {
-        Self::update_icon_static(&self.image, *self.state.borrow());
-    }
-    
     /// Static icon update (for use in closures)
     fn update_icon_static(image: &Image, state: TrashState) {
         let icon_name = match state {
@@ -140,103 +155,323 @@ impl TrashItem {
     
     /// Check if trash has items
     fn check_trash_state() -> TrashState {
-        let trash_file = gio::File::for_uri("trash:///");
+        Self::state_for_count(Self::trash_item_count())
+    }
+    
+    /// Map a count of trashed items to a state
+    fn state_for_count(count: usize) -> TrashState {
+        if count > 0 {
+            TrashState::Full
+        } else {
+            TrashState::Empty
+        }
+    }
+    
+    /// Number of items in the trash
+    ///
+    /// Prefers the backend's `trash::item-count`, then counts the entries of
+    /// `trash:///`. Without a trash backend, falls back to the spec's trash
+    /// directory, skipping the metadata that can show up there.
+    fn trash_item_count() -> usize {
+        let trash_file = gio::File::for_uri(TRASH_URI);
+        
+        let reported = trash_file
+            .query_info("trash::item-count", gio::FileQueryInfoFlags::NONE, gio::Cancellable::NONE)
+            .ok()
+            .filter(|info| info.has_attribute("trash::item-count"))
+            .map(|info| info.attribute_uint32("trash::item-count") as usize);
         
-        match trash_file.enumerate_children(
+        let count = reported
+            .or_else(|| Self::entry_names(&trash_file).map(|names| names.len()))
+            .or_else(|| {
+                let files = gio::File::for_path(glib::user_data_dir().join("Trash").join("files"));
+                Self::entry_names(&files).map(|names| count_trashed_entries(names.into_iter()))
+            })
+            .unwrap_or(0);
+        debug!("Trash holds {} items (reported by backend: {})", count, reported.is_some());
+        count
+    }
+    
+    /// Count the items directly inside `folder`
+    fn count_items(folder: &gio::File) -> usize {
+        Self::entry_names(folder).map_or(0, |names| names.len())
+    }
+    
+    /// Names of the entries directly inside `folder`, or `None` if it can't be listed
+    fn entry_names(folder: &gio::File) -> Option<Vec<String>> {
+        match folder.enumerate_children(
             "standard::name",
             gio::FileQueryInfoFlags::NONE,
             gio::Cancellable::NONE,
         ) {
-            Ok(enumerator) => {
-                // Check if there's at least one item
-                if enumerator.next_file(gio::Cancellable::NONE).ok().flatten().is_some() {
-                    TrashState::Full
-                } else {
-                    TrashState::Empty
-                }
-            }
+            Ok(enumerator) => Some(
+                std::iter::from_fn(|| enumerator.next_file(gio::Cancellable::NONE).ok().flatten())
+                    .map(|info| info.name().to_string_lossy().into_owned())
+                    .collect(),
+            ),
             Err(e) => {
-                debug!("Could not enumerate trash: {}", e);
-                TrashState::Empty
+                debug!("Could not enumerate {}: {}", folder.uri(), e);
+                None
             }
         }
     }
     
+    /// Text of the "Empty Trash" confirmation for `count` items
+    fn confirmation_text(count: usize) -> String {
+        match count {
+            1 => "Permanently delete 1 item in the trash?".to_string(),
+            n => format!("Permanently delete {} items in the trash?", n),
+        }
+    }
+    
     /// Open the trash folder in file manager
     fn open_trash() -> Result<(), Box<dyn std::error::Error>> {
         std::process::Command::new("xdg-open")
-            .arg("trash:///")
+            .arg(TRASH_URI)
             .spawn()?;
         Ok(())
     }
     
+    /// Setup right-click context menu
+    fn setup_context_menu(&self) {
+        let gesture = gtk::GestureClick::new();
+        gesture.set_button(3);
+        
+        let state = Rc::clone(&self.state);
+        let image = self.image.clone();
+        let emptying = Rc::clone(&self.emptying);
+        
+        gesture.connect_released(move |gesture, _n, x, y| {
+            let Some(widget) = gesture.widget() else {
+                return;
+            };
+            let menu = Self::create_context_menu(&widget, &state, &image, &emptying);
+            menu.set_pointing_to(Some(&gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+            menu.popup();
+        });
+        
+        self.button.add_controller(gesture);
+    }
+    
+    /// Create the context menu popover
+    fn create_context_menu(
+        parent: &gtk::Widget,
+        state: &Rc<RefCell<TrashState>>,
+        image: &Image,
+        emptying: &Rc<Cell<bool>>,
+    ) -> gtk::Popover {
+        let menu_box = popover_menu::menu_box();
+        
+        let open_btn = Button::builder()
+            .label("Open Trash")
+            .css_classes(vec!["context-menu-item"])
+            .build();
+        open_btn.connect_clicked(|btn| {
+            popover_menu::close(btn);
+            if let Err(e) = Self::open_trash() {
+                warn!("Failed to open trash: {}", e);
+            }
+        });
+        menu_box.append(&open_btn);
+        
+        let empty_btn = Button::builder()
+            .label("Empty Trash…")
+            .css_classes(vec!["context-menu-item"])
+            .sensitive(!emptying.get() && *state.borrow() == TrashState::Full)
+            .build();
+        let state = Rc::clone(state);
+        let image = image.clone();
+        let emptying = Rc::clone(emptying);
+        let parent_widget = parent.clone();
+        empty_btn.connect_clicked(move |btn| {
+            popover_menu::close(btn);
+            Self::confirm_empty_trash(&parent_widget, &state, &image, &emptying);
+        });
+        menu_box.append(&empty_btn);
+        
+        popover_menu::attach(parent, &menu_box)
+    }
+    
+    /// Ask before emptying the trash, then empty it
+    fn confirm_empty_trash(
+        parent: &gtk::Widget,
+        state: &Rc<RefCell<TrashState>>,
+        image: &Image,
+        emptying: &Rc<Cell<bool>>,
+    ) {
+        // A confirmation or an empty is already in progress
+        if emptying.replace(true) {
+            return;
+        }
+        
+        let count = Self::trash_item_count();
+        let dialog = gtk::MessageDialog::builder()
+            .modal(true)
+            .message_type(gtk::MessageType::Warning)
+            .text(Self::confirmation_text(count))
+            .secondary_text("This cannot be undone.")
+            .build();
+        if let Some(window) = parent.root().and_downcast::<gtk::Window>() {
+            dialog.set_transient_for(Some(&window));
+        }
+        dialog.add_button("Cancel", gtk::ResponseType::Cancel);
+        dialog.add_button("Empty Trash", RESPONSE_EMPTY);
+        if let Some(button) = dialog.widget_for_response(RESPONSE_EMPTY) {
+            button.add_css_class("destructive-action");
+        }
+        
+        let state = Rc::clone(state);
+        let image = image.clone();
+        let emptying = Rc::clone(emptying);
+        dialog.connect_response(move |dialog, response| {
+            dialog.destroy();
+            if response == RESPONSE_EMPTY {
+                Self::empty_trash_static(&state, &image, &emptying);
+            } else {
+                emptying.set(false);
+            }
+        });
+        
+        dialog.present();
+    }
+    
     /// Empty the trash
     pub fn empty_trash(&self) {
+        if self.emptying.replace(true) {
+            debug!("Trash is already being emptied");
+            return;
+        }
+        Self::empty_trash_static(&self.state, &self.image, &self.emptying);
+    }
+    
+    /// Delete everything in the trash on a worker thread, then refresh the icon
+    ///
+    /// The caller sets `emptying`; it is cleared once the deletion finishes.
+    fn empty_trash_static(state: &Rc<RefCell<TrashState>>, image: &Image, emptying: &Rc<Cell<bool>>) {
         info!("Emptying trash...");
         
-        // Use gio trash:/// to delete all items
+        let state = Rc::clone(state);
+        let image = image.clone();
+        let emptying = Rc::clone(emptying);
+        
         glib::spawn_future_local(async move {
-            let trash_file = gio::File::for_uri("trash:///");
-            
-            match trash_file.enumerate_children(
-                "standard::name",
-                gio::FileQueryInfoFlags::NONE,
-                gio::Cancellable::NONE,
-            ) {
-                Ok(enumerator) => {
-                    let mut count = 0;
-                    while let Ok(Some(info)) = enumerator.next_file(gio::Cancellable::NONE) {
-                        let name = info.name();
-                        let child = trash_file.child(&name);
-                        if let Err(e) = child.delete(gio::Cancellable::NONE) {
-                            warn!("Failed to delete trash item {:?}: {}", name, e);
-                        } else {
-                            count += 1;
+            let deleted = gio::spawn_blocking(|| {
+                let trash_file = gio::File::for_uri(TRASH_URI);
+                
+                match trash_file.enumerate_children(
+                    "standard::name",
+                    gio::FileQueryInfoFlags::NONE,
+                    gio::Cancellable::NONE,
+                ) {
+                    Ok(enumerator) => {
+                        let mut count = 0;
+                        while let Ok(Some(info)) = enumerator.next_file(gio::Cancellable::NONE) {
+                            let name = info.name();
+                            let child = trash_file.child(&name);
+                            if let Err(e) = child.delete(gio::Cancellable::NONE) {
+                                warn!("Failed to delete trash item {:?}: {}", name, e);
+                            } else {
+                                count += 1;
+                            }
                         }
+                        count
+                    }
+                    Err(e) => {
+                        warn!("Failed to enumerate trash for emptying: {}", e);
+                        0
                     }
-                    info!("Emptied {} items from trash", count);
-                }
-                Err(e) => {
-                    warn!("Failed to enumerate trash for emptying: {}", e);
                 }
-            }
+            })
+            .await
+            .unwrap_or(0);
+            
+            info!("Emptied {} items from trash", deleted);
+            Self::refresh_state_static(&state, &image);
+            emptying.set(false);
         });
     }
     
-    /// Setup drag-to-trash (files dropped on trash are deleted)
+    /// Setup drag-to-trash (files dropped on trash are moved to the trash)
+    ///
+    /// Only file lists are accepted, so dragging dock items (which carry
+    /// plain text) across the trash doesn't offer a drop.
     pub fn setup_drop_to_delete(&self) {
         use gtk::gdk;
         
-        let drop_target = gtk::DropTarget::new(glib::Type::STRING, gdk::DragAction::MOVE);
+        let drop_target = gtk::DropTarget::new(gdk::FileList::static_type(), gdk::DragAction::MOVE | gdk::DragAction::COPY);
+        let state = Rc::clone(&self.state);
+        let image = self.image.clone();
         
         drop_target.connect_drop(move |_target, value, _x, _y| {
-            if let Ok(uri_str) = value.get::<String>() {
-                for line in uri_str.lines() {
-                    let uri = line.trim();
-                    if uri.is_empty() || uri.starts_with('#') {
-                        continue;
-                    }
-                    
-                    info!("Moving to trash: {}", uri);
-                    let file = gio::File::for_uri(uri);
-                    
-                    if let Err(e) = file.trash(gio::Cancellable::NONE) {
-                        warn!("Failed to trash {}: {}", uri, e);
-                    }
+            let Ok(file_list) = value.get::<gdk::FileList>() else {
+                return false;
+            };
+            
+            for file in file_list.files() {
+                info!("Moving to trash: {}", file.uri());
+                if let Err(e) = file.trash(gio::Cancellable::NONE) {
+                    warn!("Failed to trash {}: {}", file.uri(), e);
                 }
-                return true;
             }
-            false
+            
+            Self::refresh_state_static(&state, &image);
+            true
         });
         
         self.button.add_controller(drop_target);
     }
 }
 
+/// Count entry names of a spec trash directory that are real trashed files
+fn count_trashed_entries(names: impl Iterator<Item = String>) -> usize {
+    names.filter(|name| is_trashed_entry(name)).count()
+}
+
+/// Whether an entry name in a spec trash directory is a trashed file
+///
+/// Tools writing the directory directly can leave the spec's `info`/`files`
+/// folders or stray `.trashinfo` records beside the trashed files. Only used
+/// for raw directories; `trash:///` lists trashed files alone.
+fn is_trashed_entry(name: &str) -> bool {
+    !TRASH_METADATA_NAMES.contains(&name) && !name.ends_with(".trashinfo")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     
+    #[test]
+    fn test_count_items_for_confirmation() {
+        let folder = std::env::temp_dir().join(format!("blazedock-trash-test-{}", std::process::id()));
+        std::fs::create_dir_all(folder.join("subdir")).unwrap();
+        std::fs::write(folder.join("a.txt"), "a").unwrap();
+        std::fs::write(folder.join("b.txt"), "b").unwrap();
+        std::fs::write(folder.join("info"), "trashed file named like metadata").unwrap();
+        
+        // Folders count as one item each, and trashed names are never filtered
+        assert_eq!(TrashItem::count_items(&gio::File::for_path(&folder)), 4);
+        assert_eq!(TrashItem::count_items(&gio::File::for_path(folder.join("missing"))), 0);
+        
+        assert_eq!(TrashItem::confirmation_text(1), "Permanently delete 1 item in the trash?");
+        assert_eq!(TrashItem::confirmation_text(3), "Permanently delete 3 items in the trash?");
+        
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+    
+    #[test]
+    fn test_metadata_only_trash_is_empty() {
+        // Stub a raw trash directory listing with no real entries
+        let names = ["info", "files", "expunged", "directorysizes", "report.pdf.trashinfo"];
+        let count = count_trashed_entries(names.iter().map(|n| n.to_string()));
+        assert_eq!(count, 0);
+        assert_eq!(TrashItem::state_for_count(count), TrashState::Empty);
+        
+        let names = ["info", "report.pdf", "report.pdf.trashinfo"];
+        let count = count_trashed_entries(names.iter().map(|n| n.to_string()));
+        assert_eq!(count, 1);
+        assert_eq!(TrashItem::state_for_count(count), TrashState::Full);
+    }
+    
     #[test]
     fn test_trash_state_default() {
         // Just verify enum works
@@ -4,18 +4,22 @@
 
 use gtk::prelude::*;
 use gtk::{Application, ApplicationWindow, Box, Orientation, Separator};
-use gtk4_layer_shell::{Edge, Layer, LayerShell};
-use log::{debug, info, warn};
+use gtk4_layer_shell::{Edge, KeyboardMode, Layer, LayerShell};
+use log::{debug, error, info, warn};
 
-use crate::config::{DockPosition, Settings, PinnedApp};
+use crate::config::{DockPosition, HideMode, Settings, PinnedApp};
 use crate::services::{
-    ProcessTracker, DBusService, WindowTracker, DriveMonitor, RecentFilesService, 
-    RunningAppsService, RunningApp, ThemeService, KeyboardService, ShortcutAction,
-    MultiMonitorService, ScreencopyService,
+    ProcessTracker, DBusService, BadgeInfo, DBusEvent, WindowTracker, DriveInfo, RecentFilesService, 
+    RunningAppsService, RunningApp, ShortcutAction,
+    MultiMonitorService, MultiMonitorMode, MonitorInfo, DockServices, ThemeService,
 };
-use crate::ui::{DockItem, RunningState, MagnificationController, SearchOverlay, SearchResult, TrashItem, StackItem};
+use crate::ui::{BadgeType, DockItem, DockItemAction, DockItemActions, ProgressDisplay, RunningState, MagnificationController, SearchOverlay, SearchResult, TrashItem, DriveItem, RecentItem, StackItem, NotificationItem, PreviewSources};
 use crate::ui::drag_drop;
-use std::cell::RefCell;
+use crate::ui::magnification::ease_scale;
+use crate::ui::search_overlay::installed_results;
+use crate::utils::desktop_entry::{discover_applications, DesktopEntry};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::rc::Rc;
 
 /// Main dock window wrapper
@@ -24,27 +28,53 @@ pub struct DockWindow {
     dock_box: Rc<RefCell<Box>>,  // Inner dock container for dynamic updates
     dock_items: Rc<RefCell<Vec<(String, Rc<RefCell<DockItem>>, bool)>>>, // (command, item, is_pinned)
     running_items: Rc<RefCell<Vec<(String, Rc<RefCell<DockItem>>)>>>, // Running (non-pinned) apps
-    process_tracker: ProcessTracker,
-    window_tracker: WindowTracker,
-    drive_monitor: DriveMonitor,
+    /// Trackers, D-Bus and shortcuts shared with the other docks
+    services: Rc<DockServices>,
+    drive_items: Rc<RefCell<Vec<DriveItem>>>,
     recent_files: RecentFilesService,
     running_apps_service: Rc<RunningAppsService>,
     magnification: Rc<RefCell<MagnificationController>>,
-    dbus_service: Option<DBusService>,
     is_hidden: Rc<RefCell<bool>>,
     settings: Rc<RefCell<Settings>>,
     separator: Rc<RefCell<Option<Separator>>>,
-    // New services
-    theme_service: ThemeService,
-    keyboard_service: KeyboardService,
-    multimonitor_service: MultiMonitorService,
-    screencopy_service: ScreencopyService,
+    notification_item: Rc<RefCell<Option<NotificationItem>>>,
+    trash_item: Rc<RefCell<Option<TrashItem>>>,
+    /// Folder stacks, kept so their folder monitors keep running
+    stack_items: RefCell<Vec<StackItem>>,
+    recent_item: Rc<RefCell<Option<RecentItem>>>,
+    search_overlay: Rc<SearchOverlay>,
+    preview_sources: PreviewSources,
     focused_item_index: Rc<RefCell<Option<usize>>>,
+    item_actions: DockItemActions,
+    /// Item being dragged to reorder, shared by the drag sources and drop targets
+    drag_state: Rc<RefCell<drag_drop::DragState>>,
+    monitor: Option<MonitorInfo>,
+    /// Edge strip that reveals the auto-hidden dock (layer-shell only)
+    reveal_zone: RefCell<Option<ApplicationWindow>>,
+    /// Called with every pinned app after this dock changed them
+    pins_changed: RefCell<Option<std::boxed::Box<dyn Fn(&Settings)>>>,
+    /// Periodic refreshers, stopped when the dock is closed
+    refresh_sources: RefCell<Vec<gtk::glib::SourceId>>,
 }
 
 impl DockWindow {
     /// Create a new dock window
-    pub fn new(app: &Application, settings: &Settings) -> Self {
+    pub fn new(app: &Application, settings: &Settings, services: &Rc<DockServices>) -> Self {
+        Self::build(app, settings, services, None)
+    }
+
+    /// Create a dock window placed on a specific monitor
+    ///
+    /// The monitor's entry in `monitor_overrides` is applied on top of
+    /// `settings`. Placement needs layer shell; floating windows are
+    /// positioned by the compositor.
+    pub fn spawn_on_monitor(app: &Application, settings: &Settings, services: &Rc<DockServices>, monitor: &MonitorInfo) -> Self {
+        info!("Creating dock on monitor {} ({})", monitor.key(), monitor.name);
+        Self::build(app, &settings.for_monitor(&monitor.connector), services, Some(monitor.clone()))
+    }
+
+    /// Build the dock window, optionally bound to a monitor
+    fn build(app: &Application, settings: &Settings, services: &Rc<DockServices>, monitor: Option<MonitorInfo>) -> Self {
         let is_hidden = Rc::new(RefCell::new(false));
         
         // Check if we should use layer shell
@@ -72,7 +102,7 @@ impl DockWindow {
 
         // Configure based on mode
         if use_layer_shell {
-            Self::setup_layer_shell(&window, settings);
+            Self::setup_layer_shell(&window, settings, monitor.as_ref());
         } else {
             // Floating window mode - position on left edge
             Self::setup_floating_window(&window, settings);
@@ -87,18 +117,29 @@ impl DockWindow {
         let dock_box = Rc::new(RefCell::new(Box::new(Orientation::Horizontal, 0)));
         let separator: Rc<RefCell<Option<Separator>>> = Rc::new(RefCell::new(None));
 
-        // Initialize D-Bus service
-        let dbus_service = DBusService::new();
-        dbus_service.start();
-
         // Create magnification controller
-        let magnification = Rc::new(RefCell::new(MagnificationController::new(
-            settings.hover_zoom_scale,
-            2,
-        )));
+        let magnification = Rc::new(RefCell::new(MagnificationController::from_settings(settings)));
+        
+        // Handler slot for dock item actions, filled in by setup_item_actions()
+        let item_actions: DockItemActions = Rc::new(RefCell::new(None));
+        let notification_item: Rc<RefCell<Option<NotificationItem>>> = Rc::new(RefCell::new(None));
+        let trash_item: Rc<RefCell<Option<TrashItem>>> = Rc::new(RefCell::new(None));
+        let recent_item: Rc<RefCell<Option<RecentItem>>> = Rc::new(RefCell::new(None));
+        
+        // Initialize recent files service
+        let recent_files = RecentFilesService::new();
+        recent_files.refresh();
+        
+        // Hover previews draw on the window tracker and thumbnail capture, progress rings on the theme
+        let preview_sources = PreviewSources {
+            window_tracker: services.window_tracker.clone(),
+            screencopy: services.screencopy.clone(),
+            theme: services.theme.clone(),
+        };
         
         // Create dock content and store dock_box reference
-        let (dock_content, inner_dock_box) = Self::create_dock_content(settings, &dock_items, &magnification);
+        let (dock_content, inner_dock_box, stack_items) = Self::create_dock_content(settings, &dock_items, &magnification, &item_actions, &preview_sources, &notification_item, &trash_item);
+        *recent_item.borrow_mut() = Self::create_recent_item(&inner_dock_box, settings, &recent_files);
         *dock_box.borrow_mut() = inner_dock_box;
         
         // Set size based on position
@@ -112,33 +153,37 @@ impl DockWindow {
         };
         
         dock_content.set_size_request(width, height);
-        window.set_child(Some(&dock_content));
+        
+        // Store settings
+        let settings_rc = Rc::new(RefCell::new(settings.clone()));
+        
+        // Wrap the content so the search overlay can be shown above it
+        let search_overlay = Rc::new(SearchOverlay::new());
+        search_overlay.set_child(&dock_content);
+        let search_settings = Rc::clone(&settings_rc);
+        search_overlay.on_select(move |result| {
+            debug!("Launching '{}' from search", result.name);
+            if let Err(e) = result.launch(search_settings.borrow().terminal.as_deref()) {
+                error!("Failed to launch '{}': {}", result.command, e);
+            }
+        });
+        window.set_child(Some(search_overlay.widget()));
 
-        // D-Bus event handling uses callbacks now (see DBusService::on_badge_update)
+        // Render LauncherEntry badges and notifications on the matching dock items
+        Self::setup_dbus_events(&window, &services.dbus, &dock_items, &running_items, &notification_item);
+        
+        // Recolor progress rings with the system accent when the theme changes
+        Self::setup_theme_updates(&window, &services.theme, &dock_items, &running_items);
 
         debug!(
             "Window created: position={:?}, size={}x{}, layer_shell={}",
             settings.position, width, height, use_layer_shell
         );
 
-        // Create process tracker and register apps
-        let process_tracker = ProcessTracker::new();
+        // Track this dock's pinned apps
         for app in &settings.pinned_apps {
-            process_tracker.register_app(&app.command);
+            services.process_tracker.register_app(&app.command);
         }
-        process_tracker.start();
-
-        // Initialize window tracker
-        let window_tracker = WindowTracker::new();
-        window_tracker.start();
-
-        // Initialize drive monitor
-        let drive_monitor = DriveMonitor::new();
-        drive_monitor.start();
-
-        // Initialize recent files service
-        let recent_files = RecentFilesService::new();
-        recent_files.refresh();
 
         // Store dock items for later updates
         let dock_items_stored = Rc::clone(&dock_items);
@@ -146,20 +191,11 @@ impl DockWindow {
         
         // Initialize running apps service
         let running_apps_service = Rc::new(RunningAppsService::new());
+        running_apps_service.set_icon_overrides(settings.icon_overrides.clone());
         
-        // Store settings
-        let settings_rc = Rc::new(RefCell::new(settings.clone()));
-
-        // Initialize new services
-        let theme_service = ThemeService::new();
-        theme_service.start_monitoring();
-        
-        let keyboard_service = KeyboardService::new();
-        let multimonitor_service = MultiMonitorService::new();
-        multimonitor_service.start_monitoring();
-        
-        let screencopy_service = ScreencopyService::new();
-        screencopy_service.start();
+        if settings.multi_monitor_mode == crate::config::MultiMonitorMode::Follow {
+            Self::setup_follow_mouse(&window, &services.monitors, &settings_rc);
+        }
         
         let focused_item_index = Rc::new(RefCell::new(None::<usize>));
         
@@ -168,21 +204,27 @@ impl DockWindow {
             dock_box: Rc::clone(&dock_box),
             dock_items: dock_items_stored,
             running_items: Rc::clone(&running_items),
-            process_tracker,
-            window_tracker,
-            drive_monitor,
+            services: Rc::clone(services),
             recent_files,
             running_apps_service: Rc::clone(&running_apps_service),
             magnification: magnification_stored,
-            dbus_service: Some(dbus_service),
             is_hidden: Rc::clone(&is_hidden),
             settings: Rc::clone(&settings_rc),
             separator: Rc::clone(&separator),
-            theme_service,
-            keyboard_service,
-            multimonitor_service,
-            screencopy_service,
+            notification_item,
+            trash_item,
+            stack_items: RefCell::new(stack_items),
+            recent_item,
+            drive_items: Rc::new(RefCell::new(Vec::new())),
+            search_overlay,
+            preview_sources,
             focused_item_index: Rc::clone(&focused_item_index),
+            item_actions,
+            drag_state: drag_drop::create_drag_state(),
+            monitor,
+            reveal_zone: RefCell::new(None),
+            pins_changed: RefCell::new(None),
+            refresh_sources: RefCell::new(Vec::new()),
         };
 
         // Setup keyboard shortcuts if enabled
@@ -190,49 +232,95 @@ impl DockWindow {
             self_instance.setup_keyboard_shortcuts();
         }
 
-        // Setup auto-hide if enabled
-        if settings.auto_hide {
-            self_instance.setup_auto_hide(settings);
+        // Setup auto-hide or intellihide if enabled
+        match settings.effective_hide_mode() {
+            HideMode::Never => {}
+            HideMode::AutoHide => self_instance.setup_auto_hide(settings),
+            HideMode::IntelliHide => self_instance.setup_intellihide(settings),
         }
         
-        // Setup drag and drop for .desktop file pinning
-        // DISABLED: Causes issues with click handling
-        // self_instance.setup_drag_drop();
+        // Setup drag and drop for reordering and .desktop file pinning
+        self_instance.setup_drag_drop();
 
         self_instance
     }
 
+    /// Move the dock to whichever monitor the pointer is on
+    fn setup_follow_mouse(
+        window: &ApplicationWindow,
+        multimonitor_service: &MultiMonitorService,
+        settings: &Rc<RefCell<Settings>>,
+    ) {
+        multimonitor_service.set_mode(MultiMonitorMode::FollowMouse);
+        
+        // Monitor callbacks must be Send, so hand changes over to the main loop
+        let (sender, receiver) = async_channel::unbounded::<MonitorInfo>();
+        multimonitor_service.on_monitor_change(move |monitor| {
+            let _ = sender.try_send(monitor.clone());
+        });
+        multimonitor_service.start_following();
+        
+        let window_weak = window.downgrade();
+        let service = multimonitor_service.clone();
+        let settings = Rc::clone(settings);
+        gtk::glib::spawn_future_local(async move {
+            while let Ok(monitor) = receiver.recv().await {
+                let Some(window) = window_weak.upgrade() else {
+                    break;
+                };
+                
+                let margin = settings.borrow().margin as i32;
+                let (x, y) = service.get_dock_geometry(&monitor, window.width(), window.height(), margin);
+                debug!("Following pointer to monitor {} (dock at {},{})", monitor.key(), x, y);
+                
+                // Only layer surfaces can be placed; the compositor positions floating windows
+                if window.is_layer_window() {
+                    match monitor.gdk_monitor() {
+                        Some(gdk_monitor) => window.set_monitor(Some(&gdk_monitor)),
+                        None => warn!("Monitor {} not found, dock not moved", monitor.key()),
+                    }
+                }
+            }
+        });
+    }
+
     /// Setup keyboard shortcuts
     fn setup_keyboard_shortcuts(&self) {
         let dock_items = Rc::clone(&self.dock_items);
         let focused_index = Rc::clone(&self.focused_item_index);
         let window = self.window.clone();
         let settings = Rc::clone(&self.settings);
+        let search_overlay = Rc::clone(&self.search_overlay);
+        let window_tracker = self.services.window_tracker.clone();
+        // Per-app position of the last window focused by CycleWindows
+        let window_cursors: Rc<RefCell<HashMap<String, usize>>> = Rc::new(RefCell::new(HashMap::new()));
         
         // Register shortcut handler
-        self.keyboard_service.on_action("main", move |action| {
+        self.services.keyboard.on_action(&self.shortcut_id(), move |action| {
             match action {
                 ShortcutAction::ActivateApp(num) => {
                     let items = dock_items.borrow();
                     let index = (num as usize).saturating_sub(1);
                     if let Some((command, _, _)) = items.get(index) {
                         debug!("Activating app at index {} via shortcut", index);
-                        crate::utils::launcher::launch_command(command);
+                        launch_item(&settings.borrow(), command);
                     }
                 }
                 ShortcutAction::ToggleDock => {
                     debug!("Toggle dock via shortcut");
-                    // Toggle visibility
-                    if window.is_visible() {
-                        window.set_visible(false);
-                    } else {
-                        window.set_visible(true);
-                        window.present();
-                    }
+                    set_window_visible(&window, !window.is_visible());
                 }
                 ShortcutAction::ShowSearch => {
                     debug!("Show search via shortcut");
-                    // TODO: Integrate search overlay
+                    if !search_overlay.is_visible() {
+                        // Refresh so newly pinned or installed apps show up
+                        search_overlay.set_apps(build_search_results(
+                            &settings.borrow().pinned_apps,
+                            discover_applications(),
+                        ));
+                        window.present();
+                    }
+                    search_overlay.toggle();
                 }
                 ShortcutAction::NavigateLeft | ShortcutAction::NavigateRight => {
                     let items = dock_items.borrow();
@@ -262,6 +350,43 @@ impl DockWindow {
                         }
                     }
                 }
+                ShortcutAction::ShowContextMenu => {
+                    let items = dock_items.borrow();
+                    
+                    // Fall back to the first item when nothing has keyboard focus
+                    let index = focused_index.borrow().unwrap_or(0);
+                    if let Some((_, item, _)) = items.get(index).or_else(|| items.first()) {
+                        debug!("Showing context menu for item {} via shortcut", index);
+                        item.borrow().show_context_menu();
+                    }
+                }
+                ShortcutAction::CycleWindows => {
+                    let items = dock_items.borrow();
+                    let app_ids: Vec<String> = items.iter()
+                        .map(|(command, _, _)| command.split_whitespace().next().unwrap_or(command).to_string())
+                        .collect();
+                    
+                    // Prefer the keyboard-focused item, else the app owning the active window
+                    let app_id = focused_index.borrow()
+                        .and_then(|idx| app_ids.get(idx).cloned())
+                        .or_else(|| app_ids.into_iter().find(|id| window_tracker.is_app_focused(id)));
+                    
+                    let Some(app_id) = app_id else {
+                        debug!("No focused app to cycle windows for");
+                        return;
+                    };
+                    
+                    let windows = window_tracker.get_windows_for_app(&app_id);
+                    let mut cursors = window_cursors.borrow_mut();
+                    let current = cursors.get(&app_id).copied()
+                        .or_else(|| windows.iter().position(|w| w.is_active));
+                    
+                    if let Some(next) = next_window_index(current, windows.len()) {
+                        debug!("Cycling '{}' to window {}/{}", app_id, next + 1, windows.len());
+                        window_tracker.focus_window(&windows[next].id);
+                        cursors.insert(app_id, next);
+                    }
+                }
                 ShortcutAction::ActivateFocused => {
                     let items = dock_items.borrow();
                     let focused = focused_index.borrow();
@@ -269,16 +394,15 @@ impl DockWindow {
                     if let Some(idx) = *focused {
                         if let Some((command, _, _)) = items.get(idx) {
                             debug!("Activating focused item at index {}", idx);
-                            crate::utils::launcher::launch_command(command);
+                            launch_item(&settings.borrow(), command);
                         }
                     }
                 }
-                _ => {}
             }
         });
 
         // Attach keyboard controller to window
-        self.keyboard_service.setup_keyboard_controller(&self.window);
+        self.services.keyboard.setup_keyboard_controller(&self.shortcut_id(), &self.window);
         
         info!("Keyboard shortcuts enabled");
     }
@@ -286,107 +410,247 @@ impl DockWindow {
     /// Setup drag and drop for reordering and pinning apps
     fn setup_drag_drop(&self) {
         let dock_box = self.dock_box.borrow();
-        let settings = Rc::clone(&self.settings);
         
-        // NOTE: Drag sources for reordering are disabled for now as they
-        // interfere with normal button clicks (cursor changes to +).
-        // TODO: Fix by using gesture-based drag initiation (long press)
-        
-        // Create shared drag state for tracking dragged item
-        // let drag_state = drag_drop::create_drag_state();
-        
-        // Setup drag source on each pinned dock item (DISABLED - causes click issues)
-        // let items = self.dock_items.borrow();
-        // for (index, (_, item, is_pinned)) in items.iter().enumerate() {
-        //     if *is_pinned {
-        //         let widget = item.borrow().widget().clone();
-        //         drag_drop::setup_drag_source_for_reorder(
-        //             &widget,
-        //             index,
-        //             Rc::clone(&drag_state),
-        //             Rc::clone(&settings),
-        //         );
-        //     }
-        // }
-        // drop(items);
-        
-        // Setup drop target on dock_box for reordering (DISABLED - needs drag sources)
-        // drag_drop::setup_drop_target_for_reorder(
-        //     &dock_box,
-        //     Rc::clone(&drag_state),
-        //     Rc::clone(&settings),
-        // );
-        
-        // Setup drop target for .desktop files from file managers (WORKS)
-        drag_drop::setup_drop_target_desktop_files(&dock_box, settings);
-        
-        info!("Drag and drop enabled - drop .desktop files to pin apps");
+        // Pinned items can be dragged to reorder them, or off the dock to unpin
+        for (_, item, is_pinned) in self.dock_items.borrow().iter() {
+            if *is_pinned {
+                self.setup_reorder_source(&item.borrow());
+            }
+        }
+        drag_drop::setup_drop_target_for_reorder(
+            &dock_box,
+            Rc::clone(&self.drag_state),
+            Rc::clone(&self.settings),
+            Rc::clone(&self.item_actions),
+        );
+        
+        // Setup drop target for .desktop files from file managers
+        drag_drop::setup_drop_target_desktop_files(&dock_box, Rc::clone(&self.drag_state), Rc::clone(&self.item_actions));
+        
+        info!("Drag and drop enabled - drag to reorder, drop .desktop files to pin apps");
+    }
+
+    /// Let a pinned item be dragged to reorder or unpin it
+    fn setup_reorder_source(&self, item: &DockItem) {
+        drag_drop::setup_drag_source_for_reorder(
+            item.widget(),
+            Rc::clone(&self.drag_state),
+            Rc::clone(&self.settings),
+            Rc::clone(&self.item_actions),
+        );
     }
 
     /// Setup auto-hide functionality
+    ///
+    /// The dock hides `auto_hide_delay` ms after the pointer leaves it. In
+    /// layer-shell mode a thin reveal zone along the screen edge brings it
+    /// back, since the hidden dock itself may be out of reach. The zone is
+    /// only mapped while the dock is hidden, so it never eats clicks meant
+    /// for windows at the edge.
     fn setup_auto_hide(&self, settings: &Settings) {
-        let is_hidden_flag = Rc::clone(&self.is_hidden);
         let window = self.window.clone();
         let position = settings.position;
+        let delay = auto_hide_delay(settings.auto_hide_delay);
         
         // Initial state: visible
         window.add_css_class("dock-visible");
         
+        let zone = if window.is_layer_window() {
+            window.application()
+                .map(|app| Self::create_reveal_zone(&app, settings, self.monitor.as_ref()))
+        } else {
+            None
+        };
+        let pending_hide: Rc<RefCell<Option<gtk::glib::SourceId>>> = Rc::new(RefCell::new(None));
+        
+        let is_hidden_reveal = Rc::clone(&self.is_hidden);
+        let window_reveal = window.clone();
+        let zone_reveal = zone.clone();
+        let pending_reveal = Rc::clone(&pending_hide);
+        let reveal: Rc<dyn Fn()> = Rc::new(move || {
+            if let Some(source) = pending_reveal.borrow_mut().take() {
+                source.remove();
+            }
+            *is_hidden_reveal.borrow_mut() = false;
+            show_dock(&window_reveal, position);
+            if let Some(zone) = &zone_reveal {
+                zone.set_visible(false);
+            }
+        });
+        
+        let is_hidden_timer = Rc::clone(&self.is_hidden);
+        let window_timer = window.clone();
+        let zone_timer = zone.clone();
+        let pending_timer = Rc::clone(&pending_hide);
+        let schedule_hide: Rc<dyn Fn()> = Rc::new(move || {
+            // Restart the delay rather than stacking another timer
+            if let Some(source) = pending_timer.borrow_mut().take() {
+                source.remove();
+            }
+            
+            let is_hidden = Rc::clone(&is_hidden_timer);
+            let window = window_timer.clone();
+            let zone = zone_timer.clone();
+            let pending = Rc::clone(&pending_timer);
+            let source = gtk::glib::timeout_add_local_once(delay, move || {
+                pending.borrow_mut().take();
+                
+                debug!("Auto-hiding dock");
+                *is_hidden.borrow_mut() = true;
+                hide_dock(&window, position);
+                if let Some(zone) = &zone {
+                    zone.present();
+                }
+            });
+            *pending_timer.borrow_mut() = Some(source);
+        });
+        
         let motion_controller = gtk::EventControllerMotion::new();
         
-        let is_hidden_enter = Rc::clone(&is_hidden_flag);
-        let window_enter = window.clone();
+        let reveal_enter = Rc::clone(&reveal);
         motion_controller.connect_enter(move |_, _, _| {
             debug!("Mouse entered dock area - cancelling hide");
-            *is_hidden_enter.borrow_mut() = false;
-            let pos_class = format!("dock-hidden-{}", match position {
-                DockPosition::Left => "left",
-                DockPosition::Right => "right",
-                DockPosition::Top => "top",
-                DockPosition::Bottom => "bottom",
-            });
-            window_enter.remove_css_class(&pos_class);
-            window_enter.add_css_class("dock-visible");
+            reveal_enter();
         });
         
-        let is_hidden_leave = Rc::clone(&is_hidden_flag);
-        let window_leave = window.clone();
+        let schedule_hide_leave = Rc::clone(&schedule_hide);
         motion_controller.connect_leave(move |_| {
             debug!("Mouse left dock area - starting hide timer");
-            *is_hidden_leave.borrow_mut() = true;
+            schedule_hide_leave();
+        });
+        
+        window.add_controller(motion_controller);
+        
+        if let Some(zone) = zone {
+            // Reveal on touch; the dock's own enter cancels the hide again
+            let zone_motion = gtk::EventControllerMotion::new();
+            zone_motion.connect_enter(move |_, _, _| {
+                debug!("Pointer touched reveal zone - showing dock");
+                reveal();
+                schedule_hide();
+            });
+            zone.add_controller(zone_motion);
+            
+            if let Some(old) = self.reveal_zone.replace(Some(zone)) {
+                old.destroy();
+            }
+        }
+    }
+
+    /// Setup intellihide: hide only while a window overlaps the dock
+    ///
+    /// Needs the dock on a layer surface and window geometry from the
+    /// compositor; without either it falls back to auto-hide.
+    fn setup_intellihide(&self, settings: &Settings) {
+        if !self.window.is_layer_window() || !self.services.window_tracker.reports_geometry() {
+            info!("Window geometry unavailable, using auto-hide instead of intellihide");
+            self.setup_auto_hide(settings);
+            return;
+        }
+        
+        let position = settings.position;
+        self.window.add_css_class("dock-visible");
+        self.services.window_tracker.watch_geometry();
+        
+        // Never hide from under the pointer
+        let hovered = Rc::new(Cell::new(false));
+        let motion_controller = gtk::EventControllerMotion::new();
+        let hovered_enter = Rc::clone(&hovered);
+        let is_hidden_enter = Rc::clone(&self.is_hidden);
+        let window_enter = self.window.clone();
+        motion_controller.connect_enter(move |_, _, _| {
+            hovered_enter.set(true);
+            *is_hidden_enter.borrow_mut() = false;
+            show_dock(&window_enter, position);
+        });
+        let hovered_leave = Rc::clone(&hovered);
+        motion_controller.connect_leave(move |_| hovered_leave.set(false));
+        self.window.add_controller(motion_controller);
+        
+        let window_weak = self.window.downgrade();
+        let window_tracker = self.services.window_tracker.clone();
+        let is_hidden = Rc::clone(&self.is_hidden);
+        let monitor = self.monitor.clone();
+        
+        let source = gtk::glib::timeout_add_local(INTELLIHIDE_INTERVAL, move || {
+            let Some(window) = window_weak.upgrade() else {
+                return gtk::glib::ControlFlow::Break;
+            };
+            if hovered.get() {
+                return gtk::glib::ControlFlow::Continue;
+            }
+            let Some(monitor_area) = monitor_rect(&window, monitor.as_ref()) else {
+                return gtk::glib::ControlFlow::Continue;
+            };
             
-            let is_hidden_timer = Rc::clone(&is_hidden_leave);
-            let window_timer = window_leave.clone();
+            let dock = dock_rect(monitor_area, position, window.width(), window.height());
+            let overlapped = window_tracker.get_all_windows().iter()
+                .filter_map(|info| info.geometry)
+                .any(|geometry| rects_overlap(geometry, dock));
             
-            // Hide after 1 second of being outside
-            gtk::glib::timeout_add_seconds_local(1, move || {
-                // If is_hidden_timer was reset to false by enter event, don't hide
-                if !*is_hidden_timer.borrow() {
-                    return gtk::glib::ControlFlow::Break;
+            if overlapped != *is_hidden.borrow() {
+                debug!("Intellihide: window overlap {}", overlapped);
+                *is_hidden.borrow_mut() = overlapped;
+                if overlapped {
+                    hide_dock(&window, position);
+                } else {
+                    show_dock(&window, position);
                 }
-                
-                debug!("Auto-hiding dock");
-                window_timer.remove_css_class("dock-visible");
-                let pos_class = format!("dock-hidden-{}", match position {
-                    DockPosition::Left => "left",
-                    DockPosition::Right => "right",
-                    DockPosition::Top => "top",
-                    DockPosition::Bottom => "bottom",
-                });
-                window_timer.add_css_class(&pos_class);
-                
-                gtk::glib::ControlFlow::Break
-            });
+            }
+            gtk::glib::ControlFlow::Continue
         });
+        self.refresh_sources.borrow_mut().push(source);
         
-        window.add_controller(motion_controller);
+        info!("Intellihide enabled");
+    }
+
+    /// Create the thin, invisible strip along the dock's screen edge
+    fn create_reveal_zone(app: &Application, settings: &Settings, monitor: Option<&MonitorInfo>) -> ApplicationWindow {
+        let zone = ApplicationWindow::builder()
+            .application(app)
+            .title("BlazeDock Reveal Zone")
+            .decorated(false)
+            .css_classes(vec!["dock-reveal-zone"])
+            .build();
+        
+        zone.init_layer_shell();
+        zone.set_layer(Layer::Overlay);
+        if let Some(gdk_monitor) = monitor.and_then(|info| info.gdk_monitor()) {
+            zone.set_monitor(Some(&gdk_monitor));
+        }
+        
+        // Span the whole edge, REVEAL_ZONE_SIZE thick
+        let (edge, horizontal) = match settings.position {
+            DockPosition::Left => (Edge::Left, false),
+            DockPosition::Right => (Edge::Right, false),
+            DockPosition::Top => (Edge::Top, true),
+            DockPosition::Bottom => (Edge::Bottom, true),
+        };
+        for other in [Edge::Left, Edge::Right, Edge::Top, Edge::Bottom] {
+            let along_edge = if horizontal {
+                matches!(other, Edge::Left | Edge::Right)
+            } else {
+                matches!(other, Edge::Top | Edge::Bottom)
+            };
+            zone.set_anchor(other, other == edge || along_edge);
+        }
+        if horizontal {
+            zone.set_default_size(-1, REVEAL_ZONE_SIZE);
+        } else {
+            zone.set_default_size(REVEAL_ZONE_SIZE, -1);
+        }
+        // Sit right at the edge, even over other exclusive zones
+        zone.set_exclusive_zone(-1);
+        
+        debug!("Reveal zone created at {:?} edge", settings.position);
+        zone
     }
 
     /// Update running state for all dock items
     pub fn update_running_states(&self) {
         let dock_items = self.dock_items.borrow();
         for (command, item, _is_pinned) in dock_items.iter() {
-            let is_running = self.process_tracker.is_running(command);
+            let is_running = self.services.process_tracker.is_running(command);
             let mut item = item.borrow_mut();
             let state = if is_running {
                 RunningState::Running { window_count: 1 }
@@ -445,45 +709,415 @@ impl DockWindow {
         self.window.present();
     }
 
+    /// Show the dock if it was hidden and raise it
+    pub fn show(&self) {
+        set_window_visible(&self.window, true);
+    }
+
+    /// The monitor this dock was spawned on, if bound to one
+    pub fn monitor(&self) -> Option<&MonitorInfo> {
+        self.monitor.as_ref()
+    }
+
+    /// Name of the profile this dock shows
+    pub fn active_profile(&self) -> String {
+        self.settings.borrow().active_profile.clone()
+    }
+
+    /// Id of this dock's handler in the shared keyboard service
+    fn shortcut_id(&self) -> String {
+        self.monitor.as_ref()
+            .map(|monitor| monitor.key().to_string())
+            .unwrap_or_else(|| "main".to_string())
+    }
+
+    /// Destroy the dock's window, leaving the shared services running
+    pub fn close(&self) {
+        self.services.keyboard.remove_action(&self.shortcut_id());
+        for source in self.refresh_sources.take() {
+            source.remove();
+        }
+        if let Some(zone) = self.reveal_zone.take() {
+            zone.destroy();
+        }
+        self.window.destroy();
+        debug!("Dock window closed");
+    }
+
     /// Reload the dock with new settings
     pub fn reload(&self, settings: &Settings) {
         debug!("Reloading dock with new settings");
         
-        // Remove old content
-        self.window.set_child(None::<&gtk::Widget>);
+        let settings = &self.monitor_settings(settings);
+        
+        // Keep the shared settings in sync for the periodic refreshers
+        *self.settings.borrow_mut() = settings.clone();
+        
+        // Track any newly pinned apps and restyle every dock
+        self.services.configure(settings);
+        self.running_apps_service.set_icon_overrides(settings.icon_overrides.clone());
+        self.magnification.borrow_mut().configure(settings);
         
         // Clear dock items and running items
         self.dock_items.borrow_mut().clear();
         self.running_items.borrow_mut().clear();
+        self.drive_items.borrow_mut().clear();
         *self.separator.borrow_mut() = None;
         
         // Re-create content
-        let (dock_content, inner_dock_box) = Self::create_dock_content(settings, &self.dock_items, &self.magnification);
+        let (dock_content, inner_dock_box, stack_items) = Self::create_dock_content(settings, &self.dock_items, &self.magnification, &self.item_actions, &self.preview_sources, &self.notification_item, &self.trash_item);
+        *self.stack_items.borrow_mut() = stack_items;
+        *self.recent_item.borrow_mut() = Self::create_recent_item(&inner_dock_box, settings, &self.recent_files);
         *self.dock_box.borrow_mut() = inner_dock_box;
-        self.window.set_child(Some(&dock_content));
+        self.search_overlay.set_child(&dock_content);
+        
+        // The drop target went away with the old dock box
+        self.setup_drag_drop();
+        
+        // The summary item was recreated, so restore its count
+        Self::update_notification_summary(&self.notification_item, &self.services.dbus);
         
         // Re-setup layer shell if needed
         if gtk4_layer_shell::is_supported() && std::env::var("BLAZEDOCK_LAYER_SHELL").is_ok() {
-            Self::setup_layer_shell(&self.window, settings);
+            Self::setup_layer_shell(&self.window, settings, self.monitor.as_ref());
         }
         
         info!("Dock reloaded successfully");
     }
 
-    /// Show settings dialog
-    pub fn show_settings(&self, settings: &Settings) {
+    /// The part of `settings` that applies to this dock's monitor
+    fn monitor_settings(&self, settings: &Settings) -> Settings {
+        match self.monitor {
+            Some(ref monitor) => settings.for_monitor(&monitor.connector),
+            None => settings.clone(),
+        }
+    }
+
+    /// Call `callback` with every pinned app whenever this dock changes them
+    ///
+    /// Lets other docks, which show the same pinned apps, follow along.
+    pub fn on_pins_changed(&self, callback: impl Fn(&Settings) + 'static) {
+        *self.pins_changed.borrow_mut() = Some(std::boxed::Box::new(callback));
+    }
+
+    /// Take over the pinned apps of `settings` after an item action saved them
+    ///
+    /// `settings` holds every pinned app; this dock keeps the ones shown
+    /// on its monitor.
+    fn update_pinned_apps(&self, settings: &Settings) {
+        self.settings.borrow_mut().pinned_apps = self.monitor_settings(settings).pinned_apps;
+        if let Some(callback) = self.pins_changed.borrow().as_ref() {
+            callback(settings);
+        }
+    }
+
+    /// Index of the pinned item for `command` among this dock's items
+    fn pinned_item_index(&self, command: &str) -> Option<usize> {
+        self.dock_items.borrow().iter()
+            .position(|(item_command, _, is_pinned)| *is_pinned && item_command == command)
+    }
+
+    /// Install the handler for actions triggered from dock item menus
+    pub fn setup_item_actions(self: &Rc<Self>) {
+        let weak = Rc::downgrade(self);
+        *self.item_actions.borrow_mut() = Some(std::boxed::Box::new(move |action| {
+            if let Some(dock) = weak.upgrade() {
+                dock.handle_item_action(action);
+            }
+        }));
+    }
+
+    /// Handle an action triggered from a dock item
+    fn handle_item_action(self: &Rc<Self>, action: DockItemAction) {
+        debug!("Handling dock item action: {:?}", action);
+        
+        match action {
+            DockItemAction::Unpin { index, command } => {
+                let mut settings = match Settings::load_active() {
+                    Ok(settings) => settings,
+                    Err(e) => {
+                        error!("Failed to load settings for unpin: {}", e);
+                        return;
+                    }
+                };
+                
+                // The index counts this dock's items, which may be a per-monitor
+                // subset of the pinned apps, so the command decides
+                let Some(current) = settings.find_pinned_index(index, &command) else {
+                    warn!("'{}' is no longer pinned, refreshing dock", command);
+                    self.schedule_reload(settings);
+                    return;
+                };
+                
+                if let Some(app) = settings.remove_pinned_app(current) {
+                    info!("Unpinned '{}' from dock", app.name);
+                }
+                self.update_pinned_apps(&settings);
+                
+                // The item's own menu sent this, so remove it once that's done
+                let weak = Rc::downgrade(self);
+                gtk::glib::idle_add_local_once(move || {
+                    let Some(dock) = weak.upgrade() else {
+                        return;
+                    };
+                    if let Some(item_index) = dock.pinned_item_index(&command) {
+                        dock.remove_item_by_index(item_index);
+                    }
+                });
+            }
+            DockItemAction::Move { from, to } => {
+                let mut settings = match Settings::load_active() {
+                    Ok(settings) => settings,
+                    Err(e) => {
+                        error!("Failed to load settings for reorder: {}", e);
+                        return;
+                    }
+                };
+                
+                if !self.move_item(&mut settings, from, to) {
+                    warn!("Reorder {} -> {} out of range", from, to);
+                    return;
+                }
+                if let Err(e) = settings.save_active() {
+                    warn!("Failed to save config after reordering: {}", e);
+                }
+                self.update_pinned_apps(&settings);
+            }
+            DockItemAction::Reload => match Settings::load_active() {
+                Ok(settings) => self.schedule_reload(settings),
+                Err(e) => error!("Failed to reload settings, keeping current dock: {}", e),
+            },
+            DockItemAction::KeepInDock(app) => self.keep_in_dock(app),
+            DockItemAction::ClearBadge { command, desktop_id } => {
+                let dbus_service = &self.services.dbus;
+                let item = [(command, desktop_id)];
+                
+                for app_id in dbus_service.get_all_badges().into_keys() {
+                    if find_item_for_app_id(&item, &app_id).is_some() {
+                        dbus_service.clear_badge(&app_id);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Pin a running app and move its item into the pinned section in place
+    ///
+    /// Only the pinned apps are saved. If this monitor's dock doesn't show
+    /// the app, the dock is reloaded instead of changed in place.
+    fn keep_in_dock(self: &Rc<Self>, app: PinnedApp) {
+        let mut settings = match Settings::load_active() {
+            Ok(settings) => settings,
+            Err(e) => {
+                error!("Failed to load settings to pin '{}': {}", app.name, e);
+                return;
+            }
+        };
+        
+        let command = app.command.clone();
+        if settings.pinned_apps.iter().any(|pinned| pinned.command == command) {
+            debug!("'{}' is already pinned", command);
+        } else {
+            settings.pinned_apps.push(app.clone());
+            if let Err(e) = settings.save_active() {
+                error!("Failed to save config after pinning '{}': {}", app.name, e);
+                return;
+            }
+            info!("App '{}' added to dock", app.name);
+        }
+        
+        if self.pinned_item_index(&command).is_some() {
+            return;
+        }
+        
+        // Refreshers now treat the app as pinned
+        self.services.process_tracker.register_app(&command);
+        self.update_pinned_apps(&settings);
+        
+        let shown = self.settings.borrow().pinned_apps.iter().any(|pinned| pinned.command == command);
+        if !shown {
+            debug!("'{}' is not shown on this monitor, refreshing dock", command);
+            self.schedule_reload(settings);
+            return;
+        }
+        
+        let dock_box = self.dock_box.borrow();
+        let anchor = self.dock_items.borrow().last()
+            .map(|(_, item, _)| item.borrow().widget().clone());
+        
+        let migrated = migrate_running_item(
+            &mut self.running_items.borrow_mut(),
+            &mut self.dock_items.borrow_mut(),
+            &command,
+        );
+        
+        if let Some(index) = migrated {
+            let dock_item = Rc::clone(&self.dock_items.borrow()[index].1);
+            // Running items are not magnified, so hook it up like a new pinned item
+            Self::setup_item_magnification(&dock_item, &self.dock_items, &self.magnification);
+            let mut item = dock_item.borrow_mut();
+            item.mark_pinned(index, &self.item_actions);
+            self.setup_reorder_source(&item);
+            // Move the widget after the last pinned item (or to the front)
+            dock_box.reorder_child_after(item.widget(), anchor.as_ref());
+        }
+        
+        if self.running_items.borrow().is_empty() {
+            if let Some(sep) = self.separator.borrow_mut().take() {
+                dock_box.remove(&sep);
+            }
+        }
+        drop(dock_box);
+        
+        // Not running, so there was no item to move over
+        if migrated.is_none() {
+            self.add_pinned_item(&app);
+        }
+    }
+
+    /// Add an item for a newly pinned app after the other pinned items
+    ///
+    /// Only the dock changes; the caller saves the app to the config.
+    /// Returns the item's index.
+    pub fn add_pinned_item(&self, app: &PinnedApp) -> usize {
+        let settings = self.settings.borrow().clone();
+        let mut dock_items = self.dock_items.borrow_mut();
+        let index = pinned_insert_index(&dock_items);
+        let anchor = index.checked_sub(1).map(|i| dock_items[i].1.borrow().widget().clone());
+        
+        let dock_item = Rc::new(RefCell::new(DockItem::new(app, &settings, index, &self.item_actions, &self.preview_sources)));
+        Self::setup_item_magnification(&dock_item, &self.dock_items, &self.magnification);
+        self.setup_reorder_source(&dock_item.borrow());
+        // Right after the last pinned item, so the separator stays put
+        self.dock_box.borrow().insert_child_after(dock_item.borrow().widget(), anchor.as_ref());
+        dock_items.insert(index, (app.command.clone(), dock_item, true));
+        drop(dock_items);
+        
+        self.services.process_tracker.register_app(&app.command);
+        self.renumber_pinned_items();
+        debug!("Added pinned item '{}' at {}", app.name, index);
+        index
+    }
+
+    /// Remove the pinned item at `index` from the dock
+    ///
+    /// Only the dock changes; the caller removes the app from the config.
+    pub fn remove_item_by_index(&self, index: usize) -> bool {
+        let removed = remove_pinned_entry(&mut self.dock_items.borrow_mut(), index);
+        let Some((command, item, _)) = removed else {
+            warn!("No pinned item at {} to remove", index);
+            return false;
+        };
+        
+        self.dock_box.borrow().remove(item.borrow().widget());
+        self.renumber_pinned_items();
+        
+        // The hovered index may now point at a different item
+        self.magnification.borrow_mut().set_hover(None);
+        Self::update_magnification_for_all(&self.dock_items, &self.magnification, &self.window);
+        
+        debug!("Removed pinned item '{}' at {}", command, index);
+        true
+    }
+
+    /// Move the pinned item at `from` to position `to`, and its app in `settings`
+    ///
+    /// `settings` holds every pinned app, of which this dock may show a
+    /// subset. The caller saves the config.
+    pub fn move_item(&self, settings: &mut Settings, from: usize, to: usize) -> bool {
+        let mut dock_items = self.dock_items.borrow_mut();
+        if !drag_drop::apply_reorder(settings, &mut dock_items, from, to) {
+            warn!("Cannot move pinned item {} -> {}", from, to);
+            return false;
+        }
+        
+        let widget = dock_items[to].1.borrow().widget().clone();
+        let anchor = to.checked_sub(1).map(|i| dock_items[i].1.borrow().widget().clone());
+        drop(dock_items);
+        
+        self.dock_box.borrow().reorder_child_after(&widget, anchor.as_ref());
+        self.renumber_pinned_items();
+        debug!("Moved pinned item {} -> {}", from, to);
+        true
+    }
+
+    /// Point each pinned item's context menu at its current index
+    fn renumber_pinned_items(&self) {
+        for (index, (_, item, is_pinned)) in self.dock_items.borrow().iter().enumerate() {
+            let mut item = item.borrow_mut();
+            if *is_pinned && item.pinned_index() != Some(index) {
+                item.mark_pinned(index, &self.item_actions);
+            }
+        }
+    }
+
+    /// Reload the dock once the current event has been handled
+    ///
+    /// Reloading destroys the item that triggered the action, so it
+    /// must not happen from inside that item's signal handler.
+    pub fn schedule_reload(self: &Rc<Self>, settings: Settings) {
+        let weak = Rc::downgrade(self);
+        gtk::glib::idle_add_local_once(move || {
+            if let Some(dock) = weak.upgrade() {
+                dock.reload(&settings);
+            }
+        });
+    }
+
+    /// Show settings dialog, saving and reloading once Apply is clicked
+    pub fn show_settings(self: &Rc<Self>, settings: &Settings) {
         use crate::ui::SettingsDialog;
         let settings_clone = settings.clone();
-        let dialog = SettingsDialog::new(&self.window, settings_clone);
-        if let Some(new_settings) = dialog.run() {
+        let dialog = {
+            let profiles = self.services.profile_manager.borrow();
+            SettingsDialog::new(&self.window, settings_clone, &profiles.list_profiles())
+        };
+        let previous_profile = settings.active_profile.clone();
+        let weak = Rc::downgrade(self);
+        
+        gtk::glib::spawn_future_local(async move {
+            let Some(mut new_settings) = dialog.run().await else {
+                debug!("Settings dialog cancelled");
+                return;
+            };
+            
+            // A different profile replaces the edited settings wholesale
+            if new_settings.active_profile != previous_profile {
+                let Some(dock) = weak.upgrade() else {
+                    return;
+                };
+                match dock.switch_profile(&new_settings.active_profile) {
+                    Ok(profile_settings) => new_settings = profile_settings,
+                    Err(e) => {
+                        log::error!("Failed to switch profile: {}", e);
+                        return;
+                    }
+                }
+                
+                // Start with it from now on, leaving the other profiles' settings alone
+                if let Err(e) = Settings::select_profile(&new_settings.active_profile) {
+                    log::error!("Failed to select profile: {}", e);
+                }
+            }
+            
             // Save new settings
-            if let Err(e) = new_settings.save() {
+            if let Err(e) = new_settings.save_active() {
                 log::error!("Failed to save settings: {}", e);
             } else {
                 log::info!("Settings saved successfully");
-                self.reload(&new_settings);
+                if let Some(dock) = weak.upgrade() {
+                    dock.reload(&new_settings);
+                }
             }
-        }
+        });
+    }
+
+    /// Switch the profile manager to `name` and return its settings
+    ///
+    /// The default profile's settings are read from the configuration file.
+    fn switch_profile(&self, name: &str) -> Result<Settings, String> {
+        let base = Settings::load().map_err(|e| format!("{:#}", e))?;
+        self.services.profile_manager.borrow_mut().switch_active(name, &base)
     }
 
     /// Check if a process is running (helper function)
@@ -499,86 +1133,247 @@ impl DockWindow {
     }
 
     /// Setup Wayland Layer Shell properties
-    fn setup_layer_shell(window: &ApplicationWindow, settings: &Settings) {
+    fn setup_layer_shell(window: &ApplicationWindow, settings: &Settings, monitor: Option<&MonitorInfo>) {
         // Initialize layer shell - transforms window into layer surface
-        window.init_layer_shell();
+        // (reload calls this again on a window that already is one)
+        if !window.is_layer_window() {
+            window.init_layer_shell();
+        }
         
         // Use Overlay layer - most compatible across compositors
         window.set_layer(Layer::Overlay);
+        
+        // Bind to the requested monitor, else let the compositor choose
+        if let Some(info) = monitor {
+            match info.gdk_monitor() {
+                Some(gdk_monitor) => window.set_monitor(Some(&gdk_monitor)),
+                None => warn!("Monitor {} not found, using the default", info.key()),
+            }
+        }
 
         // Simple anchor configuration - just anchor to the edge
         // Don't stretch, let the window size be natural
-        match settings.position {
-            DockPosition::Left => {
-                window.set_anchor(Edge::Left, true);
-            }
-            DockPosition::Right => {
-                window.set_anchor(Edge::Right, true);
-            }
-            DockPosition::Top => {
-                window.set_anchor(Edge::Top, true);
-            }
-            DockPosition::Bottom => {
-                window.set_anchor(Edge::Bottom, true);
-            }
+        let anchored = match settings.position {
+            DockPosition::Left => Edge::Left,
+            DockPosition::Right => Edge::Right,
+            DockPosition::Top => Edge::Top,
+            DockPosition::Bottom => Edge::Bottom,
+        };
+        // Clear the other edges in case the position changed on reload
+        for edge in [Edge::Left, Edge::Right, Edge::Top, Edge::Bottom] {
+            window.set_anchor(edge, edge == anchored);
+            // Only the anchored edge's margin has an effect
+            window.set_margin(edge, settings.margin as i32);
         }
+        
+        // Take keyboard focus when clicked, for shortcuts, navigation and search
+        window.set_keyboard_mode(KeyboardMode::OnDemand);
+        // Lets compositor rules match the dock (e.g. Hyprland layerrule, Sway layer_effects)
+        window.set_namespace(Some("blazedock"));
+        
+        // Reserve the dock's edge so maximized windows stop short of it
+        let zone = exclusive_zone_for(settings);
+        window.set_exclusive_zone(zone);
 
         info!(
-            "Layer shell configured: position={:?}",
-            settings.position
+            "Layer shell configured: position={:?}, exclusive zone={}",
+            settings.position, zone
         );
     }
 
 
-    /// Update magnification for all dock items
+    /// Animate every dock item toward its magnification for the current hover
+    ///
+    /// Scales ease a little each frame on `widget`'s frame clock until they
+    /// all settle; a hover change mid-animation just retargets the running loop.
     fn update_magnification_for_all(
         dock_items: &Rc<RefCell<Vec<(String, Rc<RefCell<DockItem>>, bool)>>>,
         magnification: &Rc<RefCell<MagnificationController>>,
+        widget: &impl IsA<gtk::Widget>,
+    ) {
+        if !magnification.borrow_mut().start_animation() {
+            return;
+        }
+        
+        // Tick on the toplevel, which outlives any single dock item
+        let ticker: gtk::Widget = match widget.root() {
+            Some(root) => root.upcast(),
+            None => widget.clone().upcast(),
+        };
+        let dock_items = Rc::clone(dock_items);
+        let magnification = Rc::clone(magnification);
+        
+        ticker.add_tick_callback(move |_, _| {
+            let mut mag = magnification.borrow_mut();
+            let hover_index = mag.hover_index();
+            let mut settled = true;
+            
+            for (index, (_, item, _)) in dock_items.borrow().iter().enumerate() {
+                let item = item.borrow();
+                let target = mag.calculate_scale(index, hover_index);
+                if item.scale() != target {
+                    let scale = ease_scale(item.scale(), target);
+                    item.set_scale(scale);
+                    settled &= scale == target;
+                }
+            }
+            
+            if settled {
+                mag.stop_animation();
+                return gtk::glib::ControlFlow::Break;
+            }
+            gtk::glib::ControlFlow::Continue
+        });
+    }
+
+    /// Recolor the dock items' progress rings when the system theme changes
+    ///
+    /// The loop ends when the window is destroyed.
+    fn setup_theme_updates(
+        window: &ApplicationWindow,
+        theme_service: &ThemeService,
+        dock_items: &Rc<RefCell<Vec<(String, Rc<RefCell<DockItem>>, bool)>>>,
+        running_items: &Rc<RefCell<Vec<(String, Rc<RefCell<DockItem>>)>>>,
     ) {
-        let mag = magnification.borrow();
-        let hover_index = mag.hover_index();
-        let items = dock_items.borrow();
+        // Theme callbacks must be Send, so hand changes over to the main loop
+        let (sender, receiver) = async_channel::unbounded::<()>();
+        theme_service.on_theme_change(move |_| {
+            let _ = sender.try_send(());
+        });
+        
+        let receiver_close = receiver.clone();
+        window.connect_destroy(move |_| {
+            receiver_close.close();
+        });
         
-        for (index, (_, item, _)) in items.iter().enumerate() {
-            let scale = mag.calculate_scale(index, hover_index);
-            item.borrow().set_scale(scale);
+        let theme_service = theme_service.clone();
+        let dock_items = Rc::clone(dock_items);
+        let running_items = Rc::clone(running_items);
+        gtk::glib::spawn_future_local(async move {
+            while receiver.recv().await.is_ok() {
+                let colors = theme_service.get_colors();
+                for (_, item, _) in dock_items.borrow().iter() {
+                    item.borrow().apply_theme(&colors);
+                }
+                for (_, item) in running_items.borrow().iter() {
+                    item.borrow().apply_theme(&colors);
+                }
+            }
+        });
+    }
+
+    /// Drain D-Bus events on the main loop and dispatch them to the dock items
+    ///
+    /// The loop ends when the window is destroyed.
+    fn setup_dbus_events(
+        window: &ApplicationWindow,
+        dbus_service: &DBusService,
+        dock_items: &Rc<RefCell<Vec<(String, Rc<RefCell<DockItem>>, bool)>>>,
+        running_items: &Rc<RefCell<Vec<(String, Rc<RefCell<DockItem>>)>>>,
+        notification_item: &Rc<RefCell<Option<NotificationItem>>>,
+    ) {
+        let receiver = dbus_service.subscribe();
+        
+        let receiver_close = receiver.clone();
+        window.connect_destroy(move |_| {
+            receiver_close.close();
+        });
+        
+        // Keep the state reported over D-Bus in sync with shortcuts and reloads
+        dbus_service.set_dock_visible(window.is_visible());
+        let visibility_service = dbus_service.clone();
+        window.connect_visible_notify(move |window| {
+            visibility_service.set_dock_visible(window.is_visible());
+        });
+        
+        let window = window.clone();
+        let dock_items = Rc::clone(dock_items);
+        let running_items = Rc::clone(running_items);
+        let notification_item = Rc::clone(notification_item);
+        let dbus_service = dbus_service.clone();
+        gtk::glib::spawn_future_local(async move {
+            while let Ok(event) = receiver.recv().await {
+                match event {
+                    DBusEvent::BadgeUpdate(badge) => {
+                        Self::apply_badge(&dock_items, &running_items, &badge);
+                        Self::update_notification_summary(&notification_item, &dbus_service);
+                    }
+                    DBusEvent::Notification { app_name, summary } => {
+                        debug!("Notification from '{}': {}", app_name, summary);
+                    }
+                    DBusEvent::SetVisibility(visible) => {
+                        debug!("Dock visibility set to {} over D-Bus", visible);
+                        set_window_visible(&window, visible);
+                    }
+                }
+            }
+            debug!("D-Bus event loop stopped");
+        });
+    }
+
+    /// Show the total unread count on the notification summary item, if present
+    fn update_notification_summary(
+        notification_item: &Rc<RefCell<Option<NotificationItem>>>,
+        dbus_service: &DBusService,
+    ) {
+        if let Some(item) = notification_item.borrow_mut().as_mut() {
+            let total = dbus_service.total_unread_count().clamp(0, u32::MAX as i64) as u32;
+            item.set_count(total);
         }
     }
 
-    /// Start periodic updates for running indicators
+    /// Show a badge on the item matching its app_id (pinned items first)
+    fn apply_badge(
+        dock_items: &Rc<RefCell<Vec<(String, Rc<RefCell<DockItem>>, bool)>>>,
+        running_items: &Rc<RefCell<Vec<(String, Rc<RefCell<DockItem>>)>>>,
+        badge: &BadgeInfo,
+    ) {
+        let items: Vec<(String, Rc<RefCell<DockItem>>)> = dock_items.borrow().iter()
+            .map(|(command, item, _)| (command.clone(), Rc::clone(item)))
+            .chain(running_items.borrow().iter().cloned())
+            .collect();
+        
+        let keys: Vec<(String, Option<String>)> = items.iter()
+            .map(|(command, item)| (command.clone(), item.borrow().desktop_id()))
+            .collect();
+        
+        match find_item_for_app_id(&keys, &badge.app_id) {
+            Some(index) => {
+                debug!("Badge for '{}' applied to item {}", badge.app_id, index);
+                let mut item = items[index].1.borrow_mut();
+                // Bounce once when urgency starts, not on every update while urgent
+                let newly_urgent = badge.urgent && !item.wants_attention();
+                item.set_badge(badge_type_for(badge));
+                item.set_progress(progress_display_for(badge));
+                if newly_urgent {
+                    item.bounce();
+                }
+            }
+            None => debug!("No dock item for badge app_id '{}'", badge.app_id),
+        }
+    }
+
+    /// Update running indicators whenever a pinned app starts or exits
+    ///
+    /// Window counts and focus are refreshed along with the running apps.
     pub fn start_running_updates(&self) {
         let dock_items = Rc::clone(&self.dock_items);
         let running_items = Rc::clone(&self.running_items);
-        let process_tracker = self.process_tracker.clone();
-        let window_tracker = self.window_tracker.clone();
-        
-        // Update running indicators for pinned apps
-        gtk::glib::timeout_add_seconds_local(2, move || {
-            // Update pinned apps running state
-            let dock_items_guard = dock_items.borrow();
-            for (command, item, _is_pinned) in dock_items_guard.iter() {
-                let is_running = process_tracker.is_running(command);
-                
-                let app_id = command.split_whitespace().next().unwrap_or(command);
-                let window_count = window_tracker.get_window_count(app_id);
-                
-                let state = if is_running {
-                    RunningState::Running { 
-                        window_count: if window_count > 0 { window_count.min(255) as u8 } else { 1 } 
-                    }
-                } else {
-                    RunningState::Stopped
-                };
-                item.borrow_mut().set_running_state(state);
-            }
-            
-            // Update running (non-pinned) apps - they're always running
-            let running_guard = running_items.borrow();
-            for (_, item) in running_guard.iter() {
-                item.borrow_mut().set_running_state(RunningState::Running { window_count: 1 });
-            }
-            
-            gtk::glib::ControlFlow::Continue
+        let process_tracker = self.services.process_tracker.clone();
+        let window_tracker = self.services.window_tracker.clone();
+        
+        refresh_running_indicators(&dock_items.borrow(), &running_items.borrow(), &process_tracker, &window_tracker);
+        
+        // The tracker outlives this dock, so don't keep its items alive
+        let dock_items = Rc::downgrade(&dock_items);
+        let running_items = Rc::downgrade(&running_items);
+        let tracker = process_tracker.clone();
+        process_tracker.on_change(move |process_name, is_running| {
+            let (Some(dock_items), Some(running_items)) = (dock_items.upgrade(), running_items.upgrade()) else {
+                return;
+            };
+            debug!("Process '{}' running: {}", process_name, is_running);
+            refresh_running_indicators(&dock_items.borrow(), &running_items.borrow(), &tracker, &window_tracker);
         });
         
         info!("Running updates started");
@@ -591,16 +1386,24 @@ impl DockWindow {
         let separator = Rc::clone(&self.separator);
         let settings = Rc::clone(&self.settings);
         let running_apps_service = Rc::clone(&self.running_apps_service);
+        let window_tracker = self.services.window_tracker.clone();
+        let dock_items = Rc::clone(&self.dock_items);
+        let process_tracker = self.services.process_tracker.clone();
+        let item_actions = Rc::clone(&self.item_actions);
+        let preview_sources = self.preview_sources.clone();
         
         // Refresh running apps every 3 seconds
-        gtk::glib::timeout_add_seconds_local(3, move || {
+        let source = gtk::glib::timeout_add_seconds_local(3, move || {
             let settings_guard = settings.borrow();
             let pinned_commands: Vec<String> = settings_guard.pinned_apps.iter()
                 .map(|app| app.command.clone())
                 .collect();
             
             // Get currently running apps
-            let running_apps = running_apps_service.get_running_apps(&pinned_commands);
+            let window_app_ids: Vec<String> = window_tracker.get_all_windows().into_iter()
+                .map(|window| window.app_id)
+                .collect();
+            let running_apps = running_apps_service.get_running_apps(&pinned_commands, &window_app_ids);
             
             let dock_box_ref = dock_box.borrow();
             let mut running_items_mut = running_items.borrow_mut();
@@ -644,7 +1447,7 @@ impl DockWindow {
                     .margin_end(8)
                     .css_classes(vec!["dock-separator"])
                     .build();
-                dock_box_ref.append(&sep);
+                append_before_trailing(&dock_box_ref, &sep);
                 *separator_mut = Some(sep);
             }
             
@@ -657,28 +1460,99 @@ impl DockWindow {
                         &app.command,
                         app.desktop_file.as_deref(),
                         &settings_guard,
+                        &item_actions,
+                        &preview_sources,
                     )));
                     
-                    dock_box_ref.append(dock_item.borrow().widget());
+                    append_before_trailing(&dock_box_ref, dock_item.borrow().widget());
                     running_items_mut.push((app.command.clone(), Rc::clone(&dock_item)));
                     
                     info!("Added running app to dock: {} ({})", app.name, app.command);
                 }
             }
+            drop(running_items_mut);
+            
+            // Keep window counts and focus current
+            refresh_running_indicators(&dock_items.borrow(), &running_items.borrow(), &process_tracker, &window_tracker);
             
             gtk::glib::ControlFlow::Continue
         });
+        self.refresh_sources.borrow_mut().push(source);
         
         info!("Running apps refresh started");
     }
 
+    /// Show mounted removable drives after the running apps, updating every 2 seconds
+    pub fn start_drive_refresh(&self) {
+        let dock_box = Rc::clone(&self.dock_box);
+        let drive_items = Rc::clone(&self.drive_items);
+        let settings = Rc::clone(&self.settings);
+        let drive_monitor = self.services.drive_monitor.clone();
+        
+        let source = gtk::glib::timeout_add_seconds_local(2, move || {
+            let drives = drive_monitor.get_drives();
+            let mut drive_items = drive_items.borrow_mut();
+            let shown: Vec<String> = drive_items.iter()
+                .map(|item| item.mount_point().to_string())
+                .collect();
+            let (removed, added) = diff_drives(&shown, &drives);
+            if removed.is_empty() && added.is_empty() {
+                return gtk::glib::ControlFlow::Continue;
+            }
+            
+            let dock_box = dock_box.borrow();
+            drive_items.retain(|item| {
+                if removed.iter().any(|mount| mount == item.mount_point()) {
+                    dock_box.remove(item.widget());
+                    debug!("Removed drive from dock: {}", item.mount_point());
+                    false
+                } else {
+                    true
+                }
+            });
+            
+            let icon_size = settings.borrow().icon_size;
+            for drive in added {
+                let item = DriveItem::new(drive, icon_size);
+                // After the drives already shown, ahead of the stacks and trash
+                match drive_items.last() {
+                    Some(last) => dock_box.insert_child_after(item.widget(), Some(last.widget())),
+                    None => append_before_trailing(&dock_box, item.widget()),
+                }
+                info!("Added drive to dock: {} ({})", drive.name, drive.mount_point);
+                drive_items.push(item);
+            }
+            
+            gtk::glib::ControlFlow::Continue
+        });
+        self.refresh_sources.borrow_mut().push(source);
+        
+        info!("Drive refresh started");
+    }
+
+    /// Add the recent files item ahead of the stacks and trash, if enabled
+    fn create_recent_item(dock_box: &Box, settings: &Settings, recent_files: &RecentFilesService) -> Option<RecentItem> {
+        if !settings.show_recent_files {
+            return None;
+        }
+        
+        let item = RecentItem::new(recent_files, settings.icon_size);
+        append_before_trailing(dock_box, item.widget());
+        debug!("Recent files item added to dock");
+        Some(item)
+    }
+
     /// Create the dock content container with app items
-    /// Returns (main_box, dock_box) so we can store dock_box for dynamic updates
+    /// Returns (main_box, dock_box, stack_items) so we can store dock_box for dynamic updates
     fn create_dock_content(
         settings: &Settings,
         dock_items: &Rc<RefCell<Vec<(String, Rc<RefCell<DockItem>>, bool)>>>,
         magnification: &Rc<RefCell<MagnificationController>>,
-    ) -> (Box, Box) {
+        item_actions: &DockItemActions,
+        preview_sources: &PreviewSources,
+        notification_item: &Rc<RefCell<Option<NotificationItem>>>,
+        trash_item: &Rc<RefCell<Option<TrashItem>>>,
+    ) -> (Box, Box, Vec<StackItem>) {
         let orientation = match settings.position {
             DockPosition::Left | DockPosition::Right => Orientation::Vertical,
             DockPosition::Top | DockPosition::Bottom => Orientation::Horizontal,
@@ -705,56 +1579,55 @@ impl DockWindow {
             .build();
 
         // Add pinned apps
-        let magnification_ref = Rc::clone(&magnification);
-        let dock_items_ref = Rc::clone(&dock_items);
-        
         for (index, app_info) in settings.pinned_apps.iter().enumerate() {
-            let dock_item = Rc::new(RefCell::new(DockItem::new(app_info, settings)));
+            let dock_item = Rc::new(RefCell::new(DockItem::new(app_info, settings, index, item_actions, preview_sources)));
             let command = app_info.command.clone();
-            let item_index = index;
             
-            let mag_enter = Rc::clone(&magnification_ref);
-            let items_enter = Rc::clone(&dock_items_ref);
-            let mag_leave = Rc::clone(&magnification_ref);
-            let items_leave = Rc::clone(&dock_items_ref);
-            
-            // Setup hover for magnification
-            let item_widget = dock_item.borrow().widget().clone();
-            let motion_controller = gtk::EventControllerMotion::new();
-            
-            motion_controller.connect_enter(move |_, _, _| {
-                mag_enter.borrow_mut().set_hover(Some(item_index));
-                Self::update_magnification_for_all(&items_enter, &mag_enter);
-            });
-            
-            motion_controller.connect_leave(move |_| {
-                mag_leave.borrow_mut().set_hover(None);
-                Self::update_magnification_for_all(&items_leave, &mag_leave);
-            });
-            
-            item_widget.add_controller(motion_controller);
+            Self::setup_item_magnification(&dock_item, dock_items, magnification);
             
             // (command, item, is_pinned=true)
             dock_items.borrow_mut().push((command, Rc::clone(&dock_item), true));
             dock_box.append(dock_item.borrow().widget());
         }
 
-        // Add trash icon at the end if enabled
-        if settings.show_trash {
-            let trash_item = TrashItem::new(settings.icon_size);
-            // DISABLED: This DropTarget causes cursor to change to +
-            // trash_item.setup_drop_to_delete();
-            dock_box.append(trash_item.widget());
-            debug!("Trash item added to dock");
-        }
+        // Add notification summary if enabled
+        *notification_item.borrow_mut() = if settings.show_notifications_summary {
+            let item = NotificationItem::new(settings.icon_size);
+            dock_box.append(item.widget());
+            debug!("Notification summary added to dock");
+            Some(item)
+        } else {
+            None
+        };
 
         // Add Downloads stack if enabled
+        let mut stack_items = Vec::new();
         if settings.show_downloads_stack {
             let stack_item = StackItem::downloads(settings.icon_size);
             dock_box.append(stack_item.widget());
             debug!("Downloads stack added to dock");
+            stack_items.push(stack_item);
         }
 
+        // Add configured folder stacks
+        for config in &settings.stacks {
+            let stack_item = StackItem::from_config(config, settings.icon_size);
+            dock_box.append(stack_item.widget());
+            debug!("Stack for {:?} added to dock", stack_item.folder_path());
+            stack_items.push(stack_item);
+        }
+
+        // Add trash icon at the end if enabled; it monitors the trash while kept here
+        *trash_item.borrow_mut() = if settings.show_trash {
+            let item = TrashItem::new(settings.icon_size);
+            item.setup_drop_to_delete();
+            dock_box.append(item.widget());
+            debug!("Trash item added to dock");
+            Some(item)
+        } else {
+            None
+        };
+
         main_box.append(&dock_box);
 
         debug!(
@@ -763,7 +1636,47 @@ impl DockWindow {
             orientation
         );
 
-        (main_box, dock_box)
+        (main_box, dock_box, stack_items)
+    }
+
+    /// Magnify the dock around `dock_item` while it is hovered
+    ///
+    /// The item's index is looked up on hover, so it stays right after the
+    /// item is moved. Nothing is attached when `hover_zoom` is off.
+    fn setup_item_magnification(
+        dock_item: &Rc<RefCell<DockItem>>,
+        dock_items: &Rc<RefCell<Vec<(String, Rc<RefCell<DockItem>>, bool)>>>,
+        magnification: &Rc<RefCell<MagnificationController>>,
+    ) {
+        if !magnification.borrow().is_enabled() {
+            return;
+        }
+        
+        let item_weak = Rc::downgrade(dock_item);
+        let mag_enter = Rc::clone(magnification);
+        let items_enter = Rc::clone(dock_items);
+        let mag_leave = Rc::clone(magnification);
+        let items_leave = Rc::clone(dock_items);
+        
+        let motion_controller = gtk::EventControllerMotion::new();
+        
+        motion_controller.connect_enter(move |controller, _, _| {
+            let item_index = items_enter.borrow().iter()
+                .position(|(_, item, _)| Rc::as_ptr(item) == item_weak.as_ptr());
+            mag_enter.borrow_mut().set_hover(item_index);
+            if let Some(widget) = controller.widget() {
+                Self::update_magnification_for_all(&items_enter, &mag_enter, &widget);
+            }
+        });
+        
+        motion_controller.connect_leave(move |controller| {
+            mag_leave.borrow_mut().set_hover(None);
+            if let Some(widget) = controller.widget() {
+                Self::update_magnification_for_all(&items_leave, &mag_leave, &widget);
+            }
+        });
+        
+        dock_item.borrow().widget().add_controller(motion_controller);
     }
 
     /// Refresh running apps in the dock
@@ -774,7 +1687,10 @@ impl DockWindow {
             .collect();
         
         // Get currently running apps
-        let running_apps = self.running_apps_service.get_running_apps(&pinned_commands);
+        let window_app_ids: Vec<String> = self.services.window_tracker.get_all_windows().into_iter()
+            .map(|window| window.app_id)
+            .collect();
+        let running_apps = self.running_apps_service.get_running_apps(&pinned_commands, &window_app_ids);
         
         let dock_box = self.dock_box.borrow();
         let mut running_items = self.running_items.borrow_mut();
@@ -823,7 +1739,7 @@ impl DockWindow {
                 .margin_end(8)
                 .css_classes(vec!["dock-separator"])
                 .build();
-            dock_box.append(&sep);
+            append_before_trailing(&dock_box, &sep);
             *separator = Some(sep);
         }
         
@@ -836,9 +1752,11 @@ impl DockWindow {
                     &app.command,
                     app.desktop_file.as_deref(),
                     &settings,
+                    &self.item_actions,
+                    &self.preview_sources,
                 )));
                 
-                dock_box.append(dock_item.borrow().widget());
+                append_before_trailing(&dock_box, dock_item.borrow().widget());
                 running_items.push((app.command.clone(), Rc::clone(&dock_item)));
                 
                 info!("Added running app to dock: {} ({})", app.name, app.command);
@@ -847,4 +1765,481 @@ impl DockWindow {
     }
 }
 
+/// Launch the dock item for `command` the way clicking it does
+///
+/// Pinned apps get their desktop file and environment.
+fn launch_item(settings: &Settings, command: &str) {
+    let app = settings.pinned_apps.iter().find(|app| app.command == command);
+    let no_env = HashMap::new();
+    let env = app.map_or(&no_env, |app| &app.env);
+    let desktop_file = app.and_then(|app| app.desktop_file.as_deref());
+    
+    if let Err(e) = crate::utils::launcher::launch_app(command, desktop_file, settings.terminal.as_deref(), env) {
+        error!("Failed to launch '{}': {}", command, e);
+    }
+}
+
+/// Show or hide the dock window, raising it when shown
+fn set_window_visible(window: &ApplicationWindow, visible: bool) {
+    window.set_visible(visible);
+    if visible {
+        window.present();
+    }
+}
+
+/// Set each item's running indicator from the trackers
+///
+/// Running (non-pinned) items are always running.
+fn refresh_running_indicators(
+    dock_items: &[(String, Rc<RefCell<DockItem>>, bool)],
+    running_items: &[(String, Rc<RefCell<DockItem>>)],
+    process_tracker: &ProcessTracker,
+    window_tracker: &WindowTracker,
+) {
+    for (command, item, _is_pinned) in dock_items {
+        let is_running = process_tracker.is_running(command);
+        
+        let app_id = command.split_whitespace().next().unwrap_or(command);
+        let window_count = window_tracker.get_window_count(app_id);
+        
+        let window_count = if window_count > 0 { window_count.min(255) as u8 } else { 1 };
+        
+        let state = if is_running && window_tracker.is_app_focused(app_id) {
+            RunningState::Focused { window_count }
+        } else if is_running {
+            RunningState::Running { window_count }
+        } else {
+            RunningState::Stopped
+        };
+        item.borrow_mut().set_running_state(state);
+    }
+    
+    for (_, item) in running_items {
+        item.borrow_mut().set_running_state(RunningState::Running { window_count: 1 });
+    }
+}
+
+/// CSS classes of the items kept at the end of the dock, after running apps
+const TRAILING_ITEM_CLASSES: &[&str] = &["dock-item-drive", "dock-item-recent", "dock-item-stack", "dock-item-trash"];
+
+/// Append `widget` to the dock, ahead of the stacks and trash
+fn append_before_trailing(dock_box: &Box, widget: &impl IsA<gtk::Widget>) {
+    let mut child = dock_box.first_child();
+    while let Some(current) = child {
+        if TRAILING_ITEM_CLASSES.iter().any(|class| current.has_css_class(class)) {
+            dock_box.insert_child_after(widget, current.prev_sibling().as_ref());
+            return;
+        }
+        child = current.next_sibling();
+    }
+    dock_box.append(widget);
+}
+
+/// Find the item matching a LauncherEntry app_id
+///
+/// `items` holds each item's command and desktop file ID. The app_id is
+/// usually a desktop file ID (`org.gnome.Nautilus`), so it is compared
+/// with that first, then with the command's program name and with the
+/// last segment of a reverse-DNS app_id.
+fn find_item_for_app_id(items: &[(String, Option<String>)], app_id: &str) -> Option<usize> {
+    let app_id = app_id.to_lowercase();
+    let short_id = app_id.rsplit('.').next().unwrap_or(&app_id);
+    
+    items.iter().position(|(_, desktop_id)| {
+        desktop_id.as_ref().map(|id| id.to_lowercase() == app_id).unwrap_or(false)
+    }).or_else(|| items.iter().position(|(command, _)| {
+        let program = command.split_whitespace().next().unwrap_or(command);
+        let program = program.rsplit('/').next().unwrap_or(program).to_lowercase();
+        program == app_id || program == short_id
+    }))
+}
+
+/// Pick the badge to show for a LauncherEntry update
+///
+/// Progress is drawn by the item's progress ring, not the badge.
+fn badge_type_for(badge: &BadgeInfo) -> BadgeType {
+    if badge.urgent {
+        BadgeType::Attention
+    } else if badge.count_visible {
+        BadgeType::Count(badge.count.clamp(0, u32::MAX as i64) as u32)
+    } else {
+        BadgeType::Count(0)
+    }
+}
+
+/// Pick what the progress ring shows for a LauncherEntry update
+///
+/// Completed operations hide the ring like invisible progress does.
+fn progress_display_for(badge: &BadgeInfo) -> ProgressDisplay {
+    if !badge.progress_visible {
+        ProgressDisplay::Hidden
+    } else if badge.progress_indeterminate {
+        ProgressDisplay::Indeterminate
+    } else if badge.progress >= 1.0 {
+        ProgressDisplay::Hidden
+    } else {
+        ProgressDisplay::Value(badge.progress.max(0.0))
+    }
+}
+
+/// Thickness in pixels of the strip that reveals the auto-hidden dock
+const REVEAL_ZONE_SIZE: i32 = 2;
+
+/// Hide timer duration for an `auto_hide_delay` in milliseconds
+fn auto_hide_delay(delay_ms: u32) -> std::time::Duration {
+    std::time::Duration::from_millis(delay_ms as u64)
+}
+
+/// How often intellihide checks for windows overlapping the dock
+const INTELLIHIDE_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Show the hidden dock
+fn show_dock(window: &ApplicationWindow, position: DockPosition) {
+    window.remove_css_class(hidden_class(position));
+    window.add_css_class("dock-visible");
+}
+
+/// Hide the dock toward its edge
+fn hide_dock(window: &ApplicationWindow, position: DockPosition) {
+    window.remove_css_class("dock-visible");
+    window.add_css_class(hidden_class(position));
+}
+
+/// Area `(x, y, width, height)` of the monitor the dock is on
+fn monitor_rect(window: &ApplicationWindow, monitor: Option<&MonitorInfo>) -> Option<(i32, i32, i32, i32)> {
+    let geometry = match monitor {
+        Some(info) => info.geometry,
+        None => {
+            let surface = window.surface()?;
+            WidgetExt::display(window).monitor_at_surface(&surface)?.geometry()
+        }
+    };
+    Some((geometry.x(), geometry.y(), geometry.width(), geometry.height()))
+}
+
+/// Area of a `width` x `height` dock centered along its edge of `monitor`
+fn dock_rect(monitor: (i32, i32, i32, i32), position: DockPosition, width: i32, height: i32) -> (i32, i32, i32, i32) {
+    let (mx, my, mw, mh) = monitor;
+    match position {
+        DockPosition::Left => (mx, my + (mh - height) / 2, width, height),
+        DockPosition::Right => (mx + mw - width, my + (mh - height) / 2, width, height),
+        DockPosition::Top => (mx + (mw - width) / 2, my, width, height),
+        DockPosition::Bottom => (mx + (mw - width) / 2, my + mh - height, width, height),
+    }
+}
+
+/// Whether two `(x, y, width, height)` rectangles share any area
+///
+/// Rectangles that only touch along an edge don't overlap.
+fn rects_overlap(a: (i32, i32, i32, i32), b: (i32, i32, i32, i32)) -> bool {
+    let (ax, ay, aw, ah) = a;
+    let (bx, by, bw, bh) = b;
+    aw > 0 && ah > 0 && bw > 0 && bh > 0
+        && ax < bx + bw && bx < ax + aw
+        && ay < by + bh && by < ay + ah
+}
+
+/// CSS class that hides the dock toward its edge
+fn hidden_class(position: DockPosition) -> &'static str {
+    match position {
+        DockPosition::Left => "dock-hidden-left",
+        DockPosition::Right => "dock-hidden-right",
+        DockPosition::Top => "dock-hidden-top",
+        DockPosition::Bottom => "dock-hidden-bottom",
+    }
+}
+
+/// Index a newly pinned entry goes at: right after the pinned entries
+fn pinned_insert_index<T>(items: &[(String, T, bool)]) -> usize {
+    items.iter().take_while(|(_, _, is_pinned)| *is_pinned).count()
+}
+
+/// Remove the pinned entry at `index`
+fn remove_pinned_entry<T>(items: &mut Vec<(String, T, bool)>, index: usize) -> Option<(String, T, bool)> {
+    match items.get(index) {
+        Some((_, _, true)) => Some(items.remove(index)),
+        _ => None,
+    }
+}
+
+/// Move a running item into the pinned list, returning its new index
+fn migrate_running_item<T>(
+    running: &mut Vec<(String, T)>,
+    pinned: &mut Vec<(String, T, bool)>,
+    command: &str,
+) -> Option<usize> {
+    let position = running.iter().position(|(cmd, _)| cmd == command)?;
+    let (cmd, item) = running.remove(position);
+    pinned.push((cmd, item, true));
+    Some(pinned.len() - 1)
+}
+
+/// Compare the drives shown in the dock with the mounted ones
+///
+/// Returns the mount points to remove and the drives to add, keyed by
+/// mount point.
+fn diff_drives<'a>(shown: &[String], mounted: &'a [DriveInfo]) -> (Vec<String>, Vec<&'a DriveInfo>) {
+    let removed = shown.iter()
+        .filter(|mount| !mounted.iter().any(|drive| &drive.mount_point == *mount))
+        .cloned()
+        .collect();
+    let added = mounted.iter()
+        .filter(|drive| !shown.contains(&drive.mount_point))
+        .collect();
+    (removed, added)
+}
+
+/// Exclusive zone to reserve along the dock's edge
+///
+/// The dock's thickness when `exclusive_zone` is enabled, so windows are
+/// pushed aside; 0 lets windows extend underneath the dock. Layer shell
+/// adds the anchored edge's margin to the zone itself.
+fn exclusive_zone_for(settings: &Settings) -> i32 {
+    if settings.exclusive_zone {
+        settings.dock_size as i32
+    } else {
+        0
+    }
+}
+
+/// Index of the window to focus next when cycling through `len` windows
+///
+/// Starts at the first window when there is no current one and wraps from
+/// the last window back to the first.
+fn next_window_index(current: Option<usize>, len: usize) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    
+    Some(current.map(|index| (index + 1) % len).unwrap_or(0))
+}
+
+/// Build the search entries: pinned apps first, then installed apps not already pinned
+fn build_search_results(pinned: &[PinnedApp], discovered: Vec<DesktopEntry>) -> Vec<SearchResult> {
+    let app_id_for = |desktop_file: Option<&std::path::Path>, command: &str| {
+        desktop_file
+            .and_then(|path| path.file_stem())
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| command.split_whitespace().next().unwrap_or(command).to_string())
+    };
+    
+    let mut results: Vec<SearchResult> = pinned
+        .iter()
+        .map(|app| SearchResult {
+            app_id: app_id_for(app.desktop_file.as_deref().map(std::path::Path::new), &app.command),
+            name: app.name.clone(),
+            icon_name: app.icon.clone(),
+            command: app.command.clone(),
+            desktop_file: app.desktop_file.clone(),
+            env: app.env.clone(),
+            score: 0,
+        })
+        .collect();
+    
+    for result in installed_results(discovered) {
+        if results.iter().any(|r| r.command == result.command) {
+            continue;
+        }
+        results.push(result);
+    }
+    
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_drives() {
+        let drive = |name: &str| DriveInfo {
+            name: name.to_string(),
+            mount_point: format!("/run/media/me/{}", name),
+            is_removable: true,
+        };
+        let shown = vec!["/run/media/me/STICK".to_string(), "/run/media/me/SD".to_string()];
+        
+        // Nothing changed
+        let mounted = vec![drive("STICK"), drive("SD")];
+        let (removed, added) = diff_drives(&shown, &mounted);
+        assert!(removed.is_empty() && added.is_empty());
+        
+        // SD card pulled, camera plugged in
+        let mounted = vec![drive("STICK"), drive("CAMERA")];
+        let (removed, added) = diff_drives(&shown, &mounted);
+        assert_eq!(removed, vec!["/run/media/me/SD".to_string()]);
+        assert_eq!(added, vec![&mounted[1]]);
+        
+        // Everything ejected
+        let (removed, added) = diff_drives(&shown, &[]);
+        assert_eq!(removed, shown);
+        assert!(added.is_empty());
+    }
+
+    #[test]
+    fn test_exclusive_zone_for() {
+        let mut settings = Settings::default();
+        settings.exclusive_zone = false;
+        assert_eq!(exclusive_zone_for(&settings), 0);
+        
+        settings.exclusive_zone = true;
+        settings.dock_size = 64;
+        settings.margin = 0;
+        assert_eq!(exclusive_zone_for(&settings), 64);
+        
+        // The compositor adds the margin on top
+        settings.margin = 8;
+        assert_eq!(exclusive_zone_for(&settings), 64);
+    }
+
+    #[test]
+    fn test_next_window_index_wraps() {
+        assert_eq!(next_window_index(None, 0), None);
+        assert_eq!(next_window_index(Some(2), 0), None);
+        assert_eq!(next_window_index(None, 3), Some(0));
+        
+        let mut cursor = None;
+        let visited: Vec<usize> = (0..4)
+            .map(|_| {
+                cursor = next_window_index(cursor, 3);
+                cursor.unwrap()
+            })
+            .collect();
+        assert_eq!(visited, vec![0, 1, 2, 0]);
+        
+        // A stale cursor from before windows closed still lands in range
+        assert_eq!(next_window_index(Some(5), 2), Some(0));
+    }
+
+    #[test]
+    fn test_build_search_results_skips_pinned_duplicates() {
+        let pinned = vec![PinnedApp {
+            name: "Firefox".to_string(),
+            icon: "firefox".to_string(),
+            command: "firefox".to_string(),
+            desktop_file: None,
+            env: HashMap::from([("MOZ_ENABLE_WAYLAND".to_string(), "1".to_string())]),
+            label: None,
+        }];
+        
+        let entry = |name: &str, exec: &str| DesktopEntry {
+            path: std::path::PathBuf::from(format!("/usr/share/applications/{}.desktop", exec)),
+            name: Some(name.to_string()),
+            generic_name: None,
+            comment: None,
+            icon: None,
+            exec: Some(format!("{} %u", exec)),
+            terminal: false,
+            dbus_activatable: false,
+            categories: Vec::new(),
+            no_display: false,
+            fields: Default::default(),
+        };
+        
+        let results = build_search_results(&pinned, vec![entry("Firefox", "firefox"), entry("Krita", "krita")]);
+        
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name, "Firefox");
+        assert_eq!(results[0].app_id, "firefox");
+        // The pinned app launches with its own environment
+        assert_eq!(results[0].env.get("MOZ_ENABLE_WAYLAND").map(String::as_str), Some("1"));
+        assert_eq!(results[1].command, "krita");
+        assert!(results[1].env.is_empty());
+        assert!(results[1].desktop_file.is_some());
+        assert_eq!(results[1].app_id, "krita");
+        assert_eq!(results[1].icon_name, "application-x-executable");
+    }
+
+    #[test]
+    fn test_badge_maps_to_item() {
+        let items = vec![
+            ("firefox".to_string(), Some("firefox".to_string())),
+            ("nautilus --new-window".to_string(), None),
+            ("/usr/bin/thunderbird".to_string(), None),
+            ("gnome-terminal".to_string(), Some("org.gnome.Terminal".to_string())),
+        ];
+        
+        assert_eq!(find_item_for_app_id(&items, "org.gnome.Terminal"), Some(3));
+        assert_eq!(find_item_for_app_id(&items, "org.gnome.Nautilus"), Some(1));
+        assert_eq!(find_item_for_app_id(&items, "thunderbird"), Some(2));
+        assert_eq!(find_item_for_app_id(&items, "Firefox"), Some(0));
+        assert_eq!(find_item_for_app_id(&items, "slack"), None);
+        
+        let badge = BadgeInfo { app_id: "thunderbird".to_string(), count: 7, count_visible: true, ..Default::default() };
+        assert!(matches!(badge_type_for(&badge), BadgeType::Count(7)));
+    }
 
+    #[test]
+    fn test_progress_display_for() {
+        let mut badge = BadgeInfo { app_id: "nautilus".to_string(), progress: 0.5, progress_visible: true, ..Default::default() };
+        assert_eq!(progress_display_for(&badge), ProgressDisplay::Value(0.5));
+        
+        badge.progress = 1.0;
+        assert_eq!(progress_display_for(&badge), ProgressDisplay::Hidden);
+        
+        badge.progress_indeterminate = true;
+        assert_eq!(progress_display_for(&badge), ProgressDisplay::Indeterminate);
+        
+        badge.progress_visible = false;
+        assert_eq!(progress_display_for(&badge), ProgressDisplay::Hidden);
+    }
+
+    #[test]
+    fn test_auto_hide_delay() {
+        assert_eq!(auto_hide_delay(500), std::time::Duration::from_millis(500));
+        assert_eq!(auto_hide_delay(0), std::time::Duration::ZERO);
+        assert_eq!(auto_hide_delay(1500).as_secs_f64(), 1.5);
+        assert_eq!(hidden_class(DockPosition::Bottom), "dock-hidden-bottom");
+    }
+
+    #[test]
+    fn test_rects_overlap() {
+        let dock = (560, 1000, 800, 80);
+        assert!(rects_overlap(dock, (0, 0, 1920, 1040)));
+        assert!(rects_overlap(dock, (1300, 900, 400, 300)));
+        // Touching the dock's top edge isn't overlapping
+        assert!(!rects_overlap(dock, (0, 0, 1920, 1000)));
+        assert!(!rects_overlap(dock, (0, 0, 500, 1080)));
+        assert!(!rects_overlap(dock, (600, 1010, 0, 50)));
+        
+        assert_eq!(dock_rect((0, 0, 1920, 1080), DockPosition::Bottom, 800, 80), dock);
+        assert_eq!(dock_rect((1920, 0, 1920, 1080), DockPosition::Left, 80, 600), (1920, 240, 80, 600));
+    }
+
+    #[test]
+    fn test_pinned_entry_bookkeeping() {
+        let entry = |command: &str, is_pinned: bool| (command.to_string(), (), is_pinned);
+        let commands = |items: &[(String, (), bool)]| items.iter().map(|(c, _, _)| c.clone()).collect::<Vec<_>>();
+        let mut items = vec![entry("a", true), entry("b", true), entry("c", true), entry("extra", false)];
+        
+        // Added items go after the pinned ones
+        assert_eq!(pinned_insert_index(&items), 3);
+        items.insert(3, entry("d", true));
+        assert_eq!(commands(&items), ["a", "b", "c", "d", "extra"]);
+        assert_eq!(pinned_insert_index::<()>(&[]), 0);
+        
+        assert_eq!(remove_pinned_entry(&mut items, 1).map(|(c, _, _)| c), Some("b".to_string()));
+        assert_eq!(commands(&items), ["a", "c", "d", "extra"]);
+        assert!(remove_pinned_entry(&mut items, 3).is_none());
+        assert!(remove_pinned_entry(&mut items, 10).is_none());
+        assert_eq!(pinned_insert_index(&items), 3);
+    }
+
+    #[test]
+    fn test_migrate_running_item() {
+        let mut running = vec![
+            ("gimp".to_string(), "gimp-item"),
+            ("code".to_string(), "code-item"),
+        ];
+        let mut pinned = vec![("firefox".to_string(), "firefox-item", true)];
+
+        assert_eq!(migrate_running_item(&mut running, &mut pinned, "code"), Some(1));
+        assert_eq!(running, vec![("gimp".to_string(), "gimp-item")]);
+        assert_eq!(pinned[1], ("code".to_string(), "code-item", true));
+
+        // Unknown commands leave both lists untouched
+        assert_eq!(migrate_running_item(&mut running, &mut pinned, "missing"), None);
+        assert_eq!(running.len(), 1);
+        assert_eq!(pinned.len(), 2);
+    }
+}
@@ -3,13 +3,30 @@
 //! Shows thumbnails of open windows when hovering over dock items.
 
 use gtk::prelude::*;
-use gtk::{Box, Label, Picture, Popover, Widget};
+use gtk::{glib, Box, Label, Picture, Popover, Widget};
 use log::debug;
+use std::cell::RefCell;
+
+use crate::services::screencopy_service::create_placeholder_preview;
+use crate::services::{ScreencopyService, ThemeService, WindowInfo, WindowTracker};
+
+/// Services previews use to list an app's windows and capture them
+///
+/// All are cheap handles onto the dock window's shared services; the theme
+/// also colors each item's progress ring.
+#[derive(Clone)]
+pub struct PreviewSources {
+    pub window_tracker: WindowTracker,
+    pub screencopy: ScreencopyService,
+    pub theme: ThemeService,
+}
 
 /// Window preview component
 pub struct WindowPreview {
     popover: Popover,
     content: Box,
+    /// Thumbnail captures still running for the shown previews
+    pending: RefCell<Vec<glib::JoinHandle<()>>>,
 }
 
 impl WindowPreview {
@@ -35,12 +52,76 @@ impl WindowPreview {
         // GTK4-rs PopoverExt::set_parent takes &impl IsA<Widget> directly, NOT Option
         popover.set_parent(parent);
 
-        Self { popover, content }
+        Self { popover, content, pending: RefCell::new(Vec::new()) }
+    }
+
+    /// Show live thumbnails of `windows`
+    ///
+    /// Captures run in the background and fill in each thumbnail as they
+    /// finish; windows without one get a placeholder showing `app_icon`.
+    pub fn show_windows(&self, app_name: &str, app_icon: &str, windows: &[WindowInfo], screencopy: &ScreencopyService) {
+        self.cancel_pending();
+        let previews_box = self.reset_content(app_name);
+
+        for window in windows {
+            let (item, thumbnail) = self.create_preview_item(&window.title);
+            previews_box.append(&item);
+
+            if window.geometry.is_some() {
+                screencopy.set_window_geometry(&window.id, window.geometry);
+            }
+
+            let icon = app_icon.to_string();
+            let title = window.title.clone();
+            let handle = screencopy.request_thumbnail_async(&window.id, &window.app_id, &window.title, move |pixbuf| {
+                match pixbuf {
+                    Some(pixbuf) => thumbnail.set_paintable(Some(&gtk::gdk::Texture::for_pixbuf(&pixbuf))),
+                    None => {
+                        while let Some(child) = item.first_child() {
+                            item.remove(&child);
+                        }
+                        item.append(&create_placeholder_preview(&icon, &title));
+                    }
+                }
+            });
+            self.pending.borrow_mut().extend(handle);
+        }
+
+        debug!("Showing {} window previews for {}", windows.len(), app_name);
+        self.popover.popup();
     }
 
-    /// Show previews for an application
+    /// Show placeholder previews for an application
+    ///
+    /// Used when the window tracker can't list the app's windows.
     pub fn show_previews(&self, app_name: &str, window_count: u8) {
-        // Clear old content
+        self.cancel_pending();
+        let previews_box = self.reset_content(app_name);
+
+        for i in 0..window_count {
+            let (item, _) = self.create_preview_item(&format!("Window {}", i + 1));
+            previews_box.append(&item);
+        }
+
+        debug!("Showing {} previews for {}", window_count, app_name);
+        self.popover.popup();
+    }
+
+    /// Hide the preview, dropping any captures still in flight
+    pub fn hide(&self) {
+        self.cancel_pending();
+        self.popover.popdown();
+    }
+
+    /// Abort thumbnail deliveries for the previous hover
+    fn cancel_pending(&self) {
+        for handle in self.pending.borrow_mut().drain(..) {
+            handle.abort();
+        }
+    }
+
+    /// Replace the content with a header and return the previews container
+    fn reset_content(&self, app_name: &str) -> Box {
         while let Some(child) = self.content.first_child() {
             self.content.remove(&child);
         }
@@ -59,33 +140,18 @@ impl WindowPreview {
             .spacing(12)
             .build();
 
-        // Create mock previews for now (Sprint 5 foundation)
-        // In Sprint 5.2, we will replace this with real screencopy thumbnails
-        for i in 0..window_count {
-            let item = self.create_preview_item(&format!("Window {}", i + 1));
-            previews_box.append(&item);
-        }
-
         self.content.append(&previews_box);
-        
-        debug!("Showing {} previews for {}", window_count, app_name);
-        self.popover.popup();
-    }
-
-    /// Hide the preview
-    pub fn hide(&self) {
-        self.popover.popdown();
+        previews_box
     }
 
-    /// Create a single preview item
-    fn create_preview_item(&self, title: &str) -> Box {
+    /// Create a single preview item and its thumbnail picture
+    fn create_preview_item(&self, title: &str) -> (Box, Picture) {
         let container = Box::builder()
             .orientation(gtk::Orientation::Vertical)
             .spacing(4)
             .css_classes(vec!["window-preview-item"])
             .build();
 
-        // Mock thumbnail (placeholder)
         let thumbnail = Picture::builder()
             .width_request(160)
             .height_request(100)
@@ -102,6 +168,6 @@ impl WindowPreview {
 
         container.append(&thumbnail);
         container.append(&label);
-        container
+        (container, thumbnail)
     }
 }
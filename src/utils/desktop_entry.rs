@@ -4,19 +4,29 @@
 //! for automatic app discovery and icon resolution.
 
 use anyhow::{Context, Result};
+use gtk::gio;
+use gtk::prelude::*;
 use log::debug;
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::SystemTime;
 
-/// Standard application directories to search for .desktop files
-pub const APP_DIRS: &[&str] = &[
-    "/usr/share/applications",
-    "/usr/local/share/applications",
-];
+/// `XDG_DATA_DIRS` when unset, per the base directory spec
+const DEFAULT_DATA_DIRS: &str = "/usr/local/share:/usr/share";
+
+/// `XDG_DATA_HOME` when unset (relative to home)
+const DEFAULT_DATA_HOME: &str = ".local/share";
 
-/// User application directory (relative to home)
-pub const USER_APP_DIR: &str = ".local/share/applications";
+/// Application exports searched even when missing from `XDG_DATA_DIRS`
+/// (e.g. a session started before Flatpak or Snap was installed)
+const EXTRA_DATA_DIRS: &[&str] = &[
+    "/var/lib/flatpak/exports/share",
+    "/var/lib/snapd/desktop",
+];
 
 /// Represents a parsed .desktop file
 #[derive(Debug, Clone)]
@@ -35,6 +45,8 @@ pub struct DesktopEntry {
     pub exec: Option<String>,
     /// Whether this is a terminal application
     pub terminal: bool,
+    /// Whether the app is launched over D-Bus (`DBusActivatable=true`)
+    pub dbus_activatable: bool,
     /// Categories (e.g., "Network;WebBrowser")
     pub categories: Vec<String>,
     /// Whether the entry should be hidden
@@ -96,6 +108,7 @@ impl DesktopEntry {
             icon: fields.get("Icon").cloned(),
             exec: fields.get("Exec").cloned(),
             terminal: fields.get("Terminal").map(|v| v == "true").unwrap_or(false),
+            dbus_activatable: fields.get("DBusActivatable").map(|v| v == "true").unwrap_or(false),
             categories,
             no_display: fields.get("NoDisplay").map(|v| v == "true").unwrap_or(false),
             fields,
@@ -130,6 +143,58 @@ impl DesktopEntry {
         })
     }
 
+    /// Split the Exec field into the program and its arguments
+    ///
+    /// Follows the desktop entry spec: quoted arguments may contain
+    /// spaces, and field codes are dropped. Empty if there is no Exec.
+    pub fn exec_argv(&self) -> Vec<String> {
+        self.exec.as_deref().map(split_exec).unwrap_or_default()
+    }
+
+    /// Name in the user's language, falling back to the untranslated `Name`
+    pub fn localized_name(&self) -> Option<String> {
+        self.localized("Name", &self.name, current_locale().as_deref())
+    }
+
+    /// Comment in the user's language, falling back to the untranslated `Comment`
+    pub fn localized_comment(&self) -> Option<String> {
+        self.localized("Comment", &self.comment, current_locale().as_deref())
+    }
+
+    /// Icon for the user's language, falling back to the untranslated `Icon`
+    pub fn localized_icon(&self) -> Option<String> {
+        self.localized("Icon", &self.icon, current_locale().as_deref())
+    }
+
+    /// Value of `key[locale]` for the best match of `locale`, else `default`
+    fn localized(&self, key: &str, default: &Option<String>, locale: Option<&str>) -> Option<String> {
+        locale
+            .map(locale_candidates)
+            .unwrap_or_default()
+            .iter()
+            .find_map(|candidate| self.fields.get(&format!("{}[{}]", key, candidate)))
+            .cloned()
+            .or_else(|| default.clone())
+    }
+
+    /// Desktop file ID, the file name without `.desktop`
+    pub fn desktop_id(&self) -> Option<String> {
+        self.path.file_stem().map(|stem| stem.to_string_lossy().to_string())
+    }
+
+    /// Bus name and object path to activate a `DBusActivatable` app at
+    ///
+    /// None if the app isn't D-Bus activatable or its desktop file ID is
+    /// not a valid bus name.
+    pub fn dbus_activation_target(&self) -> Option<(String, String)> {
+        if !self.dbus_activatable {
+            return None;
+        }
+        let bus_name = self.desktop_id()?;
+        let object_path = dbus_object_path(&bus_name)?;
+        Some((bus_name, object_path))
+    }
+
     /// Check if this is a valid, visible application entry
     pub fn is_visible_app(&self) -> bool {
         !self.no_display 
@@ -139,52 +204,327 @@ impl DesktopEntry {
     }
 }
 
-/// Discover all installed applications
-pub fn discover_applications() -> Vec<DesktopEntry> {
-    let mut entries = Vec::new();
+/// Directories to search for .desktop files, most important first
+///
+/// `$XDG_DATA_HOME/applications`, then `applications` in each of
+/// `$XDG_DATA_DIRS`, then the Flatpak and Snap exports.
+pub fn application_dirs() -> Vec<PathBuf> {
+    application_dirs_from(
+        std::env::var("XDG_DATA_HOME").ok().as_deref(),
+        std::env::var("XDG_DATA_DIRS").ok().as_deref(),
+        dirs::home_dir().as_deref(),
+    )
+}
 
-    // Search system directories
-    for dir in APP_DIRS {
-        if let Ok(read_dir) = fs::read_dir(dir) {
-            for entry in read_dir.filter_map(|e| e.ok()) {
-                let path = entry.path();
-                if path.extension().map(|e| e == "desktop").unwrap_or(false) {
-                    if let Ok(desktop) = DesktopEntry::parse(&path) {
-                        if desktop.is_visible_app() {
-                            debug!("Discovered app: {:?}", desktop.name);
-                            entries.push(desktop);
-                        }
-                    }
+/// [`application_dirs`] for the given environment, without duplicates
+fn application_dirs_from(data_home: Option<&str>, data_dirs: Option<&str>, home: Option<&Path>) -> Vec<PathBuf> {
+    // Empty variables count as unset
+    let data_home = data_home
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.map(|home| home.join(DEFAULT_DATA_HOME)));
+    let data_dirs = data_dirs
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or(DEFAULT_DATA_DIRS);
+    let user_flatpak = home.map(|home| home.join(DEFAULT_DATA_HOME).join("flatpak/exports/share"));
+
+    let mut result = Vec::new();
+    let candidates = data_home.into_iter()
+        .chain(data_dirs.split(':').filter(|dir| !dir.is_empty()).map(PathBuf::from))
+        .chain(user_flatpak)
+        .chain(EXTRA_DATA_DIRS.iter().map(PathBuf::from));
+    for dir in candidates {
+        let applications = dir.join("applications");
+        if !result.contains(&applications) {
+            result.push(applications);
+        }
+    }
+    result
+}
+
+/// Locale that messages should be shown in, from `LC_ALL`, `LC_MESSAGES` or `LANG`
+///
+/// None for the untranslated "C" and "POSIX" locales.
+fn current_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .filter(|locale| locale != "C" && locale != "POSIX" && !locale.starts_with("C."))
+}
+
+/// Locale keys to look up for `lang_COUNTRY.ENCODING@MODIFIER`, most specific first
+///
+/// Per the desktop entry spec, the encoding is ignored:
+/// `lang_COUNTRY@MODIFIER`, `lang_COUNTRY`, `lang@MODIFIER`, `lang`.
+fn locale_candidates(locale: &str) -> Vec<String> {
+    let (rest, modifier) = match locale.split_once('@') {
+        Some((rest, modifier)) => (rest, Some(modifier)),
+        None => (locale, None),
+    };
+    let rest = rest.split('.').next().unwrap_or(rest);
+    let (lang, country) = match rest.split_once('_') {
+        Some((lang, country)) => (lang, Some(country)),
+        None => (rest, None),
+    };
+
+    let mut candidates = Vec::new();
+    if let (Some(country), Some(modifier)) = (country, modifier) {
+        candidates.push(format!("{}_{}@{}", lang, country, modifier));
+    }
+    if let Some(country) = country {
+        candidates.push(format!("{}_{}", lang, country));
+    }
+    if let Some(modifier) = modifier {
+        candidates.push(format!("{}@{}", lang, modifier));
+    }
+    candidates.push(lang.to_string());
+    candidates
+}
+
+/// Object path of the `org.freedesktop.Application` object for a desktop file ID
+///
+/// The ID must be a well-known bus name (`org.gnome.Nautilus`); the path
+/// swaps its dots for slashes and dashes for underscores.
+fn dbus_object_path(desktop_id: &str) -> Option<String> {
+    let elements: Vec<&str> = desktop_id.split('.').collect();
+    let valid = desktop_id.len() <= 255
+        && elements.len() >= 2
+        && elements.iter().all(|element| {
+            !element.is_empty()
+                && !element.starts_with(|c: char| c.is_ascii_digit())
+                && element.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        });
+    if !valid {
+        return None;
+    }
+
+    Some(format!("/{}", desktop_id.replace('.', "/").replace('-', "_")))
+}
+
+/// Undo the escapes allowed in desktop file string values
+fn unescape_value(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => result.push(' '),
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                // Keep other escapes for the Exec quoting rules
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Split an Exec value into arguments, dropping field codes
+///
+/// Inside double quotes, `\"`, `` \` ``, `\$` and `\\` stand for the
+/// escaped character. `%%` is a literal percent sign.
+fn split_exec(exec: &str) -> Vec<String> {
+    let exec = unescape_value(exec);
+    let mut argv = Vec::new();
+    let mut current = String::new();
+    // Whether an argument is being built, so `""` yields an empty one
+    let mut in_arg = false;
+    let mut in_quotes = false;
+    let mut chars = exec.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                in_arg = true;
+            }
+            '\\' if in_quotes => match chars.peek() {
+                Some(&escaped @ ('"' | '`' | '$' | '\\')) => {
+                    current.push(escaped);
+                    chars.next();
+                }
+                _ => current.push(c),
+            },
+            '%' => match chars.peek() {
+                // Field codes (%f, %U, ...) expand to nothing without files or URLs
+                Some(code) if code.is_ascii_alphabetic() => {
+                    chars.next();
+                }
+                Some('%') => {
+                    chars.next();
+                    current.push('%');
+                    in_arg = true;
+                }
+                _ => {
+                    current.push('%');
+                    in_arg = true;
                 }
+            },
+            c if c.is_whitespace() && !in_quotes => {
+                if in_arg || !current.is_empty() {
+                    argv.push(std::mem::take(&mut current));
+                }
+                in_arg = false;
+            }
+            c => {
+                current.push(c);
+                in_arg = true;
             }
         }
     }
 
-    // Search user directory
-    if let Some(home) = dirs::home_dir() {
-        let user_apps = home.join(USER_APP_DIR);
-        if let Ok(read_dir) = fs::read_dir(&user_apps) {
+    if in_arg || !current.is_empty() {
+        argv.push(current);
+    }
+    argv
+}
+
+/// Parsed .desktop files, reused while their mtime is unchanged
+#[derive(Default)]
+struct DiscoveryCache {
+    /// Entry (None if unparsable) and the mtime it was parsed at, by path
+    files: HashMap<PathBuf, (SystemTime, Option<DesktopEntry>)>,
+    /// Last discovery result, kept until a watched directory changes
+    apps: Option<Vec<DesktopEntry>>,
+    /// Number of files parsed so far
+    parse_count: usize,
+}
+
+impl DiscoveryCache {
+    /// Find the visible apps in `dirs`, parsing only new or modified files
+    ///
+    /// An app installed in several directories is taken from the first,
+    /// so user entries override system ones, including hiding them.
+    /// The first entry claims the desktop ID even when it isn't shown.
+    fn scan(&mut self, dirs: &[PathBuf]) -> Vec<DesktopEntry> {
+        let mut entries = Vec::new();
+        let mut seen_ids = HashSet::new();
+        let mut files = HashMap::new();
+
+        for dir in dirs {
+            let Ok(read_dir) = fs::read_dir(dir) else {
+                continue;
+            };
             for entry in read_dir.filter_map(|e| e.ok()) {
                 let path = entry.path();
-                if path.extension().map(|e| e == "desktop").unwrap_or(false) {
-                    if let Ok(desktop) = DesktopEntry::parse(&path) {
-                        if desktop.is_visible_app() {
-                            debug!("Discovered user app: {:?}", desktop.name);
-                            entries.push(desktop);
-                        }
+                if !path.extension().map(|e| e == "desktop").unwrap_or(false) {
+                    continue;
+                }
+                // Follows symlinks, which Flatpak exports are
+                let Ok(modified) = fs::metadata(&path).and_then(|m| m.modified()) else {
+                    continue;
+                };
+
+                let desktop = match self.files.remove(&path) {
+                    Some((parsed_at, desktop)) if parsed_at == modified => desktop,
+                    _ => {
+                        self.parse_count += 1;
+                        DesktopEntry::parse(&path).ok()
+                    }
+                };
+
+                if let Some(desktop) = &desktop {
+                    if seen_ids.insert(desktop.desktop_id()) && desktop.is_visible_app() {
+                        debug!("Discovered app: {:?} in {:?}", desktop.name, dir);
+                        entries.push(desktop.clone());
                     }
                 }
+                files.insert(path, (modified, desktop));
             }
         }
+
+        // Files no longer found are dropped
+        self.files = files;
+
+        // Sort by name
+        entries.sort_by(|a, b| {
+            a.name.as_deref().unwrap_or("")
+                .cmp(b.name.as_deref().unwrap_or(""))
+        });
+
+        entries
     }
+}
+
+/// Cache shared by all callers of [`discover_applications`]
+static DISCOVERY_CACHE: LazyLock<Mutex<DiscoveryCache>> = LazyLock::new(Default::default);
+
+/// Whether the application directories are monitored, so that an
+/// unchanged discovery result can be returned without touching the disk
+static DIRS_WATCHED: AtomicBool = AtomicBool::new(false);
 
-    // Sort by name
-    entries.sort_by(|a, b| {
-        a.name.as_deref().unwrap_or("")
-            .cmp(b.name.as_deref().unwrap_or(""))
+thread_local! {
+    /// Monitors of the application directories, kept alive on the main thread
+    static DIR_MONITORS: RefCell<Vec<gio::FileMonitor>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Discover all installed applications
+///
+/// Searches [`application_dirs`]. Files are only re-parsed when their
+/// mtime changes; once [`watch_application_dirs`] is running, the last
+/// result is reused until something is installed or removed.
+pub fn discover_applications() -> Vec<DesktopEntry> {
+    let mut cache = DISCOVERY_CACHE.lock().unwrap();
+    if DIRS_WATCHED.load(Ordering::Relaxed) {
+        if let Some(apps) = &cache.apps {
+            return apps.clone();
+        }
+    }
+
+    let apps = cache.scan(&application_dirs());
+    cache.apps = Some(apps.clone());
+    apps
+}
+
+/// Drop all cached entries so the next discovery re-parses every file
+pub fn force_refresh() {
+    let mut cache = DISCOVERY_CACHE.lock().unwrap();
+    cache.files.clear();
+    cache.apps = None;
+    debug!("Application cache cleared");
+}
+
+/// Invalidate the discovery result whenever an application directory changes
+///
+/// Must be called on the GTK main thread; the monitors live as long as it.
+pub fn watch_application_dirs() {
+    let mut watched_all = true;
+
+    DIR_MONITORS.with(|monitors| {
+        let mut monitors = monitors.borrow_mut();
+        monitors.clear();
+
+        for dir in application_dirs() {
+            match gio::File::for_path(&dir).monitor_directory(gio::FileMonitorFlags::NONE, gio::Cancellable::NONE) {
+                Ok(monitor) => {
+                    monitor.connect_changed(|_, file, _, event| {
+                        if matches!(event, gio::FileMonitorEvent::ChangesDoneHint | gio::FileMonitorEvent::AttributeChanged) {
+                            return;
+                        }
+                        debug!("Application directory changed: {:?}", file.path());
+                        DISCOVERY_CACHE.lock().unwrap().apps = None;
+                    });
+                    monitors.push(monitor);
+                }
+                Err(e) => {
+                    debug!("Failed to monitor {:?}: {}", dir, e);
+                    watched_all = false;
+                }
+            }
+        }
     });
 
-    entries
+    // Without a monitor on every directory, keep checking mtimes on each call
+    DIRS_WATCHED.store(watched_all, Ordering::Relaxed);
 }
 
 #[cfg(test)]
@@ -211,6 +551,37 @@ Categories=Network;WebBrowser;
         assert!(entry.categories.contains(&"Network".to_string()));
     }
 
+    #[test]
+    fn test_localized_keys() {
+        let content = r#"
+[Desktop Entry]
+Type=Application
+Name=Files
+Name[fr]=Fichiers
+Name[sr@latin]=Datoteke
+Comment=Access and organize files
+Comment[fr_CA]=Accéder aux fichiers
+Icon=org.gnome.Nautilus
+Exec=nautilus
+"#;
+        let entry = DesktopEntry::parse_content(PathBuf::from("org.gnome.Nautilus.desktop"), content).unwrap();
+        let name = |locale| entry.localized("Name", &entry.name, locale);
+        let comment = |locale| entry.localized("Comment", &entry.comment, locale);
+
+        // fr_FR.UTF-8 -> fr_FR -> fr
+        assert_eq!(name(Some("fr_FR.UTF-8")), Some("Fichiers".to_string()));
+        assert_eq!(comment(Some("fr_CA.UTF-8")), Some("Accéder aux fichiers".to_string()));
+        assert_eq!(comment(Some("fr_FR.UTF-8")), Some("Access and organize files".to_string()));
+        assert_eq!(name(Some("sr_RS@latin")), Some("Datoteke".to_string()));
+
+        // No translation, or no locale at all
+        assert_eq!(name(Some("de_DE.UTF-8")), Some("Files".to_string()));
+        assert_eq!(name(None), Some("Files".to_string()));
+
+        assert_eq!(locale_candidates("fr_FR.UTF-8@euro"), vec!["fr_FR@euro", "fr_FR", "fr@euro", "fr"]);
+        assert_eq!(locale_candidates("fr"), vec!["fr"]);
+    }
+
     #[test]
     fn test_strip_field_codes() {
         let content = r#"
@@ -222,6 +593,121 @@ Exec=myapp --url %u --files %F
 
         let entry = DesktopEntry::parse_content(PathBuf::from("test.desktop"), content).unwrap();
         assert_eq!(entry.exec_command(), Some("myapp --url --files".to_string()));
+        assert_eq!(entry.exec_argv(), vec!["myapp", "--url", "--files"]);
+    }
+
+    #[test]
+    fn test_unchanged_directory_is_not_reparsed() {
+        let dir = std::env::temp_dir().join(format!("blazedock-apps-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["Editor", "Viewer"] {
+            let content = format!("[Desktop Entry]\nType=Application\nName={}\nExec={}\n", name, name.to_lowercase());
+            fs::write(dir.join(format!("{}.desktop", name.to_lowercase())), content).unwrap();
+        }
+        let dirs = vec![dir.clone()];
+        let names = |entries: Vec<DesktopEntry>| -> Vec<String> {
+            entries.into_iter().filter_map(|e| e.name).collect()
+        };
+
+        let mut cache = DiscoveryCache::default();
+        assert_eq!(names(cache.scan(&dirs)), vec!["Editor", "Viewer"]);
+        assert_eq!(cache.parse_count, 2);
+
+        // Nothing changed, so nothing is parsed again
+        assert_eq!(names(cache.scan(&dirs)), vec!["Editor", "Viewer"]);
+        assert_eq!(cache.parse_count, 2);
+
+        // Only the modified file is re-parsed, and removed files disappear
+        let editor = dir.join("editor.desktop");
+        fs::write(&editor, "[Desktop Entry]\nType=Application\nName=Editor 2\nExec=editor\n").unwrap();
+        let later = SystemTime::now() + std::time::Duration::from_secs(10);
+        fs::File::options().write(true).open(&editor).unwrap().set_modified(later).unwrap();
+        fs::remove_file(dir.join("viewer.desktop")).unwrap();
+        assert_eq!(names(cache.scan(&dirs)), vec!["Editor 2"]);
+        assert_eq!(cache.parse_count, 3);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_hidden_user_entry_shadows_system_entry() {
+        let root = std::env::temp_dir().join(format!("blazedock-apps-shadow-test-{}", std::process::id()));
+        let (user, system) = (root.join("user"), root.join("system"));
+        fs::create_dir_all(&user).unwrap();
+        fs::create_dir_all(&system).unwrap();
+        fs::write(user.join("editor.desktop"), "[Desktop Entry]\nType=Application\nName=Editor\nExec=editor\nNoDisplay=true\n").unwrap();
+        fs::write(system.join("editor.desktop"), "[Desktop Entry]\nType=Application\nName=Editor\nExec=editor\n").unwrap();
+        fs::write(system.join("viewer.desktop"), "[Desktop Entry]\nType=Application\nName=Viewer\nExec=viewer\n").unwrap();
+
+        let entries = DiscoveryCache::default().scan(&[user, system]);
+        let names: Vec<_> = entries.into_iter().filter_map(|e| e.name).collect();
+        assert_eq!(names, vec!["Viewer"]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_application_dirs() {
+        let home = Path::new("/home/me");
+
+        let dirs = application_dirs_from(None, Some("/opt/custom/share:/usr/share:/usr/share/"), Some(home));
+        assert_eq!(dirs[0], PathBuf::from("/home/me/.local/share/applications"));
+        assert_eq!(dirs[1], PathBuf::from("/opt/custom/share/applications"));
+        assert_eq!(dirs[2], PathBuf::from("/usr/share/applications"));
+        assert!(dirs.contains(&PathBuf::from("/home/me/.local/share/flatpak/exports/share/applications")));
+        assert!(dirs.contains(&PathBuf::from("/var/lib/flatpak/exports/share/applications")));
+        assert_eq!(dirs.iter().filter(|dir| dir.starts_with("/usr/share")).count(), 1);
+
+        // Unset or empty variables fall back to the spec defaults
+        let dirs = application_dirs_from(Some(""), None, Some(home));
+        assert_eq!(dirs[..3], [
+            PathBuf::from("/home/me/.local/share/applications"),
+            PathBuf::from("/usr/local/share/applications"),
+            PathBuf::from("/usr/share/applications"),
+        ]);
+        assert_eq!(application_dirs_from(Some("/data"), None, None)[0], PathBuf::from("/data/applications"));
+    }
+
+    #[test]
+    fn test_dbus_activation_target() {
+        assert_eq!(dbus_object_path("org.gnome.Nautilus"), Some("/org/gnome/Nautilus".to_string()));
+        assert_eq!(dbus_object_path("org.gnome.clocks"), Some("/org/gnome/clocks".to_string()));
+        assert_eq!(dbus_object_path("com.example.my-app"), Some("/com/example/my_app".to_string()));
+
+        // Not bus names
+        assert_eq!(dbus_object_path("firefox"), None);
+        assert_eq!(dbus_object_path("org..app"), None);
+        assert_eq!(dbus_object_path("org.7zip.App"), None);
+        assert_eq!(dbus_object_path("org.gnome.Text Editor"), None);
+
+        let content = "[Desktop Entry]\nType=Application\nName=Files\nExec=nautilus --new-window\nDBusActivatable=true\n";
+        let entry = DesktopEntry::parse_content(PathBuf::from("/usr/share/applications/org.gnome.Nautilus.desktop"), content).unwrap();
+        assert_eq!(
+            entry.dbus_activation_target(),
+            Some(("org.gnome.Nautilus".to_string(), "/org/gnome/Nautilus".to_string())),
+        );
+
+        let entry = DesktopEntry::parse_content(PathBuf::from("/usr/share/applications/org.gnome.Nautilus.desktop"), &content.replace("true", "false")).unwrap();
+        assert_eq!(entry.dbus_activation_target(), None);
+    }
+
+    #[test]
+    fn test_exec_argv() {
+        // Quoted program path with spaces
+        assert_eq!(split_exec(r#""/opt/My App/bin" %U"#), vec!["/opt/My App/bin"]);
+        assert_eq!(split_exec(r#"app --title "Hello World" -x"#), vec!["app", "--title", "Hello World", "-x"]);
+
+        // Escapes inside quotes (written doubled in the file); `\s` is a space
+        assert_eq!(
+            split_exec(r#"sh -c "echo \\"hi\\" \\$HOME \\\\ \\`x\\`""#),
+            vec!["sh", "-c", r#"echo "hi" $HOME \ `x`"#],
+        );
+        assert_eq!(split_exec(r#"app "--name=My\sApp""#), vec!["app", "--name=My App"]);
+
+        // Field codes are dropped, %% is a literal percent, "" is an empty argument
+        assert_eq!(split_exec("app %f %F %u %U %i %c %k 100%% --"), vec!["app", "100%", "--"]);
+        assert_eq!(split_exec(r#"app "" --flag=%u"#), vec!["app", "", "--flag="]);
+        assert!(split_exec("   ").is_empty());
     }
 }
 
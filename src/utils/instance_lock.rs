@@ -0,0 +1,112 @@
+//! Single-instance lock
+//!
+//! An exclusive `flock` on a PID file in `$XDG_RUNTIME_DIR` marks the
+//! running dock, so a second launch notices it even when there is no
+//! session bus for GApplication to check uniqueness with. The kernel
+//! releases the lock when the dock exits, so a crash leaves nothing stale.
+
+use anyhow::{Context, Result};
+use log::debug;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Name of the PID file
+const LOCK_FILE: &str = "blazedock.pid";
+
+/// Outcome of trying to take the lock
+pub enum LockStatus {
+    /// This process is the only dock; keep the lock alive while running
+    Acquired(InstanceLock),
+    /// Another dock holds the lock, with its PID if it has written one yet
+    AlreadyRunning(Option<u32>),
+}
+
+/// Held single-instance lock, released on drop
+pub struct InstanceLock {
+    file: File,
+}
+
+impl InstanceLock {
+    /// Path of the PID file
+    pub fn path() -> PathBuf {
+        std::env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(std::env::temp_dir)
+            .join(LOCK_FILE)
+    }
+
+    /// Take the lock, unless another dock holds it
+    pub fn acquire() -> Result<LockStatus> {
+        Self::acquire_at(&Self::path())
+    }
+
+    /// Take the lock at `path`
+    ///
+    /// The file is never removed, since unlinking a locked file would let
+    /// a later launch lock a fresh file alongside the running dock.
+    fn acquire_at(path: &Path) -> Result<LockStatus> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .with_context(|| format!("Failed to open lock file {:?}", path))?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Ok(LockStatus::AlreadyRunning(read_pid(path))),
+            Err(TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("Failed to lock {:?}", path));
+            }
+        }
+
+        file.set_len(0)
+            .and_then(|()| write!(file, "{}", std::process::id()))
+            .with_context(|| format!("Failed to write lock file {:?}", path))?;
+        debug!("Took instance lock {:?}", path);
+        Ok(LockStatus::Acquired(Self { file }))
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // Closing the file releases the lock; just clear our PID
+        let _ = self.file.set_len(0);
+    }
+}
+
+/// Read the PID stored in a lock file
+fn read_pid(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_acquire_reports_already_running() {
+        let path = std::env::temp_dir().join(format!("blazedock-lock-test-{}.pid", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let lock = match InstanceLock::acquire_at(&path).unwrap() {
+            LockStatus::Acquired(lock) => lock,
+            LockStatus::AlreadyRunning(pid) => panic!("lock unexpectedly held by {:?}", pid),
+        };
+        assert!(matches!(
+            InstanceLock::acquire_at(&path).unwrap(),
+            LockStatus::AlreadyRunning(Some(pid)) if pid == std::process::id()
+        ));
+
+        // Released on drop
+        drop(lock);
+        assert_eq!(read_pid(&path), None);
+
+        // A PID left behind without a lock holder doesn't block the next dock
+        fs::write(&path, u32::MAX.to_string()).unwrap();
+        assert!(matches!(InstanceLock::acquire_at(&path).unwrap(), LockStatus::Acquired(_)));
+        let _ = fs::remove_file(&path);
+    }
+}
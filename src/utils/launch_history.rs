@@ -0,0 +1,141 @@
+//! Launch frequency history
+//!
+//! Counts how often each command was launched so the search overlay can
+//! offer the most used apps before anything is typed. Stored as TOML next
+//! to the configuration file.
+
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Settings;
+
+/// History file name, beside the configuration file
+const HISTORY_FILE: &str = "launch_history.toml";
+
+/// Launch counts per command
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LaunchHistory {
+    #[serde(default)]
+    counts: HashMap<String, u32>,
+}
+
+impl LaunchHistory {
+    /// Path of the history file
+    pub fn path() -> Option<PathBuf> {
+        Settings::config_path()
+            .and_then(|config| config.parent().map(|dir| dir.join(HISTORY_FILE)))
+    }
+
+    /// Load the saved history, starting empty if there is none
+    pub fn load() -> Self {
+        Self::path().map(|path| Self::load_from(&path)).unwrap_or_default()
+    }
+
+    /// Load the history from `path`
+    fn load_from(path: &Path) -> Self {
+        let Ok(content) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+        toml::from_str(&content).unwrap_or_else(|e| {
+            warn!("Ignoring invalid launch history {:?}: {}", path, e);
+            Self::default()
+        })
+    }
+
+    /// Save the history
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().context("Failed to determine config directory")?;
+        self.save_to(&path)
+    }
+
+    /// Save the history to `path`
+    fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        let content = toml::to_string_pretty(self).context("Failed to serialize launch history")?;
+        fs::write(path, content).context("Failed to write launch history")?;
+        Ok(())
+    }
+
+    /// Count one more launch of `command`
+    pub fn increment(&mut self, command: &str) {
+        *self.counts.entry(command.to_string()).or_insert(0) += 1;
+    }
+
+    /// The `n` most launched commands with their counts, most launched first
+    ///
+    /// Ties are broken alphabetically so the order is stable.
+    pub fn top(&self, n: usize) -> Vec<(&str, u32)> {
+        let mut top: Vec<(&str, u32)> = self.counts
+            .iter()
+            .map(|(command, count)| (command.as_str(), *count))
+            .collect();
+        top.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        top.truncate(n);
+        top
+    }
+
+    /// Forget all launches
+    pub fn clear(&mut self) {
+        self.counts.clear();
+    }
+}
+
+/// Record a launch of `command` in the saved history
+pub fn record_launch(command: &str) {
+    let mut history = LaunchHistory::load();
+    history.increment(command);
+    match history.save() {
+        Ok(()) => debug!("Recorded launch of '{}'", command),
+        Err(e) => warn!("Failed to save launch history: {}", e),
+    }
+}
+
+/// Clear the saved history
+pub fn clear_history() {
+    if let Err(e) = LaunchHistory::default().save() {
+        warn!("Failed to clear launch history: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_increment_and_top() {
+        let mut history = LaunchHistory::default();
+        for command in ["firefox", "krita", "firefox", "gimp", "firefox", "krita"] {
+            history.increment(command);
+        }
+
+        assert_eq!(history.top(2), vec![("firefox", 3), ("krita", 2)]);
+        assert_eq!(history.top(10).len(), 3);
+
+        history.clear();
+        assert!(history.top(5).is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir()
+            .join(format!("blazedock-history-test-{}", std::process::id()))
+            .join(HISTORY_FILE);
+
+        let mut history = LaunchHistory::default();
+        history.increment("gnome-terminal --window");
+        history.increment("gnome-terminal --window");
+        history.save_to(&path).unwrap();
+
+        let loaded = LaunchHistory::load_from(&path);
+        assert_eq!(loaded.top(1), vec![("gnome-terminal --window", 2)]);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert!(LaunchHistory::load_from(&path).top(1).is_empty());
+    }
+}
@@ -4,9 +4,28 @@
 //! the dock UI never freezes when starting applications.
 
 use anyhow::{Context, Result};
-use log::{debug, info, error};
+use gtk::glib;
+use log::{debug, info, error, warn};
+use std::collections::HashMap;
 use std::process::{Command, Stdio};
 
+use crate::utils::desktop_entry::DesktopEntry;
+use crate::utils::launch_history;
+
+/// Terminal emulators to try for `Terminal=true` apps, most preferred first
+const TERMINALS: &[&str] = &[
+    "foot",
+    "kitty",
+    "alacritty",
+    "wezterm",
+    "ptyxis",
+    "kgx",
+    "gnome-terminal",
+    "konsole",
+    "xfce4-terminal",
+    "xterm",
+];
+
 /// Launch an application command
 ///
 /// This function spawns the command in a detached process so:
@@ -21,21 +40,164 @@ use std::process::{Command, Stdio};
 /// * `Ok(())` if the command was successfully spawned
 /// * `Err` if the command failed to start
 pub fn launch_command(command: &str) -> Result<()> {
+    launch_command_with_env(command, &HashMap::new())
+}
+
+/// Launch an application command with extra environment variables
+///
+/// The variables in `env` are applied over the environment inherited
+/// from the dock, so they win over any existing value.
+///
+/// # Arguments
+/// * `command` - The command to execute (can include arguments)
+/// * `env` - Environment overrides for the child process
+pub fn launch_command_with_env(command: &str, env: &HashMap<String, String>) -> Result<()> {
     debug!("Launching command: {}", command);
 
     // Parse the command into program and arguments
     let parts: Vec<&str> = command.split_whitespace().collect();
-    
-    if parts.is_empty() {
-        anyhow::bail!("Empty command provided");
+    spawn_detached(&parts, env, command)
+}
+
+/// Launch an app, preferring its desktop file's Exec arguments
+///
+/// `command` is the joined form the dock keeps, which loses the Exec
+/// field's quoting. When it still matches the desktop file, the entry is
+/// launched instead; otherwise (e.g. a command edited in the config)
+/// `command` is launched as usual.
+pub fn launch_app(
+    command: &str,
+    desktop_file: Option<&str>,
+    terminal: Option<&str>,
+    env: &HashMap<String, String>,
+) -> Result<()> {
+    let entry = desktop_file.and_then(|path| DesktopEntry::parse(path).ok());
+    match entry {
+        Some(entry) if entry.exec_command().as_deref() == Some(command) => {
+            debug!("Launching '{}' from its desktop file", command);
+            launch_entry(&entry, terminal, env)
+        }
+        _ => launch_command_with_env(command, env),
+    }
+}
+
+/// Launch a desktop entry
+///
+/// `DBusActivatable` apps are activated over D-Bus, falling back to their
+/// Exec line if that fails. `terminal` overrides the detected terminal
+/// emulator for `Terminal=true` apps.
+pub fn launch_entry(entry: &DesktopEntry, terminal: Option<&str>, env: &HashMap<String, String>) -> Result<()> {
+    let Some((bus_name, object_path)) = entry.dbus_activation_target() else {
+        return exec_entry(entry, terminal, env);
+    };
+
+    let entry = entry.clone();
+    let terminal = terminal.map(str::to_string);
+    let env = env.clone();
+    glib::spawn_future_local(async move {
+        match activate_dbus(&bus_name, &object_path).await {
+            Ok(()) => {
+                info!("Activated '{}' over D-Bus", bus_name);
+                if let Some(command) = entry.exec_command() {
+                    launch_history::record_launch(&command);
+                }
+            }
+            Err(e) => {
+                warn!("D-Bus activation of '{}' failed: {}. Launching its Exec line.", bus_name, e);
+                if let Err(e) = exec_entry(&entry, terminal.as_deref(), &env) {
+                    error!("Failed to launch '{}': {}", bus_name, e);
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Call `org.freedesktop.Application.Activate` on a D-Bus activatable app
+async fn activate_dbus(bus_name: &str, object_path: &str) -> Result<()> {
+    let connection = zbus::Connection::session().await?;
+    let platform_data: HashMap<&str, zbus::zvariant::Value> = HashMap::new();
+    connection.call_method(
+        Some(bus_name),
+        object_path,
+        Some("org.freedesktop.Application"),
+        "Activate",
+        &(platform_data,),
+    ).await?;
+    Ok(())
+}
+
+/// Run a desktop entry's Exec line, in a terminal if it has `Terminal=true`
+fn exec_entry(entry: &DesktopEntry, terminal: Option<&str>, env: &HashMap<String, String>) -> Result<()> {
+    let history_key = entry.exec_command()
+        .context("Desktop file has no Exec field")?;
+    let argv = entry.exec_argv();
+
+    if !entry.terminal {
+        return spawn_detached(&argv, env, &history_key);
+    }
+
+    match detect_terminal(terminal) {
+        Some(terminal) => spawn_detached(&terminal_argv(&terminal, &argv), env, &history_key),
+        None => {
+            warn!("No terminal emulator found for '{}', launching it directly", history_key);
+            spawn_detached(&argv, env, &history_key)
+        }
     }
+}
+
+/// Pick the terminal emulator: the override, then `$TERMINAL`, then the first installed
+pub fn detect_terminal(terminal: Option<&str>) -> Option<String> {
+    terminal
+        .map(str::to_string)
+        .or_else(|| std::env::var("TERMINAL").ok())
+        .filter(|terminal| !terminal.trim().is_empty())
+        .or_else(|| {
+            TERMINALS.iter()
+                .find(|terminal| command_exists(terminal))
+                .map(|terminal| terminal.to_string())
+        })
+}
+
+/// Wrap `argv` to run inside `terminal`, e.g. `xterm -e <argv>`
+///
+/// `terminal` may carry its own arguments (`kitty --single-instance`).
+fn terminal_argv(terminal: &str, argv: &[String]) -> Vec<String> {
+    let mut wrapped: Vec<String> = terminal.split_whitespace().map(String::from).collect();
+    let program = wrapped.first()
+        .and_then(|program| program.rsplit('/').next())
+        .unwrap_or_default();
+
+    // Most terminals take -e; these want the command after -- or -x
+    let exec_args: &[&str] = match program {
+        "gnome-terminal" | "kgx" | "ptyxis" => &["--"],
+        "wezterm" => &["start", "--"],
+        // xfce4-terminal's -e takes the whole command as one string
+        "xfce4-terminal" => &["-x"],
+        _ => &["-e"],
+    };
+    wrapped.extend(exec_args.iter().map(|arg| arg.to_string()));
+    wrapped.extend(argv.iter().cloned());
+    wrapped
+}
 
-    let program = parts[0];
-    let args = &parts[1..];
+/// Spawn `argv` detached from the dock
+///
+/// The launch is recorded in the history under `history_key`.
+fn spawn_detached<S: AsRef<str>>(argv: &[S], env: &HashMap<String, String>, history_key: &str) -> Result<()> {
+    let Some((program, args)) = argv.split_first() else {
+        anyhow::bail!("Empty command provided");
+    };
+    let program = program.as_ref();
+    let args: Vec<&str> = args.iter().map(AsRef::as_ref).collect();
+
+    if !env.is_empty() {
+        let keys: Vec<&str> = env.keys().map(|k| k.as_str()).collect();
+        debug!("Applying environment overrides for '{}': {:?}", program, keys);
+    }
 
     // Spawn the process detached from the dock
-    match Command::new(program)
-        .args(args)
+    match build_command(program, &args, env)
         // Don't inherit stdin/stdout/stderr - fully detach
         .stdin(Stdio::null())
         .stdout(Stdio::null())
@@ -48,15 +210,23 @@ pub fn launch_command(command: &str) -> Result<()> {
                 program,
                 child.id()
             );
+            launch_history::record_launch(history_key);
             Ok(())
         }
         Err(e) => {
             error!("Failed to launch '{}': {}", program, e);
-            Err(anyhow::anyhow!("Failed to spawn command '{}': {}", command, e))
+            Err(anyhow::anyhow!("Failed to spawn command '{}': {}", history_key, e))
         }
     }
 }
 
+/// Build the process command with environment overrides applied
+fn build_command(program: &str, args: &[&str], env: &HashMap<String, String>) -> Command {
+    let mut cmd = Command::new(program);
+    cmd.args(args).envs(env);
+    cmd
+}
+
 /// Launch an application from its .desktop file
 ///
 /// This provides richer integration by parsing the Exec field
@@ -65,18 +235,13 @@ pub fn launch_command(command: &str) -> Result<()> {
 /// # Arguments
 /// * `desktop_file_path` - Path to the .desktop file
 pub fn launch_desktop_file(desktop_file_path: &str) -> Result<()> {
-    use crate::utils::desktop_entry::DesktopEntry;
-
     debug!("Launching from desktop file: {}", desktop_file_path);
 
     let entry = DesktopEntry::parse(desktop_file_path)
         .context("Failed to parse desktop file")?;
 
-    // Get the exec command, stripping field codes like %u, %F, etc.
-    let exec = entry.exec_command()
-        .context("Desktop file has no Exec field")?;
-
-    launch_command(&exec)
+    // Split the Exec field into argv, honoring quoting and dropping %u, %F, etc.
+    launch_entry(&entry, None, &HashMap::new())
 }
 
 /// Check if a command exists in PATH
@@ -95,5 +260,34 @@ mod tests {
         assert_eq!(parts[0], "firefox");
         assert_eq!(parts[1], "--new-window");
     }
+
+    #[test]
+    fn test_terminal_argv() {
+        let argv = vec!["htop".to_string(), "--tree".to_string()];
+
+        assert_eq!(terminal_argv("xterm", &argv), vec!["xterm", "-e", "htop", "--tree"]);
+        assert_eq!(
+            terminal_argv("kitty --single-instance", &argv),
+            vec!["kitty", "--single-instance", "-e", "htop", "--tree"],
+        );
+        assert_eq!(terminal_argv("/usr/bin/gnome-terminal", &argv), vec!["/usr/bin/gnome-terminal", "--", "htop", "--tree"]);
+        assert_eq!(terminal_argv("wezterm", &argv), vec!["wezterm", "start", "--", "htop", "--tree"]);
+        assert_eq!(terminal_argv("xfce4-terminal", &argv), vec!["xfce4-terminal", "-x", "htop", "--tree"]);
+
+        // The override wins over $TERMINAL and detection
+        assert_eq!(detect_terminal(Some("foot")), Some("foot".to_string()));
+    }
+
+    #[test]
+    fn test_env_overrides_reach_child() {
+        let mut env = HashMap::new();
+        env.insert("BLAZEDOCK_TEST_VAR".to_string(), "wayland".to_string());
+
+        let output = build_command("sh", &["-c", "printf %s \"$BLAZEDOCK_TEST_VAR\""], &env)
+            .output()
+            .expect("failed to run sh");
+
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "wayland");
+    }
 }
 
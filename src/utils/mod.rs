@@ -4,4 +4,6 @@
 
 pub mod launcher;
 pub mod desktop_entry;
+pub mod launch_history;
+pub mod instance_lock;
 